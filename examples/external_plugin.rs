@@ -0,0 +1,30 @@
+//! Reference plugin for `bigmul --external NAME=PATH`.
+//!
+//! Reads operand pairs as two decimal lines and answers each with the
+//! product as one decimal line, until stdin closes. Registered against the
+//! built-in Karatsuba it measures the protocol's overhead:
+//!
+//! ```text
+//! cargo build --release --example external_plugin
+//! bigmul --external piped=target/release/examples/external_plugin
+//! ```
+
+use bigmul::BigUint;
+use std::io::{self, BufRead, Write};
+
+fn main() {
+    let stdin = io::stdin();
+    let mut stdout = io::BufWriter::new(io::stdout().lock());
+    let mut lines = stdin.lock().lines();
+    let parse = |line: &str| {
+        BigUint::from_str(line.trim()).unwrap_or_else(|e| {
+            eprintln!("external_plugin: {}", e);
+            std::process::exit(1);
+        })
+    };
+    while let (Some(Ok(a)), Some(Ok(b))) = (lines.next(), lines.next()) {
+        let product = parse(&a).mul_karatsuba(&parse(&b));
+        writeln!(stdout, "{}", product).unwrap();
+        stdout.flush().unwrap();
+    }
+}
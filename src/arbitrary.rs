@@ -0,0 +1,164 @@
+//! `Arbitrary` implementations for property tests in downstream crates:
+//! quickcheck's with the `quickcheck` feature, proptest's with `proptest`.
+//!
+//! Uniformly random limbs almost never produce the values where limb
+//! arithmetic goes wrong, so one value in two is drawn from a shape instead:
+//! all nines (`BASE^n - 1`, which carries on any addition), powers of
+//! `BASE` and one past them, and a single nonzero top limb over zeros.
+//! Lengths run from zero limbs up to the generator's size (quickcheck) or
+//! [`MAX_LIMBS`] (proptest).
+
+use crate::bigint::{BigInt, Sign};
+use crate::biguint::BigUint;
+use crate::limbs::BASE;
+
+/// Most limbs proptest's strategy generates.
+pub const MAX_LIMBS: usize = 64;
+
+/// Builds a value of `limbs.len()` limbs in the shape `pattern` selects;
+/// the limbs themselves (each below `BASE`) are used as they are for half
+/// the patterns.
+fn shaped(pattern: u8, mut limbs: Vec<u32>) -> BigUint {
+    let n = limbs.len();
+    let top = BASE as u32 - 1;
+    match pattern % 8 {
+        0 => limbs.fill(top),
+        1 | 2 => {
+            limbs = vec![0; n + 1];
+            limbs[n] = 1;
+            limbs[0] += (pattern % 8 - 1) as u32;
+        }
+        3 => {
+            if let Some((last, rest)) = limbs.split_last_mut() {
+                rest.fill(0);
+                *last = (*last).max(1);
+            }
+        }
+        _ => {}
+    }
+    BigUint::from_kernel(limbs)
+}
+
+/// Attaches `negative` as the sign; zero stays unsigned.
+fn signed(negative: bool, magnitude: BigUint) -> BigInt {
+    let sign = if negative { Sign::Minus } else { Sign::Plus };
+    BigInt::from_biguint(sign, magnitude)
+}
+
+#[cfg(feature = "quickcheck")]
+mod quickcheck_impls {
+    use super::*;
+    use quickcheck::{Arbitrary, Gen};
+
+    impl Arbitrary for BigUint {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let len = usize::arbitrary(g) % (g.size() + 1);
+            let limbs = (0..len).map(|_| u32::arbitrary(g) % BASE as u32).collect();
+            shaped(u8::arbitrary(g), limbs)
+        }
+
+        /// Shrinks through the limbs: dropping them, then shrinking each
+        /// toward zero.
+        fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+            let limbs = self.as_limbs().to_vec();
+            Box::new(limbs.shrink().map(BigUint::from_kernel))
+        }
+    }
+
+    impl Arbitrary for BigInt {
+        fn arbitrary(g: &mut Gen) -> Self {
+            signed(bool::arbitrary(g), BigUint::arbitrary(g))
+        }
+
+        /// Shrinks the magnitude, and tries the positive value first.
+        fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+            let negative = self.sign() == Sign::Minus;
+            let positive = negative.then(|| signed(false, self.magnitude().clone()));
+            let smaller = self.magnitude().shrink().map(move |m| signed(negative, m));
+            Box::new(positive.into_iter().chain(smaller))
+        }
+    }
+}
+
+#[cfg(feature = "proptest")]
+mod proptest_impls {
+    use super::*;
+    use proptest::arbitrary::{Arbitrary, any};
+    use proptest::collection::vec;
+    use proptest::strategy::{BoxedStrategy, Strategy};
+
+    impl Arbitrary for BigUint {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<BigUint>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            (any::<u8>(), vec(0..BASE as u32, 0..=MAX_LIMBS))
+                .prop_map(|(pattern, limbs)| shaped(pattern, limbs))
+                .boxed()
+        }
+    }
+
+    impl Arbitrary for BigInt {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<BigInt>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            (any::<bool>(), any::<BigUint>())
+                .prop_map(|(negative, magnitude)| signed(negative, magnitude))
+                .boxed()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shapes_hit_the_limb_boundaries() {
+        let limbs = vec![5, 6, 7];
+        assert_eq!(shaped(0, limbs.clone()).to_string(), "9".repeat(27));
+        assert_eq!(
+            shaped(1, limbs.clone()).to_string(),
+            format!("1{}", "0".repeat(27))
+        );
+        assert_eq!(
+            shaped(2, limbs.clone()).to_string(),
+            format!("1{}1", "0".repeat(26))
+        );
+        assert_eq!(
+            shaped(3, limbs.clone()).to_string(),
+            format!("7{}", "0".repeat(18))
+        );
+        assert_eq!(shaped(4, limbs).as_limbs(), [5, 6, 7]);
+        for pattern in 0..8 {
+            let x = shaped(pattern, Vec::new());
+            assert!(x.is_zero() || x.is_one() || x.to_string() == "2", "{}", x);
+        }
+    }
+
+    #[cfg(feature = "quickcheck")]
+    #[test]
+    fn quickcheck_values_satisfy_ring_laws() {
+        use quickcheck::Arbitrary;
+
+        fn distributes(a: BigInt, b: BigInt, c: BigInt) -> bool {
+            a.mul(&b.add(&c)) == a.mul(&b).add(&a.mul(&c))
+        }
+        quickcheck::quickcheck(distributes as fn(BigInt, BigInt, BigInt) -> bool);
+        let shrunk: Vec<BigUint> = BigUint::from_str("1000000005").unwrap().shrink().collect();
+        assert!(
+            shrunk
+                .iter()
+                .all(|x| x < &BigUint::from_str("1000000005").unwrap())
+        );
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn proptest_values_round_trip_through_strings(x in proptest::arbitrary::any::<BigInt>()) {
+            proptest::prop_assert_eq!(BigInt::from_str(&x.to_string()).unwrap(), x);
+        }
+    }
+}
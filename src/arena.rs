@@ -0,0 +1,206 @@
+use crate::biguint::BigUint;
+use crate::limbs::{BASE, add_in_place, mul_karatsuba_into};
+use std::cell::{Cell, RefCell};
+use std::ptr::NonNull;
+
+/// Smallest chunk the arena requests from the system, in limbs.
+const MIN_CHUNK: usize = 1 << 14;
+
+/// A bump allocator for temporary values. Every limb buffer, including the
+/// Karatsuba scratch space, is carved out of a few large chunks and nothing
+/// is freed until the arena is dropped, so a computation that creates
+/// millions of short-lived intermediates (binary splitting, product trees)
+/// does two or three system allocations instead of millions.
+///
+/// Use it through [`with_arena`]; values live as [`ArenaUint`]s borrowing
+/// the arena and leave it with [`ArenaUint::to_biguint`].
+pub struct Arena {
+    /// Heap chunks from `Box<[u32]>`, never moved or freed before `drop`.
+    chunks: RefCell<Vec<NonNull<[u32]>>>,
+    /// Limbs handed out from the last chunk.
+    used: Cell<usize>,
+}
+
+/// An unsigned value stored in an [`Arena`]: normalized base-10^9 limbs, as
+/// in `BigUint`. Copying it copies only the reference.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArenaUint<'a> {
+    limbs: &'a [u32],
+}
+
+/// Runs `f` with a fresh arena and frees everything it allocated at once
+/// when `f` returns. Results must be converted out (with `to_biguint`)
+/// before returning.
+pub fn with_arena<R>(f: impl FnOnce(&Arena) -> R) -> R {
+    f(&Arena::new())
+}
+
+impl Arena {
+    fn new() -> Self {
+        Arena {
+            chunks: RefCell::new(Vec::new()),
+            used: Cell::new(0),
+        }
+    }
+
+    /// Total limbs reserved from the system so far.
+    pub fn capacity(&self) -> usize {
+        self.chunks.borrow().iter().map(|c| c.len()).sum()
+    }
+
+    /// A zeroed buffer of `len` limbs that lives as long as the arena.
+    #[allow(clippy::mut_from_ref)]
+    fn alloc(&self, len: usize) -> &mut [u32] {
+        let mut chunks = self.chunks.borrow_mut();
+        let chunk = match chunks.last().copied() {
+            Some(c) if c.len() - self.used.get() >= len => c,
+            last => {
+                let size = len.max(MIN_CHUNK).max(last.map_or(0, |c| 2 * c.len()));
+                let c = NonNull::from(Box::leak(vec![0u32; size].into_boxed_slice()));
+                chunks.push(c);
+                self.used.set(0);
+                c
+            }
+        };
+        let start = self.used.get();
+        self.used.set(start + len);
+        let base = chunk.as_ptr() as *mut u32;
+        // SAFETY: the chunk stays allocated and in place until `drop`, the
+        // range start..start + len lies inside it, and no other call hands
+        // out any part of that range. Chunks start zeroed and are never
+        // reused, so the memory is still zero.
+        unsafe { std::slice::from_raw_parts_mut(base.add(start), len) }
+    }
+
+    fn finish<'a>(&'a self, limbs: &'a mut [u32]) -> ArenaUint<'a> {
+        let len = limbs.iter().rposition(|&d| d != 0).map_or(1, |i| i + 1);
+        let limbs: &'a [u32] = limbs;
+        ArenaUint {
+            limbs: &limbs[..len],
+        }
+    }
+
+    /// Copies `x` into the arena.
+    pub fn uint(&self, x: &BigUint) -> ArenaUint<'_> {
+        let src = x.as_limbs();
+        let dst = self.alloc(src.len());
+        dst.copy_from_slice(src);
+        self.finish(dst)
+    }
+
+    pub fn small(&self, mut v: u64) -> ArenaUint<'_> {
+        let dst = self.alloc(3);
+        for d in dst.iter_mut() {
+            *d = (v % BASE) as u32;
+            v /= BASE;
+        }
+        self.finish(dst)
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn add<'a>(&'a self, a: ArenaUint<'a>, b: ArenaUint<'a>) -> ArenaUint<'a> {
+        let (long, short) = if a.limbs.len() >= b.limbs.len() {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        let out = self.alloc(long.limbs.len() + 1);
+        out[..long.limbs.len()].copy_from_slice(long.limbs);
+        add_in_place(out, short.limbs);
+        self.finish(out)
+    }
+
+    /// Karatsuba product. Both operands are padded to the longer length, so
+    /// it suits the balanced products of splitting algorithms.
+    #[allow(clippy::should_implement_trait)]
+    pub fn mul<'a>(&'a self, a: ArenaUint<'a>, b: ArenaUint<'a>) -> ArenaUint<'a> {
+        let n = a.limbs.len().max(b.limbs.len());
+        let pa = self.padded(a, n);
+        let pb = self.padded(b, n);
+        let out = self.alloc(2 * n);
+        let scratch = self.alloc(6 * n);
+        mul_karatsuba_into(pa, pb, out, scratch);
+        self.finish(out)
+    }
+
+    fn padded<'a>(&'a self, x: ArenaUint<'a>, n: usize) -> &'a [u32] {
+        if x.limbs.len() == n {
+            return x.limbs;
+        }
+        let out = self.alloc(n);
+        out[..x.limbs.len()].copy_from_slice(x.limbs);
+        out
+    }
+}
+
+impl Drop for Arena {
+    fn drop(&mut self) {
+        for chunk in self.chunks.get_mut().drain(..) {
+            // SAFETY: every chunk came from `Box::leak` in `alloc`, and
+            // no `ArenaUint` can outlive the arena.
+            drop(unsafe { Box::from_raw(chunk.as_ptr()) });
+        }
+    }
+}
+
+impl<'a> ArenaUint<'a> {
+    pub fn as_limbs(&self) -> &'a [u32] {
+        self.limbs
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs == [0]
+    }
+
+    /// Copies the value out of the arena.
+    pub fn to_biguint(&self) -> BigUint {
+        BigUint::from_kernel(self.limbs.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Binary-splitting product lo * (lo + 1) * ... * (hi - 1).
+    fn range_product<'a>(arena: &'a Arena, lo: u64, hi: u64) -> ArenaUint<'a> {
+        if hi - lo == 1 {
+            return arena.small(lo);
+        }
+        let mid = (lo + hi) / 2;
+        arena.mul(range_product(arena, lo, mid), range_product(arena, mid, hi))
+    }
+
+    #[test]
+    fn binary_splitting_matches_biguint() {
+        let (factorial, capacity) = with_arena(|arena| {
+            let f = range_product(arena, 1, 2001);
+            (f.to_biguint(), arena.capacity())
+        });
+        let mut expected = BigUint::from_str("1").unwrap();
+        for k in 1..=2000u64 {
+            expected = expected.mul_direct(&BigUint::from_str(&k.to_string()).unwrap());
+        }
+        assert_eq!(factorial, expected);
+        // A few doubling chunks, not one allocation per temporary.
+        assert!(capacity < 64 * MIN_CHUNK, "{} limbs", capacity);
+    }
+
+    #[test]
+    fn add_and_small_values() {
+        with_arena(|arena| {
+            let x = arena.uint(&BigUint::from_str("999999999999999999").unwrap());
+            let one = arena.small(1);
+            assert_eq!(
+                arena.add(x, one).to_biguint().to_string(),
+                "1000000000000000000"
+            );
+            assert_eq!(arena.add(one, x), arena.add(x, one));
+            assert!(arena.small(0).is_zero());
+            assert_eq!(
+                arena.small(u64::MAX).to_biguint().to_string(),
+                u64::MAX.to_string()
+            );
+        });
+    }
+}
@@ -0,0 +1,116 @@
+//! `--bases`: the direct and Karatsuba kernels of `RadixUint` in each limb
+//! base, at the same decimal sizes, and the size from which Karatsuba wins
+//! in each. The Karatsuba base case is a fixed number of limbs, so the
+//! crossover in digits moves with the digits each limb holds.
+
+use crate::interrupt;
+use crate::primitives::seconds_per_call;
+use bigmul::report::{Chart, Scale};
+use bigmul::{Base, RadixUint, random_bits};
+use std::fmt::Write as _;
+use std::hint::black_box;
+
+/// Operand sizes in decimal digits.
+const SIZES: [usize; 9] = [100, 200, 300, 500, 1000, 2000, 3000, 10_000, 30_000];
+
+/// Seconds per direct and per Karatsuba product of two `digits`-digit
+/// operands in `base`.
+fn measure(digits: usize, base: Base) -> (f64, f64) {
+    let mut rng = rand::thread_rng();
+    // log2(10) bits per digit.
+    let bits = digits * 3322 / 1000;
+    let a = RadixUint::from_biguint(&random_bits(bits, &mut rng), base);
+    let b = RadixUint::from_biguint(&random_bits(bits, &mut rng), base);
+    (
+        seconds_per_call(|| {
+            black_box(black_box(&a).mul_direct(black_box(&b)));
+        }),
+        seconds_per_call(|| {
+            black_box(black_box(&a).mul_karatsuba(black_box(&b)));
+        }),
+    )
+}
+
+/// Runs the sweep, printing each time and each base's crossover, and
+/// writes `./assets/bases.csv` and `./assets/bases.png`. Returns `false` if
+/// interrupted.
+pub fn run() -> bool {
+    let mut rows: Vec<(usize, Base, f64, f64)> = Vec::new();
+    'sizes: for &digits in &SIZES {
+        for base in Base::ALL {
+            if interrupt::interrupted() {
+                break 'sizes;
+            }
+            let (direct, karatsuba) = measure(digits, base);
+            println!(
+                "digits={}, base={}: direct={:.3e} s karatsuba={:.3e} s",
+                digits,
+                base.name(),
+                direct,
+                karatsuba
+            );
+            rows.push((digits, base, direct, karatsuba));
+        }
+    }
+    if rows.is_empty() {
+        return false;
+    }
+
+    // The smallest size from which Karatsuba stays clearly ahead; below
+    // the base case both run the same direct kernel, and timing noise alone
+    // would pick a winner.
+    for base in Base::ALL {
+        let mut from = None;
+        for row in rows.iter().filter(|row| row.1 == base) {
+            from = if row.3 < 0.95 * row.2 {
+                from.or(Some(row.0))
+            } else {
+                None
+            };
+        }
+        let crossover = from.map_or("none measured".to_string(), |digits| {
+            format!("from {} digits", digits)
+        });
+        println!(
+            "Karatsuba beats direct in base {}: {}",
+            base.name(),
+            crossover
+        );
+    }
+
+    let mut out = String::from("digits,base,direct_seconds,karatsuba_seconds\n");
+    for (digits, base, direct, karatsuba) in &rows {
+        writeln!(
+            out,
+            "{},{},{:.12},{:.12}",
+            digits,
+            base.name(),
+            direct,
+            karatsuba
+        )
+        .unwrap();
+    }
+    std::fs::create_dir_all("./assets").expect("Failed to create ./assets directory");
+    std::fs::write("./assets/bases.csv", out).expect("Failed to write CSV results");
+    let mut chart = Chart::new()
+        .title("Multiplication by Limb Base")
+        .x_desc("Input Size (decimal digits)")
+        .scale(Scale::LogLog);
+    for base in Base::ALL {
+        let of_base = || rows.iter().filter(move |row| row.1 == base);
+        chart = chart
+            .series(
+                format!("direct, base {}", base.name()),
+                of_base().map(|row| (row.0 as f64, row.2)),
+            )
+            .series(
+                format!("karatsuba, base {}", base.name()),
+                of_base().map(|row| (row.0 as f64, row.3)),
+            );
+    }
+    if let Err(msg) = chart.draw_png("./assets/bases.png".as_ref()) {
+        eprintln!("error: {}", msg);
+    }
+    println!("Results saved to ./assets/bases.csv and ./assets/bases.png");
+    rows.len() == SIZES.len() * Base::ALL.len()
+}
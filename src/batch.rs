@@ -0,0 +1,157 @@
+//! Elementwise products of many independent pairs, for workloads such as
+//! Monte Carlo trials or polynomial coefficients where thousands of
+//! medium-size products dominate rather than one giant one. Workers take
+//! pairs from a shared counter, so uneven sizes balance themselves, and each
+//! keeps its padded operands, product and Karatsuba scratch space in buffers
+//! that grow to the largest pair it sees and are reused for every other.
+
+use crate::bigint::{BigInt, Sign};
+use crate::biguint::BigUint;
+use crate::instrument::{self, MulPath};
+use crate::limbs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Pairs a worker takes from the counter at a time.
+const GRAIN: usize = 16;
+
+/// `a * b` for every pair, in order, on as many threads as the machine runs
+/// at once.
+///
+/// ```
+/// use bigmul::{BigInt, mul_many};
+///
+/// let pairs: Vec<(BigInt, BigInt)> = (1..=3)
+///     .map(|k| (BigInt::from_str(&k.to_string()).unwrap(), BigInt::from_str("-7").unwrap()))
+///     .collect();
+/// let products: Vec<String> = mul_many(&pairs).iter().map(|p| p.to_string()).collect();
+/// assert_eq!(products, ["-7", "-14", "-21"]);
+/// ```
+pub fn mul_many(pairs: &[(BigInt, BigInt)]) -> Vec<BigInt> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    mul_many_on(pairs, threads)
+}
+
+fn mul_many_on(pairs: &[(BigInt, BigInt)], threads: usize) -> Vec<BigInt> {
+    let work: usize = pairs
+        .iter()
+        .map(|(a, b)| a.magnitude().as_limbs().len() + b.magnitude().as_limbs().len())
+        .sum();
+    let threads = threads.min(pairs.len().div_ceil(GRAIN));
+    if threads <= 1 || work < limbs::par_karatsuba_threshold() {
+        let mut buffers = Buffers::default();
+        return pairs.iter().map(|(a, b)| buffers.mul(a, b)).collect();
+    }
+    let next = AtomicUsize::new(0);
+    let worker = || {
+        let mut buffers = Buffers::default();
+        let mut done = Vec::new();
+        loop {
+            let start = next.fetch_add(GRAIN, Ordering::Relaxed);
+            if start >= pairs.len() {
+                break;
+            }
+            for (i, (a, b)) in pairs.iter().enumerate().skip(start).take(GRAIN) {
+                done.push((i, buffers.mul(a, b)));
+            }
+        }
+        done
+    };
+    let mut products = vec![BigInt::zero(); pairs.len()];
+    std::thread::scope(|scope| {
+        // Spawned threads start counting from zero; their totals are added
+        // to this thread's.
+        let spawned: Vec<_> = (1..threads)
+            .map(|_| scope.spawn(|| (worker(), instrument::limb_ops())))
+            .collect();
+        let mut place = |done: Vec<(usize, BigInt)>| {
+            for (i, p) in done {
+                products[i] = p;
+            }
+        };
+        place(worker());
+        for handle in spawned {
+            let (done, ops) = handle
+                .join()
+                .unwrap_or_else(|e| std::panic::resume_unwind(e));
+            instrument::add_limb_ops(ops as usize);
+            place(done);
+        }
+    });
+    products
+}
+
+/// One worker's reusable limb buffers.
+#[derive(Default)]
+struct Buffers {
+    a: Vec<u32>,
+    b: Vec<u32>,
+    out: Vec<u32>,
+    scratch: Vec<u32>,
+}
+
+impl Buffers {
+    fn mul(&mut self, a: &BigInt, b: &BigInt) -> BigInt {
+        let (x, y) = (a.magnitude().as_limbs(), b.magnitude().as_limbs());
+        let (long, short) = (x.len().max(y.len()), x.len().min(y.len()));
+        let sign = match (a.sign(), b.sign()) {
+            (Sign::NoSign, _) | (_, Sign::NoSign) => return BigInt::zero(),
+            (s, t) if s == t => Sign::Plus,
+            _ => Sign::Minus,
+        };
+        // Single limbs, squares and lopsided pairs have their own paths in
+        // `mul`, which padding to a common length would only slow down.
+        if short <= 1 || 2 * short <= long || x == y {
+            return BigInt::from_biguint(sign, a.magnitude().mul(b.magnitude()));
+        }
+        instrument::record(MulPath::General);
+        let n = long;
+        for (buffer, limbs, len) in [
+            (&mut self.a, x, n),
+            (&mut self.b, y, n),
+            (&mut self.out, &[][..], 2 * n),
+            (&mut self.scratch, &[][..], 6 * n),
+        ] {
+            buffer.clear();
+            buffer.extend_from_slice(limbs);
+            buffer.resize(len, 0);
+        }
+        limbs::mul_karatsuba_into(&self.a, &self.b, &mut self.out, &mut self.scratch);
+        let len = self.out.iter().rposition(|&d| d != 0).map_or(1, |i| i + 1);
+        let product = BigUint::from_kernel(self.out[..len].to_vec());
+        BigInt::from_biguint(sign, product)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random_bits;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn batches_match_one_product_at_a_time() {
+        let mut rng = StdRng::seed_from_u64(83);
+        let mut pairs: Vec<(BigInt, BigInt)> = (0..300)
+            .map(|_| {
+                let draw = |rng: &mut StdRng| {
+                    let bits = rng.gen_range(0..6000);
+                    let sign = [Sign::Minus, Sign::Plus][rng.gen_range(0..2)];
+                    BigInt::from_biguint(sign, random_bits(bits, rng))
+                };
+                (draw(&mut rng), draw(&mut rng))
+            })
+            .collect();
+        pairs.push((pairs[3].0.clone(), pairs[3].0.clone()));
+        pairs.push((BigInt::zero(), pairs[5].1.clone()));
+        let expected: Vec<BigInt> = pairs.iter().map(|(a, b)| a.mul(b)).collect();
+        for threads in [1, 3] {
+            assert_eq!(
+                mul_many_on(&pairs, threads),
+                expected,
+                "{} threads",
+                threads
+            );
+        }
+        assert!(mul_many(&[]).is_empty());
+    }
+}
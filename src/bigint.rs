@@ -0,0 +1,370 @@
+use crate::biguint::BigUint;
+use crate::error::Error;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Neg;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Sign {
+    Minus,
+    NoSign,
+    Plus,
+}
+
+impl Sign {
+    fn product(self, other: Sign) -> Sign {
+        match (self, other) {
+            (Sign::NoSign, _) | (_, Sign::NoSign) => Sign::NoSign,
+            (a, b) if a == b => Sign::Plus,
+            _ => Sign::Minus,
+        }
+    }
+}
+
+impl Neg for Sign {
+    type Output = Sign;
+
+    fn neg(self) -> Sign {
+        match self {
+            Sign::Minus => Sign::Plus,
+            Sign::NoSign => Sign::NoSign,
+            Sign::Plus => Sign::Minus,
+        }
+    }
+}
+
+/// A signed arbitrary-precision integer: a `Sign` plus a `BigUint`
+/// magnitude. Zero always has `Sign::NoSign`, so the kernels only ever see
+/// magnitudes and the sign is settled here.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    sign: Sign,
+    data: BigUint,
+}
+
+impl BigInt {
+    pub fn new() -> Self {
+        BigInt::zero()
+    }
+
+    pub const fn zero() -> Self {
+        BigInt {
+            sign: Sign::NoSign,
+            data: BigUint::zero(),
+        }
+    }
+
+    pub const fn one() -> Self {
+        BigInt {
+            sign: Sign::Plus,
+            data: BigUint::one(),
+        }
+    }
+
+    /// Builds a value from a sign and magnitude. A zero magnitude or
+    /// `Sign::NoSign` both yield zero.
+    pub fn from_biguint(sign: Sign, data: BigUint) -> Self {
+        if sign == Sign::NoSign || data.is_zero() {
+            return BigInt::new();
+        }
+        BigInt { sign, data }
+    }
+
+    /// Parses an optional `+`/`-` followed by decimal digits.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Result<Self, Error> {
+        let (sign, digits) = match s.strip_prefix('-') {
+            Some(rest) => (Sign::Minus, rest),
+            None => (Sign::Plus, s.strip_prefix('+').unwrap_or(s)),
+        };
+        // Positions in errors count the sign.
+        if let Some(i) = digits.bytes().position(|b| !b.is_ascii_digit()) {
+            let i = i + s.len() - digits.len();
+            return Err(Error::Parse(format!("byte {} is not a decimal digit", i)));
+        }
+        Ok(BigInt::from_biguint(sign, BigUint::from_str(digits)?))
+    }
+
+    /// Builds a value from a sign and magnitude limbs, as
+    /// [`BigUint::from_limbs`]. Returns `None` if any limb is out of range.
+    pub fn from_limbs(sign: Sign, limbs: Vec<u32>) -> Option<BigInt> {
+        BigUint::from_limbs(limbs).map(|data| BigInt::from_biguint(sign, data))
+    }
+
+    /// As [`BigUint::from_kernel`], with a sign.
+    pub(crate) fn from_kernel(sign: Sign, limbs: Vec<u32>) -> BigInt {
+        BigInt::from_biguint(sign, BigUint::from_kernel(limbs))
+    }
+
+    /// The normalized limbs of the magnitude.
+    pub fn as_limbs(&self) -> &[u32] {
+        self.data.as_limbs()
+    }
+
+    pub fn sign(&self) -> Sign {
+        self.sign
+    }
+
+    pub fn magnitude(&self) -> &BigUint {
+        &self.data
+    }
+
+    pub fn into_parts(self) -> (Sign, BigUint) {
+        (self.sign, self.data)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.sign == Sign::NoSign
+    }
+
+    pub fn is_one(&self) -> bool {
+        self.sign == Sign::Plus && self.data.is_one()
+    }
+
+    /// Compares absolute values, ignoring signs.
+    pub fn cmp_magnitude(&self, other: &BigInt) -> Ordering {
+        self.data.cmp(&other.data)
+    }
+
+    /// Returns `|self - other|`.
+    pub fn abs_diff(&self, other: &BigInt) -> BigUint {
+        self.sub(other).into_parts().1
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(&self, other: &BigInt) -> BigInt {
+        match (self.sign, other.sign) {
+            (_, Sign::NoSign) => self.clone(),
+            (Sign::NoSign, _) => other.clone(),
+            (a, b) if a == b => BigInt::from_biguint(a, self.data.add(&other.data)),
+            _ => match self.data.cmp(&other.data) {
+                Ordering::Equal => BigInt::new(),
+                Ordering::Greater => BigInt::from_biguint(self.sign, self.data.sub(&other.data)),
+                Ordering::Less => BigInt::from_biguint(other.sign, other.data.sub(&self.data)),
+            },
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn sub(&self, other: &BigInt) -> BigInt {
+        self.add(&-other)
+    }
+
+    /// Multiplies as [`BigUint::mul`], taking its fast paths.
+    #[allow(clippy::should_implement_trait)]
+    pub fn mul(&self, other: &BigInt) -> BigInt {
+        self.signed(other, self.data.mul(&other.data))
+    }
+
+    /// As [`mul`](BigInt::mul), failing as [`BigUint::checked_mul`] does.
+    pub fn checked_mul(&self, other: &BigInt) -> Result<BigInt, Error> {
+        Ok(self.signed(other, self.data.checked_mul(&other.data)?))
+    }
+
+    /// `self * self`, as [`BigUint::square`].
+    pub fn square(&self) -> BigInt {
+        BigInt::from_biguint(Sign::Plus, self.data.square())
+    }
+
+    pub fn mul_direct(&self, other: &BigInt) -> BigInt {
+        self.signed(other, self.data.mul_direct(&other.data))
+    }
+
+    pub fn mul_dc(&self, other: &BigInt) -> BigInt {
+        self.signed(other, self.data.mul_dc(&other.data))
+    }
+
+    pub fn mul_karatsuba(&self, other: &BigInt) -> BigInt {
+        self.signed(other, self.data.mul_karatsuba(&other.data))
+    }
+
+    pub fn mul_karatsuba_depth(&self, other: &BigInt, max_depth: usize) -> BigInt {
+        self.signed(other, self.data.mul_karatsuba_depth(&other.data, max_depth))
+    }
+
+    pub fn mul_karatsuba_sub(&self, other: &BigInt) -> BigInt {
+        self.signed(other, self.data.mul_karatsuba_sub(&other.data))
+    }
+
+    /// Computes `self^exp`; negative for a negative base and odd `exp`. For
+    /// negative exponents use [`BigRational::pow`](crate::BigRational::pow).
+    pub fn pow(&self, exp: u64) -> BigInt {
+        let sign = if exp % 2 == 1 { self.sign } else { Sign::Plus };
+        BigInt::from_biguint(sign, self.data.pow(exp))
+    }
+
+    /// Exact division as in `BigUint::div_exact`, with the sign of the
+    /// quotient.
+    pub fn div_exact(&self, other: &BigInt) -> BigInt {
+        self.signed(other, self.data.div_exact(&other.data))
+    }
+
+    pub fn is_divisible_by(&self, other: &BigInt) -> bool {
+        self.data.is_divisible_by(&other.data)
+    }
+
+    fn signed(&self, other: &BigInt, data: BigUint) -> BigInt {
+        BigInt::from_biguint(self.sign.product(other.sign), data)
+    }
+}
+
+impl Default for BigInt {
+    fn default() -> Self {
+        BigInt::new()
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        BigInt::from_str(s)
+    }
+}
+
+impl From<BigUint> for BigInt {
+    fn from(data: BigUint) -> Self {
+        BigInt::from_biguint(Sign::Plus, data)
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(n: i64) -> Self {
+        let sign = if n < 0 { Sign::Minus } else { Sign::Plus };
+        BigInt::from_biguint(sign, BigUint::from(n.unsigned_abs()))
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(mut self) -> BigInt {
+        self.sign = -self.sign;
+        self
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        -self.clone()
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sign.cmp(&other.sign).then_with(|| match self.sign {
+            Sign::Minus => other.data.cmp(&self.data),
+            Sign::NoSign => Ordering::Equal,
+            Sign::Plus => self.data.cmp(&other.data),
+        })
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.sign == Sign::Minus {
+            f.write_str("-")?;
+        }
+        write!(f, "{}", self.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(s: &str) -> BigInt {
+        BigInt::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn zero_has_no_sign() {
+        assert_eq!(big("-0").sign(), Sign::NoSign);
+        assert_eq!(big("+000").sign(), Sign::NoSign);
+        assert_eq!(big("-0").to_string(), "0");
+        assert_eq!(-BigInt::new(), BigInt::new());
+    }
+
+    #[test]
+    fn zero_and_one_constants() {
+        assert_eq!(BigInt::zero(), big("0"));
+        assert_eq!(BigInt::one(), big("1"));
+        assert!(BigInt::one().is_one() && !big("-1").is_one() && !BigInt::zero().is_one());
+        let x = big("-123456789012345678901234567890");
+        assert_eq!(x.mul_karatsuba(&BigInt::one()), x);
+        assert_eq!(big("-1").mul_dc(&x), -&x);
+        assert!(x.mul_direct(&BigInt::zero()).is_zero());
+        assert_eq!(big("-1").pow(3), big("-1"));
+    }
+
+    #[test]
+    fn parse_and_display_round_trip() {
+        for s in ["-1", "7", "-123456789012345678901234567890", "1000000000"] {
+            assert_eq!(big(s).to_string(), s);
+        }
+        assert_eq!(big("+42").to_string(), "42");
+    }
+
+    #[test]
+    fn add_and_sub_follow_signs() {
+        assert_eq!(big("5").add(&big("-7")), big("-2"));
+        assert_eq!(big("-5").add(&big("7")), big("2"));
+        assert_eq!(big("-5").add(&big("-7")), big("-12"));
+        assert_eq!(big("5").sub(&big("5")), BigInt::new());
+        assert_eq!(big("-1000000000").sub(&big("-1")), big("-999999999"));
+        assert_eq!(big("0").sub(&big("3")), big("-3"));
+    }
+
+    #[test]
+    fn products_take_sign_rule() {
+        let a = big("-123456789123456789");
+        let b = big("987654321987654321");
+        let p = big("-121932631356500531347203169112635269");
+        assert_eq!(a.mul_direct(&b), p);
+        assert_eq!(a.mul_dc(&b), p);
+        assert_eq!(a.mul_karatsuba(&b), p);
+        assert_eq!(a.mul_karatsuba(&-&b), -p);
+        assert_eq!(a.mul_karatsuba(&BigInt::new()).sign(), Sign::NoSign);
+    }
+
+    #[test]
+    fn from_limbs_round_trips() {
+        let x = big("-123456789123456789");
+        let y = BigInt::from_limbs(x.sign(), x.as_limbs().to_vec()).unwrap();
+        assert_eq!(x, y);
+        assert!(BigInt::from_limbs(Sign::Minus, vec![0]).unwrap().is_zero());
+        assert!(BigInt::from_limbs(Sign::Plus, vec![u32::MAX]).is_none());
+    }
+
+    #[test]
+    fn magnitude_comparison_and_distance() {
+        assert_eq!(big("-10").cmp_magnitude(&big("3")), Ordering::Greater);
+        assert_eq!(big("-3").cmp_magnitude(&big("3")), Ordering::Equal);
+        assert_eq!(big("0").cmp_magnitude(&big("-1")), Ordering::Less);
+        assert_eq!(big("-10").abs_diff(&big("3")).to_string(), "13");
+        assert_eq!(big("3").abs_diff(&big("10")).to_string(), "7");
+        assert_eq!(
+            BigUint::from_str("3")
+                .unwrap()
+                .abs_diff(&BigUint::from_str("1000000000000").unwrap()),
+            BigUint::from_str("999999999997").unwrap()
+        );
+    }
+
+    #[test]
+    fn ordering_is_signed() {
+        let mut v = [big("3"), big("-10"), big("0"), big("-2"), big("10")];
+        v.sort();
+        let sorted: Vec<String> = v.iter().map(|x| x.to_string()).collect();
+        assert_eq!(sorted, ["-10", "-2", "0", "3", "10"]);
+    }
+}
@@ -0,0 +1,1202 @@
+// Arithmetic on normalized values never panics; see the `limbs` docs.
+#![cfg_attr(
+    not(test),
+    deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)
+)]
+
+use crate::bigint::{BigInt, Sign};
+use crate::binary;
+use crate::error::Error;
+use crate::instrument::{self, MulPath};
+use crate::limbs;
+use crate::limbvec::{INLINE, LimbVec};
+use std::cmp;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+/// Exponent scanning strategy for [`BigUint::mod_pow`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PowWindow {
+    /// Left-to-right square and multiply, one bit at a time.
+    Binary,
+    /// Fixed 4-bit windows over a table of `base^0..base^15`.
+    Fixed4,
+    /// Sliding windows over odd powers, width chosen from the exponent size.
+    Sliding,
+}
+
+impl PowWindow {
+    pub const ALL: [PowWindow; 3] = [PowWindow::Binary, PowWindow::Fixed4, PowWindow::Sliding];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PowWindow::Binary => "binary",
+            PowWindow::Fixed4 => "window4",
+            PowWindow::Sliding => "sliding",
+        }
+    }
+}
+
+/// An unsigned arbitrary-precision integer stored as little-endian base-10^9
+/// limbs, always normalized (no leading zero limbs, zero is `[0]`).
+#[derive(Clone, Debug)]
+pub struct BigUint {
+    pub(crate) digits: LimbVec,
+}
+
+impl BigUint {
+    pub fn new() -> Self {
+        BigUint::zero()
+    }
+
+    pub const fn zero() -> Self {
+        BigUint {
+            digits: LimbVec::Inline {
+                len: 1,
+                buf: [0; INLINE],
+            },
+        }
+    }
+
+    pub const fn one() -> Self {
+        BigUint {
+            digits: LimbVec::Inline {
+                len: 1,
+                buf: [1, 0, 0, 0],
+            },
+        }
+    }
+
+    /// Parses a nonempty run of decimal digits; leading zeros are allowed.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Result<Self, Error> {
+        if s.is_empty() {
+            return Err(Error::Parse("no digits".to_string()));
+        }
+        if let Some(i) = s.bytes().position(|b| !b.is_ascii_digit()) {
+            return Err(Error::Parse(format!("byte {} is not a decimal digit", i)));
+        }
+        // Nine digits per limb from the right, parsed in place: a
+        // memory-mapped input is never copied.
+        let digits: Vec<u32> = s
+            .as_bytes()
+            .rchunks(9)
+            .map(|chunk| chunk.iter().fold(0, |acc, &d| acc * 10 + (d - b'0') as u32))
+            .collect();
+        Ok(BigUint::from_kernel(digits))
+    }
+
+    /// Wraps limbs a kernel produced, which are below [`limbs::BASE`] by
+    /// construction, trimming high zero limbs.
+    pub(crate) fn from_kernel(mut digits: Vec<u32>) -> BigUint {
+        debug_assert!(digits.iter().all(|&d| (d as u64) < limbs::BASE));
+        limbs::normalize(&mut digits);
+        if digits.is_empty() {
+            digits.push(0);
+        }
+        BigUint {
+            digits: digits.into(),
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits == [0]
+    }
+
+    pub fn is_one(&self) -> bool {
+        self.digits == [1]
+    }
+
+    /// Builds a value from little-endian base-10^9 limbs, trimming high zero
+    /// limbs (an empty vector is zero). Returns `None` if any limb is not
+    /// below [`limbs::BASE`].
+    pub fn from_limbs(digits: Vec<u32>) -> Option<BigUint> {
+        if digits.iter().any(|&d| d as u64 >= limbs::BASE) {
+            return None;
+        }
+        Some(BigUint::from_kernel(digits))
+    }
+
+    /// Takes limbs that must already be canonical, as [`limbs::validate`]
+    /// checks, for untrusted input that should be rejected rather than
+    /// repaired.
+    pub fn from_canonical_limbs(digits: Vec<u32>) -> Result<BigUint, Error> {
+        limbs::validate(&digits).map_err(Error::Parse)?;
+        Ok(BigUint {
+            digits: digits.into(),
+        })
+    }
+
+    /// The normalized little-endian base-10^9 limbs, for use with [`limbs`].
+    pub fn as_limbs(&self) -> &[u32] {
+        &self.digits
+    }
+
+    /// Consumes the value, returning its normalized limbs.
+    pub fn into_limbs(self) -> Vec<u32> {
+        self.digits.into_vec()
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(&self, other: &BigUint) -> BigUint {
+        if let Some(sum) = self.add_inline(other) {
+            return sum;
+        }
+        BigUint {
+            digits: limbs::add_slices(&self.digits, &other.digits).into(),
+        }
+    }
+
+    /// `self + other` without allocating, when the sum fits inline.
+    fn add_inline(&self, other: &BigUint) -> Option<BigUint> {
+        let (long, short) = if self.digits.len() >= other.digits.len() {
+            (&self.digits, &other.digits)
+        } else {
+            (&other.digits, &self.digits)
+        };
+        if long.len() >= INLINE {
+            return None;
+        }
+        let mut digits = LimbVec::zeroed(long.len() + 1);
+        digits[..long.len()].copy_from_slice(long);
+        limbs::add_in_place(&mut digits, short);
+        digits.normalize();
+        Some(BigUint { digits })
+    }
+
+    /// `self * other` without running a multiplication kernel, when an
+    /// operand is zero or one.
+    fn mul_trivial(&self, other: &BigUint) -> Option<BigUint> {
+        if self.is_zero() || other.is_zero() {
+            return Some(BigUint::zero());
+        }
+        if self.is_one() {
+            return Some(other.clone());
+        }
+        if other.is_one() {
+            return Some(self.clone());
+        }
+        None
+    }
+
+    /// `self * other` by the algorithm set with
+    /// [`force::force`](crate::force::force), if any.
+    #[cfg(feature = "force-algorithm")]
+    fn mul_forced(&self, other: &BigUint) -> Option<BigUint> {
+        crate::force::forced().map(|algorithm| BigUint {
+            digits: (algorithm.mul)(&self.digits, &other.digits).into(),
+        })
+    }
+
+    #[cfg(not(feature = "force-algorithm"))]
+    fn mul_forced(&self, _: &BigUint) -> Option<BigUint> {
+        None
+    }
+
+    /// `mul_trivial`, `mul_forced`, or else `self * other` without
+    /// allocating when the product fits inline.
+    fn mul_shortcut(&self, other: &BigUint) -> Option<BigUint> {
+        if let Some(product) = self.mul_trivial(other) {
+            return Some(product);
+        }
+        if let Some(product) = self.mul_forced(other) {
+            return Some(product);
+        }
+        let len = self.digits.len() + other.digits.len();
+        if len > INLINE {
+            return None;
+        }
+        let mut digits = LimbVec::zeroed(len);
+        limbs::mul_direct_into(&self.digits, &other.digits, &mut digits);
+        digits.normalize();
+        Some(BigUint { digits })
+    }
+
+    /// Computes `self - other`; requires `self >= other`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn sub(&self, other: &BigUint) -> BigUint {
+        BigUint {
+            digits: limbs::sub_slices(&self.digits, &other.digits).into(),
+        }
+    }
+
+    /// Multiplies by `BASE^k`.
+    pub fn shift_left(&self, k: usize) -> BigUint {
+        BigUint {
+            digits: limbs::shift_left_slices(&self.digits, k).into(),
+        }
+    }
+
+    /// As [`shift_left`](BigUint::shift_left), failing with
+    /// [`Error::TooLarge`] or [`Error::SizeOverflow`] instead of allocating a
+    /// result past [`limbs::max_result_limbs`].
+    pub fn checked_shift_left(&self, k: usize) -> Result<BigUint, Error> {
+        if !self.is_zero() {
+            limbs::check_result_len(self.digits.len().checked_add(k))?;
+        }
+        Ok(self.shift_left(k))
+    }
+
+    /// Returns `|self - other|`.
+    pub fn abs_diff(&self, other: &BigUint) -> BigUint {
+        BigUint {
+            digits: limbs::abs_diff_slices(&self.digits, &other.digits).1.into(),
+        }
+    }
+
+    /// Multiplies by whatever suits the operands: no kernel when one is zero
+    /// or one, a scalar pass when one is a single limb, [`square`] when they
+    /// are equal, and Karatsuba otherwise. Each choice is counted in
+    /// [`instrument`](crate::instrument).
+    ///
+    /// [`square`]: BigUint::square
+    #[allow(clippy::should_implement_trait)]
+    pub fn mul(&self, other: &BigUint) -> BigUint {
+        if let Some(product) = self.mul_trivial(other) {
+            instrument::record(MulPath::Trivial);
+            return product;
+        }
+        let scalar = match (self.digits.as_slice(), other.digits.as_slice()) {
+            (&[d], long) | (long, &[d]) => Some((long, d)),
+            _ => None,
+        };
+        if let Some((long, d)) = scalar {
+            instrument::record(MulPath::Scalar);
+            if let Some(product) = self.mul_forced(other) {
+                return product;
+            }
+            return BigUint {
+                digits: limbs::mul_small_slices(long, d).into(),
+            };
+        }
+        if std::ptr::eq(self, other) || self.digits == other.digits {
+            instrument::record(MulPath::Square);
+            return self.square();
+        }
+        instrument::record(MulPath::General);
+        self.mul_karatsuba(other)
+    }
+
+    /// As [`mul`](BigUint::mul), failing with [`Error::TooLarge`] instead of
+    /// multiplying when the product could be longer than
+    /// [`limbs::max_result_limbs`].
+    pub fn checked_mul(&self, other: &BigUint) -> Result<BigUint, Error> {
+        // A product by zero or one is no longer than the other operand.
+        if ![self, other].iter().any(|x| x.is_zero() || x.is_one()) {
+            limbs::product_len(self.digits.len(), other.digits.len())?;
+        }
+        Ok(self.mul(other))
+    }
+
+    /// `self * self` by Karatsuba squaring, which needs about two thirds of
+    /// the limb products of a general Karatsuba product.
+    pub fn square(&self) -> BigUint {
+        if let Some(product) = self.mul_shortcut(self) {
+            return product;
+        }
+        BigUint {
+            digits: limbs::square_karatsuba_slices(&self.digits).into(),
+        }
+    }
+
+    pub fn mul_direct(&self, other: &BigUint) -> BigUint {
+        if let Some(product) = self.mul_shortcut(other) {
+            return product;
+        }
+        BigUint {
+            digits: limbs::mul_direct_slices(&self.digits, &other.digits).into(),
+        }
+    }
+
+    pub fn mul_dc(&self, other: &BigUint) -> BigUint {
+        if let Some(product) = self.mul_shortcut(other) {
+            return product;
+        }
+        BigUint {
+            digits: limbs::mul_dc_slices(&self.digits, &other.digits).into(),
+        }
+    }
+
+    pub fn mul_karatsuba(&self, other: &BigUint) -> BigUint {
+        if let Some(product) = self.mul_shortcut(other) {
+            return product;
+        }
+        BigUint {
+            digits: limbs::mul_karatsuba_slices(&self.digits, &other.digits).into(),
+        }
+    }
+
+    /// Karatsuba with at most `max_depth` recursion levels before falling
+    /// back to the direct method.
+    pub fn mul_karatsuba_depth(&self, other: &BigUint, max_depth: usize) -> BigUint {
+        if let Some(product) = self.mul_shortcut(other) {
+            return product;
+        }
+        BigUint {
+            digits: limbs::mul_karatsuba_depth_slices(&self.digits, &other.digits, max_depth)
+                .into(),
+        }
+    }
+
+    /// Karatsuba using up to `threads` threads for the top recursion levels.
+    pub fn mul_karatsuba_par(&self, other: &BigUint, threads: usize) -> BigUint {
+        if let Some(product) = self.mul_shortcut(other) {
+            return product;
+        }
+        BigUint {
+            digits: limbs::mul_karatsuba_par_slices(&self.digits, &other.digits, threads).into(),
+        }
+    }
+
+    /// Returns `(self * other) mod BASE^n` without forming the high limbs.
+    pub fn mul_low(&self, other: &BigUint, n: usize) -> BigUint {
+        BigUint {
+            digits: limbs::mul_low_slices(&self.digits, &other.digits, n).into(),
+        }
+    }
+
+    pub fn mul_karatsuba_sub(&self, other: &BigUint) -> BigUint {
+        if let Some(product) = self.mul_shortcut(other) {
+            return product;
+        }
+        BigUint {
+            digits: limbs::mul_karatsuba_sub_slices(&self.digits, &other.digits).into(),
+        }
+    }
+
+    /// Returns `(self / other, self % other)`. Panics if `other` is zero.
+    pub fn div_rem(&self, other: &BigUint) -> (BigUint, BigUint) {
+        let (q, r) = limbs::div_rem_slices(&self.digits, &other.digits);
+        (BigUint { digits: q.into() }, BigUint { digits: r.into() })
+    }
+
+    /// Returns `(self / BASE^k, self % BASE^k)` by splitting the limbs.
+    pub fn div_rem_pow_base(&self, k: usize) -> (BigUint, BigUint) {
+        let (q, r) = limbs::div_rem_pow_base_slices(&self.digits, k);
+        (BigUint { digits: q.into() }, BigUint { digits: r.into() })
+    }
+
+    /// Returns `(self / 2^bits, self % 2^bits)` without general division.
+    pub fn div_rem_pow2(&self, bits: usize) -> (BigUint, BigUint) {
+        let (q, r) = limbs::div_rem_pow2_slices(&self.digits, bits);
+        (BigUint { digits: q.into() }, BigUint { digits: r.into() })
+    }
+
+    /// Returns `self / other` when `other` is known to divide `self`, about
+    /// twice as fast as `div_rem`. Debug builds check the division was
+    /// exact; release builds return a meaningless value if it was not.
+    /// Panics if `other` is zero.
+    pub fn div_exact(&self, other: &BigUint) -> BigUint {
+        let q = BigUint {
+            digits: limbs::div_exact_slices(&self.digits, &other.digits).into(),
+        };
+        debug_assert!(
+            &q.mul_karatsuba(other) == self,
+            "div_exact: {} is not divisible by {}",
+            self,
+            other
+        );
+        q
+    }
+
+    /// As [`div_rem`](BigUint::div_rem), failing with
+    /// [`Error::DivisionByZero`] instead of panicking.
+    pub fn checked_div_rem(&self, other: &BigUint) -> Result<(BigUint, BigUint), Error> {
+        if other.is_zero() {
+            return Err(Error::DivisionByZero);
+        }
+        Ok(self.div_rem(other))
+    }
+
+    /// Whether `other` divides `self`. Panics if `other` is zero.
+    pub fn is_divisible_by(&self, other: &BigUint) -> bool {
+        match other.digits.as_slice() {
+            [d] => limbs::div_rem_small(&self.digits, *d).1 == 0,
+            _ => limbs::div_rem_slices(&self.digits, &other.digits).1 == [0],
+        }
+    }
+
+    /// Computes `self^exp mod modulus`, multiplying with Karatsuba and
+    /// reducing by long division after every step. Panics if `modulus` is
+    /// zero.
+    pub fn mod_pow(&self, exp: &BigUint, modulus: &BigUint, window: PowWindow) -> BigUint {
+        assert!(!modulus.is_zero(), "modulus must be non-zero");
+        if modulus.is_one() {
+            return BigUint::zero();
+        }
+        let mul_mod = |x: &BigUint, y: &BigUint| x.mul_karatsuba(y).div_rem(modulus).1;
+        let base = self.div_rem(modulus).1;
+        if base.is_zero() || base.is_one() {
+            return if exp.is_zero() { BigUint::one() } else { base };
+        }
+        pow_windowed(base, BigUint::one(), &exp.bits_msb_first(), window, mul_mod)
+    }
+
+    /// As [`mod_pow`](BigUint::mod_pow), failing with
+    /// [`Error::DivisionByZero`] instead of panicking on a zero modulus.
+    pub fn checked_mod_pow(
+        &self,
+        exp: &BigUint,
+        modulus: &BigUint,
+        window: PowWindow,
+    ) -> Result<BigUint, Error> {
+        if modulus.is_zero() {
+            return Err(Error::DivisionByZero);
+        }
+        Ok(self.mod_pow(exp, modulus, window))
+    }
+
+    /// Computes `self^exp` by left-to-right squaring with Karatsuba.
+    pub fn pow(&self, exp: u64) -> BigUint {
+        if exp == 0 {
+            return BigUint::one();
+        }
+        if self.is_zero() || self.is_one() {
+            return self.clone();
+        }
+        let mut result = BigUint::one();
+        for bit in (0..u64::BITS - exp.leading_zeros()).rev() {
+            result = result.square();
+            if (exp >> bit) & 1 == 1 {
+                result = result.mul_karatsuba(self);
+            }
+        }
+        result
+    }
+
+    /// As [`pow`](BigUint::pow), failing instead of computing a power that
+    /// could be longer than [`limbs::max_result_limbs`]. Every limb holds at
+    /// least 29 bits, so `self^exp` needs at most `bits * exp / 29` limbs.
+    pub fn checked_pow(&self, exp: u64) -> Result<BigUint, Error> {
+        if exp > 1 && !self.is_one() {
+            let len = (self.bits() as u64)
+                .checked_mul(exp)
+                .and_then(|bits| usize::try_from(bits.div_ceil(29)).ok());
+            limbs::check_result_len(len)?;
+        }
+        Ok(self.pow(exp))
+    }
+
+    /// Floor of the square root. The top half of the limbs is rooted
+    /// recursively, which puts the Newton iteration within a few steps of the
+    /// answer, so the cost is dominated by the final full-size divisions.
+    pub fn sqrt(&self) -> BigUint {
+        let len = self.digits.len();
+        if len <= 2 {
+            let n = (self.digits.iter().rev()).fold(0, |n, &d| n * limbs::BASE + d as u64);
+            let mut r = (n as f64).sqrt() as u64;
+            while r * r > n {
+                r -= 1;
+            }
+            while (r + 1) * (r + 1) <= n {
+                r += 1;
+            }
+            return BigUint::from(r);
+        }
+        // Start from above: Newton's iteration then decreases monotonically
+        // to the floor.
+        let k = (len / 4).max(1);
+        let high = BigUint {
+            digits: limbs::shift_right_slices(&self.digits, 2 * k).into(),
+        };
+        let mut x = high.sqrt().add(&BigUint::one()).shift_left(k);
+        loop {
+            let sum = x.add(&self.div_rem(&x).0);
+            let y = BigUint {
+                digits: limbs::div_rem_small(&sum.digits, 2).0.into(),
+            };
+            if y >= x {
+                return x;
+            }
+            x = y;
+        }
+    }
+
+    /// Number of bits needed to represent the value; zero for zero. The
+    /// top three limbs give `log2` to within about `1e-14` of itself, which
+    /// settles the bit count unless the value lies that close to a power of
+    /// two; only then is it converted to binary to read the top word.
+    pub fn bits(&self) -> usize {
+        let len = self.digits.len();
+        if len <= 2 {
+            let top = match self.digits[..] {
+                [d] => d as u64,
+                [d1, d0] => d0 as u64 * limbs::BASE + d1 as u64,
+                _ => 0,
+            };
+            return (u64::BITS - top.leading_zeros()) as usize;
+        }
+        let top = self.digits[len - 3..]
+            .iter()
+            .rev()
+            .fold(0u128, |t, &d| t * limbs::BASE as u128 + d as u128);
+        let estimate = (top as f64).log2() + (len - 3) as f64 * (limbs::BASE as f64).log2();
+        let slack = estimate * 1e-14 + 1e-12;
+        let (low, high) = ((estimate - slack).floor(), (estimate + slack).floor());
+        if low == high {
+            return low as usize + 1;
+        }
+        let words = binary::to_words(self);
+        words
+            .last()
+            .map_or(0, |&top| 32 * words.len() - top.leading_zeros() as usize)
+    }
+
+    /// Greatest common divisor by the Euclidean algorithm; `gcd(0, 0) = 0`.
+    pub fn gcd(&self, other: &BigUint) -> BigUint {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        while !b.is_zero() {
+            let r = a.div_rem(&b).1;
+            a = b;
+            b = r;
+        }
+        a
+    }
+
+    /// Returns `x` in `[0, modulus)` with `self * x = 1 (mod modulus)`.
+    /// Fails with `NotInvertible` if `self` and `modulus` are not coprime,
+    /// and with `DivisionByZero` if `modulus` is zero.
+    pub fn mod_inverse(&self, modulus: &BigUint) -> Result<BigUint, Error> {
+        if modulus.is_zero() {
+            return Err(Error::DivisionByZero);
+        }
+        let (mut r0, mut r1) = (modulus.clone(), self.div_rem(modulus).1);
+        let (mut t0, mut t1) = (BigInt::new(), BigInt::one());
+        while !r1.is_zero() {
+            let (q, r) = r0.div_rem(&r1);
+            let t = t0.sub(&BigInt::from(q).mul_karatsuba(&t1));
+            (r0, r1) = (r1, r);
+            (t0, t1) = (t1, t);
+        }
+        if r0.digits != [1] {
+            return Err(Error::NotInvertible);
+        }
+        Ok(match t0.into_parts() {
+            (Sign::Minus, magnitude) => modulus.sub(&magnitude),
+            (_, magnitude) => magnitude,
+        })
+    }
+
+    /// Binary digits, most significant first; empty for zero. Read off the
+    /// base-2^32 words of [`binary::to_words`], so long exponents cost one
+    /// divide-and-conquer conversion rather than a division per chunk.
+    pub(crate) fn bits_msb_first(&self) -> Vec<u8> {
+        let mut bits: Vec<u8> = binary::to_words(self)
+            .iter()
+            .rev()
+            .flat_map(|&w| (0..32).rev().map(move |i| ((w >> i) & 1) as u8))
+            .collect();
+        let leading = bits.iter().take_while(|&&b| b == 0).count();
+        bits.drain(..leading);
+        bits
+    }
+}
+
+/// Raises `base` to the exponent whose bits (most significant first) are
+/// `bits`, using `mul_mod` for every product. `one` is the identity in the
+/// caller's representation, which lets Montgomery-form callers share this.
+pub(crate) fn pow_windowed<F>(
+    base: BigUint,
+    one: BigUint,
+    bits: &[u8],
+    window: PowWindow,
+    mul_mod: F,
+) -> BigUint
+where
+    F: Fn(&BigUint, &BigUint) -> BigUint,
+{
+    if bits.is_empty() {
+        return one;
+    }
+    match window {
+        PowWindow::Binary => {
+            let mut acc = one;
+            for &bit in bits {
+                acc = mul_mod(&acc, &acc);
+                if bit == 1 {
+                    acc = mul_mod(&acc, &base);
+                }
+            }
+            acc
+        }
+        PowWindow::Fixed4 => {
+            let mut table = vec![one.clone(), base.clone()];
+            for i in 2..16 {
+                table.push(mul_mod(&table[i - 1], &base));
+            }
+            let pad = (4 - bits.len() % 4) % 4;
+            let padded: Vec<u8> = std::iter::repeat_n(0, pad)
+                .chain(bits.iter().copied())
+                .collect();
+            let mut acc = one;
+            for chunk in padded.chunks(4) {
+                for _ in 0..4 {
+                    acc = mul_mod(&acc, &acc);
+                }
+                let w = chunk.iter().fold(0, |w, &bit| (w << 1) | bit as usize);
+                if w != 0 {
+                    acc = mul_mod(&acc, &table[w]);
+                }
+            }
+            acc
+        }
+        PowWindow::Sliding => {
+            let k = match bits.len() {
+                0..=24 => 1,
+                25..=80 => 3,
+                81..=240 => 4,
+                241..=672 => 5,
+                _ => 6,
+            };
+            // odd[i] = base^(2i + 1)
+            let square = mul_mod(&base, &base);
+            let mut odd = vec![base];
+            for i in 1..(1 << (k - 1)) {
+                odd.push(mul_mod(&odd[i - 1], &square));
+            }
+            let mut acc = one;
+            let mut i = 0;
+            while i < bits.len() {
+                if bits[i] == 0 {
+                    acc = mul_mod(&acc, &acc);
+                    i += 1;
+                    continue;
+                }
+                // Longest window of at most k bits starting here that
+                // ends in a one.
+                let mut end = cmp::min(i + k, bits.len());
+                while bits[end - 1] == 0 {
+                    end -= 1;
+                }
+                let mut w = 0;
+                for &bit in &bits[i..end] {
+                    acc = mul_mod(&acc, &acc);
+                    w = (w << 1) | bit as usize;
+                }
+                acc = mul_mod(&acc, &odd[w >> 1]);
+                i = end;
+            }
+            acc
+        }
+    }
+}
+
+impl Default for BigUint {
+    fn default() -> Self {
+        BigUint::new()
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some((top, rest)) = self.digits.split_last() else {
+            return f.write_str("0");
+        };
+        write!(f, "{}", top)?;
+        for &d in rest.iter().rev() {
+            write!(f, "{:09}", d)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for BigUint {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        BigUint::from_str(s)
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(n: u64) -> Self {
+        let base = limbs::BASE;
+        BigUint::from_kernel(vec![
+            (n % base) as u32,
+            (n / base % base) as u32,
+            (n / base / base) as u32,
+        ])
+    }
+}
+
+impl PartialEq for BigUint {
+    fn eq(&self, other: &Self) -> bool {
+        self.digits == other.digits
+    }
+}
+
+impl Eq for BigUint {}
+
+impl Hash for BigUint {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_limbs().hash(state);
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        limbs::cmp_slices(&self.digits, &other.digits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_str_strips_leading_zeros() {
+        assert!(BigUint::from_str("").is_err());
+        assert_eq!(BigUint::from_str("0000").unwrap().digits, vec![0]);
+        assert_eq!(
+            BigUint::from_str("000000000000000123").unwrap().digits,
+            vec![123]
+        );
+        assert_eq!(
+            BigUint::from_str("0001000000000").unwrap().digits,
+            vec![0, 1],
+            "zero low limb must survive"
+        );
+    }
+
+    #[test]
+    fn display_pads_inner_limbs() {
+        assert_eq!(BigUint::new().to_string(), "0");
+        assert_eq!(
+            BigUint {
+                digits: vec![1, 2].into()
+            }
+            .to_string(),
+            "2000000001"
+        );
+        assert_eq!(
+            BigUint::from_str("12000000000000000034")
+                .unwrap()
+                .to_string(),
+            "12000000000000000034"
+        );
+    }
+
+    #[test]
+    fn small_results_stay_inline() {
+        let x = BigUint::from_str("123456789123").unwrap();
+        assert!(BigUint::new().digits.is_inline());
+        assert_eq!(x.add(&x).to_string(), "246913578246");
+        assert!(x.add(&x).digits.is_inline());
+        assert_eq!(x.mul_karatsuba(&x), x.mul_dc(&x));
+        assert!(x.mul_karatsuba(&x).digits.is_inline());
+        let big = x.mul_karatsuba(&x).mul_karatsuba(&x.mul_karatsuba(&x));
+        assert!(!big.digits.is_inline());
+        assert_eq!(
+            big.to_string(),
+            "232305723724044020453897060201539959787138641"
+        );
+    }
+
+    #[test]
+    fn from_limbs_validates_and_normalizes() {
+        let x = BigUint::from_limbs(vec![5, 999_999_999, 0, 0]).unwrap();
+        assert_eq!(x.as_limbs(), [5, 999_999_999]);
+        assert_eq!(x.to_string(), "999999999000000005");
+        assert_eq!(BigUint::from_limbs(Vec::new()).unwrap(), BigUint::new());
+        assert_eq!(BigUint::from_limbs(vec![0, 0]).unwrap().into_limbs(), [0]);
+        assert!(BigUint::from_limbs(vec![1, 1_000_000_000]).is_none());
+        assert_eq!(
+            BigUint::from_canonical_limbs(vec![5, 999_999_999]).unwrap(),
+            x
+        );
+        assert!(BigUint::from_canonical_limbs(vec![5, 0]).is_err());
+    }
+
+    #[test]
+    fn shift_left_of_zero_stays_zero() {
+        assert_eq!(BigUint::new().shift_left(5), BigUint::new());
+        assert_eq!(
+            BigUint::from_str("7").unwrap().shift_left(1),
+            BigUint::from_str("7000000000").unwrap()
+        );
+    }
+
+    #[test]
+    fn div_rem_splits_dividend() {
+        let a = BigUint::from_str("123456789012345678901234567890").unwrap();
+        let b = BigUint::from_str("9876543210987").unwrap();
+        let (q, r) = a.div_rem(&b);
+        assert_eq!(q.to_string(), "12499999886094578");
+        assert_eq!(r.to_string(), "1249943839404");
+        assert_eq!(q.mul_karatsuba(&b).add(&r), a);
+    }
+
+    #[test]
+    fn div_exact_and_divisibility() {
+        let a = BigUint::from_str("123456789012345678901234567890").unwrap();
+        let b = BigUint::from_str("9876543210987").unwrap();
+        let product = a.mul_karatsuba(&b);
+        assert_eq!(product.div_exact(&b), a);
+        assert_eq!(product.div_exact(&a), b);
+        assert!(product.is_divisible_by(&b));
+        assert!(
+            !product
+                .add(&BigUint::from_str("1").unwrap())
+                .is_divisible_by(&b)
+        );
+        assert!(a.is_divisible_by(&BigUint::from_str("10").unwrap()));
+        assert!(!a.is_divisible_by(&BigUint::from_str("11").unwrap()));
+    }
+
+    #[test]
+    #[should_panic(expected = "not divisible")]
+    #[cfg(debug_assertions)]
+    fn div_exact_checks_exactness_in_debug() {
+        BigUint::from_str("1000000000001")
+            .unwrap()
+            .div_exact(&BigUint::from_str("3").unwrap());
+    }
+
+    #[test]
+    fn zero_and_one_operands_short_circuit() {
+        let x = BigUint::from_str(&"9".repeat(2000)).unwrap();
+        let (zero, one) = (BigUint::zero(), BigUint::one());
+        assert!(zero.is_zero() && one.is_one() && !x.is_one());
+        assert_eq!(zero, BigUint::from_str("0").unwrap());
+        assert_eq!(one, BigUint::from_str("1").unwrap());
+        let products = |a: &BigUint, b: &BigUint| {
+            [
+                a.mul_direct(b),
+                a.mul_dc(b),
+                a.mul_karatsuba(b),
+                a.mul_karatsuba_depth(b, 2),
+                a.mul_karatsuba_par(b, 2),
+                a.mul_karatsuba_sub(b),
+            ]
+        };
+        for p in products(&x, &zero).iter().chain(&products(&zero, &x)) {
+            assert!(p.is_zero());
+        }
+        for p in products(&x, &one).iter().chain(&products(&one, &x)) {
+            assert_eq!(*p, x);
+        }
+        assert_eq!(zero.pow(5), zero);
+        assert_eq!(one.pow(u64::MAX), one);
+        assert_eq!(x.pow(0), one);
+        let m = BigUint::from_str("1000000007").unwrap();
+        assert_eq!(x.mod_pow(&zero, &one, PowWindow::Binary), zero);
+        assert_eq!(m.mod_pow(&x, &m, PowWindow::Binary), zero);
+        assert_eq!(zero.mod_pow(&zero, &m, PowWindow::Sliding), one);
+        assert_eq!(m.add(&one).mod_pow(&x, &m, PowWindow::Fixed4), one);
+    }
+
+    /// The one path `mul(a, b)` took, and its product.
+    fn dispatched(a: &BigUint, b: &BigUint) -> (MulPath, BigUint) {
+        instrument::reset();
+        let product = a.mul(b);
+        let taken: Vec<MulPath> = MulPath::ALL
+            .into_iter()
+            .filter(|&p| instrument::count(p) > 0)
+            .collect();
+        assert_eq!(taken.len(), 1);
+        assert_eq!(instrument::count(taken[0]), 1);
+        (taken[0], product)
+    }
+
+    #[test]
+    fn mul_takes_fast_paths() {
+        let x = BigUint::from_str(&"7".repeat(500)).unwrap();
+        let y = BigUint::from_str(&"3".repeat(400)).unwrap();
+        let (zero, one) = (BigUint::zero(), BigUint::one());
+        let d = BigUint::from_str("999999999").unwrap();
+        for (a, b, path) in [
+            (&x, &zero, MulPath::Trivial),
+            (&one, &y, MulPath::Trivial),
+            (&x, &d, MulPath::Scalar),
+            (&d, &y, MulPath::Scalar),
+            (&d, &d, MulPath::Scalar),
+            (&x, &x, MulPath::Square),
+            (&y, &y.clone(), MulPath::Square),
+            (&x, &y, MulPath::General),
+        ] {
+            let (taken, product) = dispatched(a, b);
+            assert_eq!(
+                taken,
+                path,
+                "{} limbs * {} limbs",
+                a.digits.len(),
+                b.digits.len()
+            );
+            assert_eq!(product, a.mul_direct(b));
+        }
+        assert_eq!(x.square(), x.mul_karatsuba(&x));
+    }
+
+    #[test]
+    fn pow_matches_repeated_multiplication() {
+        let x = BigUint::from_str("123456789").unwrap();
+        let mut expected = BigUint::from_str("1").unwrap();
+        for exp in 0..20 {
+            assert_eq!(x.pow(exp), expected);
+            expected = expected.mul_direct(&x);
+        }
+        assert_eq!(BigUint::new().pow(0).to_string(), "1");
+        assert_eq!(
+            BigUint::from_str("3").unwrap().pow(100).to_string(),
+            "515377520732011331036461129765621272702107522001"
+        );
+    }
+
+    #[test]
+    fn mod_pow_strategies_agree() {
+        let m = BigUint::from_str("1000000000000000000000000000057").unwrap();
+        let base = BigUint::from_str("987654321987654321987654321").unwrap();
+        for exp in ["0", "1", "2", "65537", "1000000000000000000000000000056"] {
+            let exp = BigUint::from_str(exp).unwrap();
+            let expected = base.mod_pow(&exp, &m, PowWindow::Binary);
+            for window in PowWindow::ALL {
+                assert_eq!(base.mod_pow(&exp, &m, window), expected, "{:?}", window);
+            }
+        }
+        let e = BigUint::from_str("65537").unwrap();
+        assert_eq!(
+            base.mod_pow(&e, &m, PowWindow::Sliding).to_string(),
+            "203773893599509910687678287589"
+        );
+        // 2^10 mod 1000 and Fermat: 3^(p - 1) = 1 mod p for p = 2^61 - 1.
+        let two = BigUint::from_str("2").unwrap();
+        let thousand = BigUint::from_str("1000").unwrap();
+        let ten = BigUint::from_str("10").unwrap();
+        for window in PowWindow::ALL {
+            assert_eq!(two.mod_pow(&ten, &thousand, window).to_string(), "24");
+            let p = BigUint::from_str("2305843009213693951").unwrap();
+            let p_1 = p.sub(&BigUint::from_str("1").unwrap());
+            let three = BigUint::from_str("3").unwrap();
+            assert_eq!(three.mod_pow(&p_1, &p, window).to_string(), "1");
+            assert!(
+                three
+                    .mod_pow(&p_1, &BigUint::from_str("1").unwrap(), window)
+                    .is_zero()
+            );
+        }
+    }
+
+    #[test]
+    fn bits_at_powers_of_two() {
+        let two = BigUint::from_str("2").unwrap();
+        for k in [1, 29, 30, 59, 60, 64, 90, 1000, 4321, 30000] {
+            let p = two.pow(k);
+            assert_eq!(p.bits(), k as usize + 1, "2^{}", k);
+            assert_eq!(p.sub(&BigUint::one()).bits(), k as usize, "2^{} - 1", k);
+            assert_eq!(p.add(&BigUint::one()).bits(), k as usize + 1, "2^{} + 1", k);
+        }
+    }
+
+    #[test]
+    fn bits_msb_first_spell_the_value() {
+        assert!(BigUint::new().bits_msb_first().is_empty());
+        assert_eq!(BigUint::from(10u64).bits_msb_first(), [1, 0, 1, 0]);
+        let x = BigUint::from_str("123456789012345678901234567890123").unwrap();
+        let bits = x.bits_msb_first();
+        assert_eq!(bits.len(), x.bits());
+        let two = BigUint::from(2u64);
+        let back = bits.iter().fold(BigUint::new(), |acc, &b| {
+            acc.mul(&two).add(&BigUint::from(b as u64))
+        });
+        assert_eq!(back, x);
+    }
+
+    #[test]
+    fn bits_match_the_binary_form() {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        for bits in [1, 50, 61, 62, 63, 64, 65, 89, 90, 91, 500, 3001, 40000] {
+            let x = crate::random_bits(bits, &mut rng);
+            assert_eq!(x.bits(), bits);
+            let words = binary::to_words(&x);
+            assert_eq!(
+                x.bits(),
+                32 * words.len() - words[words.len() - 1].leading_zeros() as usize
+            );
+        }
+    }
+
+    #[test]
+    fn gcd_and_inverse() {
+        let a = BigUint::from_str("1071").unwrap();
+        let b = BigUint::from_str("462").unwrap();
+        assert_eq!(a.gcd(&b).to_string(), "21");
+        assert_eq!(BigUint::new().gcd(&b), b);
+        assert_eq!(BigUint::new().bits(), 0);
+        assert_eq!(BigUint::from_str("1024").unwrap().bits(), 11);
+        assert_eq!(
+            BigUint::from_str("1000000000000000000000000000057")
+                .unwrap()
+                .bits(),
+            100
+        );
+        assert!(matches!(a.mod_inverse(&b), Err(Error::NotInvertible)));
+        assert!(matches!(
+            a.mod_inverse(&BigUint::new()),
+            Err(Error::DivisionByZero)
+        ));
+        let m = BigUint::from_str("1000000000000000000000000000057").unwrap();
+        let x = BigUint::from_str("123456789123456789").unwrap();
+        let inv = x.mod_inverse(&m).unwrap();
+        assert!(inv < m);
+        assert_eq!(x.mul_karatsuba(&inv).div_rem(&m).1.to_string(), "1");
+        assert_eq!(
+            BigUint::from_str("3")
+                .unwrap()
+                .mod_inverse(&BigUint::from_str("7").unwrap())
+                .unwrap()
+                .to_string(),
+            "5"
+        );
+        assert!(
+            BigUint::from_str("5")
+                .unwrap()
+                .mod_inverse(&BigUint::from_str("1").unwrap())
+                .unwrap()
+                .is_zero()
+        );
+    }
+
+    #[test]
+    fn parse_errors_say_where() {
+        for (s, msg) in [
+            ("", "invalid number: no digits"),
+            ("12a4", "invalid number: byte 2 is not a decimal digit"),
+            ("-5", "invalid number: byte 0 is not a decimal digit"),
+        ] {
+            assert_eq!(BigUint::from_str(s).unwrap_err().to_string(), msg);
+        }
+        assert_eq!("0042".parse::<BigUint>().unwrap().to_string(), "42");
+    }
+
+    #[test]
+    fn checked_divisions_refuse_zero() {
+        let (x, zero) = (BigUint::from(17u64), BigUint::zero());
+        assert!(matches!(
+            x.checked_div_rem(&zero),
+            Err(Error::DivisionByZero)
+        ));
+        assert!(matches!(
+            x.checked_mod_pow(&x, &zero, PowWindow::Binary),
+            Err(Error::DivisionByZero)
+        ));
+        let five = BigUint::from(5u64);
+        assert_eq!(x.checked_div_rem(&five).unwrap(), x.div_rem(&five));
+        assert_eq!(
+            x.checked_mod_pow(&x, &five, PowWindow::Binary).unwrap(),
+            x.mod_pow(&x, &five, PowWindow::Binary)
+        );
+    }
+
+    #[test]
+    fn equal_values_hash_alike() {
+        use std::collections::HashSet;
+        let set: HashSet<BigUint> = [
+            BigUint::from_str("0042").unwrap(),
+            BigUint::from(42u64),
+            BigUint::from_limbs(vec![42, 0, 0]).unwrap(),
+            BigUint::from(43u64),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(set.len(), 2);
+        let signed: HashSet<BigInt> = ["-7", "-0007", "7", "0", "-0"]
+            .into_iter()
+            .map(|s| BigInt::from_str(s).unwrap())
+            .collect();
+        assert_eq!(signed.len(), 3);
+    }
+
+    #[test]
+    fn from_u64_splits_into_limbs() {
+        assert_eq!(BigUint::from(0u64), BigUint::new());
+        assert_eq!(BigUint::from(u64::MAX).to_string(), u64::MAX.to_string());
+        assert_eq!(BigUint::from(u64::MAX).as_limbs().len(), 3);
+    }
+
+    #[test]
+    fn checked_operations_refuse_huge_results() {
+        limbs::with_max_result_limbs(limbs::MAX_RESULT_LIMBS, || {
+            let two = BigUint::from(2u64);
+            assert_eq!(two.checked_pow(100).unwrap(), two.pow(100));
+            assert!(matches!(
+                two.checked_pow(u64::MAX),
+                Err(Error::SizeOverflow)
+            ));
+            assert_eq!(
+                BigUint::one().checked_pow(u64::MAX).unwrap(),
+                BigUint::one()
+            );
+            assert!(matches!(
+                two.checked_shift_left(usize::MAX),
+                Err(Error::SizeOverflow)
+            ));
+            assert!(
+                BigUint::new()
+                    .checked_shift_left(usize::MAX)
+                    .unwrap()
+                    .is_zero()
+            );
+            assert_eq!(two.checked_mul(&two).unwrap(), BigUint::from(4u64));
+        });
+    }
+
+    #[test]
+    fn checked_operations_respect_a_lowered_limit() {
+        let x = BigUint::from_limbs(vec![1; 10]).unwrap();
+        limbs::with_max_result_limbs(15, || {
+            assert!(matches!(
+                x.checked_mul(&x),
+                Err(Error::TooLarge { limbs: 20, max: 15 })
+            ));
+            assert!(matches!(x.checked_pow(2), Err(Error::TooLarge { .. })));
+            assert!(matches!(
+                x.checked_shift_left(6),
+                Err(Error::TooLarge { limbs: 16, max: 15 })
+            ));
+            assert_eq!(x.checked_shift_left(5).unwrap(), x.shift_left(5));
+            // Products by zero and one allocate nothing new.
+            assert!(x.mul(&x).checked_mul(&BigUint::zero()).unwrap().is_zero());
+            assert_eq!(x.mul(&x).checked_mul(&BigUint::one()).unwrap(), x.mul(&x));
+        });
+        assert_eq!(limbs::max_result_limbs(), limbs::MAX_RESULT_LIMBS);
+    }
+
+    #[test]
+    fn sqrt_is_floor() {
+        for n in [
+            "0",
+            "1",
+            "3",
+            "4",
+            "999999999999999999",
+            "1000000000000000000",
+        ] {
+            let x = BigUint::from_str(n).unwrap();
+            let r: u128 = x.sqrt().to_string().parse().unwrap();
+            let n: u128 = n.parse().unwrap();
+            assert!(r * r <= n && (r + 1) * (r + 1) > n, "sqrt({})", n);
+        }
+        let one = BigUint::from_str("1").unwrap();
+        let a = BigUint::from_str(&"98765432123456789".repeat(40)).unwrap();
+        let square = a.mul_karatsuba(&a);
+        assert_eq!(square.sqrt(), a);
+        assert_eq!(square.sub(&one).sqrt(), a.sub(&one));
+        assert_eq!(square.add(&a).add(&a).sqrt(), a);
+    }
+
+    #[test]
+    fn ordering_follows_magnitude() {
+        let small = BigUint::from_str("999999999").unwrap();
+        let big = BigUint::from_str("1000000000").unwrap();
+        assert!(small < big);
+        assert_eq!(big.cmp(&big.clone()), cmp::Ordering::Equal);
+    }
+}
@@ -0,0 +1,196 @@
+//! Conversion between decimal limbs and binary: base-2^32 words inside the
+//! crate, and canonical byte strings, in the I2OSP/OS2IP sense of RFC 8017,
+//! for everyone else. Both directions divide and conquer over the powers
+//! `2^(32 * 2^j)`, each computed once by repeated squaring.
+
+use crate::biguint::BigUint;
+use crate::limbs;
+
+impl BigUint {
+    /// The value as little-endian bytes with no high zero bytes; zero is
+    /// `[0]`, as in num-bigint.
+    ///
+    /// ```
+    /// use bigmul::BigUint;
+    ///
+    /// assert_eq!(BigUint::from_str("258").unwrap().to_bytes_le(), [2, 1]);
+    /// assert_eq!(BigUint::zero().to_bytes_le(), [0]);
+    /// ```
+    pub fn to_bytes_le(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = to_words(self)
+            .iter()
+            .flat_map(|w| w.to_le_bytes())
+            .collect();
+        while bytes.len() > 1 && bytes.last() == Some(&0) {
+            bytes.pop();
+        }
+        if bytes.is_empty() {
+            bytes.push(0);
+        }
+        bytes
+    }
+
+    /// The value as big-endian bytes with no leading zero bytes (the
+    /// octet string of I2OSP, before padding to a fixed length); zero is
+    /// `[0]`.
+    ///
+    /// ```
+    /// use bigmul::BigUint;
+    ///
+    /// let n = BigUint::from_str("65537").unwrap();
+    /// assert_eq!(n.to_bytes_be(), [1, 0, 1]);
+    /// // I2OSP(n, 4)
+    /// let mut octets = vec![0; 4 - n.to_bytes_be().len()];
+    /// octets.extend(n.to_bytes_be());
+    /// assert_eq!(octets, [0, 1, 0, 1]);
+    /// ```
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        let mut bytes = self.to_bytes_le();
+        bytes.reverse();
+        bytes
+    }
+
+    /// Reads little-endian bytes; high zero bytes are allowed, and no
+    /// bytes at all is zero.
+    pub fn from_bytes_le(bytes: &[u8]) -> BigUint {
+        let words: Vec<u32> = bytes
+            .chunks(4)
+            .map(|chunk| {
+                let mut word = [0; 4];
+                word[..chunk.len()].copy_from_slice(chunk);
+                u32::from_le_bytes(word)
+            })
+            .collect();
+        from_words(&words)
+    }
+
+    /// Reads big-endian bytes, as OS2IP does; leading zero bytes are
+    /// allowed, and no bytes at all is zero.
+    ///
+    /// ```
+    /// use bigmul::BigUint;
+    ///
+    /// assert_eq!(BigUint::from_bytes_be(&[0, 1, 0, 1]).to_string(), "65537");
+    /// ```
+    pub fn from_bytes_be(bytes: &[u8]) -> BigUint {
+        let reversed: Vec<u8> = bytes.iter().rev().copied().collect();
+        BigUint::from_bytes_le(&reversed)
+    }
+}
+
+/// `2^(32 * 2^j)` for every `j` with `2^j < words`, and at least `2^32`.
+fn powers(words: usize) -> Vec<BigUint> {
+    let mut powers = vec![BigUint::from_kernel(vec![294_967_296, 4])];
+    while let Some(last) = powers.last().filter(|_| (1 << powers.len()) < words) {
+        let square = last.mul_karatsuba(last);
+        powers.push(square);
+    }
+    powers
+}
+
+/// Converts little-endian base-2^32 words into a `BigUint`.
+///
+/// The words are split at the largest power-of-two word count below their
+/// length, so only the powers `2^(32 * 2^j)` are needed.
+pub(crate) fn from_words(words: &[u32]) -> BigUint {
+    from_words_rec(words, &powers(words.len()))
+}
+
+fn from_words_rec(words: &[u32], powers: &[BigUint]) -> BigUint {
+    if words.len() <= 32 {
+        let mut x = BigUint::new();
+        for &w in words.iter().rev() {
+            x = x.mul_direct(&powers[0]).add(&BigUint::from(w as u64));
+        }
+        return x;
+    }
+    let j = (words.len() - 1).ilog2() as usize;
+    let (lo, hi) = words.split_at(1 << j);
+    let hi = from_words_rec(hi, powers).mul_karatsuba(&powers[j]);
+    hi.add(&from_words_rec(lo, powers))
+}
+
+/// Converts a `BigUint` into little-endian base-2^32 words with no high
+/// zero words; zero is empty. The inverse of [`from_words`], splitting by
+/// division at the same powers.
+pub(crate) fn to_words(x: &BigUint) -> Vec<u32> {
+    // A limb holds under 30 bits, so this many words always suffice.
+    let words = (x.as_limbs().len() * 30).div_ceil(32);
+    let mut out = Vec::with_capacity(words);
+    to_words_rec(x.clone(), words, &powers(words), &mut out);
+    while out.last() == Some(&0) {
+        out.pop();
+    }
+    out
+}
+
+/// Appends exactly `words` words of `x`, which must be below
+/// `2^(32 * words)`.
+fn to_words_rec(x: BigUint, words: usize, powers: &[BigUint], out: &mut Vec<u32>) {
+    if words <= 32 {
+        let mut rest = x.into_limbs();
+        for _ in 0..words {
+            let (q, lo) = limbs::div_rem_small(&rest, 1 << 16);
+            let (q, hi) = limbs::div_rem_small(&q, 1 << 16);
+            out.push(hi << 16 | lo);
+            rest = q;
+        }
+        return;
+    }
+    let j = (words - 1).ilog2() as usize;
+    let (hi, lo) = x.div_rem(&powers[j]);
+    to_words_rec(lo, 1 << j, powers, out);
+    to_words_rec(hi, words - (1 << j), powers, out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random_bits;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn from_words_matches_powers_of_two() {
+        assert_eq!(from_words(&[0, 1]).to_string(), "4294967296");
+        let mut words = vec![0u32; 100];
+        words.push(1);
+        let all_ones = vec![u32::MAX; 100];
+        let expected = from_words(&words).sub(&BigUint::from_str("1").unwrap());
+        assert_eq!(from_words(&all_ones), expected);
+    }
+
+    #[test]
+    fn to_words_inverts_from_words() {
+        assert!(to_words(&BigUint::zero()).is_empty());
+        assert_eq!(to_words(&BigUint::from_str("4294967296").unwrap()), [0, 1]);
+        let mut rng = StdRng::seed_from_u64(9);
+        for bits in [1, 31, 32, 33, 64, 1000, 1024, 5000, 40000] {
+            let x = random_bits(bits, &mut rng);
+            let words = to_words(&x);
+            assert_eq!(words.len(), bits.div_ceil(32), "bits={}", bits);
+            assert_eq!(from_words(&words), x, "bits={}", bits);
+        }
+    }
+
+    #[test]
+    fn bytes_round_trip_in_both_orders() {
+        let two_64 = BigUint::from_str("18446744073709551616").unwrap();
+        let mut le = vec![0; 8];
+        le.push(1);
+        assert_eq!(two_64.to_bytes_le(), le);
+        assert_eq!(BigUint::from_bytes_le(&le), two_64);
+        assert_eq!(BigUint::from_bytes_le(&[1, 0, 0, 0, 0, 0]).to_string(), "1");
+        assert!(BigUint::from_bytes_be(&[]).is_zero());
+        assert!(BigUint::from_bytes_be(&[0, 0]).is_zero());
+        assert_eq!(BigUint::zero().to_bytes_be(), [0]);
+        let mut rng = StdRng::seed_from_u64(10);
+        for bits in [8, 9, 100, 2048, 10000] {
+            let x = random_bits(bits, &mut rng);
+            let be = x.to_bytes_be();
+            assert_eq!(be.len(), bits.div_ceil(8));
+            assert_ne!(be[0], 0);
+            assert_eq!(BigUint::from_bytes_be(&be), x);
+            assert_eq!(BigUint::from_bytes_le(&x.to_bytes_le()), x);
+        }
+    }
+}
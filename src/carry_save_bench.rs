@@ -0,0 +1,106 @@
+//! `--carry-save`: the divide-and-conquer recombination step, `p + (r + s)
+//! B^m + q B^2m`, done with one `add_slices` per term and shifted copies as
+//! before, and with the deferred carries of `limbs::CarrySave`, which makes
+//! one pass over the four partial products and one carry pass. The whole
+//! `mul_dc_slices` product is timed alongside to show what share of it the
+//! top-level recombination is.
+
+use crate::interrupt;
+use crate::primitives::{random_limbs, seconds_per_call};
+use bigmul::limbs::{self, CarrySave};
+use std::fmt::Write as _;
+use std::hint::black_box;
+
+/// Operand sizes in limbs: powers of two from 64 to 16384.
+const SIZES: [usize; 9] = [64, 128, 256, 512, 1024, 2048, 4096, 8192, 16384];
+
+fn sequential(p: &[u32], q: &[u32], r: &[u32], s: &[u32], m: usize) -> Vec<u32> {
+    let mid = limbs::add_slices(r, s);
+    let q_shifted = limbs::shift_left_slices(q, 2 * m);
+    let mid_shifted = limbs::shift_left_slices(&mid, m);
+    let temp = limbs::add_slices(&q_shifted, &mid_shifted);
+    limbs::add_slices(&temp, p)
+}
+
+fn carry_save(p: &[u32], q: &[u32], r: &[u32], s: &[u32], m: usize) -> Vec<u32> {
+    let mut sum = CarrySave::new(4 * m);
+    sum.add(p, 0);
+    sum.add(r, m);
+    sum.add(s, m);
+    sum.add(q, 2 * m);
+    sum.finish()
+}
+
+/// Seconds for the sequential and carry-save recombinations of the
+/// top-level partial products of an `n`-limb square product, and for the
+/// whole product.
+fn measure(n: usize) -> [f64; 3] {
+    let m = n / 2;
+    let [p, q, r, s] = [0; 4].map(|_| random_limbs(2 * m));
+    assert_eq!(sequential(&p, &q, &r, &s, m), carry_save(&p, &q, &r, &s, m));
+    let (a, b) = (random_limbs(n), random_limbs(n));
+    [
+        seconds_per_call(|| {
+            black_box(sequential(
+                black_box(&p),
+                black_box(&q),
+                black_box(&r),
+                black_box(&s),
+                m,
+            ));
+        }),
+        seconds_per_call(|| {
+            black_box(carry_save(
+                black_box(&p),
+                black_box(&q),
+                black_box(&r),
+                black_box(&s),
+                m,
+            ));
+        }),
+        seconds_per_call(|| {
+            black_box(limbs::mul_dc_slices(black_box(&a), black_box(&b)));
+        }),
+    ]
+}
+
+/// Runs the comparison, printing both recombination times at each size,
+/// and writes `./assets/carry_save.csv`. Returns `false` if interrupted
+/// before the end.
+pub fn run() -> bool {
+    let mut rows: Vec<(usize, [f64; 3])> = Vec::new();
+    for &n in &SIZES {
+        if interrupt::interrupted() {
+            break;
+        }
+        let [seq, cs, product] = measure(n);
+        println!(
+            "limbs={}, sequential={:.3e} s, carry_save={:.3e} s, speedup={:.2}x, mul_dc={:.3e} s",
+            n,
+            seq,
+            cs,
+            seq / cs,
+            product
+        );
+        rows.push((n, [seq, cs, product]));
+    }
+
+    let mut out =
+        String::from("limbs,sequential_seconds,carry_save_seconds,speedup,mul_dc_seconds\n");
+    for (n, [seq, cs, product]) in &rows {
+        writeln!(
+            out,
+            "{},{:.12},{:.12},{:.3},{:.12}",
+            n,
+            seq,
+            cs,
+            seq / cs,
+            product
+        )
+        .unwrap();
+    }
+    std::fs::create_dir_all("./assets").expect("Failed to create ./assets directory");
+    std::fs::write("./assets/carry_save.csv", out).expect("Failed to write CSV results");
+    println!("Results saved to ./assets/carry_save.csv");
+    rows.len() == SIZES.len()
+}
@@ -0,0 +1,79 @@
+//! How the sweep checks that every algorithm computed the same product,
+//! each time a product's timing stops. `full` keeps the first algorithm's
+//! product for the pair and compares the others with it limb by limb;
+//! `residue` reduces each product to its residues modulo a fixed 64-bit
+//! prime and modulo 9 and compares those with the residues of the operands'
+//! product, one pass and no retained product per multiplication, for sweeps
+//! at millions of digits; `none` drops every product unchecked. Either way a
+//! product is freed before the next timing starts.
+
+use bigmul::BigUint;
+
+/// Exit status when two products that must agree do not, from the sweep,
+/// `--quick-check` or `verify`.
+pub const EXIT_MISMATCH: i32 = 3;
+
+/// The largest prime below 2^64.
+const PRIME: u64 = 18_446_744_073_709_551_557;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Check {
+    #[default]
+    Full,
+    Residue,
+    None,
+}
+
+/// Residues of a value modulo `PRIME` and modulo 9.
+pub type Fingerprint = (u64, u64);
+
+impl Check {
+    /// Parses a `--check` value.
+    pub fn parse(s: &str) -> Result<Check, String> {
+        match s {
+            "full" => Ok(Check::Full),
+            "residue" => Ok(Check::Residue),
+            "none" => Ok(Check::None),
+            _ => Err(format!(
+                "unknown check '{}' (expected full, residue or none)",
+                s
+            )),
+        }
+    }
+}
+
+/// The fingerprint every correct product of `a` and `b` has.
+pub fn expected(a: &BigUint, b: &BigUint) -> Fingerprint {
+    let residue = a.mod_small(PRIME) as u128 * b.mod_small(PRIME) as u128 % PRIME as u128;
+    (residue as u64, a.mod_small(9) * b.mod_small(9) % 9)
+}
+
+/// The fingerprint of a product returned as limbs; `None` if a limb is out
+/// of range, which no correct product has.
+pub fn fingerprint(product: Vec<u32>) -> Option<Fingerprint> {
+    let product = BigUint::from_limbs(product)?;
+    Some((product.mod_small(PRIME), product.digit_sum() % 9))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprints_tell_products_apart() {
+        let a = BigUint::from_str(&"123456789".repeat(40)).unwrap();
+        let b = BigUint::from_str(&"987654321".repeat(35)).unwrap();
+        let product = a.mul(&b);
+        assert_eq!(
+            fingerprint(product.as_limbs().to_vec()),
+            Some(expected(&a, &b))
+        );
+        let mut wrong = product.into_limbs();
+        wrong[3] += 1;
+        assert_ne!(fingerprint(wrong.clone()), Some(expected(&a, &b)));
+        wrong[3] = 1_000_000_000;
+        assert_eq!(fingerprint(wrong), None);
+        assert_eq!(Check::parse("residue"), Ok(Check::Residue));
+        assert!(Check::parse("sha256").is_err());
+    }
+}
@@ -0,0 +1,78 @@
+//! Cheap fingerprints of huge values, for spot-checking a product without
+//! recomputing it: `(a * b) mod m = (a mod m)(b mod m) mod m` for any `m`,
+//! and casting out nines is the case `m = 9` done with the digit sum. Both
+//! take one pass over the limbs.
+
+use crate::biguint::BigUint;
+use crate::limbs::BASE;
+
+impl BigUint {
+    /// `self mod m`, by Horner's rule over the limbs in 128-bit arithmetic.
+    /// Panics if `m` is zero.
+    ///
+    /// ```
+    /// use bigmul::BigUint;
+    ///
+    /// let a = BigUint::from_str("123456789012345678901234567890").unwrap();
+    /// let b = BigUint::from_str("987654321098765432109876543210").unwrap();
+    /// let p = 18446744073709551557; // the largest 64-bit prime
+    /// let expected = a.mod_small(p) as u128 * b.mod_small(p) as u128 % p as u128;
+    /// assert_eq!(a.mul(&b).mod_small(p) as u128, expected);
+    /// ```
+    pub fn mod_small(&self, m: u64) -> u64 {
+        assert!(m != 0, "mod_small: modulus is zero");
+        let m = m as u128;
+        self.as_limbs()
+            .iter()
+            .rev()
+            .fold(0u128, |r, &d| (r * BASE as u128 + d as u128) % m) as u64
+    }
+
+    /// Sum of the decimal digits; congruent to `self` modulo 9.
+    ///
+    /// ```
+    /// use bigmul::BigUint;
+    ///
+    /// assert_eq!(BigUint::from_str("1000000007").unwrap().digit_sum(), 8);
+    /// ```
+    pub fn digit_sum(&self) -> u64 {
+        self.as_limbs()
+            .iter()
+            .map(|&d| {
+                let (mut d, mut sum) = (d, 0);
+                while d > 0 {
+                    sum += (d % 10) as u64;
+                    d /= 10;
+                }
+                sum
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn residues_match_string_arithmetic() {
+        let n = BigUint::from_str(&"9876543210".repeat(30)).unwrap();
+        for m in [1, 2, 9, 10, 1_000_000_000, 1_000_000_007, u64::MAX] {
+            let expected = n
+                .to_string()
+                .bytes()
+                .fold(0u128, |r, b| (r * 10 + (b - b'0') as u128) % m as u128);
+            assert_eq!(n.mod_small(m) as u128, expected, "m={}", m);
+        }
+        assert_eq!(BigUint::zero().mod_small(7), 0);
+        assert_eq!(n.digit_sum(), 45 * 30);
+        assert_eq!(n.digit_sum() % 9, n.mod_small(9));
+        assert_eq!(BigUint::zero().digit_sum(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "modulus is zero")]
+    fn zero_modulus_panics() {
+        BigUint::one().mod_small(0);
+    }
+}
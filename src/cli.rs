@@ -0,0 +1,1205 @@
+//! Command-line options for the benchmark binary.
+
+use crate::check::Check;
+use crate::completions::Shell;
+use crate::inputs::{self, Axis, InputClass};
+use crate::timer::Timer;
+use crate::verify::Oracle;
+use bigmul::{BigInt, BigUint, Notation};
+use std::io::IsTerminal;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: bigmul [OPTIONS]
+       bigmul rsa-demo [--bits <N>]
+       bigmul compute-pi [--digits <N>]
+       bigmul mul <A> <B> [--full | --group <SEP> | --scientific <P> |
+                           --engineering <P>] [--algorithm <NAME>]
+                          [--digits-estimate [--fallback]]
+       bigmul mul-disk <A> <B> --out <FILE> [--block <LIMBS>]
+                       [--max-limbs <N>]
+       bigmul factor <N>
+       bigmul dlog <G> <H> <MOD>
+       bigmul bench-primitives
+       bigmul gen --digits <N> --count <C> --out <DIR> [--seed <S>]
+                  [--input-class <CLASS>]
+       bigmul profile --algorithm <NAME> [--digits <N>] [--seconds <S>]
+       bigmul verify [--iterations <N>] [--max-digits <D>] [--seed <S>]
+                     [--paranoid] [--oracle <ORACLE>]... [--quiet]
+                     [--output <FORMAT>]
+       bigmul merge <FILE>... --out <FILE> [--algorithm <NAME>]
+       bigmul history --algorithm <NAME> --digits <N> [--db <FILE>]
+       bigmul completions <SHELL>
+       bigmul man
+
+Commands:
+  rsa-demo               Generate an RSA keypair with an N-bit modulus
+                         (N >= 512) [default: 2048], encrypt and decrypt a
+                         sample message, and report the time spent in each
+                         phase
+  compute-pi             Compute pi to N decimal places [default: 100000]
+                         with the Chudnovsky series, timing each phase over
+                         a doubling sweep of digit counts; writes
+                         assets/compute_pi.csv and assets/compute_pi.png
+  mul                    Print the product of the integers A and B (either
+                         may be @FILE to read it from FILE): in full, in
+                         groups of three digits joined by SEP (such as _ ,
+                         or a space), or with P digits after the point in
+                         scientific or engineering notation. Without a
+                         flag, a terminal gets only the first and last
+                         6 digits and the digit count, and a pipe or file
+                         every digit; --full prints every digit anywhere.
+                         --algorithm multiplies with one registered
+                         algorithm instead of the library's choice.
+                         --digits-estimate first estimates the peak memory
+                         of the product from the operands' lengths and
+                         stops if it exceeds available memory (warning
+                         above 80%); --fallback instead switches to the
+                         fastest of kara, dc and direct that fits
+  mul-disk               Multiply the non-negative integers in the decimal
+                         files A and B into the decimal file FILE without
+                         holding them in memory: the operands and product
+                         are memory-mapped limb files next to FILE, and
+                         the product is summed from products of
+                         LIMBS-limb blocks [default: 1048576], each done
+                         in memory. A product of more than N limbs is
+                         refused before it is computed (Unix only)
+  factor                 Factor the integer N completely: trial division up
+                         to 10,000, then Pollard's rho, with Baillie-PSW
+                         deciding primality; prints the factors and the
+                         time spent in each phase
+  dlog                   Find the least x >= 0 with G^x = H (mod MOD) by
+                         baby-step giant-step; MOD has at most 44 bits and
+                         G must be coprime to it
+  bench-primitives       Measure add, sub and shift throughput in limbs per
+                         second from 16 to 65536 limbs, and the cost of one
+                         Karatsuba product in additions of the same size;
+                         writes assets/primitives.csv and
+                         assets/primitives.png
+  gen                    Write C reproducible pairs of N-digit operands to
+                         DIR in the --operands layout, one pair per file as
+                         two decimal lines, with the seed and generator
+                         version recorded in DIR/manifest.txt; a random
+                         seed is drawn when --seed is absent. A seed only
+                         reproduces a set of the same generator version
+  profile                Multiply two random N-digit operands [default:
+                         50000] with one algorithm (registry name such as
+                         kara, or its label such as karatsuba) in a loop for
+                         S seconds [default: 10], for attaching perf or
+                         cargo flamegraph; build with `--profile profiling`
+                         to keep symbols
+  merge                  Combine results.json files from several machines
+                         (each tagged with its host) into one JSON file and
+                         chart every algorithm once per host in a PNG next
+                         to it, or only NAME with --algorithm
+  history                Show the mean and fastest time of one algorithm
+                         (registry name or label) at N digits for every run
+                         recorded with --db in FILE [default: bench.sqlite],
+                         oldest first, with the commit each run was built
+                         from
+  verify                 Multiply N random pairs [default: 200] of up to D
+                         digits each [default: 2000], drawn from every input
+                         class at independent sizes, with every algorithm
+                         and compare against direct multiplication; exits
+                         with status 3 on any mismatch. --paranoid turns on
+                         the kernels' invariant checks (limb range, borrow
+                         underflow, sub-product placement) in release builds
+                         --oracle adds an independent implementation to
+                         the comparison and names it on disagreement:
+                         `python` (python3 built-in integers) or NAME=PATH
+                         for a program speaking the --external protocol,
+                         such as a num-bigint or rug wrapper (repeatable).
+                         --quiet and --output work as for the sweep; the
+                         JSON lists every mismatch
+  completions            Print a completion script for SHELL: bash, zsh or
+                         fish. Flags taking a FILE or DIR complete file
+                         names
+  man                    Print the man page in roff, to install as
+                         bigmul.1 or read with `bigmul man | man -l -`
+
+Options:
+  --input-class <CLASS>  Operand generator: random, all-nines, power-of-ten,
+                         repunit, zero-runs, fibonacci [default: random]
+  --x-axis <UNIT>        Size unit for generation and the chart: bits, digits,
+                         limbs [default: digits]
+  --corpus <DIR>         Benchmark the operand pairs stored in DIR instead of
+                         generated inputs (one pair per file, one operand
+                         per line)
+  --save-operands <DIR>  Write the sweep's operands to DIR (created if
+                         missing) before timing, for replay with --operands
+  --operands <DIR>       Replay operands saved with --save-operands instead
+                         of generating new ones
+  --external <NAME=PATH> Also benchmark the program at PATH under NAME
+                         (repeatable). It receives the two operands as
+                         decimal lines on stdin and must answer each pair
+                         with the product as one decimal line on stdout.
+                         Builds with `--features ibig,malachite` chart those
+                         crates without an external program
+  --db <FILE>            Append every timed multiplication of the sweep, with
+                         the run's start time, git commit and input class,
+                         to the SQLite database FILE (created if missing;
+                         needs a build with `--features sqlite`)
+  --budget <SECONDS>     Time each algorithm at each point until it has spent
+                         SECONDS there instead of a fixed 10 multiplications
+                         (3 with --large): many samples at small sizes,
+                         as few as one at large ones
+  --timer <CLOCK>        Clock for timing: monotonic (the OS clock), tsc
+                         (x86-64 time-stamp counter, for sub-microsecond
+                         kernels; needs an invariant TSC) or ops (counts limb
+                         operations instead of time, on seeded operands, so
+                         results and charts are identical on every machine)
+                         [default: monotonic]. The clock's resolution is
+                         printed at startup, with a warning for results close
+                         to it
+  --check <MODE>         How the algorithms' products are checked against
+                         each other, always outside the timed regions: full
+                         (every limb; keeps the first product of each pair
+                         in memory), residue (residues modulo a 64-bit prime
+                         and modulo 9, one pass per product and none kept,
+                         for very large sweeps) or none [default: full].
+                         Every other product is freed before the next
+                         timing starts
+  --quick-check          Check that every algorithm agrees on one seeded pair
+                         of each input class at a few sizes spanning the
+                         kernels' thresholds, without writing results or
+                         charts; stops with exit status 3 on the first
+                         mismatch. For CI: `cargo test` runs it
+  --quiet                Print nothing but errors: no banner, per-point
+                         lines, summaries or warnings. Results files and
+                         charts are still written
+  --output <FORMAT>      What the sweep prints on stdout: text (the
+                         per-point lines and summaries) or json (only the
+                         document written to assets/results.json, once the
+                         sweep is done) [default: text]. Combine with
+                         --quiet to script the binary: stdout is then
+                         exactly one JSON document. Applies to the sweep,
+                         --quick-check and verify
+  --markdown             Also write a GitHub-flavored Markdown table of median
+                         times and speedups over the first algorithm to
+                         assets/results.md and print it
+  --gnuplot <FILE>       Also write the average times to FILE as gnuplot
+                         data, with a script FILE.plt next to it that redraws
+                         the comparison chart (run `gnuplot FILE.plt`)
+  --pgfplots <FILE>      Also write the comparison chart to FILE as a LaTeX
+                         pgfplots/TikZ picture with error bars of one
+                         standard deviation, to include in a LaTeX paper
+  --energy               Also record package energy per multiplication from
+                         Intel RAPL (Linux powercap; usually needs root)
+  --perf                 Also record instructions, cycles, cache misses and
+                         branch misses per multiplication (Linux, needs a
+                         build with `--features perf`)
+  --smooth <N>           Draw each point of the charts as the mean of the N
+                         points around it, for noisy dense sweeps; results
+                         files keep the measured values [default: 1]
+  --max-points <N>       Draw at most N evenly spaced points of each chart
+                         line, the last always among them; results files
+                         keep every point
+  --panels               Also measure each algorithm's peak heap and limb
+                         operations at every point, in one untimed product
+                         each, and chart them under the times as panels of
+                         one figure; writes assets/panels.csv and
+                         assets/panels.png
+  --large                Skip the quadratic algorithms and sweep geometrically
+                         spaced sizes up to 2,000,000 digits (log-log chart)
+  --pedagogical          Also chart the educational baselines (direct and
+                         four-product divide and conquer) and the practical
+                         algorithms separately, each on its own scale; writes
+                         assets/multiplication_times_baselines.png and
+                         assets/multiplication_times_practical.png
+  --mod-pow              Compare modular exponentiation window strategies
+                         (binary, 4-bit window, sliding) across exponent
+                         sizes with a 2048-bit modulus; writes
+                         assets/mod_pow.csv
+  --primality            Compare Miller-Rabin and Solovay-Strassen at 1 to 8
+                         rounds: time to accept a 512-bit prime and how many
+                         of 100 Carmichael numbers pass; writes
+                         assets/primality.csv
+  --mod-mul              Measure modular multiplications per second with long
+                         division, Barrett and Montgomery reduction across
+                         modulus sizes; writes assets/mod_mul.csv
+  --fixed-width          Compare the stack-allocated FixedUint with BigUint in
+                         multiplications per second at 256 to 4096 bits;
+                         writes assets/fixed_width.csv
+  --wrapper-overhead     Time the same product through the slice kernels,
+                         BigUint, BigInt and BigInt with cloned operands from
+                         1 to 256 limbs and report each layer's overhead;
+                         writes assets/wrapper_overhead.csv and
+                         assets/wrapper_overhead.png
+  --shared-clone         Compare cloning BigInt with the reference-counted
+                         SharedBigInt from 1,000 to 1,000,000 digits, alone
+                         and in a workload that hands out 64 copies and
+                         negates every eighth; writes assets/shared_clone.csv
+  --mixed-sizes          Run a loop over 100 values, 0% to 100% of them small
+                         (two limbs), with BigUint's inline limbs and with
+                         always-allocating Vec limbs; writes
+                         assets/mixed_sizes.csv
+  --square               Compare squaring with a * a against the dedicated
+                         schoolbook and Karatsuba squaring kernels from 8 to
+                         8192 limbs; writes assets/square.csv and
+                         assets/square.png
+  --carry-save           Time divide and conquer's recombination with one
+                         addition pass per partial product against deferred
+                         carry-save accumulation, from 64 to 16384 limbs;
+                         writes assets/carry_save.csv
+  --matrix               Multiply square matrices of 500-digit BigInts
+                         naively and by Strassen's method from 8x8 to
+                         64x64; writes assets/matrix.csv and
+                         assets/matrix.png
+  --bases                Time direct and Karatsuba multiplication with limbs
+                         in base 10^4, 10^9 and 2^32 from 100 to 30,000
+                         digits and report where Karatsuba starts to win in
+                         each; writes assets/bases.csv and assets/bases.png
+  --end-to-end           Time parsing two decimal operands, multiplying them
+                         with each algorithm and printing the product, from
+                         5,000 to 80,000 digits; writes assets/end_to_end.csv
+                         and a phase chart per algorithm,
+                         assets/end_to_end_<name>.png
+  --heatmap              Time every algorithm on each pair of operand lengths
+                         from 250 to 32,000 digits and chart the fastest per
+                         cell; writes assets/heatmap.csv and
+                         assets/heatmap.png
+  --scaling              Multiply 200,000-digit operands with each parallel
+                         algorithm at 1 to num_cpus threads; writes speedup
+                         and efficiency to assets/scaling.csv and
+                         assets/scaling.png
+  -h, --help             Print this help
+
+Exit status:
+  0    Success
+  1    An error, such as an unreadable file or a failed external program
+  2    Invalid arguments
+  3    Two algorithms, or an algorithm and the reference, disagreed on a
+       product (sweep, --quick-check and verify)
+  130  Interrupted by Ctrl-C; partial results were written
+
+Environment:
+  BIGMUL_KARATSUBA_THRESHOLD       Limbs at or below which Karatsuba,
+                                   Karatsuba squaring and the short and
+                                   middle products multiply directly
+                                   [default: 32]
+  BIGMUL_DC_THRESHOLD              The same for divide and conquer
+                                   [default: 32]
+  BIGMUL_PAR_KARATSUBA_THRESHOLD   Limbs below which parallel Karatsuba
+                                   stops spawning threads [default: 1024]
+";
+
+/// What the binary was asked to do.
+pub enum Command {
+    Bench(Config),
+    RsaDemo {
+        bits: usize,
+    },
+    ComputePi {
+        digits: usize,
+    },
+    Mul {
+        a: Operand,
+        b: Operand,
+        notation: Notation,
+        algorithm: Option<String>,
+        estimate: bool,
+        fallback: bool,
+    },
+    MulDisk {
+        a: PathBuf,
+        b: PathBuf,
+        out: PathBuf,
+        block: usize,
+        max_limbs: Option<usize>,
+    },
+    Factor {
+        n: BigInt,
+    },
+    Dlog {
+        g: BigUint,
+        h: BigUint,
+        modulus: BigUint,
+    },
+    BenchPrimitives,
+    Gen {
+        input_class: InputClass,
+        digits: usize,
+        count: usize,
+        seed: Option<u64>,
+        out: PathBuf,
+    },
+    Profile {
+        algorithm: String,
+        digits: usize,
+        seconds: f64,
+    },
+    Merge {
+        inputs: Vec<PathBuf>,
+        out: PathBuf,
+        algorithm: Option<String>,
+    },
+    History {
+        db: PathBuf,
+        algorithm: String,
+        digits: usize,
+    },
+    Verify {
+        iterations: usize,
+        max_digits: usize,
+        seed: Option<u64>,
+        paranoid: bool,
+        oracles: Vec<Oracle>,
+        quiet: bool,
+        output: Output,
+    },
+    Completions {
+        shell: Shell,
+    },
+    Man,
+}
+
+/// What the sweep and `verify` print on stdout.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Output {
+    #[default]
+    Text,
+    Json,
+}
+
+impl Output {
+    /// Parses an `--output` value.
+    pub fn parse(s: &str) -> Result<Output, String> {
+        match s {
+            "text" => Ok(Output::Text),
+            "json" => Ok(Output::Json),
+            _ => Err(format!(
+                "unknown output format '{}' (expected text or json)",
+                s
+            )),
+        }
+    }
+}
+
+impl Command {
+    /// Parses the process arguments, printing usage and exiting on `--help`
+    /// or on invalid input.
+    pub fn from_env() -> Command {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        if args.iter().any(|a| a == "-h" || a == "--help") {
+            print!("{}", USAGE);
+            std::process::exit(0);
+        }
+        match Command::from_args(args) {
+            Ok(command) => command,
+            Err(msg) => {
+                eprintln!("error: {}\n\n{}", msg, USAGE);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Command, String> {
+        let mut args = args.into_iter().peekable();
+        match args.peek().map(String::as_str) {
+            Some("rsa-demo") => {
+                args.next();
+                let mut bits = 2048;
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--bits" => {
+                            let value = value_of(&arg, args.next())?;
+                            bits = match value.parse::<usize>() {
+                                Ok(b) if b >= 512 => b,
+                                _ => {
+                                    return Err(format!(
+                                        "invalid bit count '{}' (expected an integer >= 512)",
+                                        value
+                                    ));
+                                }
+                            };
+                        }
+                        _ => return Err(format!("unexpected argument '{}'", arg)),
+                    }
+                }
+                Ok(Command::RsaDemo { bits })
+            }
+            Some("compute-pi") => {
+                args.next();
+                let mut digits = 100_000;
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--digits" => {
+                            let value = value_of(&arg, args.next())?;
+                            digits = match value.parse::<usize>() {
+                                Ok(d) if d >= 1 => d,
+                                _ => {
+                                    return Err(format!(
+                                        "invalid digit count '{}' (expected a positive integer)",
+                                        value
+                                    ));
+                                }
+                            };
+                        }
+                        _ => return Err(format!("unexpected argument '{}'", arg)),
+                    }
+                }
+                Ok(Command::ComputePi { digits })
+            }
+            Some("mul") => {
+                args.next();
+                let mut operands = Vec::new();
+                let mut notation = None;
+                let mut algorithm = None;
+                let (mut estimate, mut fallback) = (false, false);
+                while let Some(arg) = args.next() {
+                    let flag = match arg.as_str() {
+                        "--algorithm" => {
+                            let value = value_of(&arg, args.next())?;
+                            let m = crate::profile::find(&value)
+                                .ok_or_else(|| format!("unknown algorithm '{}'", value))?;
+                            algorithm = Some(m.name().to_string());
+                            continue;
+                        }
+                        "--digits-estimate" => {
+                            estimate = true;
+                            continue;
+                        }
+                        "--fallback" => {
+                            fallback = true;
+                            continue;
+                        }
+                        "--full" => Notation::Plain,
+                        "--group" => Notation::Grouped(value_of(&arg, args.next())?),
+                        "--scientific" | "--engineering" => {
+                            let value = value_of(&arg, args.next())?;
+                            let precision = value.parse::<usize>().map_err(|_| {
+                                format!(
+                                    "invalid value '{}' for {} (expected a non-negative integer)",
+                                    value, arg
+                                )
+                            })?;
+                            if arg == "--scientific" {
+                                Notation::Scientific { precision }
+                            } else {
+                                Notation::Engineering { precision }
+                            }
+                        }
+                        _ if operands.len() < 2 && !arg.starts_with("--") => {
+                            operands.push(Operand::parse(&arg)?);
+                            continue;
+                        }
+                        _ => return Err(format!("unexpected argument '{}'", arg)),
+                    };
+                    if notation.replace(flag).is_some() {
+                        return Err(
+                            "--full, --group, --scientific and --engineering cannot be combined"
+                                .to_string(),
+                        );
+                    }
+                }
+                let [a, b]: [Operand; 2] = operands
+                    .try_into()
+                    .map_err(|_| "mul requires two operands")?;
+                if fallback && !estimate {
+                    return Err("--fallback needs --digits-estimate".to_string());
+                }
+                Ok(Command::Mul {
+                    a,
+                    b,
+                    algorithm,
+                    estimate,
+                    fallback,
+                    notation: notation.unwrap_or_else(|| {
+                        if std::io::stdout().is_terminal() {
+                            Notation::Truncated { keep: 6 }
+                        } else {
+                            Notation::Plain
+                        }
+                    }),
+                })
+            }
+            Some("mul-disk") => {
+                args.next();
+                let mut files = Vec::new();
+                let mut out = None;
+                let mut block = crate::out_of_core::DEFAULT_BLOCK;
+                let mut max_limbs = None;
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--out" => out = Some(PathBuf::from(value_of(&arg, args.next())?)),
+                        "--block" => {
+                            let value = value_of(&arg, args.next())?;
+                            block = match value.parse::<usize>() {
+                                Ok(b) if b >= 1 => b,
+                                _ => {
+                                    return Err(format!(
+                                        "invalid block size '{}' (expected a positive integer)",
+                                        value
+                                    ));
+                                }
+                            };
+                        }
+                        "--max-limbs" => {
+                            let value = value_of(&arg, args.next())?;
+                            max_limbs = Some(value.parse::<usize>().map_err(|_| {
+                                format!(
+                                    "invalid limb limit '{}' (expected a non-negative integer)",
+                                    value
+                                )
+                            })?);
+                        }
+                        _ if files.len() < 2 && !arg.starts_with("--") => {
+                            files.push(PathBuf::from(arg));
+                        }
+                        _ => return Err(format!("unexpected argument '{}'", arg)),
+                    }
+                }
+                let [a, b]: [PathBuf; 2] = files
+                    .try_into()
+                    .map_err(|_| "mul-disk requires two operand files")?;
+                let out = out.ok_or("mul-disk requires --out")?;
+                Ok(Command::MulDisk {
+                    a,
+                    b,
+                    out,
+                    block,
+                    max_limbs,
+                })
+            }
+            Some("factor") => {
+                args.next();
+                let value = args.next().ok_or("factor requires a number")?;
+                if let Some(arg) = args.next() {
+                    return Err(format!("unexpected argument '{}'", arg));
+                }
+                let n = parse_integer(&value).filter(|n| n.magnitude() >= &BigUint::from(2u64));
+                let Some(n) = n else {
+                    return Err(format!(
+                        "invalid number '{}' (expected an integer of magnitude at least 2)",
+                        value
+                    ));
+                };
+                Ok(Command::Factor { n })
+            }
+            Some("dlog") => {
+                args.next();
+                let mut values = Vec::new();
+                for arg in args.by_ref() {
+                    if values.len() == 3 {
+                        return Err(format!("unexpected argument '{}'", arg));
+                    }
+                    let value = BigUint::from_str(&arg).map_err(|_| {
+                        format!("invalid number '{}' (expected a non-negative integer)", arg)
+                    })?;
+                    values.push(value);
+                }
+                let [g, h, modulus]: [BigUint; 3] = values
+                    .try_into()
+                    .map_err(|_| "dlog requires G, H and MOD")?;
+                if modulus < BigUint::from(2u64) || modulus.bits() > crate::dlog::MAX_BITS {
+                    return Err(format!(
+                        "invalid modulus '{}' (expected at least 2 and at most {} bits)",
+                        modulus,
+                        crate::dlog::MAX_BITS
+                    ));
+                }
+                if !g.gcd(&modulus).is_one() {
+                    return Err(format!("{} is not coprime to {}", g, modulus));
+                }
+                Ok(Command::Dlog { g, h, modulus })
+            }
+            Some("bench-primitives") => {
+                args.next();
+                match args.next() {
+                    Some(arg) => Err(format!("unexpected argument '{}'", arg)),
+                    None => Ok(Command::BenchPrimitives),
+                }
+            }
+            Some("gen") => {
+                args.next();
+                let mut input_class = InputClass::Random;
+                let mut digits = None;
+                let mut count = None;
+                let mut seed = None;
+                let mut out = None;
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--input-class" => {
+                            input_class = parse_input_class(&value_of(&arg, args.next())?)?;
+                        }
+                        "--digits" | "--count" => {
+                            let value = value_of(&arg, args.next())?;
+                            let n = match value.parse::<usize>() {
+                                Ok(n) if n >= 1 => n,
+                                _ => {
+                                    return Err(format!(
+                                        "invalid value '{}' for {} (expected a positive integer)",
+                                        value, arg
+                                    ));
+                                }
+                            };
+                            if arg == "--digits" {
+                                digits = Some(n);
+                            } else {
+                                count = Some(n);
+                            }
+                        }
+                        "--seed" => {
+                            let value = value_of(&arg, args.next())?;
+                            seed = Some(value.parse::<u64>().map_err(|_| {
+                                format!("invalid seed '{}' (expected an unsigned integer)", value)
+                            })?);
+                        }
+                        "--out" => out = Some(PathBuf::from(value_of(&arg, args.next())?)),
+                        _ => return Err(format!("unexpected argument '{}'", arg)),
+                    }
+                }
+                Ok(Command::Gen {
+                    input_class,
+                    digits: digits.ok_or("gen requires --digits")?,
+                    count: count.ok_or("gen requires --count")?,
+                    seed,
+                    out: out.ok_or("gen requires --out")?,
+                })
+            }
+            Some("profile") => {
+                args.next();
+                let mut algorithm = None;
+                let mut digits = 50_000;
+                let mut seconds = 10.0;
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--algorithm" => {
+                            let value = value_of(&arg, args.next())?;
+                            if crate::profile::find(&value).is_none() {
+                                let names: Vec<String> = crate::multiplier::registry()
+                                    .iter()
+                                    .map(|m| m.name().to_string())
+                                    .collect();
+                                return Err(format!(
+                                    "unknown algorithm '{}' (expected one of: {})",
+                                    value,
+                                    names.join(", ")
+                                ));
+                            }
+                            algorithm = Some(value);
+                        }
+                        "--digits" => {
+                            let value = value_of(&arg, args.next())?;
+                            digits = match value.parse::<usize>() {
+                                Ok(d) if d >= 1 => d,
+                                _ => {
+                                    return Err(format!(
+                                        "invalid digit count '{}' (expected a positive integer)",
+                                        value
+                                    ));
+                                }
+                            };
+                        }
+                        "--seconds" => {
+                            let value = value_of(&arg, args.next())?;
+                            seconds = match value.parse::<f64>() {
+                                Ok(s) if s > 0.0 && s.is_finite() => s,
+                                _ => {
+                                    return Err(format!(
+                                        "invalid duration '{}' (expected a positive number of seconds)",
+                                        value
+                                    ));
+                                }
+                            };
+                        }
+                        _ => return Err(format!("unexpected argument '{}'", arg)),
+                    }
+                }
+                let algorithm = algorithm.ok_or("profile requires --algorithm")?;
+                Ok(Command::Profile {
+                    algorithm,
+                    digits,
+                    seconds,
+                })
+            }
+            Some("merge") => {
+                args.next();
+                let mut inputs = Vec::new();
+                let mut out = None;
+                let mut algorithm = None;
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--out" => out = Some(PathBuf::from(value_of(&arg, args.next())?)),
+                        "--algorithm" => algorithm = Some(value_of(&arg, args.next())?),
+                        _ if arg.starts_with("--") => {
+                            return Err(format!("unexpected argument '{}'", arg));
+                        }
+                        _ => inputs.push(PathBuf::from(arg)),
+                    }
+                }
+                if inputs.is_empty() {
+                    return Err("merge requires at least one results file".to_string());
+                }
+                Ok(Command::Merge {
+                    inputs,
+                    out: out.ok_or("merge requires --out")?,
+                    algorithm,
+                })
+            }
+            Some("history") => {
+                args.next();
+                let mut db = PathBuf::from("bench.sqlite");
+                let mut algorithm = None;
+                let mut digits = None;
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--db" => db = PathBuf::from(value_of(&arg, args.next())?),
+                        "--algorithm" => algorithm = Some(value_of(&arg, args.next())?),
+                        "--digits" => {
+                            let value = value_of(&arg, args.next())?;
+                            digits = match value.parse::<usize>() {
+                                Ok(d) if d >= 1 => Some(d),
+                                _ => {
+                                    return Err(format!(
+                                        "invalid digit count '{}' (expected a positive integer)",
+                                        value
+                                    ));
+                                }
+                            };
+                        }
+                        _ => return Err(format!("unexpected argument '{}'", arg)),
+                    }
+                }
+                Ok(Command::History {
+                    db,
+                    algorithm: algorithm.ok_or("history requires --algorithm")?,
+                    digits: digits.ok_or("history requires --digits")?,
+                })
+            }
+            Some("verify") => {
+                args.next();
+                let mut iterations = 200;
+                let mut max_digits = 2000;
+                let mut seed = None;
+                let mut paranoid = false;
+                let mut oracles = Vec::new();
+                let mut quiet = false;
+                let mut output = Output::Text;
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--iterations" | "--max-digits" => {
+                            let value = value_of(&arg, args.next())?;
+                            let n = match value.parse::<usize>() {
+                                Ok(n) if n >= 1 => n,
+                                _ => {
+                                    return Err(format!(
+                                        "invalid value '{}' for {} (expected a positive integer)",
+                                        value, arg
+                                    ));
+                                }
+                            };
+                            if arg == "--iterations" {
+                                iterations = n;
+                            } else {
+                                max_digits = n;
+                            }
+                        }
+                        "--seed" => {
+                            let value = value_of(&arg, args.next())?;
+                            seed = Some(value.parse::<u64>().map_err(|_| {
+                                format!("invalid seed '{}' (expected an unsigned integer)", value)
+                            })?);
+                        }
+                        "--paranoid" => paranoid = true,
+                        "--quiet" => quiet = true,
+                        "--output" => output = Output::parse(&value_of(&arg, args.next())?)?,
+                        "--oracle" => {
+                            let value = value_of(&arg, args.next())?;
+                            oracles.push(match value.split_once('=') {
+                                _ if value == "python" => Oracle::Python,
+                                Some((name, path)) if !name.is_empty() && !path.is_empty() => {
+                                    Oracle::Program(name.to_string(), PathBuf::from(path))
+                                }
+                                _ => {
+                                    return Err(format!(
+                                        "invalid oracle '{}' (expected python or NAME=PATH)",
+                                        value
+                                    ));
+                                }
+                            });
+                        }
+                        _ => return Err(format!("unexpected argument '{}'", arg)),
+                    }
+                }
+                Ok(Command::Verify {
+                    iterations,
+                    max_digits,
+                    seed,
+                    paranoid,
+                    oracles,
+                    quiet,
+                    output,
+                })
+            }
+            Some("completions") => {
+                args.next();
+                let shell = args.next().ok_or("completions requires a shell")?;
+                let shell = Shell::parse(&shell)?;
+                match args.next() {
+                    Some(arg) => Err(format!("unexpected argument '{}'", arg)),
+                    None => Ok(Command::Completions { shell }),
+                }
+            }
+            Some("man") => {
+                args.next();
+                match args.next() {
+                    Some(arg) => Err(format!("unexpected argument '{}'", arg)),
+                    None => Ok(Command::Man),
+                }
+            }
+            _ => Config::from_args(args).map(Command::Bench),
+        }
+    }
+}
+
+pub struct Config {
+    pub input_class: InputClass,
+    pub x_axis: Axis,
+    pub corpus: Option<PathBuf>,
+    pub operands: Option<PathBuf>,
+    pub save_operands: Option<PathBuf>,
+    pub db: Option<PathBuf>,
+    pub markdown: bool,
+    pub gnuplot: Option<PathBuf>,
+    pub pgfplots: Option<PathBuf>,
+    pub budget: Option<f64>,
+    pub smooth: usize,
+    pub panels: bool,
+    pub max_points: Option<usize>,
+    pub timer: Timer,
+    pub check: Check,
+    pub quick_check: bool,
+    pub quiet: bool,
+    pub output: Output,
+    pub external: Vec<(String, PathBuf)>,
+    pub large: bool,
+    pub energy: bool,
+    pub perf: bool,
+    pub mod_pow: bool,
+    pub mod_mul: bool,
+    pub scaling: bool,
+    pub heatmap: bool,
+    pub fixed_width: bool,
+    pub wrapper_overhead: bool,
+    pub shared_clone: bool,
+    pub mixed_sizes: bool,
+    pub square: bool,
+    pub carry_save: bool,
+    pub matrix: bool,
+    pub bases: bool,
+    pub end_to_end: bool,
+    pub pedagogical: bool,
+    pub primality: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            input_class: InputClass::Random,
+            x_axis: Axis::Digits,
+            corpus: None,
+            operands: None,
+            save_operands: None,
+            db: None,
+            markdown: false,
+            gnuplot: None,
+            pgfplots: None,
+            budget: None,
+            smooth: 1,
+            panels: false,
+            max_points: None,
+            timer: Timer::Monotonic,
+            check: Check::Full,
+            quick_check: false,
+            quiet: false,
+            output: Output::Text,
+            external: Vec::new(),
+            large: false,
+            energy: false,
+            perf: false,
+            mod_pow: false,
+            mod_mul: false,
+            scaling: false,
+            heatmap: false,
+            fixed_width: false,
+            wrapper_overhead: false,
+            shared_clone: false,
+            mixed_sizes: false,
+            square: false,
+            carry_save: false,
+            matrix: false,
+            bases: false,
+            end_to_end: false,
+            pedagogical: false,
+            primality: false,
+        }
+    }
+}
+
+/// Flags that only shape the sweep, refused alongside a standalone benchmark
+/// rather than silently ignored.
+const SWEEP_ONLY: &[&str] = &[
+    "--input-class",
+    "--x-axis",
+    "--corpus",
+    "--operands",
+    "--save-operands",
+    "--db",
+    "--markdown",
+    "--gnuplot",
+    "--pgfplots",
+    "--budget",
+    "--smooth",
+    "--panels",
+    "--max-points",
+    "--timer",
+    "--check",
+    "--external",
+    "--large",
+    "--energy",
+    "--perf",
+    "--pedagogical",
+];
+
+impl Config {
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Config, String> {
+        let mut config = Config::default();
+        let mut sweep_flags = Vec::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if SWEEP_ONLY.contains(&arg.as_str()) {
+                sweep_flags.push(arg.clone());
+            }
+            match arg.as_str() {
+                "--input-class" => {
+                    config.input_class = parse_input_class(&value_of(&arg, args.next())?)?;
+                }
+                "--x-axis" => {
+                    let value = value_of(&arg, args.next())?;
+                    config.x_axis = Axis::parse(&value).ok_or_else(|| {
+                        format!(
+                            "unknown x axis '{}' (expected one of: bits, digits, limbs)",
+                            value
+                        )
+                    })?;
+                }
+                "--corpus" => {
+                    config.corpus = Some(PathBuf::from(value_of(&arg, args.next())?));
+                }
+                "--operands" => {
+                    config.operands = Some(PathBuf::from(value_of(&arg, args.next())?));
+                }
+                "--save-operands" => {
+                    config.save_operands = Some(PathBuf::from(value_of(&arg, args.next())?));
+                }
+                "--db" => config.db = Some(PathBuf::from(value_of(&arg, args.next())?)),
+                "--external" => {
+                    let value = value_of(&arg, args.next())?;
+                    match value.split_once('=') {
+                        Some((name, path)) if !name.is_empty() && !path.is_empty() => {
+                            config
+                                .external
+                                .push((name.to_string(), PathBuf::from(path)));
+                        }
+                        _ => {
+                            return Err(format!(
+                                "invalid external multiplier '{}' (expected NAME=PATH)",
+                                value
+                            ));
+                        }
+                    }
+                }
+                "--markdown" => config.markdown = true,
+                "--pgfplots" => config.pgfplots = Some(PathBuf::from(value_of(&arg, args.next())?)),
+                "--gnuplot" => config.gnuplot = Some(PathBuf::from(value_of(&arg, args.next())?)),
+                "--timer" => config.timer = Timer::parse(&value_of(&arg, args.next())?)?,
+                "--check" => config.check = Check::parse(&value_of(&arg, args.next())?)?,
+                "--budget" => {
+                    let value = value_of(&arg, args.next())?;
+                    config.budget = match value.parse::<f64>() {
+                        Ok(s) if s > 0.0 && s.is_finite() => Some(s),
+                        _ => {
+                            return Err(format!(
+                                "invalid budget '{}' (expected a positive number of seconds)",
+                                value
+                            ));
+                        }
+                    };
+                }
+                "--smooth" => {
+                    let value = value_of(&arg, args.next())?;
+                    config.smooth = match value.parse::<usize>() {
+                        Ok(n) if n >= 1 => n,
+                        _ => {
+                            return Err(format!(
+                                "invalid smoothing window '{}' (expected a positive number of points)",
+                                value
+                            ));
+                        }
+                    };
+                }
+                "--max-points" => {
+                    let value = value_of(&arg, args.next())?;
+                    config.max_points = match value.parse::<usize>() {
+                        Ok(n) if n >= 2 => Some(n),
+                        _ => {
+                            return Err(format!(
+                                "invalid point count '{}' (expected an integer of at least 2)",
+                                value
+                            ));
+                        }
+                    };
+                }
+                "--panels" => config.panels = true,
+                "--quick-check" => config.quick_check = true,
+                "--quiet" => config.quiet = true,
+                "--output" => config.output = Output::parse(&value_of(&arg, args.next())?)?,
+                "--large" => config.large = true,
+                "--energy" => config.energy = true,
+                "--perf" => config.perf = true,
+                "--mod-pow" => config.mod_pow = true,
+                "--mod-mul" => config.mod_mul = true,
+                "--scaling" => config.scaling = true,
+                "--heatmap" => config.heatmap = true,
+                "--fixed-width" => config.fixed_width = true,
+                "--wrapper-overhead" => config.wrapper_overhead = true,
+                "--shared-clone" => config.shared_clone = true,
+                "--mixed-sizes" => config.mixed_sizes = true,
+                "--square" => config.square = true,
+                "--carry-save" => config.carry_save = true,
+                "--matrix" => config.matrix = true,
+                "--bases" => config.bases = true,
+                "--end-to-end" => config.end_to_end = true,
+                "--pedagogical" => config.pedagogical = true,
+                "--primality" => config.primality = true,
+                _ => return Err(format!("unexpected argument '{}'", arg)),
+            }
+        }
+        if config.pedagogical && config.large {
+            return Err(
+                "--pedagogical charts the quadratic baselines, which --large leaves out"
+                    .to_string(),
+            );
+        }
+        if config.timer.deterministic() && config.budget.is_some() {
+            return Err("--budget needs a clock; it cannot be used with --timer ops".to_string());
+        }
+        if config.timer.deterministic() && !config.external.is_empty() {
+            return Err(
+                "--timer ops only counts the built-in kernels; drop --external".to_string(),
+            );
+        }
+        if config.quick_check && (config.corpus.is_some() || config.operands.is_some()) {
+            return Err("--quick-check generates its own operands".to_string());
+        }
+        if config.corpus.is_some() && config.operands.is_some() {
+            return Err("--corpus and --operands cannot be combined".to_string());
+        }
+        let standalone = [
+            ("--mod-pow", config.mod_pow),
+            ("--primality", config.primality),
+            ("--mod-mul", config.mod_mul),
+            ("--fixed-width", config.fixed_width),
+            ("--wrapper-overhead", config.wrapper_overhead),
+            ("--shared-clone", config.shared_clone),
+            ("--mixed-sizes", config.mixed_sizes),
+            ("--square", config.square),
+            ("--carry-save", config.carry_save),
+            ("--matrix", config.matrix),
+            ("--bases", config.bases),
+            ("--end-to-end", config.end_to_end),
+            ("--scaling", config.scaling),
+            ("--heatmap", config.heatmap),
+        ];
+        let chosen: Vec<&str> = standalone
+            .iter()
+            .filter(|&&(_, on)| on)
+            .map(|&(flag, _)| flag)
+            .collect();
+        if let [first @ .., last] = chosen.as_slice()
+            && !first.is_empty()
+        {
+            return Err(format!(
+                "{} and {} cannot be combined; each runs on its own",
+                first.join(", "),
+                last
+            ));
+        }
+        if let (true, [flag]) = (config.quick_check, chosen.as_slice()) {
+            return Err(format!(
+                "--quick-check checks the sweep's algorithms, which {} does not run",
+                flag
+            ));
+        }
+        // The heatmap times the sweep's algorithms, so it honours the flags
+        // that choose them.
+        let ignored = sweep_flags
+            .iter()
+            .find(|&flag| !(config.heatmap && (flag == "--large" || flag == "--external")));
+        if let ([mode], Some(flag)) = (chosen.as_slice(), ignored) {
+            return Err(format!(
+                "{} applies to the sweep, which {} does not run",
+                flag, mode
+            ));
+        }
+        if (config.quiet || config.output == Output::Json) && !chosen.is_empty() {
+            return Err(
+                "--quiet and --output apply to the sweep, not to the other benchmarks".to_string(),
+            );
+        }
+        Ok(config)
+    }
+
+    /// Whether the human-readable report goes to stdout.
+    pub fn reports(&self) -> bool {
+        !self.quiet && self.output == Output::Text
+    }
+}
+
+fn parse_input_class(value: &str) -> Result<InputClass, String> {
+    InputClass::parse(value).ok_or_else(|| {
+        let names: Vec<&str> = InputClass::ALL.iter().map(|c| c.name()).collect();
+        format!(
+            "unknown input class '{}' (expected one of: {})",
+            value,
+            names.join(", ")
+        )
+    })
+}
+
+/// A decimal integer with an optional sign, or `None` if `value` is not one.
+fn parse_integer(value: &str) -> Option<BigInt> {
+    BigInt::from_str(value).ok()
+}
+
+/// A `mul` operand: an integer given inline, or `@PATH` naming a file that
+/// holds one. Files are read by [`Operand::value`], not while parsing, so an
+/// unreadable one is an error (exit status 1) rather than a usage mistake.
+pub enum Operand {
+    Inline(BigInt),
+    File(PathBuf),
+}
+
+impl Operand {
+    fn parse(value: &str) -> Result<Operand, String> {
+        match value.strip_prefix('@') {
+            Some(path) => Ok(Operand::File(PathBuf::from(path))),
+            None => parse_integer(value.trim())
+                .map(Operand::Inline)
+                .ok_or_else(|| format!("invalid integer '{}'", value)),
+        }
+    }
+
+    pub fn value(self) -> Result<BigInt, String> {
+        match self {
+            Operand::Inline(n) => Ok(n),
+            Operand::File(path) => inputs::with_text(&path, |text| parse_integer(text.trim()))?
+                .ok_or_else(|| format!("{}: not a decimal integer", path.display())),
+        }
+    }
+}
+
+fn value_of(flag: &str, value: Option<String>) -> Result<String, String> {
+    value.ok_or_else(|| format!("{} requires a value", flag))
+}
@@ -0,0 +1,391 @@
+//! `bigmul completions <SHELL>` and `bigmul man`: completion scripts for
+//! bash, zsh and fish, and a roff man page, all read off `cli::USAGE`. The
+//! help text is the one description of the command line, so a new flag
+//! documented there is completed and in the man page without further work.
+//!
+//! Each command's flags come from its synopsis line, the sweep's from the
+//! Options section. A flag whose value is a `<FILE>` or `<DIR>` completes
+//! file names; other values are left to the user.
+
+use crate::cli::USAGE;
+use std::fmt::Write as _;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    /// Parses a `completions` argument.
+    pub fn parse(s: &str) -> Result<Shell, String> {
+        match s {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            _ => Err(format!(
+                "unknown shell '{}' (expected bash, zsh or fish)",
+                s
+            )),
+        }
+    }
+}
+
+/// A flag as the help text shows it: `--out <FILE>` has the value `FILE`.
+#[derive(Clone, Debug, PartialEq)]
+struct Flag {
+    name: String,
+    value: Option<String>,
+    help: String,
+}
+
+impl Flag {
+    fn takes_path(&self) -> bool {
+        matches!(self.value.as_deref(), Some("FILE" | "DIR"))
+    }
+}
+
+/// One two-column entry of a help section: a name column and the help
+/// beside and below it, joined into one line.
+struct Entry {
+    name: String,
+    help: String,
+}
+
+/// The entries of the section headed `heading`. The help column is where
+/// the section's continuation lines start.
+fn section(heading: &str) -> Vec<Entry> {
+    let lines: Vec<&str> = USAGE
+        .lines()
+        .skip_while(|l| *l != heading)
+        .skip(1)
+        .take_while(|l| l.is_empty() || l.starts_with(' '))
+        .collect();
+    let indent = |l: &str| l.len() - l.trim_start().len();
+    let column = lines
+        .iter()
+        .map(|l| indent(l))
+        .filter(|&i| i > 2)
+        .min()
+        .unwrap_or(usize::MAX);
+    let mut entries: Vec<Entry> = Vec::new();
+    for line in lines {
+        if indent(line) == 2 {
+            let (name, help) = line.split_at(column.min(line.len()));
+            entries.push(Entry {
+                name: name.trim().to_string(),
+                help: help.trim().to_string(),
+            });
+        } else if let Some(entry) = entries.last_mut()
+            && !line.trim().is_empty()
+        {
+            entry.help.push(' ');
+            entry.help.push_str(line.trim());
+        }
+    }
+    entries
+}
+
+/// The flags in `text`, each with the `<VALUE>` right after it, if any.
+fn flags_in(text: &str) -> Vec<Flag> {
+    let words: Vec<&str> = text
+        .split(|c: char| c.is_whitespace() || "[]|".contains(c))
+        .filter(|w| !w.is_empty())
+        .collect();
+    let mut flags: Vec<Flag> = Vec::new();
+    for (i, word) in words.iter().enumerate() {
+        let Some(name) = word.strip_prefix("--") else {
+            continue;
+        };
+        let value = words
+            .get(i + 1)
+            .and_then(|w| w.strip_prefix('<'))
+            .and_then(|w| w.strip_suffix('>'));
+        if !flags.iter().any(|f| f.name == name) {
+            flags.push(Flag {
+                name: name.to_string(),
+                value: value.map(str::to_string),
+                help: String::new(),
+            });
+        }
+    }
+    flags
+}
+
+/// The help text up to its first clause break, for one-line descriptions.
+fn summary(help: &str) -> &str {
+    help.split(['(', '[', ':', ';', ',', '.'])
+        .next()
+        .unwrap_or(help)
+        .trim()
+}
+
+/// A subcommand with its one-line description and its flags.
+struct Subcommand {
+    name: String,
+    help: String,
+    flags: Vec<Flag>,
+}
+
+/// The subcommands and the sweep's own flags.
+fn commands() -> (Vec<Subcommand>, Vec<Flag>) {
+    let synopsis: Vec<&str> = USAGE.lines().take_while(|l| !l.is_empty()).collect();
+    // Lines that start a command's synopsis, with the ones continuing it.
+    let mut usages: Vec<(String, String)> = Vec::new();
+    for line in &synopsis {
+        let line = line.strip_prefix("Usage:").unwrap_or(line).trim();
+        match line.strip_prefix("bigmul ") {
+            Some(rest) => {
+                let name = rest.split_whitespace().next().unwrap_or("");
+                usages.push((name.to_string(), rest.to_string()));
+            }
+            None => {
+                if let Some((_, text)) = usages.last_mut() {
+                    text.push(' ');
+                    text.push_str(line);
+                }
+            }
+        }
+    }
+    let helps = section("Commands:");
+    let commands = usages
+        .into_iter()
+        .filter_map(|(name, text)| {
+            let help = helps.iter().find(|e| e.name == name)?;
+            Some(Subcommand {
+                help: summary(&help.help).to_string(),
+                flags: flags_in(&text),
+                name,
+            })
+        })
+        .collect();
+    let options = section("Options:")
+        .into_iter()
+        .flat_map(|e| {
+            let help = summary(&e.help).to_string();
+            flags_in(&e.name).into_iter().map(move |f| Flag {
+                help: help.clone(),
+                ..f
+            })
+        })
+        .collect();
+    (commands, options)
+}
+
+/// The completion script for `shell`.
+pub fn script(shell: Shell) -> String {
+    let (commands, options) = commands();
+    match shell {
+        Shell::Bash => bash(&commands, &options),
+        Shell::Zsh => zsh(&commands, &options),
+        Shell::Fish => fish(&commands, &options),
+    }
+}
+
+fn dashed(flags: &[Flag]) -> Vec<String> {
+    flags.iter().map(|f| format!("--{}", f.name)).collect()
+}
+
+/// Flags of every command that complete file names, for `case` patterns.
+fn path_flags(commands: &[Subcommand], options: &[Flag]) -> Vec<String> {
+    let mut names: Vec<String> = commands
+        .iter()
+        .flat_map(|c| &c.flags)
+        .chain(options)
+        .filter(|f| f.takes_path())
+        .map(|f| format!("--{}", f.name))
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+fn bash(commands: &[Subcommand], options: &[Flag]) -> String {
+    let mut out = String::from("_bigmul() {\n");
+    out.push_str(
+        "    local cur=\"${COMP_WORDS[COMP_CWORD]}\" prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n",
+    );
+    writeln!(
+        out,
+        "    case \"$prev\" in\n        {})\n            COMPREPLY=($(compgen -f -- \"$cur\"))\n            return\n            ;;\n    esac",
+        path_flags(commands, options).join("|")
+    )
+    .unwrap();
+    out.push_str("    local words\n    case \"${COMP_WORDS[1]}\" in\n");
+    for c in commands {
+        writeln!(
+            out,
+            "        {}) words=\"{}\" ;;",
+            c.name,
+            dashed(&c.flags).join(" ")
+        )
+        .unwrap();
+    }
+    let mut top: Vec<String> = commands.iter().map(|c| c.name.clone()).collect();
+    top.extend(dashed(options));
+    writeln!(out, "        *) words=\"{}\" ;;", top.join(" ")).unwrap();
+    out.push_str("    esac\n");
+    out.push_str("    COMPREPLY=($(compgen -W \"$words\" -- \"$cur\"))\n}\n");
+    out.push_str("complete -o default -F _bigmul bigmul\n");
+    out
+}
+
+/// `name:description` for zsh's `_describe`, with colons escaped.
+fn described(name: &str, help: &str) -> String {
+    format!(
+        "'{}:{}'",
+        name,
+        help.replace(':', "\\:").replace('\'', "'\\''")
+    )
+}
+
+fn zsh(commands: &[Subcommand], options: &[Flag]) -> String {
+    let mut out = String::from("#compdef bigmul\n\n_bigmul() {\n");
+    writeln!(
+        out,
+        "    case \"${{words[CURRENT-1]}}\" in\n        {}) _files; return ;;\n    esac",
+        path_flags(commands, options).join("|")
+    )
+    .unwrap();
+    out.push_str("    local -a entries\n    case \"${words[2]}\" in\n");
+    for c in commands {
+        writeln!(
+            out,
+            "        {}) entries=({}) ;;",
+            c.name,
+            dashed(&c.flags)
+                .iter()
+                .map(|f| format!("'{}'", f))
+                .collect::<Vec<_>>()
+                .join(" ")
+        )
+        .unwrap();
+    }
+    let top: Vec<String> = commands
+        .iter()
+        .map(|c| described(&c.name, &c.help))
+        .chain(
+            options
+                .iter()
+                .map(|f| described(&format!("--{}", f.name), &f.help)),
+        )
+        .collect();
+    writeln!(out, "        *) entries=({}) ;;", top.join(" ")).unwrap();
+    out.push_str("    esac\n    _describe 'bigmul' entries\n}\n\n_bigmul \"$@\"\n");
+    out
+}
+
+fn fish(commands: &[Subcommand], options: &[Flag]) -> String {
+    let quote = |s: &str| format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"));
+    let flag = |condition: &str, f: &Flag| {
+        let mut line = format!("complete -c bigmul -n {} -l {}", quote(condition), f.name);
+        if f.takes_path() {
+            line.push_str(" -r -F");
+        } else if f.value.is_some() {
+            line.push_str(" -x");
+        }
+        if !f.help.is_empty() {
+            write!(line, " -d {}", quote(&f.help)).unwrap();
+        }
+        line
+    };
+    let mut out = String::from("complete -c bigmul -f\n");
+    for c in commands {
+        writeln!(
+            out,
+            "complete -c bigmul -n __fish_use_subcommand -a {} -d {}",
+            c.name,
+            quote(&c.help)
+        )
+        .unwrap();
+    }
+    for f in options {
+        writeln!(out, "{}", flag("__fish_use_subcommand", f)).unwrap();
+    }
+    for c in commands {
+        let condition = format!("__fish_seen_subcommand_from {}", c.name);
+        for f in &c.flags {
+            writeln!(out, "{}", flag(&condition, f)).unwrap();
+        }
+    }
+    out
+}
+
+/// `text` escaped for roff: backslashes and hyphens, and a leading period
+/// or quote that would read as a request.
+fn roff(text: &str) -> String {
+    let text = text.replace('\\', "\\e").replace('-', "\\-");
+    if text.starts_with(['.', '\'']) {
+        format!("\\&{}", text)
+    } else {
+        text
+    }
+}
+
+/// The man page, `bigmul(1)`, in roff.
+pub fn man_page() -> String {
+    let mut out = format!(
+        ".TH BIGMUL 1 \"\" \"bigmul {}\" \"User Commands\"\n",
+        env!("CARGO_PKG_VERSION")
+    );
+    out.push_str(
+        ".SH NAME\nbigmul \\- benchmark and compare big integer multiplication algorithms\n",
+    );
+    out.push_str(".SH SYNOPSIS\n.nf\n");
+    for line in USAGE.lines().take_while(|l| !l.is_empty()) {
+        let line = line.strip_prefix("Usage: ").unwrap_or(line);
+        writeln!(
+            out,
+            "{}",
+            roff(line.strip_prefix("       ").unwrap_or(line))
+        )
+        .unwrap();
+    }
+    out.push_str(".fi\n");
+    for (heading, title) in [
+        ("Commands:", "COMMANDS"),
+        ("Options:", "OPTIONS"),
+        ("Exit status:", "EXIT STATUS"),
+        ("Environment:", "ENVIRONMENT"),
+    ] {
+        writeln!(out, ".SH \"{}\"", title).unwrap();
+        for entry in section(heading) {
+            writeln!(out, ".TP\n.B {}\n{}", roff(&entry.name), roff(&entry.help)).unwrap();
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_and_flags_come_from_the_help() {
+        let (commands, options) = commands();
+        let mul = commands.iter().find(|c| c.name == "mul").unwrap();
+        assert_eq!(mul.help, "Print the product of the integers A and B");
+        assert!(mul.flags.iter().any(|f| f.name == "fallback"));
+        let disk = commands.iter().find(|c| c.name == "mul-disk").unwrap();
+        assert!(disk.flags.iter().any(|f| f.name == "out" && f.takes_path()));
+        assert!(commands.iter().any(|c| c.name == "completions"));
+        let output = options.iter().find(|f| f.name == "output").unwrap();
+        assert_eq!(output.value.as_deref(), Some("FORMAT"));
+        assert!(options.iter().any(|f| f.name == "corpus" && f.takes_path()));
+        assert!(options.iter().any(|f| f.name == "help"));
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let script = script(shell);
+            assert!(script.contains("max-points"), "{:?}", shell);
+            assert!(script.contains("rsa-demo"), "{:?}", shell);
+        }
+    }
+
+    #[test]
+    fn man_page_escapes_roff() {
+        let page = man_page();
+        assert!(page.contains(".B \\-\\-input\\-class <CLASS>\n"));
+        assert!(page.contains(".SH \"EXIT STATUS\"\n.TP\n.B 0\nSuccess\n"));
+        assert!(page.lines().all(|l| !l.starts_with('.') || l.len() > 1));
+    }
+}
@@ -0,0 +1,491 @@
+use crate::bigint::{BigInt, Sign};
+use crate::biguint::BigUint;
+use crate::error::Error;
+use crate::limbs;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Neg;
+use std::str::FromStr;
+
+/// How [`BigDecimal::round`] resolves discarded digits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RoundingMode {
+    /// Toward zero (truncate).
+    Down,
+    /// Away from zero.
+    Up,
+    /// Toward negative infinity.
+    Floor,
+    /// Toward positive infinity.
+    Ceiling,
+    /// To nearest, ties away from zero.
+    HalfUp,
+    /// To nearest, ties toward zero.
+    HalfDown,
+    /// To nearest, ties to the even neighbour.
+    HalfEven,
+}
+
+/// A decimal number `mantissa * 10^exponent`. The exponent is kept as given,
+/// so `1.50` and `1.5` are distinct representations of the same value;
+/// comparisons are by value.
+#[derive(Clone, Debug)]
+pub struct BigDecimal {
+    mantissa: BigInt,
+    exponent: i64,
+}
+
+fn pow10(k: u64) -> BigUint {
+    let mut digits = vec![0; (k / 9) as usize];
+    digits.push(10u32.pow((k % 9) as u32));
+    BigUint::from_kernel(digits)
+}
+
+impl BigDecimal {
+    pub fn new(mantissa: BigInt, exponent: i64) -> Self {
+        BigDecimal { mantissa, exponent }
+    }
+
+    /// Parses `[+-]digits[.digits][e[+-]digits]`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Result<Self, Error> {
+        let (body, exp) = match s.split_once(['e', 'E']) {
+            Some((body, exp)) => (
+                body,
+                exp.parse::<i64>()
+                    .map_err(|_| Error::Parse(format!("invalid exponent {:?}", exp)))?,
+            ),
+            None => (s, 0),
+        };
+        let (int, frac) = body.split_once('.').unwrap_or((body, ""));
+        let exponent = i64::try_from(frac.len())
+            .ok()
+            .and_then(|len| exp.checked_sub(len))
+            .ok_or_else(|| Error::Parse(format!("exponent {} out of range", exp)))?;
+        Ok(BigDecimal {
+            mantissa: BigInt::from_str(&format!("{}{}", int, frac))?,
+            exponent,
+        })
+    }
+
+    pub fn mantissa(&self) -> &BigInt {
+        &self.mantissa
+    }
+
+    pub fn exponent(&self) -> i64 {
+        self.exponent
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa.is_zero()
+    }
+
+    /// Decimal digits in the mantissa; zero has none.
+    fn digit_count(&self) -> u64 {
+        let limbs = self.mantissa.as_limbs();
+        match limbs.last() {
+            Some(&top) => 9 * (limbs.len() as u64 - 1) + u64::from(top.ilog10()) + 1,
+            None => 0,
+        }
+    }
+
+    /// Decimal digits in the mantissa plus the exponent: the position just
+    /// above the leading digit. Meaningless for zero.
+    fn adjusted_exponent(&self) -> i128 {
+        i128::from(self.digit_count()) + i128::from(self.exponent)
+    }
+
+    /// The mantissa times `10^k`, unchecked.
+    fn scaled(&self, k: u64) -> BigInt {
+        if k == 0 || self.is_zero() {
+            return self.mantissa.clone();
+        }
+        self.mantissa.mul_karatsuba(&BigInt::from(pow10(k)))
+    }
+
+    /// The same value with its exponent lowered to `exponent` by scaling
+    /// the mantissa. Requires `exponent <= self.exponent`; fails if the
+    /// scaled mantissa would pass [`limbs::max_result_limbs`].
+    fn rescaled(&self, exponent: i64) -> Result<BigInt, Error> {
+        let k = u64::try_from(i128::from(self.exponent) - i128::from(exponent))
+            .map_err(|_| Error::SizeOverflow)?;
+        if k != 0 && !self.is_zero() {
+            limbs::check_result_len(
+                usize::try_from(k / 9)
+                    .ok()
+                    .and_then(|shift| shift.checked_add(self.mantissa.as_limbs().len() + 1)),
+            )?;
+        }
+        Ok(self.scaled(k))
+    }
+
+    /// Sum, or [`Error::TooLarge`] if aligning the exponents would scale a
+    /// mantissa past [`limbs::max_result_limbs`].
+    pub fn checked_add(&self, other: &BigDecimal) -> Result<BigDecimal, Error> {
+        let exponent = self.exponent.min(other.exponent);
+        Ok(BigDecimal {
+            mantissa: self.rescaled(exponent)?.add(&other.rescaled(exponent)?),
+            exponent,
+        })
+    }
+
+    pub fn checked_sub(&self, other: &BigDecimal) -> Result<BigDecimal, Error> {
+        self.checked_add(&-other)
+    }
+
+    /// Panics where [`checked_add`](BigDecimal::checked_add) fails.
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(&self, other: &BigDecimal) -> BigDecimal {
+        self.checked_add(other)
+            .expect("aligning the exponents exceeds the result limit")
+    }
+
+    /// Panics where [`checked_sub`](BigDecimal::checked_sub) fails.
+    #[allow(clippy::should_implement_trait)]
+    pub fn sub(&self, other: &BigDecimal) -> BigDecimal {
+        self.add(&-other)
+    }
+
+    /// Exact product, or [`Error::SizeOverflow`] if the exponents' sum
+    /// leaves `i64`; the mantissas multiply as [`BigInt::checked_mul`].
+    pub fn checked_mul(&self, other: &BigDecimal) -> Result<BigDecimal, Error> {
+        Ok(BigDecimal {
+            exponent: self
+                .exponent
+                .checked_add(other.exponent)
+                .ok_or(Error::SizeOverflow)?,
+            mantissa: self.mantissa.checked_mul(&other.mantissa)?,
+        })
+    }
+
+    /// Panics where [`checked_mul`](BigDecimal::checked_mul) fails.
+    #[allow(clippy::should_implement_trait)]
+    pub fn mul(&self, other: &BigDecimal) -> BigDecimal {
+        self.checked_mul(other)
+            .expect("decimal product out of range")
+    }
+
+    /// Rounds to `places` digits after the decimal point (negative `places`
+    /// rounds to tens, hundreds, ...). Values already that short are only
+    /// rescaled, never rounded, and fail as
+    /// [`checked_add`](BigDecimal::checked_add) does if that is too large.
+    /// `places == i64::MIN` has no representable exponent and fails with
+    /// [`Error::SizeOverflow`].
+    pub fn checked_round(&self, places: i64, mode: RoundingMode) -> Result<BigDecimal, Error> {
+        let exponent = places.checked_neg().ok_or(Error::SizeOverflow)?;
+        if self.exponent >= exponent {
+            return Ok(BigDecimal {
+                mantissa: self.rescaled(exponent)?,
+                exponent,
+            });
+        }
+        // Fits: `exponent > self.exponent`, both `i64`.
+        let k = (i128::from(exponent) - i128::from(self.exponent)) as u64;
+        let negative = self.mantissa.sign() == Sign::Minus;
+        // Dropping more digits than the mantissa has leaves a remainder
+        // below half the divisor, so `10^k` is never built.
+        let (q, r, half) = if k > self.digit_count() {
+            let r = self.mantissa.magnitude().clone();
+            (BigUint::new(), r, Ordering::Less)
+        } else {
+            let divisor = pow10(k);
+            let (q, r) = self.mantissa.magnitude().div_rem(&divisor);
+            let half = r.add(&r).cmp(&divisor);
+            (q, r, half)
+        };
+        let odd = q.div_rem(&BigUint::from(2u64)).1.digits == [1];
+        let away = !r.is_zero()
+            && match mode {
+                RoundingMode::Down => false,
+                RoundingMode::Up => true,
+                RoundingMode::Floor => negative,
+                RoundingMode::Ceiling => !negative,
+                RoundingMode::HalfUp => half != Ordering::Less,
+                RoundingMode::HalfDown => half == Ordering::Greater,
+                RoundingMode::HalfEven => {
+                    half == Ordering::Greater || (half == Ordering::Equal && odd)
+                }
+            };
+        let q = if away { q.add(&BigUint::one()) } else { q };
+        Ok(BigDecimal {
+            mantissa: BigInt::from_biguint(self.mantissa.sign(), q),
+            exponent,
+        })
+    }
+
+    /// Panics where [`checked_round`](BigDecimal::checked_round) fails.
+    pub fn round(&self, places: i64, mode: RoundingMode) -> BigDecimal {
+        self.checked_round(places, mode)
+            .expect("rounded decimal out of range")
+    }
+
+    /// Product rounded to `places` digits after the point; panics where
+    /// [`mul`](BigDecimal::mul) or [`round`](BigDecimal::round) would.
+    pub fn mul_rounded(&self, other: &BigDecimal, places: i64, mode: RoundingMode) -> BigDecimal {
+        self.mul(other).round(places, mode)
+    }
+}
+
+impl Default for BigDecimal {
+    fn default() -> Self {
+        BigDecimal::new(BigInt::new(), 0)
+    }
+}
+
+impl FromStr for BigDecimal {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        BigDecimal::from_str(s)
+    }
+}
+
+impl From<BigInt> for BigDecimal {
+    fn from(n: BigInt) -> Self {
+        BigDecimal::new(n, 0)
+    }
+}
+
+impl Neg for BigDecimal {
+    type Output = BigDecimal;
+
+    fn neg(self) -> BigDecimal {
+        BigDecimal {
+            mantissa: -self.mantissa,
+            exponent: self.exponent,
+        }
+    }
+}
+
+impl Neg for &BigDecimal {
+    type Output = BigDecimal;
+
+    fn neg(self) -> BigDecimal {
+        -self.clone()
+    }
+}
+
+impl PartialEq for BigDecimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for BigDecimal {}
+
+impl PartialOrd for BigDecimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigDecimal {
+    /// Signs first, then the position of the leading digit; only values
+    /// that agree on both are rescaled, and then by at most the length of
+    /// the longer mantissa.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (sign, other_sign) = (self.mantissa.sign(), other.mantissa.sign());
+        if sign != other_sign || sign == Sign::NoSign {
+            return sign.cmp(&other_sign);
+        }
+        let magnitude = match self.adjusted_exponent().cmp(&other.adjusted_exponent()) {
+            Ordering::Equal => {
+                // Equal leading positions bound each shift by the other
+                // mantissa's length, so no limit check is needed.
+                let exponent = self.exponent.min(other.exponent);
+                let a = self.scaled(self.exponent.abs_diff(exponent));
+                let b = other.scaled(other.exponent.abs_diff(exponent));
+                a.magnitude().cmp(b.magnitude())
+            }
+            ord => ord,
+        };
+        if sign == Sign::Minus {
+            magnitude.reverse()
+        } else {
+            magnitude
+        }
+    }
+}
+
+/// Zeros [`Display`](fmt::Display) pads with before it switches to `e`
+/// notation, which keeps `1e1000000000000000000` printable.
+const MAX_PADDING: u64 = 1 << 12;
+
+/// Plain positional notation, keeping every digit the exponent implies,
+/// unless that needs more than 4096 padding zeros; then
+/// `digits e exponent`, which [`BigDecimal::from_str`] reads back.
+impl fmt::Display for BigDecimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.mantissa.sign() == Sign::Minus {
+            f.write_str("-")?;
+        }
+        let digits = self.mantissa.magnitude().to_string();
+        let places = self.exponent.unsigned_abs();
+        let padding = if self.exponent >= 0 {
+            if self.is_zero() { 0 } else { places }
+        } else {
+            (places + 1).saturating_sub(digits.len() as u64)
+        };
+        if padding > MAX_PADDING {
+            return write!(f, "{}e{}", digits, self.exponent);
+        }
+        if self.exponent >= 0 {
+            return write!(f, "{}{}", digits, "0".repeat(padding as usize));
+        }
+        let places = places as usize;
+        let padded = format!("{:0>width$}", digits, width = places + 1);
+        let (int, frac) = padded.split_at(padded.len() - places);
+        write!(f, "{}.{}", int, frac)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(s: &str) -> BigDecimal {
+        BigDecimal::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        for s in ["0", "1.50", "-0.001", "123456789012.345678901", "-7"] {
+            assert_eq!(d(s).to_string(), s);
+        }
+        assert_eq!(d("1.5e3").to_string(), "1500");
+        assert_eq!(d("-25e-4").to_string(), "-0.0025");
+        assert_eq!(d("1.50"), d("1.5"));
+        assert_eq!(d("1.50").exponent(), -2);
+    }
+
+    #[test]
+    fn arithmetic_aligns_exponents() {
+        assert_eq!(d("1.25").add(&d("10")).to_string(), "11.25");
+        assert_eq!(d("1.25").sub(&d("10.5")).to_string(), "-9.25");
+        assert_eq!(d("1.5").mul(&d("-0.25")).to_string(), "-0.375");
+        assert_eq!(d("2e3").mul(&d("3e-5")).to_string(), "0.06");
+        assert!(d("0.1") < d("0.25") && d("-0.1") > d("-0.25"));
+    }
+
+    #[test]
+    fn far_apart_exponents_compare_without_rescaling() {
+        let huge = d("1e1000000000000000000");
+        assert!(huge > d("1") && -&huge < d("-1") && huge > d("-1e2000"));
+        assert!(d("1e-1000000000000000000") < d("1"));
+        assert!(d("0e1000000000000000000") == d("0") && d("0") < d("1e-9"));
+        assert_eq!(d("1e18"), d("1000000000000000000.000"));
+        assert!(d("123e18") < d("1230000000000000000001"));
+        assert!(d("-123e18") > d("-1230000000000000000001"));
+    }
+
+    #[test]
+    fn checked_add_refuses_huge_alignments() {
+        limbs::with_max_result_limbs(4, || {
+            assert_eq!(
+                d("1e18").checked_add(&d("1")).unwrap(),
+                d("1000000000000000001")
+            );
+            assert!(matches!(
+                d("1e27").checked_add(&d("1")),
+                Err(Error::TooLarge { limbs: 5, max: 4 })
+            ));
+            assert!(matches!(
+                d("1").checked_sub(&d("1e1000000000000000000")),
+                Err(Error::TooLarge { .. })
+            ));
+            assert!(matches!(
+                d("1e9223372036854775807").checked_add(&d("1e-9223372036854775808")),
+                Err(Error::TooLarge { .. })
+            ));
+        });
+    }
+
+    #[test]
+    fn equal_leading_positions_compare_under_a_lowered_limit() {
+        let long = format!("1{}", "0".repeat(35));
+        limbs::with_max_result_limbs(4, || {
+            assert_eq!(d(&long), d("1e35"));
+            assert!(d(&format!("{}1", &long[..35])) > d("1e34"));
+            assert!(d(&format!("-{}1e-1", long)) < d("-1e35"));
+        });
+    }
+
+    #[test]
+    fn extreme_exponents_fail_instead_of_overflowing() {
+        let max = d("1e9223372036854775807");
+        assert!(matches!(
+            max.checked_mul(&d("1e1")),
+            Err(Error::SizeOverflow)
+        ));
+        assert_eq!(
+            max.checked_mul(&d("1e-1")).unwrap(),
+            d("1e9223372036854775806")
+        );
+        assert!(matches!(
+            d("1").checked_round(i64::MIN, RoundingMode::Up),
+            Err(Error::SizeOverflow)
+        ));
+        let tiny = d("-1e-9223372036854775808");
+        assert_eq!(tiny.round(i64::MIN + 1, RoundingMode::Down), d("0"));
+        assert_eq!(
+            tiny.round(-i64::MAX, RoundingMode::Floor).to_string(),
+            "-1e9223372036854775807"
+        );
+        assert_eq!(
+            d("7e-1000000000000000000").round(0, RoundingMode::Ceiling),
+            d("1")
+        );
+    }
+
+    #[test]
+    fn long_paddings_display_in_e_notation() {
+        for s in [
+            "1e1000000000000000000",
+            "-1e-9223372036854775808",
+            "25e9223372036854775807",
+            "0e-5000",
+        ] {
+            assert_eq!(d(s).to_string(), s);
+            assert_eq!(d(&d(s).to_string()), d(s));
+        }
+        assert_eq!(d("0e1000000000000000000").to_string(), "0");
+        assert_eq!(d("1e4096").to_string().len(), 4097);
+        assert_eq!(d("1e-4095").to_string().len(), 4097);
+        assert_eq!(d("1e-4097").to_string(), "1e-4097");
+    }
+
+    #[test]
+    fn rounding_modes() {
+        use RoundingMode::*;
+        let cases = [
+            // value, Down, Up, Floor, Ceiling, HalfUp, HalfDown, HalfEven
+            ("2.5", ["2", "3", "2", "3", "3", "2", "2"]),
+            ("3.5", ["3", "4", "3", "4", "4", "3", "4"]),
+            ("-2.5", ["-2", "-3", "-3", "-2", "-3", "-2", "-2"]),
+            ("2.51", ["2", "3", "2", "3", "3", "3", "3"]),
+            ("-2.49", ["-2", "-3", "-3", "-2", "-2", "-2", "-2"]),
+            ("7", ["7", "7", "7", "7", "7", "7", "7"]),
+        ];
+        let modes = [Down, Up, Floor, Ceiling, HalfUp, HalfDown, HalfEven];
+        for (value, expected) in cases {
+            for (mode, want) in modes.iter().zip(expected) {
+                assert_eq!(
+                    d(value).round(0, *mode).to_string(),
+                    want,
+                    "{} {:?}",
+                    value,
+                    mode
+                );
+            }
+        }
+        assert_eq!(d("-0.0004").round(3, HalfUp).to_string(), "0.000");
+        assert_eq!(d("1234.5").round(-2, HalfEven).to_string(), "1200");
+        assert_eq!(d("1.5").round(3, Down).to_string(), "1.500");
+        assert_eq!(
+            d("3.14159265358979")
+                .mul_rounded(&d("2"), 5, HalfEven)
+                .to_string(),
+            "6.28319"
+        );
+    }
+}
@@ -0,0 +1,333 @@
+//! Out-of-core multiplication for operands too large for memory.
+//!
+//! Operands and the product live in limb files: raw `u32` limbs in the
+//! machine's byte order, least significant first, memory-mapped so the OS
+//! pages them in and out as the work moves along. The product is built
+//! block by block: each pair of `block`-limb pieces is multiplied in memory
+//! with Karatsuba and added into the mapped product file at its offset, in
+//! order of offset so the pages being added to stay close together. Only
+//! one block pair and its product are ever resident, about `16 * block`
+//! limbs. The block products make the whole quadratic in the number of
+//! blocks; slow, but bounded by disk rather than RAM.
+//!
+//! Base `10^9` limbs are exactly nine decimal digits each, so conversion
+//! between decimal text and limb files streams in one pass either way.
+
+use crate::error::Error;
+use crate::limbs::{self, BASE};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::os::fd::AsRawFd;
+use std::path::Path;
+use std::ptr::NonNull;
+
+/// Decimal digits per limb.
+const LIMB_DIGITS: usize = 9;
+
+/// A file mapped into memory, read-only or shared read-write.
+struct Mapping {
+    ptr: NonNull<u8>,
+    len: usize,
+}
+
+impl Mapping {
+    /// Maps the first `len` bytes of `file`, which must be at least that
+    /// long; `path` names it in errors.
+    fn new(file: &File, path: &Path, len: usize, writable: bool) -> Result<Mapping, Error> {
+        if len == 0 {
+            return Ok(Mapping {
+                ptr: NonNull::<u32>::dangling().cast(),
+                len,
+            });
+        }
+        let prot = if writable {
+            libc::PROT_READ | libc::PROT_WRITE
+        } else {
+            libc::PROT_READ
+        };
+        // SAFETY: a fresh shared mapping of an open file; the kernel checks
+        // the descriptor, length and protection.
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                prot,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        // Without a placement hint mmap never maps page zero, so null only
+        // stands in for MAP_FAILED here.
+        let ptr = (ptr != libc::MAP_FAILED)
+            .then(|| NonNull::new(ptr as *mut u8))
+            .flatten();
+        match ptr {
+            Some(ptr) => Ok(Mapping { ptr, len }),
+            None => Err(io_error(path)(std::io::Error::last_os_error())),
+        }
+    }
+
+    fn bytes(&self) -> &[u8] {
+        // SAFETY: the mapping covers `len` readable bytes until `drop`.
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    /// The mapping as limbs; mappings start page-aligned, and the trailing
+    /// bytes of a length that is not a multiple of four are left out.
+    fn limbs(&self) -> &[u32] {
+        // SAFETY: as `bytes`, and page alignment satisfies `u32`'s.
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr() as *const u32, self.len / 4) }
+    }
+
+    /// Only for mappings made writable.
+    fn limbs_mut(&mut self) -> &mut [u32] {
+        // SAFETY: as `limbs`, with `&mut self` making the slice unique.
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr() as *mut u32, self.len / 4) }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        if self.len > 0 {
+            // SAFETY: `ptr` and `len` describe a mapping made by `new` that
+            // nothing borrows any more.
+            unsafe {
+                libc::munmap(self.ptr.as_ptr() as *mut libc::c_void, self.len);
+            }
+        }
+    }
+}
+
+/// Wraps an I/O error on `path`.
+fn io_error(path: &Path) -> impl Fn(std::io::Error) -> Error + '_ {
+    move |source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+fn open(path: &Path) -> Result<(File, usize), Error> {
+    let file = File::open(path).map_err(io_error(path))?;
+    let len = file.metadata().map_err(io_error(path))?.len();
+    let len = usize::try_from(len).map_err(|_| Error::SizeOverflow)?;
+    Ok((file, len))
+}
+
+/// A file mapped read-only, for parsing large operand files without
+/// copying them into a `String`: pages are read as the parser reaches them
+/// and can be dropped again under memory pressure. The file must not be
+/// truncated or rewritten while it is mapped.
+pub struct MappedFile(Mapping);
+
+impl MappedFile {
+    pub fn open(path: &Path) -> Result<MappedFile, Error> {
+        let (file, len) = open(path)?;
+        Ok(MappedFile(Mapping::new(&file, path, len, false)?))
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        self.0.bytes()
+    }
+}
+
+/// Creates `path` as `limbs` zero limbs, mapped for writing.
+fn create(path: &Path, limbs: usize) -> Result<Mapping, Error> {
+    let bytes = limbs.checked_mul(4).ok_or(Error::SizeOverflow)?;
+    let err = io_error(path);
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+        .map_err(&err)?;
+    file.set_len(bytes as u64).map_err(&err)?;
+    Mapping::new(&file, path, bytes, true)
+}
+
+/// Converts the non-negative decimal integer in the text file `src`
+/// (surrounding whitespace allowed) into the limb file `dst`, returning
+/// its length in limbs.
+pub fn decimal_to_limbs(src: &Path, dst: &Path) -> Result<usize, Error> {
+    let (file, len) = open(src)?;
+    let text = Mapping::new(&file, src, len, false)?;
+    let digits = text.bytes().trim_ascii();
+    if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+        return Err(Error::Parse(format!(
+            "{} is not a decimal integer",
+            src.display()
+        )));
+    }
+    let count = digits.len().div_ceil(LIMB_DIGITS);
+    let mut out = create(dst, count)?;
+    for (limb, chunk) in out.limbs_mut().iter_mut().zip(digits.rchunks(LIMB_DIGITS)) {
+        *limb = chunk.iter().fold(0, |acc, &d| acc * 10 + (d - b'0') as u32);
+    }
+    Ok(count)
+}
+
+/// Writes the limb file `src` to `dst` as decimal text and a newline,
+/// returning the number of digits.
+pub fn limbs_to_decimal(src: &Path, dst: &Path) -> Result<usize, Error> {
+    let (file, len) = open(src)?;
+    let map = Mapping::new(&file, src, len, false)?;
+    let limbs = map.limbs();
+    let err = io_error(dst);
+    let mut out = BufWriter::new(File::create(dst).map_err(&err)?);
+    let digits = match limbs.iter().rposition(|&d| d != 0) {
+        None => {
+            out.write_all(b"0").map_err(&err)?;
+            1
+        }
+        Some(top) => {
+            let head = limbs[top].to_string();
+            out.write_all(head.as_bytes()).map_err(&err)?;
+            for &d in limbs[..top].iter().rev() {
+                write!(out, "{:09}", d).map_err(&err)?;
+            }
+            head.len() + LIMB_DIGITS * top
+        }
+    };
+    out.write_all(b"\n").map_err(&err)?;
+    out.flush().map_err(&err)?;
+    Ok(digits)
+}
+
+/// Adds `p` into `out` at limb `offset`, carrying as far as needed.
+fn add_at(out: &mut [u32], offset: usize, p: &[u32]) {
+    let mut carry = 0;
+    for (o, &d) in out[offset..].iter_mut().zip(p) {
+        let t = *o as u64 + d as u64 + carry;
+        *o = (t % BASE) as u32;
+        carry = t / BASE;
+    }
+    for o in &mut out[offset + p.len()..] {
+        if carry == 0 {
+            break;
+        }
+        let t = *o as u64 + carry;
+        *o = (t % BASE) as u32;
+        carry = t / BASE;
+    }
+    debug_assert_eq!(carry, 0, "add_at: carry out of the product");
+}
+
+/// Multiplies the limb files `a` and `b` into the limb file `out`, holding
+/// `block`-limb pieces in memory at a time. `progress` hears the number of
+/// block products done and the total after each one, and stops the work
+/// by returning `false`; the result is then `Ok(false)` and `out` is
+/// incomplete. A product longer than [`limbs::max_result_limbs`] fails
+/// before `out` is created.
+pub fn mul_files(
+    a: &Path,
+    b: &Path,
+    out: &Path,
+    block: usize,
+    mut progress: impl FnMut(usize, usize) -> bool,
+) -> Result<bool, Error> {
+    assert!(block > 0, "mul_files: zero block size");
+    let ((fa, la), (fb, lb)) = (open(a)?, open(b)?);
+    let ma = Mapping::new(&fa, a, la, false)?;
+    let mb = Mapping::new(&fb, b, lb, false)?;
+    for (map, path) in [(&ma, a), (&mb, b)] {
+        if map.limbs().is_empty() {
+            return Err(Error::Parse(format!("{} has no limbs", path.display())));
+        }
+    }
+    let (a, b) = (ma.limbs(), mb.limbs());
+    let len = limbs::product_len(a.len(), b.len())?;
+    let mut product = create(out, len)?;
+    let sum = product.limbs_mut();
+    let (na, nb) = (a.len().div_ceil(block), b.len().div_ceil(block));
+    let total = na.checked_mul(nb).ok_or(Error::SizeOverflow)?;
+    let mut done = 0;
+    for k in 0..na + nb - 1 {
+        for i in k.saturating_sub(nb - 1)..=k.min(na - 1) {
+            let x = &a[i * block..a.len().min((i + 1) * block)];
+            let y = &b[(k - i) * block..b.len().min((k - i + 1) * block)];
+            add_at(sum, k * block, &limbs::mul_karatsuba_slices(x, y));
+            done += 1;
+            if !progress(done, total) {
+                return Ok(false);
+            }
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random_bits;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn blockwise_products_match_in_memory_ones() {
+        // The products are checked against the result limit.
+        limbs::with_max_result_limbs(limbs::MAX_RESULT_LIMBS, || {
+            let dir = std::env::temp_dir().join(format!("bigmul-disk-{}", std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let path = |name: &str| dir.join(name);
+            let mut rng = StdRng::seed_from_u64(11);
+            for (bits_a, bits_b, block) in
+                [(3000, 2000, 7), (5000, 90, 4), (64, 64, 1), (1, 900, 3)]
+            {
+                let x = random_bits(bits_a, &mut rng);
+                let y = random_bits(bits_b, &mut rng);
+                std::fs::write(path("a.txt"), format!("  {}\n", x)).unwrap();
+                std::fs::write(path("b.txt"), format!("000{}", y)).unwrap();
+                decimal_to_limbs(&path("a.txt"), &path("a.limbs")).unwrap();
+                decimal_to_limbs(&path("b.txt"), &path("b.limbs")).unwrap();
+                let mut calls = 0;
+                let finished = mul_files(
+                    &path("a.limbs"),
+                    &path("b.limbs"),
+                    &path("p.limbs"),
+                    block,
+                    |done, _| {
+                        calls += 1;
+                        done == calls
+                    },
+                );
+                assert!(matches!(finished, Ok(true)));
+                let digits = limbs_to_decimal(&path("p.limbs"), &path("p.txt")).unwrap();
+                let expected = x.mul(&y).to_string();
+                let text = std::fs::read_to_string(path("p.txt")).unwrap();
+                assert_eq!(
+                    text,
+                    format!("{}\n", expected),
+                    "{} x {} bits",
+                    bits_a,
+                    bits_b
+                );
+                assert_eq!(digits, expected.len());
+            }
+            let mapped = MappedFile::open(&path("a.txt")).unwrap();
+            assert_eq!(mapped.bytes(), std::fs::read(path("a.txt")).unwrap());
+            std::fs::write(path("z.txt"), "0").unwrap();
+            decimal_to_limbs(&path("z.txt"), &path("z.limbs")).unwrap();
+            mul_files(
+                &path("z.limbs"),
+                &path("a.limbs"),
+                &path("p.limbs"),
+                2,
+                |_, _| true,
+            )
+            .unwrap();
+            limbs_to_decimal(&path("p.limbs"), &path("p.txt")).unwrap();
+            assert_eq!(std::fs::read_to_string(path("p.txt")).unwrap(), "0\n");
+            std::fs::write(path("bad.txt"), "12x4").unwrap();
+            assert!(decimal_to_limbs(&path("bad.txt"), &path("bad.limbs")).is_err());
+            let stopped = mul_files(
+                &path("a.limbs"),
+                &path("b.limbs"),
+                &path("p.limbs"),
+                1,
+                |_, _| false,
+            );
+            assert!(matches!(stopped, Ok(false)));
+            std::fs::remove_dir_all(&dir).unwrap();
+        });
+    }
+}
@@ -0,0 +1,122 @@
+//! `dlog`: discrete logarithms by baby-step giant-step. With `m =
+//! ceil(sqrt(n))`, the baby steps `g^j` for `j < m` go into a hash table
+//! keyed by the residue, and the giant steps walk `h g^(-im)` until
+//! one lands in the table, so `x = im + j`. Time and memory are both
+//! `O(sqrt(n))`, which caps the modulus at `MAX_BITS`.
+
+use crate::interrupt;
+use bigmul::{BigUint, ModRing, PowWindow};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::time::Instant;
+
+/// Largest modulus, in bits: `2^22` baby steps already hold a few hundred
+/// megabytes of table.
+pub const MAX_BITS: usize = 44;
+
+/// Baby and giant steps taken by [`bsgs`].
+#[derive(Default)]
+struct Steps {
+    baby: u64,
+    giant: u64,
+}
+
+/// The least `x >= 0` with `g^x = h (mod n)`, or `None` if there is none.
+/// `g` must be invertible modulo `n`. The outer `None` means interrupted.
+fn bsgs(g: &BigUint, h: &BigUint, n: &BigUint, steps: &mut Steps) -> Option<Option<u64>> {
+    let ring = ModRing::new(n.clone());
+    let m = n.sub(&BigUint::one()).sqrt().add(&BigUint::one());
+    let m_steps: u64 = m.to_string().parse().expect("modulus within MAX_BITS");
+    let (g, h) = (ring.reduce(g), ring.reduce(h));
+
+    let mut table: HashMap<BigUint, u64> = HashMap::new();
+    let mut power = ring.reduce(&BigUint::one());
+    for j in 0..m_steps {
+        if j % 4096 == 0 && interrupt::interrupted() {
+            return None;
+        }
+        // Keep the first exponent reaching each residue, so the answer is
+        // the least one.
+        if let Entry::Vacant(entry) = table.entry(power.clone()) {
+            entry.insert(j);
+        }
+        power = ring.mul(&power, &g);
+        steps.baby += 1;
+    }
+
+    let giant = g
+        .mod_pow(&m, n, PowWindow::Sliding)
+        .mod_inverse(n)
+        .expect("g is invertible modulo n");
+    let mut gamma = h;
+    for i in 0..m_steps {
+        if i % 4096 == 0 && interrupt::interrupted() {
+            return None;
+        }
+        steps.giant += 1;
+        if let Some(&j) = table.get(&gamma) {
+            return Some(Some(i * m_steps + j));
+        }
+        gamma = ring.mul(&gamma, &giant);
+    }
+    Some(None)
+}
+
+/// Solves `g^x = h (mod n)` and prints `x`, the steps taken and the time.
+/// `n` must be at least 2 and at most `MAX_BITS` bits, and `g` coprime to
+/// it. Returns `false` if interrupted.
+pub fn run(g: &BigUint, h: &BigUint, n: &BigUint) -> bool {
+    let start = Instant::now();
+    let mut steps = Steps::default();
+    let Some(x) = bsgs(g, h, n, &mut steps) else {
+        println!("interrupted");
+        return false;
+    };
+    let elapsed = start.elapsed().as_secs_f64();
+    match x {
+        Some(x) => println!("{}^x = {} (mod {}): x = {}", g, h, n, x),
+        None => println!("{}^x = {} (mod {}): no solution", g, h, n),
+    }
+    println!("baby steps:  {}", steps.baby);
+    println!("giant steps: {}", steps.giant);
+    println!("total:       {:.6} s", elapsed);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dlog(g: u64, h: u64, n: u64) -> Option<u64> {
+        let [g, h, n] = [g, h, n].map(|v| BigUint::from_str(&v.to_string()).unwrap());
+        bsgs(&g, &h, &n, &mut Steps::default()).unwrap()
+    }
+
+    #[test]
+    fn finds_the_least_exponent() {
+        // 2 generates the units modulo the prime 1000003.
+        let n = BigUint::from_str("1000003").unwrap();
+        let two = BigUint::from_str("2").unwrap();
+        for x in [0u64, 1, 17, 999, 123456, 1000001] {
+            let h = two.mod_pow(
+                &BigUint::from_str(&x.to_string()).unwrap(),
+                &n,
+                PowWindow::Binary,
+            );
+            let h: u64 = h.to_string().parse().unwrap();
+            assert_eq!(dlog(2, h, 1000003), Some(x));
+        }
+        // 10 is even and a multiple of 5, so the Barrett path is taken.
+        assert_eq!(dlog(3, 1, 10), Some(0));
+        assert_eq!(dlog(3, 7, 10), Some(3));
+        assert_eq!(dlog(3, 17, 10), Some(3));
+    }
+
+    #[test]
+    fn reports_missing_logarithms() {
+        // 4 generates {1, 4, 2} modulo 7.
+        assert_eq!(dlog(4, 3, 7), None);
+        assert_eq!(dlog(4, 0, 7), None);
+        assert_eq!(dlog(4, 2, 7), Some(2));
+    }
+}
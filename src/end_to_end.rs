@@ -0,0 +1,112 @@
+//! `--end-to-end`: the time from two decimal strings to the decimal string
+//! of their product, split into parsing, multiplying and converting back.
+//! Parsing and printing are linear in base `10^9`, so the multiplication
+//! should dominate; the phase chart shows by how much at each size.
+
+use crate::interrupt;
+use crate::multiplier;
+use crate::primitives::seconds_per_call;
+use bigmul::report::{Chart, Kind};
+use bigmul::{BigUint, random_bits};
+use std::fmt::Write as _;
+use std::hint::black_box;
+
+/// Operand sizes in decimal digits.
+const SIZES: [usize; 5] = [5_000, 10_000, 20_000, 40_000, 80_000];
+const PHASES: [&str; 3] = ["parse", "multiply", "convert"];
+
+/// Seconds per parse of both operands and per conversion of their product
+/// to a string; neither depends on the algorithm, so they are measured once
+/// per size. Also returns the operands' limbs.
+fn measure_io(digits: usize) -> (f64, f64, Vec<u32>, Vec<u32>) {
+    let mut rng = rand::thread_rng();
+    // log2(10) bits per digit.
+    let bits = digits * 3322 / 1000;
+    let a = random_bits(bits, &mut rng).to_string();
+    let b = random_bits(bits, &mut rng).to_string();
+    let parsed = |s: &str| BigUint::from_str(s).expect("printed operands are decimal");
+    let parse = seconds_per_call(|| {
+        black_box(parsed(black_box(&a)));
+        black_box(parsed(black_box(&b)));
+    });
+    let (a, b) = (parsed(&a), parsed(&b));
+    let product = a.mul(&b);
+    let convert = seconds_per_call(|| {
+        black_box(black_box(&product).to_string());
+    });
+    (parse, convert, a.as_limbs().to_vec(), b.as_limbs().to_vec())
+}
+
+/// Runs the sweep, printing each algorithm's phases and total, and writes
+/// `./assets/end_to_end.csv` and one stacked chart per algorithm,
+/// `./assets/end_to_end_<name>.png`. Returns `false` if interrupted.
+pub fn run() -> bool {
+    let multipliers = multiplier::registry();
+    // Per size: the digits and, per algorithm, the three phase times. A size
+    // cut short by an interrupt is dropped whole.
+    let mut rows: Vec<(usize, Vec<[f64; 3]>)> = Vec::new();
+    'sizes: for &digits in &SIZES {
+        let (parse, convert, a, b) = measure_io(digits);
+        let mut phases = Vec::new();
+        for m in &multipliers {
+            if interrupt::interrupted() {
+                break 'sizes;
+            }
+            let multiply = seconds_per_call(|| {
+                black_box(m.mul(black_box(&a), black_box(&b)));
+            });
+            println!(
+                "digits={}, {}: parse={:.3e} s multiply={:.3e} s convert={:.3e} s total={:.3e} s",
+                digits,
+                m.name(),
+                parse,
+                multiply,
+                convert,
+                parse + multiply + convert
+            );
+            phases.push([parse, multiply, convert]);
+        }
+        rows.push((digits, phases));
+    }
+    if rows.is_empty() {
+        return false;
+    }
+
+    let mut out = String::from("digits,algorithm");
+    for phase in PHASES {
+        write!(out, ",{}_seconds", phase).unwrap();
+    }
+    out.push_str(",total_seconds\n");
+    for (digits, phases) in &rows {
+        for (m, times) in multipliers.iter().zip(phases) {
+            write!(out, "{},{}", digits, m.name()).unwrap();
+            for t in times {
+                write!(out, ",{:.12}", t).unwrap();
+            }
+            writeln!(out, ",{:.12}", times.iter().sum::<f64>()).unwrap();
+        }
+    }
+    std::fs::create_dir_all("./assets").expect("Failed to create ./assets directory");
+    std::fs::write("./assets/end_to_end.csv", out).expect("Failed to write CSV results");
+    for (k, m) in multipliers.iter().enumerate() {
+        let chart = PHASES.iter().enumerate().fold(
+            Chart::new()
+                .title(format!("End to End: {}", m.label()))
+                .x_desc("Input Size (decimal digits)")
+                .kind(Kind::Stacked),
+            |chart, (p, phase)| {
+                chart.series(
+                    *phase,
+                    rows.iter()
+                        .map(|(digits, phases)| (*digits as f64, phases[k][p])),
+                )
+            },
+        );
+        let path = format!("./assets/end_to_end_{}.png", m.name());
+        if let Err(msg) = chart.draw_png(path.as_ref()) {
+            eprintln!("error: {}", msg);
+        }
+    }
+    println!("Results saved to ./assets/end_to_end.csv and ./assets/end_to_end_*.png");
+    rows.len() == SIZES.len()
+}
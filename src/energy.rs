@@ -0,0 +1,126 @@
+//! Package energy readings from Intel RAPL through the Linux powercap sysfs
+//! interface, for `--energy`.
+//!
+//! Only top-level package domains (`intel-rapl:N`) are summed; their
+//! subdomains are already included in them. The counters cover the whole
+//! package, including idle power and other processes, and update roughly
+//! every millisecond, so readings for fast multiplications are coarse and
+//! only meaningful averaged over many instances. Since 2020 most kernels
+//! make `energy_uj` readable by root only.
+
+use std::path::{Path, PathBuf};
+
+const POWERCAP: &str = "/sys/class/powercap";
+
+struct Domain {
+    energy: PathBuf,
+    /// Counter value at which `energy_uj` wraps back to zero.
+    max_range: u64,
+}
+
+pub struct Rapl {
+    domains: Vec<Domain>,
+}
+
+/// Raw counter values, one per domain.
+pub struct Reading(Vec<u64>);
+
+fn read_u64(path: &Path) -> Result<u64, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    text.trim()
+        .parse()
+        .map_err(|_| format!("{} does not hold a number", path.display()))
+}
+
+impl Rapl {
+    pub fn open() -> Result<Rapl, String> {
+        Rapl::open_at(Path::new(POWERCAP))
+    }
+
+    fn open_at(root: &Path) -> Result<Rapl, String> {
+        let entries = std::fs::read_dir(root)
+            .map_err(|e| format!("RAPL unavailable: cannot read {}: {}", root.display(), e))?;
+        let mut dirs: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .and_then(|n| n.strip_prefix("intel-rapl:"))
+                    .is_some_and(|rest| !rest.contains(':'))
+            })
+            .collect();
+        dirs.sort();
+        if dirs.is_empty() {
+            return Err(format!(
+                "RAPL unavailable: no package domains under {}",
+                root.display()
+            ));
+        }
+        let mut domains = Vec::new();
+        for dir in dirs {
+            let energy = dir.join("energy_uj");
+            read_u64(&energy)?;
+            domains.push(Domain {
+                max_range: read_u64(&dir.join("max_energy_range_uj"))?,
+                energy,
+            });
+        }
+        Ok(Rapl { domains })
+    }
+
+    pub fn read(&self) -> Reading {
+        Reading(
+            self.domains
+                .iter()
+                .map(|d| read_u64(&d.energy).unwrap_or_else(|msg| panic!("{}", msg)))
+                .collect(),
+        )
+    }
+
+    /// Joules used between two readings, allowing each counter to have
+    /// wrapped once.
+    pub fn joules_between(&self, start: &Reading, end: &Reading) -> f64 {
+        let micro: u64 = self
+            .domains
+            .iter()
+            .zip(start.0.iter().zip(&end.0))
+            .map(|(d, (&s, &e))| if e >= s { e - s } else { d.max_range - s + e })
+            .sum();
+        micro as f64 / 1e6
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_domain(root: &Path, name: &str, energy: u64, max: u64) {
+        let dir = root.join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("energy_uj"), format!("{}\n", energy)).unwrap();
+        std::fs::write(dir.join("max_energy_range_uj"), format!("{}\n", max)).unwrap();
+    }
+
+    #[test]
+    fn sums_packages_and_handles_wrap() {
+        let root = std::env::temp_dir().join(format!("bigmul-rapl-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        write_domain(&root, "intel-rapl:0", 1_000_000, 10_000_000);
+        write_domain(&root, "intel-rapl:0:0", 5, 10_000_000);
+        write_domain(&root, "intel-rapl:1", 9_500_000, 10_000_000);
+
+        let rapl = Rapl::open_at(&root).unwrap();
+        assert_eq!(rapl.domains.len(), 2, "subdomains are not counted");
+        let start = rapl.read();
+        write_domain(&root, "intel-rapl:0", 3_000_000, 10_000_000);
+        write_domain(&root, "intel-rapl:1", 500_000, 10_000_000);
+        let end = rapl.read();
+        // 2 J on package 0, 1 J across the wrap on package 1.
+        assert!((rapl.joules_between(&start, &end) - 3.0).abs() < 1e-9);
+
+        std::fs::remove_dir_all(&root).unwrap();
+        assert!(Rapl::open_at(&root).is_err());
+    }
+}
@@ -0,0 +1,55 @@
+//! The library's error type. Parsing, modular inverses and the out-of-core
+//! files report failures through it rather than panicking or reading bad
+//! input as zero. Division keeps both forms, as the standard integers do:
+//! `div_rem`, `mod_pow` and `ModRing::new` panic on a zero divisor, and
+//! only `checked_div_rem`, `checked_mod_pow` and `ModRing::try_new` return
+//! [`Error::DivisionByZero`].
+
+use std::path::PathBuf;
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// Text or limbs that do not spell a number; the message says where.
+    #[error("invalid number: {0}")]
+    Parse(String),
+    /// A zero modulus or divisor passed to one of the `checked_` divisions,
+    /// `ModRing::try_new` or `mod_inverse`.
+    #[error("division by zero")]
+    DivisionByZero,
+    /// A modular inverse that does not exist: the value shares a factor
+    /// with the modulus.
+    #[error("not invertible modulo the modulus")]
+    NotInvertible,
+    /// A length that does not fit in `usize`, such as the limb count of a
+    /// product of two enormous files.
+    #[error("size overflows usize")]
+    SizeOverflow,
+    /// A result longer than [`limbs::max_result_limbs`](crate::limbs::max_result_limbs),
+    /// refused before anything is allocated.
+    #[error("result of {limbs} limbs exceeds the limit of {max}")]
+    TooLarge { limbs: usize, max: usize },
+    /// A file that could not be opened, created, mapped or written.
+    #[error("{}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn io_errors_name_the_path_and_keep_the_cause() {
+        let err = Error::Io {
+            path: PathBuf::from("a.limbs"),
+            source: std::io::Error::from(std::io::ErrorKind::NotFound),
+        };
+        assert_eq!(err.to_string(), "a.limbs: entity not found");
+        assert!(err.source().is_some());
+        assert!(Error::NotInvertible.source().is_none());
+    }
+}
@@ -0,0 +1,214 @@
+//! Chart data for other plotting tools: `--gnuplot` writes the sweep as a
+//! whitespace-separated data file plus a script that redraws the comparison
+//! chart from it, and `--pgfplots` a TikZ picture of the same curves, with
+//! error bars, to typeset natively in LaTeX.
+
+use crate::cli::Config;
+use crate::{Measurements, Series};
+use bigmul::report::{Marker, Style};
+use plotters::style::RGBColor;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// A gnuplot single-quoted string.
+fn gnuplot_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+fn hex(color: RGBColor) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.0, color.1, color.2)
+}
+
+/// gnuplot's filled point type and pgfplots' mark for `marker`.
+fn point_type(marker: Marker) -> (u8, &'static str) {
+    match marker {
+        Marker::Circle => (7, "*"),
+        Marker::Square => (5, "square*"),
+        Marker::Triangle => (9, "triangle*"),
+        Marker::Cross => (2, "x"),
+    }
+}
+
+/// Every how many points a marker is drawn, for about a dozen per curve as
+/// on the PNG charts.
+fn marker_interval(points: usize) -> usize {
+    points.div_ceil(12).max(1)
+}
+
+/// Writes the average times to `data` (one row per size, one column per
+/// algorithm) and a script next to it, with the `.plt` extension, that
+/// draws them to a PNG of the same name. Returns the script's path.
+pub fn gnuplot(
+    data: &Path,
+    config: &Config,
+    results: &Measurements,
+    series: &[Series],
+) -> Result<String, String> {
+    let mut out = format!("# {}", config.x_axis.name());
+    for s in series {
+        write!(out, " {}", s.name).unwrap();
+    }
+    out.push('\n');
+    for (i, n) in results.ns.iter().enumerate() {
+        write!(out, "{}", n).unwrap();
+        for s in series {
+            write!(out, " {:.9e}", s.times[i]).unwrap();
+        }
+        out.push('\n');
+    }
+    std::fs::write(data, out).map_err(|e| format!("cannot write {}: {}", data.display(), e))?;
+
+    let script = data.with_extension("plt");
+    let image = data.with_extension("png");
+    let data_name = gnuplot_quote(&data.display().to_string());
+    let mut plt = String::from("set terminal pngcairo size 800,600 noenhanced\n");
+    writeln!(
+        plt,
+        "set output {}",
+        gnuplot_quote(&image.display().to_string())
+    )
+    .unwrap();
+    writeln!(plt, "set title {}", gnuplot_quote(&crate::caption(config))).unwrap();
+    let scale = if config.large {
+        plt.push_str("set logscale xy\n");
+        ", log scale"
+    } else {
+        ""
+    };
+    writeln!(
+        plt,
+        "set xlabel 'Input Size (number of {}{})'",
+        config.x_axis.name(),
+        scale
+    )
+    .unwrap();
+    writeln!(plt, "set ylabel {}", gnuplot_quote(crate::y_desc(config))).unwrap();
+    plt.push_str("set key top left box\nset grid\n");
+    let lines: Vec<String> = series
+        .iter()
+        .enumerate()
+        .map(|(k, s)| {
+            let style = Style::of(s.name);
+            format!(
+                "{} using 1:{} with linespoints lw 2 lc rgb '{}' pt {} pi {} title {}",
+                if k == 0 { data_name.as_str() } else { "''" },
+                k + 2,
+                hex(style.color),
+                point_type(style.marker).0,
+                marker_interval(results.ns.len()),
+                gnuplot_quote(s.name)
+            )
+        })
+        .collect();
+    writeln!(plt, "plot {}", lines.join(", \\\n     ")).unwrap();
+    std::fs::write(&script, plt)
+        .map_err(|e| format!("cannot write {}: {}", script.display(), e))?;
+    Ok(script.display().to_string())
+}
+
+/// Escapes LaTeX's special characters in text.
+fn latex_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\textbackslash{}"),
+            '~' => out.push_str("\\textasciitilde{}"),
+            '^' => out.push_str("\\textasciicircum{}"),
+            '_' | '%' | '$' | '#' | '&' | '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Sample standard deviation; zero for fewer than two samples.
+fn std_dev(samples: &[f64]) -> f64 {
+    if samples.len() < 2 {
+        return 0.0;
+    }
+    let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+    let var = samples.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / (samples.len() - 1) as f64;
+    var.sqrt()
+}
+
+/// Writes to `path` a `tikzpicture` with one `pgfplots` curve per algorithm:
+/// average time per size, with error bars of one standard deviation of the
+/// samples. Include it with `\input` in a document that loads `pgfplots`.
+pub fn pgfplots(
+    path: &Path,
+    config: &Config,
+    results: &Measurements,
+    series: &[Series],
+) -> Result<(), String> {
+    let scale = if config.large { ", log scale" } else { "" };
+    let mut out = String::from("% Generated by bigmul; needs \\usepackage{pgfplots}.\n");
+    out.push_str("\\begin{tikzpicture}\n\\begin{axis}[\n");
+    writeln!(
+        out,
+        "  title={{{}}},",
+        latex_escape(&crate::caption(config))
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "  xlabel={{Input Size (number of {}{})}},",
+        config.x_axis.name(),
+        scale
+    )
+    .unwrap();
+    writeln!(out, "  ylabel={{{}}},", crate::y_desc(config)).unwrap();
+    if config.large {
+        out.push_str("  xmode=log, ymode=log,\n");
+    }
+    out.push_str("  legend pos=north west,\n  grid=major,\n  width=12cm, height=9cm,\n]\n");
+    for s in series {
+        let style = Style::of(s.name);
+        let RGBColor(r, g, b) = style.color;
+        writeln!(
+            out,
+            "\\addplot[color={{rgb,255:red,{};green,{};blue,{}}}, mark={}, mark repeat={}, \
+             error bars/.cd, y dir=both, y explicit] coordinates {{",
+            r,
+            g,
+            b,
+            point_type(style.marker).1,
+            marker_interval(results.ns.len())
+        )
+        .unwrap();
+        for (i, n) in results.ns.iter().enumerate() {
+            writeln!(
+                out,
+                "  ({}, {:.6e}) +- (0, {:.6e})",
+                n,
+                s.times[i],
+                std_dev(&s.samples[i])
+            )
+            .unwrap();
+        }
+        writeln!(out, "}};\n\\addlegendentry{{{}}}", latex_escape(s.name)).unwrap();
+    }
+    out.push_str("\\end{axis}\n\\end{tikzpicture}\n");
+    std::fs::write(path, out).map_err(|e| format!("cannot write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_and_colors() {
+        assert_eq!(gnuplot_quote("it's"), "'it''s'");
+        assert_eq!(hex(RGBColor(255, 140, 0)), "#ff8c00");
+        assert_eq!(latex_escape("kara_sub 50%"), "kara\\_sub 50\\%");
+        assert_eq!((marker_interval(5), marker_interval(100)), (1, 9));
+    }
+
+    #[test]
+    fn std_dev_of_samples() {
+        assert_eq!(std_dev(&[1.0]), 0.0);
+        assert!((std_dev(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]) - 2.138).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,136 @@
+//! Multipliers implemented by external programs, for comparing outside
+//! implementations on the same inputs and chart as the built-ins.
+//!
+//! The harness starts each program once, without arguments, and talks to it
+//! over pipes. Per multiplication it writes two lines holding the operands
+//! in decimal and reads back one line holding the product in decimal. When
+//! the sweep ends the program's stdin is closed and it should exit. The
+//! timing covers the whole round trip, so it includes pipe I/O and decimal
+//! conversion on both sides; `examples/external_plugin.rs` is a reference
+//! implementation that shows the overhead.
+
+use crate::multiplier::Multiplier;
+use bigmul::BigUint;
+use std::cell::RefCell;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+struct Pipes {
+    stdin: Option<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+}
+
+pub struct External {
+    name: String,
+    child: Child,
+    pipes: RefCell<Pipes>,
+}
+
+/// Writes limbs as decimal, like `BigUint`'s `Display`.
+fn to_decimal(limbs: &[u32], out: &mut String) {
+    use std::fmt::Write as _;
+    let mut iter = limbs.iter().rev().skip_while(|&&d| d == 0);
+    match iter.next() {
+        Some(top) => write!(out, "{}", top).unwrap(),
+        None => out.push('0'),
+    }
+    for d in iter {
+        write!(out, "{:09}", d).unwrap();
+    }
+}
+
+impl External {
+    pub fn spawn(name: &str, program: &Path) -> Result<External, String> {
+        External::spawn_command(name, Command::new(program))
+    }
+
+    /// Like `spawn`, for a program that needs arguments or environment.
+    pub fn spawn_command(name: &str, mut command: Command) -> Result<External, String> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                format!(
+                    "cannot start '{}': {}",
+                    command.get_program().to_string_lossy(),
+                    e
+                )
+            })?;
+        let pipes = Pipes {
+            stdin: child.stdin.take(),
+            stdout: BufReader::new(child.stdout.take().unwrap()),
+        };
+        Ok(External {
+            name: name.to_string(),
+            child,
+            pipes: RefCell::new(pipes),
+        })
+    }
+
+    fn fail(&self, msg: &str) -> ! {
+        eprintln!("error: external multiplier '{}': {}", self.name, msg);
+        std::process::exit(1);
+    }
+}
+
+impl Multiplier for External {
+    fn mul(&self, a: &[u32], b: &[u32]) -> Vec<u32> {
+        let mut request = String::new();
+        to_decimal(a, &mut request);
+        request.push('\n');
+        to_decimal(b, &mut request);
+        request.push('\n');
+
+        let mut pipes = self.pipes.borrow_mut();
+        let stdin = pipes.stdin.as_mut().unwrap();
+        if let Err(e) = stdin
+            .write_all(request.as_bytes())
+            .and_then(|_| stdin.flush())
+        {
+            self.fail(&format!("write failed: {}", e));
+        }
+        let mut line = String::new();
+        match pipes.stdout.read_line(&mut line) {
+            Ok(0) => self.fail("exited without answering"),
+            Err(e) => self.fail(&format!("read failed: {}", e)),
+            Ok(_) => {}
+        }
+        let line = line.trim_end();
+        match BigUint::from_str(line) {
+            Ok(product) => product.into_limbs(),
+            Err(_) => self.fail("reply is not a decimal number"),
+        }
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Drop for External {
+    fn drop(&mut self) {
+        // Closing stdin is the end-of-session signal.
+        self.pipes.get_mut().stdin.take();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decimal_matches_display() {
+        for s in ["0", "7", "1000000000", "123456789000000001000000002"] {
+            let x = BigUint::from_str(s).unwrap();
+            let mut out = String::new();
+            to_decimal(x.as_limbs(), &mut out);
+            assert_eq!(out, s);
+        }
+        let mut out = String::new();
+        to_decimal(&[5, 0, 0], &mut out);
+        assert_eq!(out, "5");
+    }
+}
@@ -0,0 +1,191 @@
+//! `factor`: complete factorization of an integer by trial division up to
+//! 10,000, then Pollard's rho on whatever is left, with Baillie-PSW deciding
+//! when a cofactor is prime. Rho is a workout for the parts of the crate
+//! around multiplication: every step is a modular squaring, every batch a
+//! gcd, and every restart a random draw.
+
+use crate::interrupt;
+use bigmul::{BigInt, BigUint, ModRing, Sign, limbs, random_below};
+use std::time::Instant;
+
+const TRIAL_LIMIT: u32 = 10_000;
+/// Rho steps whose differences are multiplied together before one gcd.
+const BATCH: u64 = 100;
+
+fn primes_below(limit: u32) -> Vec<u32> {
+    let mut composite = vec![false; limit as usize];
+    let mut primes = Vec::new();
+    for p in 2..limit {
+        if !composite[p as usize] {
+            primes.push(p);
+            for m in (p * p..limit).step_by(p as usize) {
+                composite[m as usize] = true;
+            }
+        }
+    }
+    primes
+}
+
+/// Divides every prime below `TRIAL_LIMIT` out of `n`, pushing each onto
+/// `factors` once per multiplicity, and returns the cofactor.
+fn trial_divide(mut n: BigUint, factors: &mut Vec<BigUint>) -> BigUint {
+    for p in primes_below(TRIAL_LIMIT) {
+        loop {
+            let (q, r) = limbs::div_rem_small(n.as_limbs(), p);
+            if r != 0 {
+                break;
+            }
+            n = BigUint::from_limbs(q).unwrap();
+            factors.push(BigUint::from_limbs(vec![p]).unwrap());
+        }
+    }
+    n
+}
+
+/// A nontrivial factor of the composite `n` by Pollard's rho with Floyd
+/// cycle detection, `x -> x^2 + c`, restarting with a fresh `c` when a cycle
+/// closes without splitting `n`. Adds the steps taken to `steps`. Returns
+/// `None` if interrupted.
+fn rho(n: &BigUint, steps: &mut u64) -> Option<BigUint> {
+    let ring = ModRing::new(n.clone());
+    let mut rng = rand::thread_rng();
+    let one = BigUint::one();
+    loop {
+        let c = random_below(&n.sub(&one), &mut rng).add(&one);
+        let f = |x: &BigUint| ring.add(&ring.mul(x, x), &c);
+        let mut x = random_below(n, &mut rng);
+        let mut y = x.clone();
+        let d = loop {
+            if interrupt::interrupted() {
+                return None;
+            }
+            let (x0, y0) = (x.clone(), y.clone());
+            let mut product = one.clone();
+            for _ in 0..BATCH {
+                x = f(&x);
+                y = f(&f(&y));
+                product = ring.mul(&product, &x.abs_diff(&y));
+            }
+            *steps += BATCH;
+            let d = product.gcd(n);
+            if d.is_one() {
+                continue;
+            }
+            if d != *n {
+                break d;
+            }
+            // Several factors were caught in one batch, or the cycle closed;
+            // replay the batch a step at a time.
+            let (mut x, mut y) = (x0, y0);
+            break loop {
+                x = f(&x);
+                y = f(&f(&y));
+                let d = x.abs_diff(&y).gcd(n);
+                if !d.is_one() {
+                    break d;
+                }
+            };
+        };
+        if d != *n {
+            return Some(d);
+        }
+    }
+}
+
+/// `p1^e1 * p2^e2 * ...` in increasing order of the primes.
+fn format_factors(mut factors: Vec<BigUint>) -> String {
+    factors.sort();
+    let mut terms: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < factors.len() {
+        let run = factors[i..]
+            .iter()
+            .take_while(|f| **f == factors[i])
+            .count();
+        terms.push(if run == 1 {
+            factors[i].to_string()
+        } else {
+            format!("{}^{}", factors[i], run)
+        });
+        i += run;
+    }
+    terms.join(" * ")
+}
+
+/// Factors `n` (`|n| >= 2`) and prints the factors and the time spent in
+/// each phase. Returns `false` if interrupted, after printing the factors
+/// found so far.
+pub fn run(n: &BigInt) -> bool {
+    let total = Instant::now();
+    let mut factors = Vec::new();
+
+    let start = Instant::now();
+    let cofactor = trial_divide(n.magnitude().clone(), &mut factors);
+    let t_trial = start.elapsed().as_secs_f64();
+    let small = factors.len();
+
+    let (mut t_rho, mut t_prime, mut steps) = (0.0, 0.0, 0);
+    let mut pending = vec![cofactor];
+    let mut complete = true;
+    while let Some(m) = pending.pop() {
+        if m.is_one() {
+            continue;
+        }
+        let start = Instant::now();
+        let prime = m.is_probable_prime_bpsw();
+        t_prime += start.elapsed().as_secs_f64();
+        if prime {
+            factors.push(m);
+            continue;
+        }
+        let start = Instant::now();
+        let split = rho(&m, &mut steps);
+        t_rho += start.elapsed().as_secs_f64();
+        match split {
+            Some(d) => {
+                pending.push(m.div_exact(&d));
+                pending.push(d);
+            }
+            None => {
+                println!("interrupted; unfactored cofactor: {}", m);
+                complete = false;
+                break;
+            }
+        }
+    }
+
+    let sign = if n.sign() == Sign::Minus { "-1 * " } else { "" };
+    println!("{} = {}{}", n, sign, format_factors(factors));
+    println!(
+        "trial division:  {:.6} s ({} factors below {})",
+        t_trial, small, TRIAL_LIMIT
+    );
+    println!("pollard rho:     {:.6} s ({} steps)", t_rho, steps);
+    println!("primality tests: {:.6} s", t_prime);
+    println!("total:           {:.6} s", total.elapsed().as_secs_f64());
+    complete
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rho_splits_semiprimes() {
+        let p = BigUint::from_str("1000000007").unwrap();
+        let q = BigUint::from_str("998244353").unwrap();
+        let n = p.mul(&q);
+        let d = rho(&n, &mut 0).unwrap();
+        assert!(d == p || d == q, "{}", d);
+    }
+
+    #[test]
+    fn trial_division_and_formatting() {
+        let mut factors = Vec::new();
+        let n = BigUint::from_str(&(2u64.pow(10) * 3 * 9973 * 10007).to_string()).unwrap();
+        let rest = trial_divide(n, &mut factors);
+        assert_eq!(rest, BigUint::from_str("10007").unwrap());
+        factors.push(rest);
+        assert_eq!(format_factors(factors), "2^10 * 3 * 9973 * 10007");
+    }
+}
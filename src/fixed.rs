@@ -0,0 +1,170 @@
+use crate::biguint::BigUint;
+use crate::limbs::{BASE, add_in_place, mul_direct_into, mul_karatsuba_into, sub_in_place};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A fixed-width unsigned integer of `LIMBS` base-10^9 limbs, stored inline
+/// with no heap allocation. Values range over `0..BASE^LIMBS`; addition and
+/// subtraction wrap modulo `BASE^LIMBS` and report overflow, and products
+/// come back whole as a `(low, high)` pair.
+///
+/// 256-bit values need 9 limbs, 1024-bit values 35 and 4096-bit values 138.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FixedUint<const LIMBS: usize> {
+    limbs: [u32; LIMBS],
+}
+
+impl<const LIMBS: usize> FixedUint<LIMBS> {
+    pub const ZERO: Self = FixedUint { limbs: [0; LIMBS] };
+
+    /// Returns `None` if any limb is not below `BASE`.
+    pub fn from_limbs(limbs: [u32; LIMBS]) -> Option<Self> {
+        limbs
+            .iter()
+            .all(|&d| (d as u64) < BASE)
+            .then_some(FixedUint { limbs })
+    }
+
+    /// Little-endian limbs, high zero limbs included.
+    pub fn as_limbs(&self) -> &[u32; LIMBS] {
+        &self.limbs
+    }
+
+    /// Returns `None` if `x` needs more than `LIMBS` limbs.
+    pub fn from_biguint(x: &BigUint) -> Option<Self> {
+        let digits = x.as_limbs();
+        if digits.len() > LIMBS {
+            return None;
+        }
+        let mut limbs = [0; LIMBS];
+        limbs[..digits.len()].copy_from_slice(digits);
+        Some(FixedUint { limbs })
+    }
+
+    pub fn to_biguint(&self) -> BigUint {
+        BigUint::from_kernel(self.limbs.to_vec())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.iter().all(|&d| d == 0)
+    }
+
+    /// Returns `(self + other) mod BASE^LIMBS` and whether it wrapped.
+    pub fn overflowing_add(&self, other: &Self) -> (Self, bool) {
+        let mut limbs = self.limbs;
+        let carry = add_in_place(&mut limbs, &other.limbs);
+        (FixedUint { limbs }, carry)
+    }
+
+    /// Returns `(self - other) mod BASE^LIMBS` and whether it wrapped.
+    pub fn overflowing_sub(&self, other: &Self) -> (Self, bool) {
+        let mut limbs = self.limbs;
+        let borrow = sub_in_place(&mut limbs, &other.limbs);
+        (FixedUint { limbs }, borrow)
+    }
+
+    /// Schoolbook product as `(low, high)` halves: `self * other =
+    /// low + high * BASE^LIMBS`.
+    pub fn widening_mul_direct(&self, other: &Self) -> (Self, Self) {
+        let mut out = [[0; LIMBS]; 2];
+        mul_direct_into(&self.limbs, &other.limbs, out.as_flattened_mut());
+        (FixedUint { limbs: out[0] }, FixedUint { limbs: out[1] })
+    }
+
+    /// Karatsuba product as `(low, high)` halves, using stack scratch space.
+    pub fn widening_mul_karatsuba(&self, other: &Self) -> (Self, Self) {
+        let mut out = [[0; LIMBS]; 2];
+        let mut scratch = [[0; LIMBS]; 6];
+        mul_karatsuba_into(
+            &self.limbs,
+            &other.limbs,
+            out.as_flattened_mut(),
+            scratch.as_flattened_mut(),
+        );
+        (FixedUint { limbs: out[0] }, FixedUint { limbs: out[1] })
+    }
+}
+
+impl<const LIMBS: usize> Default for FixedUint<LIMBS> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<const LIMBS: usize> PartialOrd for FixedUint<LIMBS> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const LIMBS: usize> Ord for FixedUint<LIMBS> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs.iter().rev().cmp(other.limbs.iter().rev())
+    }
+}
+
+impl<const LIMBS: usize> fmt::Display for FixedUint<LIMBS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.to_biguint(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn random<const L: usize>(rng: &mut StdRng) -> FixedUint<L> {
+        FixedUint::from_limbs(std::array::from_fn(|_| rng.gen_range(0..BASE as u32))).unwrap()
+    }
+
+    fn check_products<const L: usize>() {
+        let mut rng = StdRng::seed_from_u64(L as u64);
+        let max = FixedUint::<L>::from_limbs([BASE as u32 - 1; L]).unwrap();
+        for (a, b) in [
+            (random::<L>(&mut rng), random::<L>(&mut rng)),
+            (max, max),
+            (FixedUint::ZERO, max),
+        ] {
+            let expected = a.to_biguint().mul_karatsuba(&b.to_biguint());
+            for (low, high) in [a.widening_mul_direct(&b), a.widening_mul_karatsuba(&b)] {
+                let product = high.to_biguint().shift_left(L).add(&low.to_biguint());
+                assert_eq!(product, expected, "L = {}", L);
+            }
+        }
+    }
+
+    #[test]
+    fn products_match_biguint() {
+        check_products::<1>();
+        check_products::<9>();
+        check_products::<17>();
+        check_products::<35>();
+        check_products::<69>();
+        check_products::<138>();
+    }
+
+    #[test]
+    fn add_and_sub_wrap() {
+        let max = FixedUint::<2>::from_limbs([999_999_999; 2]).unwrap();
+        let one = FixedUint::<2>::from_limbs([1, 0]).unwrap();
+        assert_eq!(max.overflowing_add(&one), (FixedUint::ZERO, true));
+        assert_eq!(FixedUint::ZERO.overflowing_sub(&one), (max, true));
+        assert_eq!(max.overflowing_sub(&max), (FixedUint::ZERO, false));
+        assert!(one < max);
+    }
+
+    #[test]
+    fn biguint_conversion_checks_width() {
+        let x = BigUint::from_str("123456789012345678").unwrap();
+        assert_eq!(FixedUint::<2>::from_biguint(&x).unwrap().to_biguint(), x);
+        assert!(FixedUint::<1>::from_biguint(&x).is_none());
+        assert!(FixedUint::<2>::from_limbs([BASE as u32, 0]).is_none());
+        assert!(
+            FixedUint::<3>::from_biguint(&BigUint::new())
+                .unwrap()
+                .is_zero()
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! `--fixed-width`: multiplications per second for the stack-allocated
+//! `FixedUint` against the heap-backed `BigUint` at 256 to 4096 bits, with
+//! both the direct and the Karatsuba kernels.
+
+use crate::inputs;
+use crate::interrupt;
+use bigmul::{BigUint, FixedUint};
+use std::fmt::Write as _;
+use std::hint::black_box;
+use std::time::Instant;
+
+const METHODS: [&str; 4] = [
+    "fixed_direct",
+    "fixed_kara",
+    "biguint_direct",
+    "biguint_kara",
+];
+
+/// Products per method and size: enough for a stable rate at 256 bits
+/// without dragging out 4096.
+fn count(bits: usize) -> usize {
+    (20_000_000 / bits).max(1000)
+}
+
+fn rate<F: FnMut()>(count: usize, mut f: F) -> f64 {
+    let start = Instant::now();
+    for _ in 0..count {
+        f();
+    }
+    count as f64 / start.elapsed().as_secs_f64()
+}
+
+/// Measures one width; instantiated once per `LIMBS`.
+type Measure = fn(usize) -> [f64; 4];
+
+/// Rates for one width. `LIMBS` must hold `bits` bits.
+fn measure<const LIMBS: usize>(bits: usize) -> [f64; 4] {
+    let a = inputs::random_bits(bits);
+    let b = inputs::random_bits(bits);
+    let fa = FixedUint::<LIMBS>::from_biguint(&a).expect("operand fits LIMBS");
+    let fb = FixedUint::<LIMBS>::from_biguint(&b).expect("operand fits LIMBS");
+    let (low, high) = fa.widening_mul_karatsuba(&fb);
+    let product = high.to_biguint().shift_left(LIMBS).add(&low.to_biguint());
+    assert_eq!(product, a.mul_karatsuba(&b));
+    assert_eq!(fa.widening_mul_direct(&fb), (low, high));
+
+    let n = count(bits);
+    let (ba, bb): (&BigUint, &BigUint) = (&a, &b);
+    [
+        rate(n, || {
+            black_box(black_box(&fa).widening_mul_direct(black_box(&fb)));
+        }),
+        rate(n, || {
+            black_box(black_box(&fa).widening_mul_karatsuba(black_box(&fb)));
+        }),
+        rate(n, || {
+            black_box(black_box(ba).mul_direct(black_box(bb)));
+        }),
+        rate(n, || {
+            black_box(black_box(ba).mul_karatsuba(black_box(bb)));
+        }),
+    ]
+}
+
+/// Runs the comparison, printing products per second at each width and
+/// writing `./assets/fixed_width.csv`. Returns `false` if interrupted
+/// before the end.
+pub fn run() -> bool {
+    let widths: [(usize, Measure); 5] = [
+        (256, measure::<9>),
+        (512, measure::<18>),
+        (1024, measure::<35>),
+        (2048, measure::<69>),
+        (4096, measure::<138>),
+    ];
+    let mut rows: Vec<(usize, [f64; 4])> = Vec::new();
+    for (bits, measure) in widths {
+        if interrupt::interrupted() {
+            break;
+        }
+        let rates = measure(bits);
+        let mut line = format!("bits={}", bits);
+        for (name, rate) in METHODS.iter().zip(&rates) {
+            write!(line, ", {}={:.0}", name, rate).unwrap();
+        }
+        println!("{} (muls/s)", line);
+        rows.push((bits, rates));
+    }
+
+    let mut out = format!("bits,{}\n", METHODS.join(","));
+    for (bits, rates) in &rows {
+        write!(out, "{}", bits).unwrap();
+        for rate in rates {
+            write!(out, ",{:.1}", rate).unwrap();
+        }
+        out.push('\n');
+    }
+    std::fs::create_dir_all("./assets").expect("Failed to create ./assets directory");
+    std::fs::write("./assets/fixed_width.csv", out).expect("Failed to write CSV results");
+    println!("Results saved to ./assets/fixed_width.csv");
+    rows.len() == widths.len()
+}
@@ -0,0 +1,90 @@
+//! Forcing every `BigUint` product through one algorithm, for differential
+//! debugging: when a product deep inside `pow`, `mod_pow` or division comes
+//! out wrong, rerunning with each algorithm forced in turn shows which
+//! kernel is at fault without touching the call sites. Compiled only with
+//! the `force-algorithm` feature.
+//!
+//! The override applies to [`BigUint::mul`](crate::BigUint::mul),
+//! [`square`](crate::BigUint::square) and every `mul_*` method except
+//! `mul_low`, whatever the operand sizes (products by zero or one still
+//! short-circuit, as they run no kernel), and so to `BigInt` and everything
+//! built on them. [`instrument`](crate::instrument) still counts the path
+//! `mul` would have taken. The override starts out as `BIGMUL_FORCE_ALGORITHM`
+//! names it (`direct`, `dc`, `kara` or `kara_sub`, as in
+//! [`Algorithm::by_name`]) and can be changed at any time with [`force`].
+
+use crate::sweep::Algorithm;
+use std::sync::{OnceLock, PoisonError, RwLock};
+
+fn cell() -> &'static RwLock<Option<Algorithm>> {
+    static FORCED: OnceLock<RwLock<Option<Algorithm>>> = OnceLock::new();
+    FORCED.get_or_init(|| {
+        let forced = std::env::var("BIGMUL_FORCE_ALGORITHM").ok().map(|name| {
+            Algorithm::by_name(&name).unwrap_or_else(|| {
+                panic!(
+                    "unknown BIGMUL_FORCE_ALGORITHM '{}' (expected direct, dc, kara or kara_sub)",
+                    name
+                )
+            })
+        });
+        RwLock::new(forced)
+    })
+}
+
+/// Routes every product, on all threads, through `algorithm`; `None`
+/// restores the usual dispatch. Replaces `BIGMUL_FORCE_ALGORITHM`.
+pub fn force(algorithm: Option<Algorithm>) {
+    *cell().write().unwrap_or_else(PoisonError::into_inner) = algorithm;
+}
+
+/// The algorithm products are currently forced through, if any.
+pub fn forced() -> Option<Algorithm> {
+    *cell().read().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BigInt, BigUint, limbs};
+    use std::cell::Cell;
+
+    thread_local! {
+        static CALLS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// Schoolbook multiplication that counts its calls on this thread, so
+    /// tests running concurrently under the same override don't interfere.
+    fn counting(a: &[u32], b: &[u32]) -> Vec<u32> {
+        CALLS.with(|c| c.set(c.get() + 1));
+        limbs::mul_direct_slices(a, b)
+    }
+
+    #[test]
+    fn forced_algorithm_sees_every_product() {
+        let calls = |f: &dyn Fn()| {
+            CALLS.with(|c| c.set(0));
+            f();
+            CALLS.with(|c| c.get())
+        };
+        let x = BigUint::from_str(&"7".repeat(500)).unwrap();
+        let y = BigUint::from_str("123456789123456789").unwrap();
+        let expected = (x.mul_karatsuba(&y), x.pow(5), x.square());
+        force(Some(Algorithm {
+            name: "counting",
+            mul: counting,
+        }));
+        assert_eq!(forced().unwrap().name, "counting");
+        assert_eq!(calls(&|| assert_eq!(x.mul(&y), expected.0)), 1);
+        assert_eq!(calls(&|| assert_eq!(x.mul_dc(&y), expected.0)), 1);
+        assert_eq!(calls(&|| assert_eq!(x.square(), expected.2)), 1);
+        assert!(calls(&|| assert_eq!(x.pow(5), expected.1)) >= 3);
+        let product = calls(&|| {
+            let signed = BigInt::from_biguint(crate::Sign::Minus, y.clone());
+            assert_eq!(signed.mul(&signed).magnitude(), &y.square());
+        });
+        assert_eq!(product, 2);
+        force(None);
+        assert!(forced().is_none());
+        assert_eq!(calls(&|| assert_eq!(x.mul(&y), expected.0)), 0);
+    }
+}
@@ -0,0 +1,65 @@
+//! `ibig` and `malachite` as series in the sweep, behind the `ibig` and
+//! `malachite` features, so the other pure-Rust bignum crates share a chart
+//! with this one's kernels.
+//!
+//! Operands cross as decimal text, as they would through `--external`: each
+//! timed product includes parsing both operands into the crate's own binary
+//! representation and printing the product back, which is what a caller
+//! holding decimal data pays for switching crates.
+
+use crate::multiplier::Multiplier;
+use bigmul::BigUint;
+
+fn decimal(limbs: &[u32]) -> String {
+    BigUint::from_limbs(limbs.to_vec())
+        .expect("operands are normalized limbs")
+        .to_string()
+}
+
+fn limbs(decimal: &str) -> Vec<u32> {
+    BigUint::from_str(decimal)
+        .expect("products print as decimal")
+        .into_limbs()
+}
+
+#[cfg(feature = "ibig")]
+struct Ibig;
+
+#[cfg(feature = "ibig")]
+impl Multiplier for Ibig {
+    fn mul(&self, a: &[u32], b: &[u32]) -> Vec<u32> {
+        let parse =
+            |x: &[u32]| ibig::UBig::from_str_radix(&decimal(x), 10).expect("decimal operand");
+        limbs(&(parse(a) * parse(b)).to_string())
+    }
+
+    fn name(&self) -> &str {
+        "ibig"
+    }
+}
+
+#[cfg(feature = "malachite")]
+struct Malachite;
+
+#[cfg(feature = "malachite")]
+impl Multiplier for Malachite {
+    fn mul(&self, a: &[u32], b: &[u32]) -> Vec<u32> {
+        use std::str::FromStr;
+        let parse = |x: &[u32]| malachite::Natural::from_str(&decimal(x)).expect("decimal operand");
+        limbs(&(parse(a) * parse(b)).to_string())
+    }
+
+    fn name(&self) -> &str {
+        "malachite"
+    }
+}
+
+/// The crates enabled in this build, in chart order.
+pub fn multipliers() -> Vec<Box<dyn Multiplier>> {
+    vec![
+        #[cfg(feature = "ibig")]
+        Box::new(Ibig),
+        #[cfg(feature = "malachite")]
+        Box::new(Malachite),
+    ]
+}
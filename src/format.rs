@@ -0,0 +1,199 @@
+//! Human-readable layouts of huge numbers: digit grouping, scientific and
+//! engineering notation, and truncation to the leading and trailing digits,
+//! for output where a million raw digits would be useless to read.
+//! Separators are arbitrary strings, so `,`, `.`, `'`, a space or U+202F
+//! NARROW NO-BREAK SPACE all work for the locale at hand.
+
+use crate::bigint::{BigInt, Sign};
+use crate::biguint::BigUint;
+
+/// How [`BigUint::format`] and [`BigInt::format`] lay out the digits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Notation {
+    /// Every digit, as `Display` prints it.
+    Plain,
+    /// Every digit, in groups of three from the right joined by the
+    /// separator: `1_234_567`.
+    Grouped(String),
+    /// One digit before the point and `precision` after, rounded half up:
+    /// `1.2346e+1234`.
+    Scientific { precision: usize },
+    /// As `Scientific`, with the exponent a multiple of three and one to
+    /// three digits before the point: `12.346e+1233`.
+    Engineering { precision: usize },
+    /// The first and last `keep` digits around `...`, then the digit count:
+    /// `123456...987654 (2,000,000 digits)`. Values of at most `2 * keep`
+    /// digits print in full.
+    Truncated { keep: usize },
+}
+
+impl BigUint {
+    /// The value laid out as `notation` says.
+    ///
+    /// ```
+    /// use bigmul::{BigUint, Notation};
+    ///
+    /// let n = BigUint::from_str("1234567890").unwrap();
+    /// assert_eq!(n.format(&Notation::Grouped("_".into())), "1_234_567_890");
+    /// assert_eq!(n.format(&Notation::Scientific { precision: 3 }), "1.235e+9");
+    /// assert_eq!(n.format(&Notation::Engineering { precision: 2 }), "1.23e+9");
+    /// ```
+    pub fn format(&self, notation: &Notation) -> String {
+        let digits = self.to_string();
+        match notation {
+            Notation::Plain => digits,
+            Notation::Grouped(separator) => group(&digits, separator),
+            Notation::Scientific { precision } => {
+                let (mantissa, exponent) = round(&digits, 1 + precision);
+                exponential(&mantissa, 1, exponent)
+            }
+            Notation::Engineering { precision } => {
+                let before = |exponent: usize| 1 + exponent % 3;
+                let exponent = digits.len() - 1;
+                let (mut mantissa, mut rounded) = round(&digits, before(exponent) + precision);
+                if rounded != exponent {
+                    // Rounded up to a power of ten: the leading digit moved.
+                    mantissa = format!("1{}", "0".repeat(before(rounded) + precision - 1));
+                }
+                let point = before(rounded);
+                rounded -= point - 1;
+                exponential(&mantissa, point, rounded)
+            }
+            Notation::Truncated { keep } => {
+                let len = digits.len();
+                if len <= 2 * keep {
+                    return digits;
+                }
+                format!(
+                    "{}...{} ({} digits)",
+                    &digits[..*keep],
+                    &digits[len - keep..],
+                    group(&len.to_string(), ",")
+                )
+            }
+        }
+    }
+}
+
+impl BigInt {
+    /// [`BigUint::format`] of the magnitude, after a minus sign for
+    /// negative values.
+    pub fn format(&self, notation: &Notation) -> String {
+        let sign = if self.sign() == Sign::Minus { "-" } else { "" };
+        format!("{}{}", sign, self.magnitude().format(notation))
+    }
+}
+
+/// `digits` in groups of three from the right, joined by `separator`.
+fn group(digits: &str, separator: &str) -> String {
+    let head = match digits.len() % 3 {
+        0 => 3,
+        r => r,
+    };
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3 * separator.len());
+    out.push_str(&digits[..head]);
+    for start in (head..digits.len()).step_by(3) {
+        out.push_str(separator);
+        out.push_str(&digits[start..start + 3]);
+    }
+    out
+}
+
+/// The first `keep` significant digits of `digits`, rounded half up and
+/// padded with zeros, and the decimal exponent of the leading one.
+fn round(digits: &str, keep: usize) -> (String, usize) {
+    let mut exponent = digits.len() - 1;
+    if digits.len() <= keep {
+        return (format!("{:0<keep$}", digits), exponent);
+    }
+    let mut kept = digits.as_bytes()[..keep].to_vec();
+    if digits.as_bytes()[keep] >= b'5' {
+        match kept.iter().rposition(|&d| d != b'9') {
+            Some(i) => {
+                kept[i] += 1;
+                kept[i + 1..].fill(b'0');
+            }
+            None => {
+                kept.fill(b'0');
+                kept[0] = b'1';
+                exponent += 1;
+            }
+        }
+    }
+    (kept.into_iter().map(char::from).collect(), exponent)
+}
+
+/// `mantissa` with a point after its first `point` digits (none if nothing
+/// follows), then `e+exponent`.
+fn exponential(mantissa: &str, point: usize, exponent: usize) -> String {
+    let (int, frac) = mantissa.split_at(point);
+    if frac.is_empty() {
+        format!("{}e+{}", int, exponent)
+    } else {
+        format!("{}.{}e+{}", int, frac, exponent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(s: &str, notation: Notation) -> String {
+        BigInt::from_str(s).unwrap().format(&notation)
+    }
+
+    #[test]
+    fn grouping_counts_from_the_right() {
+        let comma = || Notation::Grouped(",".into());
+        assert_eq!(format("0", comma()), "0");
+        assert_eq!(format("999", comma()), "999");
+        assert_eq!(format("1000", comma()), "1,000");
+        assert_eq!(format("-123456", comma()), "-123,456");
+        assert_eq!(
+            format("12345678901234567890", Notation::Grouped("\u{202f}".into())),
+            "12\u{202f}345\u{202f}678\u{202f}901\u{202f}234\u{202f}567\u{202f}890"
+        );
+        assert_eq!(format("-42", Notation::Plain), "-42");
+    }
+
+    #[test]
+    fn truncation_keeps_both_ends() {
+        let digits: String = (0..2_000_000u32)
+            .map(|i| char::from(b'1' + (i % 9) as u8))
+            .collect();
+        assert_eq!(
+            format(&digits, Notation::Truncated { keep: 6 }),
+            "123456...678912 (2,000,000 digits)"
+        );
+        assert_eq!(
+            format("-12345678901234", Notation::Truncated { keep: 3 }),
+            "-123...234 (14 digits)"
+        );
+        assert_eq!(format("123456", Notation::Truncated { keep: 3 }), "123456");
+    }
+
+    #[test]
+    fn scientific_rounds_half_up() {
+        let sci = |s, precision| format(s, Notation::Scientific { precision });
+        assert_eq!(sci("0", 2), "0.00e+0");
+        assert_eq!(sci("7", 0), "7e+0");
+        assert_eq!(sci("12345", 2), "1.23e+4");
+        assert_eq!(sci("12355", 2), "1.24e+4");
+        assert_eq!(sci("-19999", 2), "-2.00e+4");
+        assert_eq!(sci("99999", 0), "1e+5");
+        assert_eq!(sci("5", 4), "5.0000e+0");
+        let big = format!("31415{}", "9".repeat(2000));
+        assert_eq!(sci(&big, 4), "3.1416e+2004");
+    }
+
+    #[test]
+    fn engineering_keeps_the_exponent_a_multiple_of_three() {
+        let eng = |s, precision| format(s, Notation::Engineering { precision });
+        assert_eq!(eng("1", 2), "1.00e+0");
+        assert_eq!(eng("12345", 1), "12.3e+3");
+        assert_eq!(eng("123456", 0), "123e+3");
+        assert_eq!(eng("1234567", 3), "1.235e+6");
+        assert_eq!(eng("999951", 1), "1.0e+6");
+        assert_eq!(eng("99951", 1), "100.0e+3");
+    }
+}
@@ -0,0 +1,389 @@
+//! Schoolbook multiplication on an NVIDIA GPU, as one more series in the
+//! comparison. Only built with the `cuda` feature, which links the driver's
+//! `libcuda`; the kernel ships as PTX text that the driver compiles for
+//! whatever device it finds.
+//!
+//! One GPU thread per product column sums `a[i] * b[k - i]` into 128 bits,
+//! with no carries between columns, and the host folds the column sums into
+//! base `10^9` limbs in one pass. The work stays quadratic, but spread over
+//! thousands of threads; copying the operands in and the columns out is
+//! linear, so the GPU can only win at large sizes.
+
+use crate::multiplier::Multiplier;
+use bigmul::limbs;
+use std::ffi::{CStr, c_char, c_int, c_uint, c_void};
+use std::ptr;
+use std::sync::{Mutex, OnceLock};
+
+/// `mul_columns(a, na, b, nb, columns)`: thread `k` writes column `k` of the
+/// product as a little-endian pair of 64-bit words.
+const PTX: &CStr = c".version 7.0
+.target sm_50
+.address_size 64
+
+.visible .entry mul_columns(
+    .param .u64 p_a,
+    .param .u32 p_na,
+    .param .u64 p_b,
+    .param .u32 p_nb,
+    .param .u64 p_out
+)
+{
+    .reg .pred %p<4>;
+    .reg .b32 %r<16>;
+    .reg .b64 %rd<16>;
+
+    ld.param.u64 %rd1, [p_a];
+    ld.param.u32 %r1, [p_na];
+    ld.param.u64 %rd2, [p_b];
+    ld.param.u32 %r2, [p_nb];
+    ld.param.u64 %rd3, [p_out];
+    cvta.to.global.u64 %rd1, %rd1;
+    cvta.to.global.u64 %rd2, %rd2;
+    cvta.to.global.u64 %rd3, %rd3;
+
+    mov.u32 %r3, %ctaid.x;
+    mov.u32 %r4, %ntid.x;
+    mov.u32 %r5, %tid.x;
+    mad.lo.u32 %r6, %r3, %r4, %r5;
+    add.u32 %r7, %r1, %r2;
+    sub.u32 %r7, %r7, 1;
+    setp.ge.u32 %p1, %r6, %r7;
+    @%p1 bra DONE;
+
+    sub.u32 %r8, %r2, 1;
+    setp.gt.u32 %p2, %r6, %r8;
+    sub.u32 %r9, %r6, %r8;
+    selp.u32 %r9, %r9, 0, %p2;
+    sub.u32 %r10, %r1, 1;
+    min.u32 %r10, %r10, %r6;
+    mov.u64 %rd4, 0;
+    mov.u64 %rd5, 0;
+LOOP:
+    setp.gt.u32 %p3, %r9, %r10;
+    @%p3 bra STORE;
+    mul.wide.u32 %rd6, %r9, 4;
+    add.u64 %rd7, %rd1, %rd6;
+    ld.global.u32 %r11, [%rd7];
+    sub.u32 %r12, %r6, %r9;
+    mul.wide.u32 %rd8, %r12, 4;
+    add.u64 %rd9, %rd2, %rd8;
+    ld.global.u32 %r13, [%rd9];
+    mul.wide.u32 %rd10, %r11, %r13;
+    add.cc.u64 %rd4, %rd4, %rd10;
+    addc.u64 %rd5, %rd5, 0;
+    add.u32 %r9, %r9, 1;
+    bra LOOP;
+STORE:
+    mul.wide.u32 %rd11, %r6, 16;
+    add.u64 %rd12, %rd3, %rd11;
+    st.global.v2.u64 [%rd12], {%rd4, %rd5};
+DONE:
+    ret;
+}
+";
+
+/// Threads per block.
+const BLOCK: c_uint = 256;
+
+const CUDA_SUCCESS: c_int = 0;
+
+type CuDevice = c_int;
+type CuDevicePtr = u64;
+
+#[repr(C)]
+struct CuContext {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+struct CuModule {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+struct CuFunction {
+    _private: [u8; 0],
+}
+
+#[link(name = "cuda")]
+unsafe extern "C" {
+    fn cuInit(flags: c_uint) -> c_int;
+    fn cuDeviceGet(device: *mut CuDevice, ordinal: c_int) -> c_int;
+    fn cuDeviceGetName(name: *mut c_char, len: c_int, device: CuDevice) -> c_int;
+    fn cuDevicePrimaryCtxRetain(ctx: *mut *mut CuContext, device: CuDevice) -> c_int;
+    fn cuCtxSetCurrent(ctx: *mut CuContext) -> c_int;
+    fn cuCtxSynchronize() -> c_int;
+    fn cuModuleLoadData(module: *mut *mut CuModule, image: *const c_void) -> c_int;
+    fn cuModuleGetFunction(
+        function: *mut *mut CuFunction,
+        module: *mut CuModule,
+        name: *const c_char,
+    ) -> c_int;
+    fn cuMemAlloc_v2(ptr: *mut CuDevicePtr, bytes: usize) -> c_int;
+    fn cuMemFree_v2(ptr: CuDevicePtr) -> c_int;
+    fn cuMemcpyHtoD_v2(dst: CuDevicePtr, src: *const c_void, bytes: usize) -> c_int;
+    fn cuMemcpyDtoH_v2(dst: *mut c_void, src: CuDevicePtr, bytes: usize) -> c_int;
+    #[allow(clippy::too_many_arguments)]
+    fn cuLaunchKernel(
+        function: *mut CuFunction,
+        grid_x: c_uint,
+        grid_y: c_uint,
+        grid_z: c_uint,
+        block_x: c_uint,
+        block_y: c_uint,
+        block_z: c_uint,
+        shared_bytes: c_uint,
+        stream: *mut c_void,
+        params: *mut *mut c_void,
+        extra: *mut *mut c_void,
+    ) -> c_int;
+    fn cuGetErrorString(error: c_int, text: *mut *const c_char) -> c_int;
+}
+
+/// Turns a driver status into an error naming the call that failed.
+fn check(status: c_int, call: &str) -> Result<(), String> {
+    if status == CUDA_SUCCESS {
+        return Ok(());
+    }
+    let mut text = ptr::null();
+    // SAFETY: the driver points `text` at a static string or leaves it null.
+    let reason =
+        if unsafe { cuGetErrorString(status, &mut text) } == CUDA_SUCCESS && !text.is_null() {
+            // SAFETY: non-null and NUL-terminated, as above.
+            unsafe { CStr::from_ptr(text) }
+                .to_string_lossy()
+                .into_owned()
+        } else {
+            format!("error {}", status)
+        };
+    Err(format!("{} failed: {}", call, reason))
+}
+
+/// The first device's primary context and the loaded kernel.
+struct Device {
+    name: String,
+    context: *mut CuContext,
+    kernel: *mut CuFunction,
+    /// Launches share the kernel handle; one product at a time.
+    lock: Mutex<()>,
+}
+
+// SAFETY: the driver's handles may be used from any thread once the context
+// is made current there, which `Device::columns` does under `lock`.
+unsafe impl Send for Device {}
+unsafe impl Sync for Device {}
+
+/// A device allocation, freed on drop.
+struct Buffer(CuDevicePtr);
+
+impl Buffer {
+    fn new(bytes: usize) -> Result<Buffer, String> {
+        let mut ptr = 0;
+        // SAFETY: `ptr` is a valid out-pointer.
+        check(
+            unsafe { cuMemAlloc_v2(&mut ptr, bytes.max(1)) },
+            "cuMemAlloc",
+        )?;
+        Ok(Buffer(ptr))
+    }
+
+    fn upload(data: &[u32]) -> Result<Buffer, String> {
+        let bytes = std::mem::size_of_val(data);
+        let buffer = Buffer::new(bytes)?;
+        // SAFETY: the allocation holds `bytes` bytes and `data` as many.
+        check(
+            unsafe { cuMemcpyHtoD_v2(buffer.0, data.as_ptr() as *const c_void, bytes) },
+            "cuMemcpyHtoD",
+        )?;
+        Ok(buffer)
+    }
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        // SAFETY: allocated by `Buffer::new` and not freed elsewhere.
+        unsafe {
+            cuMemFree_v2(self.0);
+        }
+    }
+}
+
+impl Device {
+    fn open() -> Result<Device, String> {
+        let mut device = 0;
+        let mut name = [0 as c_char; 128];
+        let mut context = ptr::null_mut();
+        let mut module = ptr::null_mut();
+        let mut kernel = ptr::null_mut();
+        // SAFETY: every out-pointer is valid, `name` holds its stated
+        // length, and `PTX` and the entry name are NUL-terminated.
+        unsafe {
+            check(cuInit(0), "cuInit")?;
+            check(cuDeviceGet(&mut device, 0), "cuDeviceGet")?;
+            check(
+                cuDeviceGetName(name.as_mut_ptr(), name.len() as c_int, device),
+                "cuDeviceGetName",
+            )?;
+            check(
+                cuDevicePrimaryCtxRetain(&mut context, device),
+                "cuDevicePrimaryCtxRetain",
+            )?;
+            check(cuCtxSetCurrent(context), "cuCtxSetCurrent")?;
+            check(
+                cuModuleLoadData(&mut module, PTX.as_ptr() as *const c_void),
+                "cuModuleLoadData",
+            )?;
+            check(
+                cuModuleGetFunction(&mut kernel, module, c"mul_columns".as_ptr()),
+                "cuModuleGetFunction",
+            )?;
+        }
+        // SAFETY: the driver NUL-terminated the name within the buffer.
+        let name = unsafe { CStr::from_ptr(name.as_ptr()) }
+            .to_string_lossy()
+            .into_owned();
+        Ok(Device {
+            name,
+            context,
+            kernel,
+            lock: Mutex::new(()),
+        })
+    }
+
+    /// The column sums of `a * b`, `a.len() + b.len() - 1` of them.
+    fn columns(&self, a: &[u32], b: &[u32]) -> Result<Vec<u128>, String> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let count = a.len() + b.len() - 1;
+        let (na, nb) = (a.len() as c_uint, b.len() as c_uint);
+        // SAFETY: the context was created by `open` and is never destroyed.
+        check(unsafe { cuCtxSetCurrent(self.context) }, "cuCtxSetCurrent")?;
+        let (da, db) = (Buffer::upload(a)?, Buffer::upload(b)?);
+        let out = Buffer::new(16 * count)?;
+        let mut params = [
+            &da.0 as *const _ as *mut c_void,
+            &na as *const _ as *mut c_void,
+            &db.0 as *const _ as *mut c_void,
+            &nb as *const _ as *mut c_void,
+            &out.0 as *const _ as *mut c_void,
+        ];
+        let grid = (count as c_uint).div_ceil(BLOCK);
+        let mut words = vec![0u64; 2 * count];
+        // SAFETY: `params` points at values matching the kernel's parameter
+        // list, which outlive the synchronized launch; `words` holds the
+        // `16 * count` bytes copied back.
+        unsafe {
+            check(
+                cuLaunchKernel(
+                    self.kernel,
+                    grid,
+                    1,
+                    1,
+                    BLOCK,
+                    1,
+                    1,
+                    0,
+                    ptr::null_mut(),
+                    params.as_mut_ptr(),
+                    ptr::null_mut(),
+                ),
+                "cuLaunchKernel",
+            )?;
+            check(cuCtxSynchronize(), "cuCtxSynchronize")?;
+            check(
+                cuMemcpyDtoH_v2(words.as_mut_ptr() as *mut c_void, out.0, 16 * count),
+                "cuMemcpyDtoH",
+            )?;
+        }
+        Ok(words
+            .chunks_exact(2)
+            .map(|w| w[0] as u128 | (w[1] as u128) << 64)
+            .collect())
+    }
+}
+
+/// Folds column sums into normalized base `10^9` limbs.
+fn fold_columns(columns: &[u128]) -> Vec<u32> {
+    let base = limbs::BASE as u128;
+    let mut result = Vec::with_capacity(columns.len() + 1);
+    let mut carry = 0u128;
+    for &column in columns {
+        carry += column;
+        result.push((carry % base) as u32);
+        carry /= base;
+    }
+    while carry > 0 {
+        result.push((carry % base) as u32);
+        carry /= base;
+    }
+    limbs::normalize(&mut result);
+    result
+}
+
+/// The registered GPU multiplier. Quadratic, but not marked so: thousands
+/// of columns at a time keep it in reach of `--large` sizes, the only place
+/// it could come out ahead.
+struct Cuda {
+    device: &'static Device,
+    label: String,
+}
+
+impl Multiplier for Cuda {
+    fn mul(&self, a: &[u32], b: &[u32]) -> Vec<u32> {
+        if a.is_empty() || b.is_empty() {
+            return vec![0];
+        }
+        let columns = self
+            .device
+            .columns(a, b)
+            .unwrap_or_else(|e| panic!("{}: {}", self.device.name, e));
+        fold_columns(&columns)
+    }
+
+    fn name(&self) -> &str {
+        "gpu"
+    }
+
+    fn label(&self) -> &str {
+        &self.label
+    }
+}
+
+/// The GPU multiplier on the first CUDA device, or `None` when there is
+/// none; the reason is printed once.
+pub fn multiplier() -> Option<Box<dyn Multiplier>> {
+    static DEVICE: OnceLock<Option<Device>> = OnceLock::new();
+    let device = DEVICE
+        .get_or_init(|| {
+            Device::open()
+                .inspect_err(|e| eprintln!("warning: GPU multiplier unavailable: {}", e))
+                .ok()
+        })
+        .as_ref()?;
+    Some(Box::new(Cuda {
+        device,
+        label: format!("Schoolbook (GPU, {})", device.name),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_fold_into_the_product() {
+        let a: Vec<u32> = (1..=60).map(|i| 999_999_999 - i * 13).collect();
+        let b: Vec<u32> = (1..=45).map(|i| i * 7_777_777 % 1_000_000_000).collect();
+        let mut columns = vec![0u128; a.len() + b.len() - 1];
+        for (i, &x) in a.iter().enumerate() {
+            for (j, &y) in b.iter().enumerate() {
+                columns[i + j] += x as u128 * y as u128;
+            }
+        }
+        assert_eq!(fold_columns(&columns), limbs::mul_direct_slices(&a, &b));
+        assert_eq!(fold_columns(&[0, 0]), [0]);
+        if let Some(gpu) = multiplier() {
+            assert_eq!(gpu.mul(&a, &b), limbs::mul_direct_slices(&a, &b));
+        }
+    }
+}
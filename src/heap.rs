@@ -0,0 +1,81 @@
+//! The binary's global allocator: the system one, plus a count of live
+//! heap bytes and their high-water mark while [`peak_bytes`] is measuring.
+//! Outside a measurement each allocation pays one relaxed load, so timings
+//! are unaffected. The counts cover every thread, which is what the
+//! parallel kernels need.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering::Relaxed};
+
+struct Counting;
+
+static TRACKING: AtomicBool = AtomicBool::new(false);
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() && TRACKING.load(Relaxed) {
+            grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        if TRACKING.load(Relaxed) {
+            shrink(layout.size());
+        }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new.is_null() && TRACKING.load(Relaxed) {
+            shrink(layout.size());
+            grow(new_size);
+        }
+        new
+    }
+}
+
+fn grow(bytes: usize) {
+    let live = LIVE.fetch_add(bytes, Relaxed) + bytes;
+    PEAK.fetch_max(live, Relaxed);
+}
+
+/// Saturates, since blocks allocated before the measurement may be freed
+/// during it.
+fn shrink(bytes: usize) {
+    let _ = LIVE.fetch_update(Relaxed, Relaxed, |live| Some(live.saturating_sub(bytes)));
+}
+
+/// Runs `f` and returns the most heap it held at once beyond what was live
+/// when it started, in bytes. Measurements must not overlap.
+pub fn peak_bytes(f: impl FnOnce()) -> usize {
+    LIVE.store(0, Relaxed);
+    PEAK.store(0, Relaxed);
+    TRACKING.store(true, Relaxed);
+    f();
+    TRACKING.store(false, Relaxed);
+    PEAK.load(Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peak_counts_what_was_held_at_once() {
+        let peak = peak_bytes(|| {
+            let a = vec![0u8; 1 << 20];
+            let b = vec![0u8; 1 << 20];
+            std::hint::black_box((a, b));
+            std::hint::black_box(vec![0u8; 1 << 19]);
+        });
+        assert!(peak >= 2 << 20, "{}", peak);
+    }
+}
@@ -0,0 +1,176 @@
+//! `--heatmap`: times every registered multiplier on each `(len_a, len_b)`
+//! pair of a geometric grid and charts which one wins each cell. This is
+//! the data a dispatch policy for unbalanced operands has to be tuned on.
+
+use crate::inputs::{Axis, InputClass};
+use crate::interrupt;
+use crate::multiplier::Multiplier;
+use bigmul::report::Style;
+use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use std::fmt::Write as _;
+use std::time::Instant;
+
+/// Operand lengths in decimal digits along both axes.
+const GRID_DIGITS: [usize; 8] = [250, 500, 1000, 2000, 4000, 8000, 16000, 32000];
+const NUM_INSTANCES: usize = 3;
+
+/// Average seconds per multiplier for one cell, or `None` if interrupted.
+fn time_cell(multipliers: &[Box<dyn Multiplier>], da: usize, db: usize) -> Option<Vec<f64>> {
+    let mut totals = vec![0.0; multipliers.len()];
+    for _ in 0..NUM_INSTANCES {
+        if interrupt::interrupted() {
+            return None;
+        }
+        let a = InputClass::Random.generate(da, Axis::Digits);
+        let b = InputClass::Random.generate(db, Axis::Digits);
+        let mut reference: Option<Vec<u32>> = None;
+        for (total, m) in totals.iter_mut().zip(multipliers) {
+            let start = Instant::now();
+            let product = m.mul(a.as_limbs(), b.as_limbs());
+            *total += start.elapsed().as_secs_f64();
+            match &reference {
+                Some(expected) => assert_eq!(&product, expected, "{} disagrees", m.name()),
+                None => reference = Some(product),
+            }
+        }
+    }
+    Some(totals.iter().map(|t| t / NUM_INSTANCES as f64).collect())
+}
+
+fn fastest(times: &[f64]) -> usize {
+    (0..times.len())
+        .min_by(|&i, &j| times[i].total_cmp(&times[j]))
+        .unwrap()
+}
+
+/// Runs the grid, prints the winner of each cell and writes
+/// `./assets/heatmap.csv` and `./assets/heatmap.png`. Returns `false` if
+/// interrupted before the end.
+pub fn run(multipliers: &[Box<dyn Multiplier>]) -> bool {
+    let mut cells: Vec<(usize, usize, Vec<f64>)> = Vec::new();
+    'grid: for &da in &GRID_DIGITS {
+        for &db in &GRID_DIGITS {
+            let Some(times) = time_cell(multipliers, da, db) else {
+                break 'grid;
+            };
+            let winner = fastest(&times);
+            println!(
+                "len_a={}, len_b={}: {} ({:.6} s)",
+                da,
+                db,
+                multipliers[winner].name(),
+                times[winner]
+            );
+            cells.push((da, db, times));
+        }
+    }
+
+    let mut out = String::from("len_a,len_b");
+    for m in multipliers {
+        write!(out, ",{}", m.name()).unwrap();
+    }
+    out.push_str(",fastest\n");
+    for (da, db, times) in &cells {
+        write!(out, "{},{}", da, db).unwrap();
+        for t in times {
+            write!(out, ",{:.9}", t).unwrap();
+        }
+        writeln!(out, ",{}", multipliers[fastest(times)].name()).unwrap();
+    }
+    std::fs::create_dir_all("./assets").expect("Failed to create ./assets directory");
+    std::fs::write("./assets/heatmap.csv", out).expect("Failed to write CSV results");
+    plot("./assets/heatmap.png", multipliers, &cells);
+    println!("Results saved to ./assets/heatmap.csv and ./assets/heatmap.png");
+    cells.len() == GRID_DIGITS.len() * GRID_DIGITS.len()
+}
+
+/// One square per cell in the winner's chart color, with the winner's
+/// margin over the runner-up written inside and the color key on the right.
+fn plot(path: &str, multipliers: &[Box<dyn Multiplier>], cells: &[(usize, usize, Vec<f64>)]) {
+    let root = BitMapBackend::new(path, (1100, 800)).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+    let (grid, key) = root.split_horizontally(850);
+    let n = GRID_DIGITS.len() as i32;
+    let axis_label = |v: &SegmentValue<i32>| match v {
+        SegmentValue::CenterOf(i) => GRID_DIGITS
+            .get(*i as usize)
+            .map_or(String::new(), |d| d.to_string()),
+        _ => String::new(),
+    };
+    let mut chart = ChartBuilder::on(&grid)
+        .caption(
+            "Fastest Algorithm by Operand Lengths",
+            ("sans-serif", 36).into_font(),
+        )
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d((0..n - 1).into_segmented(), (0..n - 1).into_segmented())
+        .unwrap();
+    chart
+        .configure_mesh()
+        .disable_mesh()
+        .x_label_formatter(&axis_label)
+        .y_label_formatter(&axis_label)
+        .x_desc("len_a (digits)")
+        .y_desc("len_b (digits)")
+        .draw()
+        .unwrap();
+
+    let index = |d: usize| GRID_DIGITS.iter().position(|&g| g == d).unwrap() as i32;
+    chart
+        .draw_series(cells.iter().map(|(da, db, times)| {
+            let (x, y) = (index(*da), index(*db));
+            let color = Style::of(multipliers[fastest(times)].name()).color;
+            Rectangle::new(
+                [
+                    (SegmentValue::Exact(x), SegmentValue::Exact(y)),
+                    (SegmentValue::Exact(x + 1), SegmentValue::Exact(y + 1)),
+                ],
+                color.mix(0.6).filled(),
+            )
+        }))
+        .unwrap();
+    let centered = ("sans-serif", 16)
+        .into_font()
+        .into_text_style(&grid)
+        .pos(Pos::new(HPos::Center, VPos::Center));
+    chart
+        .draw_series(
+            cells
+                .iter()
+                .filter(|(_, _, t)| t.len() > 1)
+                .map(|(da, db, times)| {
+                    let winner = fastest(times);
+                    let runner_up = (0..times.len())
+                        .filter(|&i| i != winner)
+                        .map(|i| times[i])
+                        .fold(f64::INFINITY, f64::min);
+                    Text::new(
+                        format!("x{:.2}", runner_up / times[winner]),
+                        (
+                            SegmentValue::CenterOf(index(*da)),
+                            SegmentValue::CenterOf(index(*db)),
+                        ),
+                        centered.clone(),
+                    )
+                }),
+        )
+        .unwrap();
+
+    let font = ("sans-serif", 16).into_font();
+    for (k, m) in multipliers.iter().enumerate() {
+        let color = Style::of(m.name()).color;
+        let y = 80 + 30 * k as i32;
+        key.draw(&Rectangle::new(
+            [(10, y), (30, y + 20)],
+            color.mix(0.6).filled(),
+        ))
+        .unwrap();
+        key.draw(&Text::new(m.label().to_string(), (40, y + 3), font.clone()))
+            .unwrap();
+    }
+
+    root.present().unwrap();
+}
@@ -0,0 +1,451 @@
+//! `--db FILE` and `bigmul history`: a local SQLite database that every
+//! sweep appends its individual timings to, for tracking performance across
+//! commits without an external service.
+//!
+//! Each run is one row of `runs` (start time, git commit, input class, size
+//! unit, whether it finished) and each timed multiplication one row of
+//! `samples`. Only built with the `sqlite` feature, which links the system
+//! `libsqlite3`; otherwise `Db::open` reports how to enable it.
+
+use std::path::Path;
+
+/// What a run records about itself besides its samples.
+#[cfg_attr(not(feature = "sqlite"), allow(dead_code))]
+pub struct RunInfo<'a> {
+    pub input_class: &'a str,
+    pub x_axis: &'a str,
+    pub complete: bool,
+}
+
+/// One timed multiplication.
+#[cfg_attr(not(feature = "sqlite"), allow(dead_code))]
+pub struct Sample<'a> {
+    pub algorithm: &'a str,
+    pub size: usize,
+    pub seconds: f64,
+}
+
+/// One run's summary for a single algorithm and size.
+pub struct HistoryRow {
+    pub started: String,
+    pub commit: String,
+    pub samples: usize,
+    pub mean: f64,
+    pub min: f64,
+}
+
+/// The short hash of the checkout the binary runs in, or `unknown` outside
+/// a git work tree.
+fn current_commit() -> String {
+    std::process::Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .and_then(|out| String::from_utf8(out.stdout).ok())
+        .map(|s| s.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(feature = "sqlite")]
+mod imp {
+    use super::{HistoryRow, RunInfo, Sample};
+    use std::ffi::{CStr, CString, c_char, c_int, c_void};
+    use std::path::Path;
+    use std::ptr;
+
+    #[repr(C)]
+    struct Sqlite3 {
+        _private: [u8; 0],
+    }
+
+    #[repr(C)]
+    struct Sqlite3Stmt {
+        _private: [u8; 0],
+    }
+
+    const SCHEMA: &str = "\
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    started INTEGER NOT NULL,
+    git_commit TEXT NOT NULL,
+    input_class TEXT NOT NULL,
+    x_axis TEXT NOT NULL,
+    complete INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS samples (
+    run INTEGER NOT NULL REFERENCES runs(id),
+    algorithm TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    seconds REAL NOT NULL
+);
+CREATE INDEX IF NOT EXISTS samples_by_algorithm ON samples(algorithm, size);
+";
+
+    const SQLITE_OK: c_int = 0;
+    const SQLITE_ROW: c_int = 100;
+    const SQLITE_DONE: c_int = 101;
+    /// `SQLITE_TRANSIENT`: the library copies bound text before returning.
+    const SQLITE_TRANSIENT: isize = -1;
+
+    #[link(name = "sqlite3")]
+    unsafe extern "C" {
+        fn sqlite3_open(filename: *const c_char, db: *mut *mut Sqlite3) -> c_int;
+        fn sqlite3_close(db: *mut Sqlite3) -> c_int;
+        fn sqlite3_errmsg(db: *mut Sqlite3) -> *const c_char;
+        fn sqlite3_exec(
+            db: *mut Sqlite3,
+            sql: *const c_char,
+            callback: *const c_void,
+            arg: *mut c_void,
+            errmsg: *mut *mut c_char,
+        ) -> c_int;
+        fn sqlite3_prepare_v2(
+            db: *mut Sqlite3,
+            sql: *const c_char,
+            nbyte: c_int,
+            stmt: *mut *mut Sqlite3Stmt,
+            tail: *mut *const c_char,
+        ) -> c_int;
+        fn sqlite3_bind_int64(stmt: *mut Sqlite3Stmt, index: c_int, value: i64) -> c_int;
+        fn sqlite3_bind_double(stmt: *mut Sqlite3Stmt, index: c_int, value: f64) -> c_int;
+        fn sqlite3_bind_text(
+            stmt: *mut Sqlite3Stmt,
+            index: c_int,
+            value: *const c_char,
+            n: c_int,
+            destructor: isize,
+        ) -> c_int;
+        fn sqlite3_step(stmt: *mut Sqlite3Stmt) -> c_int;
+        fn sqlite3_reset(stmt: *mut Sqlite3Stmt) -> c_int;
+        fn sqlite3_finalize(stmt: *mut Sqlite3Stmt) -> c_int;
+        fn sqlite3_column_int64(stmt: *mut Sqlite3Stmt, column: c_int) -> i64;
+        fn sqlite3_column_double(stmt: *mut Sqlite3Stmt, column: c_int) -> f64;
+        fn sqlite3_column_text(stmt: *mut Sqlite3Stmt, column: c_int) -> *const u8;
+        fn sqlite3_last_insert_rowid(db: *mut Sqlite3) -> i64;
+    }
+
+    pub struct Db {
+        handle: *mut Sqlite3,
+    }
+
+    struct Statement<'a> {
+        db: &'a Db,
+        stmt: *mut Sqlite3Stmt,
+    }
+
+    /// A value bound to a statement parameter.
+    enum Value<'a> {
+        Int(i64),
+        Real(f64),
+        Text(&'a str),
+    }
+
+    impl Db {
+        pub fn open(path: &Path) -> Result<Db, String> {
+            let name = CString::new(path.to_string_lossy().as_bytes())
+                .map_err(|_| format!("invalid database path {}", path.display()))?;
+            let mut handle = ptr::null_mut();
+            let rc = unsafe { sqlite3_open(name.as_ptr(), &mut handle) };
+            let db = Db { handle };
+            if rc != SQLITE_OK {
+                return Err(format!("cannot open {}: {}", path.display(), db.error()));
+            }
+            db.exec(SCHEMA)?;
+            Ok(db)
+        }
+
+        fn error(&self) -> String {
+            unsafe { CStr::from_ptr(sqlite3_errmsg(self.handle)) }
+                .to_string_lossy()
+                .into_owned()
+        }
+
+        fn exec(&self, sql: &str) -> Result<(), String> {
+            let sql = CString::new(sql).unwrap();
+            let rc = unsafe {
+                sqlite3_exec(
+                    self.handle,
+                    sql.as_ptr(),
+                    ptr::null(),
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                )
+            };
+            self.check(rc)
+        }
+
+        fn check(&self, rc: c_int) -> Result<(), String> {
+            if rc == SQLITE_OK {
+                Ok(())
+            } else {
+                Err(format!("sqlite: {}", self.error()))
+            }
+        }
+
+        fn prepare(&self, sql: &str) -> Result<Statement<'_>, String> {
+            let sql = CString::new(sql).unwrap();
+            let mut stmt = ptr::null_mut();
+            let rc = unsafe {
+                sqlite3_prepare_v2(self.handle, sql.as_ptr(), -1, &mut stmt, ptr::null_mut())
+            };
+            self.check(rc)?;
+            Ok(Statement { db: self, stmt })
+        }
+
+        /// Appends a run and its samples in one transaction, returning the
+        /// run's id.
+        pub fn record(
+            &self,
+            info: &RunInfo,
+            commit: &str,
+            started: i64,
+            samples: &[Sample],
+        ) -> Result<i64, String> {
+            self.exec("BEGIN")?;
+            let mut insert_run = self.prepare(
+                "INSERT INTO runs (started, git_commit, input_class, x_axis, complete) \
+                 VALUES (?, ?, ?, ?, ?)",
+            )?;
+            insert_run.execute(&[
+                Value::Int(started),
+                Value::Text(commit),
+                Value::Text(info.input_class),
+                Value::Text(info.x_axis),
+                Value::Int(info.complete as i64),
+            ])?;
+            let run = unsafe { sqlite3_last_insert_rowid(self.handle) };
+            let mut insert_sample = self.prepare(
+                "INSERT INTO samples (run, algorithm, size, seconds) VALUES (?, ?, ?, ?)",
+            )?;
+            for s in samples {
+                insert_sample.execute(&[
+                    Value::Int(run),
+                    Value::Text(s.algorithm),
+                    Value::Int(s.size as i64),
+                    Value::Real(s.seconds),
+                ])?;
+            }
+            self.exec("COMMIT")?;
+            Ok(run)
+        }
+
+        /// Per-run statistics for `algorithm` at `size` units of `x_axis`,
+        /// oldest run first.
+        pub fn query(
+            &self,
+            algorithm: &str,
+            size: usize,
+            x_axis: &str,
+        ) -> Result<Vec<HistoryRow>, String> {
+            let mut stmt = self.prepare(
+                "SELECT datetime(runs.started, 'unixepoch'), runs.git_commit, \
+                        count(*), avg(samples.seconds), min(samples.seconds) \
+                 FROM samples JOIN runs ON runs.id = samples.run \
+                 WHERE samples.algorithm = ? AND samples.size = ? AND runs.x_axis = ? \
+                 GROUP BY runs.id ORDER BY runs.id",
+            )?;
+            stmt.bind(&[
+                Value::Text(algorithm),
+                Value::Int(size as i64),
+                Value::Text(x_axis),
+            ])?;
+            let mut rows = Vec::new();
+            while stmt.step()? {
+                rows.push(HistoryRow {
+                    started: stmt.text(0),
+                    commit: stmt.text(1),
+                    samples: stmt.int(2) as usize,
+                    mean: stmt.real(3),
+                    min: stmt.real(4),
+                });
+            }
+            Ok(rows)
+        }
+    }
+
+    impl Drop for Db {
+        fn drop(&mut self) {
+            unsafe {
+                sqlite3_close(self.handle);
+            }
+        }
+    }
+
+    impl Statement<'_> {
+        fn bind(&mut self, values: &[Value]) -> Result<(), String> {
+            unsafe { sqlite3_reset(self.stmt) };
+            for (i, value) in values.iter().enumerate() {
+                let index = i as c_int + 1;
+                let rc = unsafe {
+                    match *value {
+                        Value::Int(v) => sqlite3_bind_int64(self.stmt, index, v),
+                        Value::Real(v) => sqlite3_bind_double(self.stmt, index, v),
+                        Value::Text(v) => sqlite3_bind_text(
+                            self.stmt,
+                            index,
+                            v.as_ptr() as *const c_char,
+                            v.len() as c_int,
+                            SQLITE_TRANSIENT,
+                        ),
+                    }
+                };
+                self.db.check(rc)?;
+            }
+            Ok(())
+        }
+
+        /// Advances to the next row, returning `false` when there is none.
+        fn step(&mut self) -> Result<bool, String> {
+            match unsafe { sqlite3_step(self.stmt) } {
+                SQLITE_ROW => Ok(true),
+                SQLITE_DONE => Ok(false),
+                _ => Err(format!("sqlite: {}", self.db.error())),
+            }
+        }
+
+        fn execute(&mut self, values: &[Value]) -> Result<(), String> {
+            self.bind(values)?;
+            while self.step()? {}
+            Ok(())
+        }
+
+        fn int(&self, column: c_int) -> i64 {
+            unsafe { sqlite3_column_int64(self.stmt, column) }
+        }
+
+        fn real(&self, column: c_int) -> f64 {
+            unsafe { sqlite3_column_double(self.stmt, column) }
+        }
+
+        fn text(&self, column: c_int) -> String {
+            let p = unsafe { sqlite3_column_text(self.stmt, column) };
+            if p.is_null() {
+                return String::new();
+            }
+            unsafe { CStr::from_ptr(p as *const c_char) }
+                .to_string_lossy()
+                .into_owned()
+        }
+    }
+
+    impl Drop for Statement<'_> {
+        fn drop(&mut self) {
+            unsafe {
+                sqlite3_finalize(self.stmt);
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "sqlite"))]
+mod imp {
+    use super::{HistoryRow, RunInfo, Sample};
+    use std::path::Path;
+
+    pub struct Db;
+
+    impl Db {
+        pub fn open(_: &Path) -> Result<Db, String> {
+            Err("history database unavailable: rebuild with `--features sqlite`".to_string())
+        }
+
+        pub fn record(&self, _: &RunInfo, _: &str, _: i64, _: &[Sample]) -> Result<i64, String> {
+            unreachable!()
+        }
+
+        pub fn query(&self, _: &str, _: usize, _: &str) -> Result<Vec<HistoryRow>, String> {
+            unreachable!()
+        }
+    }
+}
+
+pub use imp::Db;
+
+impl Db {
+    /// Records a finished (or interrupted) sweep under the current commit.
+    pub fn record_run(&self, info: &RunInfo, samples: &[Sample]) -> Result<i64, String> {
+        let started = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        self.record(info, &current_commit(), started, samples)
+    }
+}
+
+/// Prints one line per recorded run of `algorithm` at `digits` digits.
+pub fn show(db: &Path, algorithm: &str, digits: usize) -> Result<(), String> {
+    let name =
+        crate::profile::find(algorithm).map_or(algorithm.to_string(), |m| m.name().to_string());
+    let rows = Db::open(db)?.query(&name, digits, "digits")?;
+    if rows.is_empty() {
+        println!(
+            "no samples of {} at {} digits in {}",
+            name,
+            digits,
+            db.display()
+        );
+        return Ok(());
+    }
+    println!(
+        "{:<19}  {:<10}  {:>7}  {:>12}  {:>12}",
+        "run", "commit", "samples", "mean s", "min s"
+    );
+    for r in rows {
+        println!(
+            "{:<19}  {:<10}  {:>7}  {:>12.9}  {:>12.9}",
+            r.started, r.commit, r.samples, r.mean, r.min
+        );
+    }
+    Ok(())
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_and_queries_runs() {
+        let path =
+            std::env::temp_dir().join(format!("bigmul-history-{}.sqlite", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let db = Db::open(&path).unwrap();
+        let info = RunInfo {
+            input_class: "random",
+            x_axis: "digits",
+            complete: true,
+        };
+        let samples = [
+            Sample {
+                algorithm: "kara",
+                size: 1000,
+                seconds: 2.0,
+            },
+            Sample {
+                algorithm: "kara",
+                size: 1000,
+                seconds: 4.0,
+            },
+            Sample {
+                algorithm: "kara",
+                size: 2000,
+                seconds: 9.0,
+            },
+            Sample {
+                algorithm: "direct",
+                size: 1000,
+                seconds: 7.0,
+            },
+        ];
+        db.record(&info, "abc1234", 0, &samples).unwrap();
+        db.record(&info, "def5678", 86_400, &samples[..1]).unwrap();
+        let rows = db.query("kara", 1000, "digits").unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].started, "1970-01-01 00:00:00");
+        assert_eq!((rows[0].commit.as_str(), rows[0].samples), ("abc1234", 2));
+        assert_eq!((rows[0].mean, rows[0].min), (3.0, 2.0));
+        assert_eq!(rows[1].commit, "def5678");
+        assert!(db.query("kara", 1000, "bits").unwrap().is_empty());
+        drop(db);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
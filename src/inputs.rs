@@ -0,0 +1,300 @@
+//! Operand generators for the benchmark sweep.
+//!
+//! Besides uniformly random digits, a few deterministic classes are provided
+//! because carry-heavy and sparse inputs stress the kernels differently.
+
+use bigmul::BigUint;
+use rand::Rng;
+use std::cmp;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+const ZERO_RUN: usize = 100;
+const LIMB_DIGITS: usize = 9;
+
+/// Unit of the sweep's size axis. It decides both how operands are generated
+/// and how the chart is labeled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    Bits,
+    Digits,
+    Limbs,
+}
+
+impl Axis {
+    pub const ALL: [Axis; 3] = [Axis::Bits, Axis::Digits, Axis::Limbs];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Axis::Bits => "bits",
+            Axis::Digits => "digits",
+            Axis::Limbs => "limbs",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Axis> {
+        Self::ALL.into_iter().find(|a| a.name() == s)
+    }
+
+    /// Converts a decimal digit count into the size of a comparable operand
+    /// in this unit.
+    pub fn digits_to_unit(self, d: usize) -> usize {
+        match self {
+            Axis::Bits => (d as f64 * 10f64.log2()).ceil() as usize,
+            Axis::Digits => d,
+            Axis::Limbs => d.div_ceil(LIMB_DIGITS),
+        }
+    }
+
+    /// Size of `x` in this unit. Bit lengths are estimated from the leading
+    /// decimal digits and can be off by one right next to a power of two.
+    pub fn size_of(self, x: &BigUint) -> usize {
+        let s = x.to_string();
+        if s == "0" {
+            return 0;
+        }
+        match self {
+            Axis::Bits => {
+                let lead = &s[..s.len().min(17)];
+                let skipped = (s.len() - lead.len()) as f64 * 10f64.log2();
+                (lead.parse::<f64>().unwrap().log2() + skipped).floor() as usize + 1
+            }
+            Axis::Digits => s.len(),
+            Axis::Limbs => s.len().div_ceil(LIMB_DIGITS),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputClass {
+    Random,
+    AllNines,
+    PowerOfTen,
+    Repunit,
+    ZeroRuns,
+    Fibonacci,
+}
+
+impl InputClass {
+    pub const ALL: [InputClass; 6] = [
+        InputClass::Random,
+        InputClass::AllNines,
+        InputClass::PowerOfTen,
+        InputClass::Repunit,
+        InputClass::ZeroRuns,
+        InputClass::Fibonacci,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            InputClass::Random => "random",
+            InputClass::AllNines => "all-nines",
+            InputClass::PowerOfTen => "power-of-ten",
+            InputClass::Repunit => "repunit",
+            InputClass::ZeroRuns => "zero-runs",
+            InputClass::Fibonacci => "fibonacci",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<InputClass> {
+        Self::ALL.into_iter().find(|c| c.name() == s)
+    }
+
+    /// Returns an operand of size `n` measured in `axis` units. Random bit
+    /// sizes are exact; the decimal patterns of the other classes are
+    /// produced at the digit count of an `n`-bit number instead.
+    pub fn generate(self, n: usize, axis: Axis) -> BigUint {
+        self.generate_with(n, axis, &mut rand::thread_rng())
+    }
+
+    /// `generate` drawing from `rng`, for reproducible operands.
+    pub fn generate_with<R: Rng + ?Sized>(self, n: usize, axis: Axis, rng: &mut R) -> BigUint {
+        match (axis, self) {
+            (Axis::Bits, InputClass::Random) => bigmul::random_bits(n, rng),
+            (Axis::Bits, _) => {
+                let digits = (n as f64 * 2f64.log10()).ceil() as usize;
+                self.generate_digits(digits, rng)
+            }
+            (Axis::Digits, _) => self.generate_digits(n, rng),
+            (Axis::Limbs, _) => self.generate_digits(n * LIMB_DIGITS, rng),
+        }
+    }
+
+    /// Returns an operand with `d` decimal digits (approximately, for
+    /// `Fibonacci`, whose digit count can only be hit to within one).
+    fn generate_digits<R: Rng + ?Sized>(self, d: usize, rng: &mut R) -> BigUint {
+        if d == 0 {
+            return BigUint::new();
+        }
+        match self {
+            InputClass::Random => random_bigint(d, rng),
+            InputClass::AllNines => digits(&"9".repeat(d)),
+            InputClass::PowerOfTen => digits(&format!("1{}", "0".repeat(d - 1))),
+            InputClass::Repunit => digits(&"1".repeat(d)),
+            InputClass::ZeroRuns => {
+                let s: String = (0..d)
+                    .map(|i| if i % (ZERO_RUN + 1) == 0 { '9' } else { '0' })
+                    .collect();
+                digits(&s)
+            }
+            InputClass::Fibonacci => fibonacci(fibonacci_index(d)),
+        }
+    }
+}
+
+fn digits(s: &str) -> BigUint {
+    BigUint::from_str(s).expect("generated operands are decimal")
+}
+
+/// A uniformly random `d`-digit number, drawn limb by limb.
+fn random_bigint<R: Rng + ?Sized>(d: usize, rng: &mut R) -> BigUint {
+    if d == 0 {
+        return BigUint::new();
+    }
+    let ten = BigUint::from(10u64);
+    let low = ten.pow(d as u64 - 1);
+    let high = low.mul(&ten);
+    rng.gen_range(low..high)
+}
+
+/// Returns a uniformly random number with exactly `bits` bits.
+pub fn random_bits(bits: usize) -> BigUint {
+    bigmul::random_bits(bits, &mut rand::thread_rng())
+}
+
+/// Smallest `k` such that `F(k)` has at least `d` decimal digits, using
+/// `F(k) ~ phi^k / sqrt(5)`.
+fn fibonacci_index(d: usize) -> u64 {
+    let log_phi = ((1.0 + 5f64.sqrt()) / 2.0).log10();
+    let log_sqrt5 = 5f64.sqrt().log10();
+    ((d as f64 - 1.0 + log_sqrt5) / log_phi).ceil() as u64
+}
+
+/// Computes `F(k)` by fast doubling:
+/// `F(2j) = F(j) * (2 F(j+1) - F(j))` and `F(2j+1) = F(j)^2 + F(j+1)^2`.
+fn fibonacci(k: u64) -> BigUint {
+    let mut a = BigUint::new();
+    let mut b = BigUint::one();
+    for bit in (0..u64::BITS - k.leading_zeros()).rev() {
+        let c = a.mul_karatsuba(&b.add(&b).sub(&a));
+        let d = a.mul_karatsuba(&a).add(&b.mul_karatsuba(&b));
+        if (k >> bit) & 1 == 1 {
+            b = c.add(&d);
+            a = d;
+        } else {
+            a = c;
+            b = d;
+        }
+    }
+    a
+}
+
+/// One operand pair read from a corpus file.
+pub struct CorpusPair {
+    pub name: String,
+    pub a: BigUint,
+    pub b: BigUint,
+    /// Size of the larger operand in the chosen axis unit, used as the x
+    /// coordinate.
+    pub size: usize,
+    /// Wall seconds spent reading and parsing the file.
+    pub seconds: f64,
+}
+
+/// Calls `f` with the text of the file at `path`. On Unix the file is
+/// memory-mapped rather than read, so an operand file of hundreds of
+/// megabytes is parsed straight from the page cache without a copy.
+pub fn with_text<R>(path: &Path, f: impl FnOnce(&str) -> R) -> Result<R, String> {
+    #[cfg(unix)]
+    {
+        let map = bigmul::disk::MappedFile::open(path).map_err(|e| e.to_string())?;
+        let text = std::str::from_utf8(map.bytes())
+            .map_err(|_| format!("{}: not valid UTF-8", path.display()))?;
+        Ok(f(text))
+    }
+    #[cfg(not(unix))]
+    {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        Ok(f(&text))
+    }
+}
+
+/// Loads every regular file in `dir` as an operand pair, sorted by operand
+/// size. Each file must hold exactly two non-empty lines of decimal digits.
+/// The manifest of a saved operand set is skipped, so such a set doubles as
+/// a corpus.
+pub fn load_corpus(dir: &Path, axis: Axis) -> Result<Vec<CorpusPair>, String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("cannot read corpus {}: {}", dir.display(), e))?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && !p.ends_with(crate::operands::MANIFEST))
+        .collect();
+    paths.sort();
+
+    let mut pairs = Vec::with_capacity(paths.len());
+    for path in paths {
+        let start = Instant::now();
+        let (a, b) = with_text(&path, |text| {
+            let operands: Vec<&str> = text
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .collect();
+            if operands.len() != 2 {
+                return Err(format!(
+                    "{}: expected 2 operands, found {}",
+                    path.display(),
+                    operands.len()
+                ));
+            }
+            let parse = |s: &str| {
+                BigUint::from_str(s).map_err(|_| {
+                    format!(
+                        "{}: operand is not a decimal number: {:.20}",
+                        path.display(),
+                        s
+                    )
+                })
+            };
+            Ok((parse(operands[0])?, parse(operands[1])?))
+        })??;
+        pairs.push(CorpusPair {
+            name: path.file_name().unwrap().to_string_lossy().into_owned(),
+            size: cmp::max(axis.size_of(&a), axis.size_of(&b)),
+            a,
+            b,
+            seconds: start.elapsed().as_secs_f64(),
+        });
+    }
+    if pairs.is_empty() {
+        return Err(format!("corpus {} contains no files", dir.display()));
+    }
+    pairs.sort_by_key(|p| p.size);
+    Ok(pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_bits_has_exact_bit_length() {
+        for bits in [1, 31, 32, 33, 64, 1000, 4097] {
+            assert_eq!(Axis::Bits.size_of(&random_bits(bits)), bits);
+        }
+    }
+
+    #[test]
+    fn limb_axis_generates_exact_limb_counts() {
+        for class in InputClass::ALL {
+            if class == InputClass::Fibonacci {
+                continue;
+            }
+            let x = class.generate(37, Axis::Limbs);
+            assert_eq!(Axis::Limbs.size_of(&x), 37, "{}", class.name());
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Per-thread counters of the paths taken by the dispatching
+//! [`BigUint::mul`](crate::BigUint::mul), so tests and benchmarks can check
+//! that the fast paths fire, and of the limb operations the slice kernels
+//! perform, a machine-independent stand-in for time. Counting is a
+//! thread-local addition per kernel call, not per limb.
+
+use std::cell::Cell;
+
+/// How `mul` computed a product.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MulPath {
+    /// An operand was zero or one; no kernel ran.
+    Trivial,
+    /// An operand was a single limb; one scalar pass.
+    Scalar,
+    /// The operands were equal; routed to `square`.
+    Square,
+    /// The general Karatsuba product.
+    General,
+}
+
+impl MulPath {
+    pub const ALL: [MulPath; 4] = [
+        MulPath::Trivial,
+        MulPath::Scalar,
+        MulPath::Square,
+        MulPath::General,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MulPath::Trivial => "trivial",
+            MulPath::Scalar => "scalar",
+            MulPath::Square => "square",
+            MulPath::General => "general",
+        }
+    }
+}
+
+thread_local! {
+    static COUNTS: Cell<[u64; 4]> = const { Cell::new([0; 4]) };
+    static LIMB_OPS: Cell<u64> = const { Cell::new(0) };
+}
+
+pub(crate) fn record(path: MulPath) {
+    COUNTS.with(|c| {
+        let mut counts = c.get();
+        counts[path as usize] += 1;
+        c.set(counts);
+    });
+}
+
+/// Products that took `path` on this thread since the last [`reset`].
+pub fn count(path: MulPath) -> u64 {
+    COUNTS.with(|c| c.get()[path as usize])
+}
+
+pub(crate) fn add_limb_ops(n: usize) {
+    LIMB_OPS.with(|c| c.set(c.get() + n as u64));
+}
+
+/// Limb operations performed on this thread since the last [`reset`]: one
+/// per limb product and one per limb of an addition or subtraction. Work a
+/// parallel kernel hands to other threads is added back when they finish.
+pub fn limb_ops() -> u64 {
+    LIMB_OPS.with(|c| c.get())
+}
+
+/// Zeroes this thread's counters.
+pub fn reset() {
+    COUNTS.with(|c| c.set([0; 4]));
+    LIMB_OPS.with(|c| c.set(0));
+}
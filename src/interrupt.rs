@@ -0,0 +1,35 @@
+//! SIGINT handling for long sweeps.
+//!
+//! The first Ctrl-C only raises a flag that the sweep polls between
+//! measurements, so the in-flight size finishes and partial results can be
+//! written. The handler then restores the default disposition, so a second
+//! Ctrl-C terminates the process immediately.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Exit status used when a sweep stops early because of SIGINT (128 + 2).
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+extern "C" fn on_sigint(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+    unsafe {
+        libc::signal(libc::SIGINT, libc::SIG_DFL);
+    }
+}
+
+#[cfg(unix)]
+pub fn install() {
+    unsafe {
+        libc::signal(libc::SIGINT, on_sigint as *const () as libc::sighandler_t);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn install() {}
+
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
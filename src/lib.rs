@@ -0,0 +1,90 @@
+//! Arbitrary-precision integers in base 10^9 with several multiplication
+//! algorithms (direct, four-product divide and conquer, additive and
+//! subtractive Karatsuba). `mul` dispatches between them and dedicated
+//! scalar and squaring kernels, counting its choices in [`instrument`].
+//!
+//! The layout follows num-bigint: `BigUint` is the unsigned core built on
+//! the slice-level operations in [`limbs`], and `BigInt` wraps it with a
+//! `Sign`. `FixedUint` is a stack-allocated fixed-width counterpart for
+//! small sizes, and `SharedBigInt` a reference-counted one that clones in
+//! O(1). `BigRational` builds exact fractions on top of both, and `BigDecimal`
+//! pairs a `BigInt` mantissa with a decimal exponent. [`with_arena`] runs a
+//! computation whose temporaries all come from one bump allocator.
+//! [`product`] multiplies a whole list of factors as a balanced tree, and
+//! [`ProductAccumulator`] a stream of them, while [`mul_many`] multiplies
+//! a batch of independent pairs across threads. [`sum`] adds a whole list
+//! in limb columns, carrying once instead of once per term, and
+//! [`addmul_sub`] forms `a*x - b*y` for small factors in one pass. [`Poly`]
+//! multiplies polynomials with big coefficients through one integer product
+//! by Kronecker substitution, and [`BigMatrix`] multiplies matrices naively
+//! or by Strassen's method. [`RadixUint`] runs the same kernels in a limb
+//! base chosen at runtime, for comparing bases. [`random_bits`] and
+//! [`random_below`] draw operands by bit length or bound, and `rng.gen_range`
+//! accepts `BigUint` and `BigInt` ranges. [`BigUint::to_bytes_be`] and its
+//! siblings convert to and from canonical byte strings, [`BigInt::format`]
+//! lays out huge values for reading, and [`BigUint::mod_small`] and
+//! [`BigUint::digit_sum`] fingerprint them for spot checks. On Unix,
+//! `disk` multiplies operands too large for memory through memory-mapped
+//! files and maps operand files for parsing in place. The `quickcheck`
+//! and `proptest` features implement `Arbitrary` for both integer types.
+//! Parsing, modular inverses and the out-of-core files fail with an
+//! [`Error`] rather than panicking; division panics on a zero divisor
+//! unless called through its `checked_` form.
+//!
+//! [`Sweep`] runs the benchmark's timed comparisons from code, for
+//! integration tests and other crates that want to assert on relative
+//! performance, and [`report`] charts its results the way the binary
+//! charts its own.
+
+#[cfg(any(feature = "quickcheck", feature = "proptest"))]
+pub mod arbitrary;
+mod arena;
+mod batch;
+mod bigint;
+mod biguint;
+mod binary;
+mod checksum;
+mod decimal;
+#[cfg(unix)]
+pub mod disk;
+mod error;
+mod fixed;
+#[cfg(feature = "force-algorithm")]
+pub mod force;
+mod format;
+pub mod instrument;
+pub mod limbs;
+mod limbvec;
+mod matrix;
+mod modring;
+mod poly;
+mod prime;
+mod product;
+mod radix;
+mod random;
+mod rational;
+pub mod report;
+mod shared;
+mod sum;
+mod sweep;
+#[cfg(feature = "verified")]
+pub mod verified;
+
+pub use arena::{Arena, ArenaUint, with_arena};
+pub use batch::mul_many;
+pub use bigint::{BigInt, Sign};
+pub use biguint::{BigUint, PowWindow};
+pub use decimal::{BigDecimal, RoundingMode};
+pub use error::Error;
+pub use fixed::FixedUint;
+pub use format::Notation;
+pub use matrix::BigMatrix;
+pub use modring::ModRing;
+pub use poly::Poly;
+pub use product::{ProductAccumulator, product, product_par};
+pub use radix::{Base, RadixUint};
+pub use random::{UniformBigInt, UniformBigUint, random_below, random_bits};
+pub use rational::BigRational;
+pub use shared::SharedBigInt;
+pub use sum::{addmul_sub, sum};
+pub use sweep::{Algorithm, Results, Sweep};
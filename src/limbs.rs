@@ -0,0 +1,1865 @@
+//! Slice-level arithmetic on little-endian base-10^9 limbs.
+//!
+//! These are the primitives `BigUint` is built on, exposed so custom
+//! algorithms can be written without the owning types. Everything here works
+//! on plain magnitudes; sign handling lives in `BigInt` and never reaches the
+//! kernels.
+//!
+//! Conventions shared by every function:
+//!
+//! - A number is a slice of limbs, least significant first, each `< BASE`.
+//! - Inputs need not be normalized: high zero limbs are allowed and an empty
+//!   slice means zero.
+//! - Returned vectors are normalized: never empty, no high zero limbs, and
+//!   zero is `[0]`. `shift_left_slices` is the one exception, preserving
+//!   whatever high zero limbs its input had.
+//! - No kernel panics on such inputs, whatever their lengths; the only
+//!   panics are a zero divisor and the invariant checks of
+//!   [`set_paranoid`]. The module denies `unwrap`, `expect` and `panic!` to
+//!   keep it that way.
+//!
+//! ```
+//! use bigmul::limbs;
+//!
+//! // (10^9 + 5) * 3 = 3 * 10^9 + 15
+//! let product = limbs::mul_karatsuba_slices(&[5, 1], &[3]);
+//! assert_eq!(product, vec![15, 3]);
+//! assert_eq!(limbs::sub_slices(&product, &[15]), vec![0, 3]);
+//! ```
+
+#![cfg_attr(
+    not(test),
+    deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)
+)]
+
+use crate::error::Error;
+use crate::instrument;
+use std::cmp::{self, Ordering};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrdering};
+
+/// Radix of a limb.
+pub const BASE: u64 = 1_000_000_000;
+
+/// Default length, in limbs, at or below which the Karatsuba kernels
+/// (including squaring and the short and middle products) multiply
+/// directly. `BIGMUL_KARATSUBA_THRESHOLD`
+/// overrides it; see [`karatsuba_threshold`].
+pub const KARATSUBA_THRESHOLD: usize = 32;
+
+/// Default base-case length for `mul_dc_slices`; `BIGMUL_DC_THRESHOLD`
+/// overrides it.
+pub const DC_THRESHOLD: usize = 32;
+
+/// Reads a threshold from the environment variable `var`, as
+/// [`parse_threshold`].
+fn threshold_from_env(var: &str, default: usize) -> usize {
+    parse_threshold(std::env::var(var).ok().as_deref(), default)
+}
+
+/// A threshold override, or `default` when it is missing, unparsable or
+/// below 4, which would keep the recursion from shrinking the operands.
+fn parse_threshold(value: Option<&str>, default: usize) -> usize {
+    value
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|&t| t >= 4)
+        .unwrap_or(default)
+}
+
+/// The Karatsuba base-case length in effect: `BIGMUL_KARATSUBA_THRESHOLD`
+/// as set when first asked, else [`KARATSUBA_THRESHOLD`]. Read once per
+/// process, so tuning needs no rebuild but cannot change mid-run.
+pub fn karatsuba_threshold() -> usize {
+    static THRESHOLD: OnceLock<usize> = OnceLock::new();
+    *THRESHOLD.get_or_init(|| threshold_from_env("BIGMUL_KARATSUBA_THRESHOLD", KARATSUBA_THRESHOLD))
+}
+
+/// The divide-and-conquer base-case length in effect, from
+/// `BIGMUL_DC_THRESHOLD` or [`DC_THRESHOLD`], as [`karatsuba_threshold`].
+pub fn dc_threshold() -> usize {
+    static THRESHOLD: OnceLock<usize> = OnceLock::new();
+    *THRESHOLD.get_or_init(|| threshold_from_env("BIGMUL_DC_THRESHOLD", DC_THRESHOLD))
+}
+
+/// The length below which `mul_karatsuba_par_slices` stops spawning
+/// threads, from `BIGMUL_PAR_KARATSUBA_THRESHOLD` or
+/// [`PAR_KARATSUBA_MIN_LIMBS`], as [`karatsuba_threshold`].
+pub fn par_karatsuba_threshold() -> usize {
+    static THRESHOLD: OnceLock<usize> = OnceLock::new();
+    *THRESHOLD.get_or_init(|| {
+        threshold_from_env("BIGMUL_PAR_KARATSUBA_THRESHOLD", PAR_KARATSUBA_MIN_LIMBS)
+    })
+}
+
+/// Default for [`max_result_limbs`]: the most limbs a `Vec<u32>` can hold,
+/// so by default only arithmetic overflow is refused.
+pub const MAX_RESULT_LIMBS: usize = isize::MAX as usize / 4;
+
+static MAX_RESULT: AtomicUsize = AtomicUsize::new(MAX_RESULT_LIMBS);
+
+/// Caps the length of results the checked operations will produce
+/// (`BigUint::checked_mul` and its siblings, and the out-of-core product):
+/// longer ones fail with [`Error::TooLarge`] before anything is allocated.
+/// The kernels themselves never check; their callers size the result.
+pub fn set_max_result_limbs(limbs: usize) {
+    MAX_RESULT.store(limbs, AtomicOrdering::Relaxed);
+}
+
+/// The limit set by [`set_max_result_limbs`], [`MAX_RESULT_LIMBS`] if unset.
+pub fn max_result_limbs() -> usize {
+    MAX_RESULT.load(AtomicOrdering::Relaxed)
+}
+
+/// Checks a result length computed with checked arithmetic: `None` (it
+/// overflowed) is [`Error::SizeOverflow`], and a length past
+/// [`max_result_limbs`] is [`Error::TooLarge`].
+pub fn check_result_len(len: Option<usize>) -> Result<usize, Error> {
+    let len = len.ok_or(Error::SizeOverflow)?;
+    let max = max_result_limbs();
+    if len > max {
+        return Err(Error::TooLarge { limbs: len, max });
+    }
+    Ok(len)
+}
+
+/// `len_a + len_b`, the most limbs a product can have, checked as
+/// [`check_result_len`].
+pub fn product_len(len_a: usize, len_b: usize) -> Result<usize, Error> {
+    check_result_len(len_a.checked_add(len_b))
+}
+
+/// Runs `f` with the result limit set to `limbs`, then restores the
+/// default even if `f` panics. The limit is global, so every test that
+/// depends on it goes through here and holds the lock.
+#[cfg(test)]
+pub(crate) fn with_max_result_limbs<R>(limbs: usize, f: impl FnOnce() -> R) -> R {
+    static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    struct Restore;
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            set_max_result_limbs(MAX_RESULT_LIMBS);
+        }
+    }
+    let _lock = LOCK
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let _restore = Restore;
+    set_max_result_limbs(limbs);
+    f()
+}
+
+static PARANOID: AtomicBool = AtomicBool::new(false);
+
+/// Enables the kernels' invariant checks (limbs below `BASE`, no final
+/// borrow in `sub_slices`, sub-products fitting where they are shifted to)
+/// in release builds. Debug builds always run them. The checks are linear
+/// and sit at kernel boundaries, never in inner loops.
+pub fn set_paranoid(on: bool) {
+    PARANOID.store(on, AtomicOrdering::Relaxed);
+}
+
+/// Runs `f` with paranoid checks set to `on`, then restores the previous
+/// setting even if `f` panics, holding a lock as
+/// [`with_max_result_limbs`] does.
+#[cfg(test)]
+pub(crate) fn with_paranoid<R>(on: bool, f: impl FnOnce() -> R) -> R {
+    static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            set_paranoid(self.0);
+        }
+    }
+    let _lock = LOCK
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let _restore = Restore(PARANOID.swap(on, AtomicOrdering::Relaxed));
+    f()
+}
+
+fn checks_enabled() -> bool {
+    cfg!(debug_assertions) || PARANOID.load(AtomicOrdering::Relaxed)
+}
+
+macro_rules! invariant {
+    ($cond:expr, $($msg:tt)+) => {
+        if checks_enabled() {
+            assert!($cond, $($msg)+);
+        }
+    };
+}
+
+fn limbs_in_range(digits: &[u32]) -> bool {
+    digits.iter().all(|&d| (d as u64) < BASE)
+}
+
+/// Strips high zero limbs, leaving at least one limb if `digits` was
+/// non-empty.
+pub fn normalize(digits: &mut Vec<u32>) {
+    while digits.len() > 1 && digits.last() == Some(&0) {
+        digits.pop();
+    }
+}
+
+/// Whether `digits` is in the canonical form the owning types keep and the
+/// kernels return: see [`validate`].
+pub fn is_normalized(digits: &[u32]) -> bool {
+    validate(digits).is_ok()
+}
+
+/// Checks limbs from outside the crate (deserialized, or handed over FFI)
+/// for canonical form: at least one limb, every limb below [`BASE`], and
+/// no high zero limb unless it is the only one. The error names the first
+/// problem found.
+///
+/// ```
+/// use bigmul::limbs;
+///
+/// assert_eq!(limbs::validate(&[7, 1]), Ok(()));
+/// assert!(limbs::validate(&[7, 1, 0]).is_err());
+/// assert!(limbs::validate(&[1_000_000_000]).is_err());
+/// ```
+pub fn validate(digits: &[u32]) -> Result<(), String> {
+    if let Some(i) = digits.iter().position(|&d| d as u64 >= BASE) {
+        return Err(format!(
+            "limb {} is {}, not below the base 10^9",
+            i, digits[i]
+        ));
+    }
+    match digits {
+        [] => Err("no limbs; zero is one zero limb".to_string()),
+        [.., 0] if digits.len() > 1 => Err(format!(
+            "limb {} of {} is a high zero limb",
+            digits.len() - 1,
+            digits.len()
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Compares two magnitudes, ignoring high zero limbs.
+pub fn cmp_slices(a: &[u32], b: &[u32]) -> Ordering {
+    let len_a = a.iter().rposition(|&d| d != 0).map_or(0, |i| i + 1);
+    let len_b = b.iter().rposition(|&d| d != 0).map_or(0, |i| i + 1);
+    len_a
+        .cmp(&len_b)
+        .then_with(|| a[..len_a].iter().rev().cmp(b[..len_b].iter().rev()))
+}
+
+/// Returns `|a - b|` together with how `a` compares to `b`, so callers
+/// tracking a sign need only one comparison.
+pub fn abs_diff_slices(a: &[u32], b: &[u32]) -> (Ordering, Vec<u32>) {
+    let a = &a[..a.iter().rposition(|&d| d != 0).map_or(0, |i| i + 1)];
+    let b = &b[..b.iter().rposition(|&d| d != 0).map_or(0, |i| i + 1)];
+    match cmp_slices(a, b) {
+        Ordering::Less => (Ordering::Less, sub_slices(b, a)),
+        Ordering::Equal => (Ordering::Equal, vec![0]),
+        Ordering::Greater => (Ordering::Greater, sub_slices(a, b)),
+    }
+}
+
+/// Computes `a + b`.
+pub fn add_slices(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let max_len = cmp::max(a.len(), b.len());
+    instrument::add_limb_ops(max_len);
+    let mut result = vec![0u32; max_len + 1];
+    let mut carry: u64 = 0;
+    for i in 0..max_len {
+        let ai = if i < a.len() { a[i] as u64 } else { 0 };
+        let bi = if i < b.len() { b[i] as u64 } else { 0 };
+        let sum = ai + bi + carry;
+        result[i] = (sum % BASE) as u32;
+        carry = sum / BASE;
+    }
+    if carry > 0 {
+        result[max_len] = carry as u32;
+    }
+    normalize(&mut result);
+    invariant!(
+        limbs_in_range(a) && limbs_in_range(b),
+        "add_slices: limb out of range"
+    );
+    result
+}
+
+/// Computes `a - b`. Requires `a >= b` and `b.len() <= a.len()`; the result
+/// is unspecified otherwise.
+pub fn sub_slices(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let max_len = a.len();
+    instrument::add_limb_ops(max_len);
+    let mut result = vec![0u32; max_len];
+    let mut borrow: i64 = 0;
+    for i in 0..max_len {
+        let ai = a[i] as i64;
+        let bi = if i < b.len() { b[i] as i64 } else { 0 };
+        let mut diff = ai - bi - borrow;
+        if diff < 0 {
+            diff += BASE as i64;
+            borrow = 1;
+        } else {
+            borrow = 0;
+        }
+        result[i] = diff as u32;
+    }
+    normalize(&mut result);
+    invariant!(
+        limbs_in_range(a) && limbs_in_range(b),
+        "sub_slices: limb out of range"
+    );
+    invariant!(
+        borrow == 0 && b.iter().skip(max_len).all(|&d| d == 0),
+        "sub_slices: a < b"
+    );
+    result
+}
+
+/// Computes `digits * BASE^k` by prepending `k` zero limbs. Zero (empty or
+/// `[0]`) stays `[0]`.
+pub fn shift_left_slices(digits: &[u32], k: usize) -> Vec<u32> {
+    if digits.is_empty() || digits == [0] {
+        return vec![0];
+    }
+    let mut res = vec![0u32; k + digits.len()];
+    res[k..].copy_from_slice(digits);
+    res
+}
+
+/// Computes `floor(digits / BASE^k)` by dropping the `k` lowest limbs.
+pub fn shift_right_slices(digits: &[u32], k: usize) -> Vec<u32> {
+    if k >= digits.len() {
+        return vec![0];
+    }
+    let mut res = digits[k..].to_vec();
+    normalize(&mut res);
+    res
+}
+
+/// Returns `(digits / BASE^k, digits % BASE^k)` by splitting the limbs at
+/// `k`.
+pub fn div_rem_pow_base_slices(digits: &[u32], k: usize) -> (Vec<u32>, Vec<u32>) {
+    let mut low = digits[..cmp::min(k, digits.len())].to_vec();
+    if low.is_empty() {
+        low.push(0);
+    }
+    normalize(&mut low);
+    (shift_right_slices(digits, k), low)
+}
+
+/// Schoolbook multiplication, `O(len(a) * len(b))`.
+pub fn mul_direct_slices(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let len_a = a.len();
+    let len_b = b.len();
+    if len_a == 0 || len_b == 0 {
+        return vec![0];
+    }
+    instrument::add_limb_ops(len_a * len_b);
+    let mut result = vec![0u32; len_a + len_b];
+    for i in 0..len_a {
+        let mut carry: u64 = 0;
+        for j in 0..len_b {
+            let temp = a[i] as u64 * b[j] as u64 + result[i + j] as u64 + carry;
+            result[i + j] = (temp % BASE) as u32;
+            carry = temp / BASE;
+        }
+        let mut k = i + len_b;
+        while carry > 0 {
+            if k == result.len() {
+                result.push(0);
+            }
+            let temp = result[k] as u64 + carry;
+            result[k] = (temp % BASE) as u32;
+            carry = temp / BASE;
+            k += 1;
+        }
+    }
+    normalize(&mut result);
+    invariant!(
+        limbs_in_range(a) && limbs_in_range(b),
+        "mul_direct_slices: limb out of range"
+    );
+    result
+}
+
+/// A sum of shifted limb slices, some of them subtracted, with the carries
+/// deferred: each limb position accumulates in an `i64`, and a single pass
+/// in [`finish`](CarrySave::finish) propagates them. Recombining `k` partial
+/// products with `add_slices` takes `k` passes and as many allocations; this
+/// takes one of each plus the final pass.
+///
+/// ```
+/// use bigmul::limbs::CarrySave;
+///
+/// // (999999999 + 999999999 B) + (1 + 0 B) B - 2 = 999999997 + B^2
+/// let mut sum = CarrySave::new(3);
+/// sum.add(&[999_999_999, 999_999_999], 0);
+/// sum.add(&[1], 1);
+/// sum.sub(&[2], 0);
+/// assert_eq!(sum.finish(), vec![999_999_997, 0, 1]);
+/// ```
+pub struct CarrySave {
+    acc: Vec<i64>,
+}
+
+impl CarrySave {
+    /// An accumulator for sums of at most `len` limbs.
+    pub fn new(len: usize) -> Self {
+        CarrySave { acc: vec![0; len] }
+    }
+
+    /// Adds `digits * BASE^shift`, growing the accumulator if needed.
+    pub fn add(&mut self, digits: &[u32], shift: usize) {
+        self.reserve(digits.len() + shift);
+        instrument::add_limb_ops(digits.len());
+        for (acc, &d) in self.acc[shift..].iter_mut().zip(digits) {
+            *acc += d as i64;
+        }
+    }
+
+    /// Subtracts `digits * BASE^shift`. The total must not end up negative.
+    pub fn sub(&mut self, digits: &[u32], shift: usize) {
+        self.reserve(digits.len() + shift);
+        instrument::add_limb_ops(digits.len());
+        for (acc, &d) in self.acc[shift..].iter_mut().zip(digits) {
+            *acc -= d as i64;
+        }
+    }
+
+    fn reserve(&mut self, len: usize) {
+        if self.acc.len() < len {
+            self.acc.resize(len, 0);
+        }
+    }
+
+    /// Propagates the carries and returns the normalized sum.
+    pub fn finish(self) -> Vec<u32> {
+        instrument::add_limb_ops(self.acc.len());
+        let mut result = Vec::with_capacity(self.acc.len() + 1);
+        let mut carry: i64 = 0;
+        for v in self.acc {
+            let t = v + carry;
+            result.push(t.rem_euclid(BASE as i64) as u32);
+            carry = t.div_euclid(BASE as i64);
+        }
+        invariant!(carry >= 0, "CarrySave: negative total");
+        while carry > 0 {
+            result.push((carry % BASE as i64) as u32);
+            carry /= BASE as i64;
+        }
+        normalize(&mut result);
+        result
+    }
+}
+
+/// Divide and conquer with four half-size products. Still quadratic; kept as
+/// the baseline Karatsuba improves on. Falls back to `mul_direct_slices` at
+/// [`dc_threshold`] limbs.
+pub fn mul_dc_slices(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return vec![0];
+    }
+    let n = cmp::max(a.len(), b.len());
+    if n <= dc_threshold() {
+        return mul_direct_slices(a, b);
+    }
+    let m = n / 2;
+    let a0 = &a[0..cmp::min(m, a.len())];
+    let a1 = if a.len() > m { &a[m..] } else { &[] };
+    let b0 = &b[0..cmp::min(m, b.len())];
+    let b1 = if b.len() > m { &b[m..] } else { &[] };
+    let p = mul_dc_slices(a0, b0);
+    let q = mul_dc_slices(a1, b1);
+    let r = mul_dc_slices(a0, b1);
+    let s = mul_dc_slices(a1, b0);
+    invariant!(
+        p.len() <= 2 * m,
+        "mul_dc: low product overlaps the high one"
+    );
+    let mut sum = CarrySave::new(a.len() + b.len());
+    sum.add(&p, 0);
+    sum.add(&r, m);
+    sum.add(&s, m);
+    sum.add(&q, 2 * m);
+    sum.finish()
+}
+
+/// Default recursion bound for the Karatsuba variants. Balanced splits need
+/// about `log2(n / 32)` levels, so only inputs far larger than memory reach
+/// it.
+pub const MAX_KARATSUBA_DEPTH: usize = 48;
+
+/// Karatsuba multiplication with three half-size products,
+/// `O(n^log2(3))`. Falls back to `mul_direct_slices` at
+/// [`karatsuba_threshold`] limbs.
+pub fn mul_karatsuba_slices(a: &[u32], b: &[u32]) -> Vec<u32> {
+    mul_karatsuba_depth_slices(a, b, MAX_KARATSUBA_DEPTH)
+}
+
+/// `mul_karatsuba_slices` with at most `max_depth` levels of recursion;
+/// sub-products below that use `mul_direct_slices`, so `max_depth == 0` is
+/// the direct method. Bounds stack use independently of the input.
+pub fn mul_karatsuba_depth_slices(a: &[u32], b: &[u32], max_depth: usize) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return vec![0];
+    }
+    let n = cmp::max(a.len(), b.len());
+    if n <= karatsuba_threshold() || max_depth == 0 {
+        return mul_direct_slices(a, b);
+    }
+    let m = n / 2;
+    if cmp::min(a.len(), b.len()) <= m {
+        return mul_unbalanced(a, b, |x, y| mul_karatsuba_depth_slices(x, y, max_depth - 1));
+    }
+    let a0 = &a[0..m];
+    let a1 = &a[m..];
+    let b0 = &b[0..m];
+    let b1 = &b[m..];
+    let p = mul_karatsuba_depth_slices(a0, b0, max_depth - 1);
+    let q = mul_karatsuba_depth_slices(a1, b1, max_depth - 1);
+    let sum_a = add_slices(a0, a1);
+    let sum_b = add_slices(b0, b1);
+    let u = mul_karatsuba_depth_slices(&sum_a, &sum_b, max_depth - 1);
+    invariant!(
+        p.len() <= 2 * m,
+        "karatsuba: low product overlaps the high one"
+    );
+    let sum_pq = add_slices(&p, &q);
+    let mid = sub_slices(&u, &sum_pq);
+    let q_shifted = shift_left_slices(&q, 2 * m);
+    let mid_shifted = shift_left_slices(&mid, m);
+    let temp = add_slices(&q_shifted, &mid_shifted);
+    add_slices(&temp, &p)
+}
+
+/// Multiplies operands of very different lengths by cutting the longer one
+/// into pieces as long as the shorter and summing the shifted products.
+/// Splitting both at half the longer length instead would leave one half of
+/// the shorter operand empty and waste a recursive product on padding.
+/// The products go into a [`CarrySave`], so each costs its own length
+/// rather than a pass over the whole running sum.
+fn mul_unbalanced<F>(a: &[u32], b: &[u32], mul: F) -> Vec<u32>
+where
+    F: Fn(&[u32], &[u32]) -> Vec<u32>,
+{
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    if short.is_empty() {
+        return vec![0];
+    }
+    let mut result = CarrySave::new(long.len() + short.len());
+    for (i, chunk) in long.chunks(short.len()).enumerate() {
+        result.add(&mul(chunk, short), i * short.len());
+    }
+    result.finish()
+}
+
+/// Computes `a * d` for a single limb `d < BASE` in one pass.
+pub fn mul_small_slices(a: &[u32], d: u32) -> Vec<u32> {
+    instrument::add_limb_ops(a.len());
+    let mut result = Vec::with_capacity(a.len() + 1);
+    let mut carry = 0u64;
+    for &x in a {
+        let t = x as u64 * d as u64 + carry;
+        result.push((t % BASE) as u32);
+        carry = t / BASE;
+    }
+    result.push(carry as u32);
+    normalize(&mut result);
+    result
+}
+
+/// Computes `a * x + b * y` for single limbs `a, b < BASE` in one pass:
+/// both products and the carry stay under `2 * BASE^2`, well inside a `u64`.
+pub fn addmul_slices(a: u32, x: &[u32], b: u32, y: &[u32]) -> Vec<u32> {
+    let len = x.len().max(y.len());
+    instrument::add_limb_ops(2 * len);
+    let mut result = Vec::with_capacity(len + 1);
+    let mut carry = 0u64;
+    for i in 0..len {
+        let xi = x.get(i).copied().unwrap_or(0) as u64;
+        let yi = y.get(i).copied().unwrap_or(0) as u64;
+        let t = a as u64 * xi + b as u64 * yi + carry;
+        result.push((t % BASE) as u32);
+        carry = t / BASE;
+    }
+    result.push((carry % BASE) as u32);
+    result.push((carry / BASE) as u32);
+    normalize(&mut result);
+    result
+}
+
+/// Returns `|a * x - b * y|` for single limbs `a, b < BASE`, together with
+/// how `a * x` compares to `b * y`. One signed pass forms the difference;
+/// a second pass negates it only when it comes out below zero.
+pub fn addmul_sub_slices(a: u32, x: &[u32], b: u32, y: &[u32]) -> (Ordering, Vec<u32>) {
+    let len = x.len().max(y.len());
+    instrument::add_limb_ops(2 * len);
+    let base = BASE as i64;
+    let mut result = Vec::with_capacity(len + 1);
+    // Each step lies in (-BASE^2 - BASE, BASE^2 + BASE), so the carry stays
+    // within one limb either way and the whole fits an `i64`.
+    let mut carry = 0i64;
+    for i in 0..len {
+        let xi = x.get(i).copied().unwrap_or(0) as i64;
+        let yi = y.get(i).copied().unwrap_or(0) as i64;
+        let t = a as i64 * xi - b as i64 * yi + carry;
+        result.push(t.rem_euclid(base) as u32);
+        carry = t.div_euclid(base);
+    }
+    let order = if carry < 0 {
+        // The value is `result - |carry| * BASE^len`; its magnitude is the
+        // complement of `result` below `|carry| * BASE^len`.
+        let mut borrow = 0i64;
+        for d in result.iter_mut() {
+            let t = -(*d as i64) - borrow;
+            borrow = (t < 0) as i64;
+            *d = (t + borrow * base) as u32;
+        }
+        result.push((-carry - borrow) as u32);
+        Ordering::Less
+    } else {
+        result.push(carry as u32);
+        if result.iter().all(|&d| d == 0) {
+            Ordering::Equal
+        } else {
+            Ordering::Greater
+        }
+    };
+    normalize(&mut result);
+    (order, result)
+}
+
+/// Schoolbook squaring. Each cross product `a[i] * a[j]` with `i < j` is
+/// formed once and the sum doubled before the squares `a[i]^2` are added,
+/// about half the limb products of `mul_direct_slices(a, a)`.
+pub fn square_direct_slices(a: &[u32]) -> Vec<u32> {
+    let n = a.len();
+    if n == 0 {
+        return vec![0];
+    }
+    // Cross products, their doubling, and the squares.
+    instrument::add_limb_ops(n * (n - 1) / 2 + 2 * n + n);
+    let mut result = vec![0u32; 2 * n];
+    for i in 0..n {
+        let mut carry = 0u64;
+        for j in i + 1..n {
+            let t = a[i] as u64 * a[j] as u64 + result[i + j] as u64 + carry;
+            result[i + j] = (t % BASE) as u32;
+            carry = t / BASE;
+        }
+        result[i + n] = carry as u32;
+    }
+    let mut carry = 0u64;
+    for d in result.iter_mut() {
+        let t = 2 * *d as u64 + carry;
+        *d = (t % BASE) as u32;
+        carry = t / BASE;
+    }
+    let mut carry = 0u64;
+    for (i, &x) in a.iter().enumerate() {
+        let sq = x as u64 * x as u64;
+        let t = result[2 * i] as u64 + sq % BASE + carry;
+        result[2 * i] = (t % BASE) as u32;
+        let t = result[2 * i + 1] as u64 + sq / BASE + t / BASE;
+        result[2 * i + 1] = (t % BASE) as u32;
+        carry = t / BASE;
+    }
+    normalize(&mut result);
+    invariant!(limbs_in_range(a), "square_direct_slices: limb out of range");
+    result
+}
+
+/// Karatsuba squaring: `(a1 B^m + a0)^2` from the three half-size squares
+/// `a0^2`, `a1^2` and `(a0 + a1)^2`. Falls back to `square_direct_slices`
+/// at [`karatsuba_threshold`] limbs.
+pub fn square_karatsuba_slices(a: &[u32]) -> Vec<u32> {
+    if a.len() <= karatsuba_threshold() {
+        return square_direct_slices(a);
+    }
+    let m = a.len() / 2;
+    let (a0, a1) = a.split_at(m);
+    let p = square_karatsuba_slices(a0);
+    let q = square_karatsuba_slices(a1);
+    let u = square_karatsuba_slices(&add_slices(a0, a1));
+    invariant!(
+        p.len() <= 2 * m,
+        "square_karatsuba: low square overlaps the high one"
+    );
+    let mid = sub_slices(&u, &add_slices(&p, &q));
+    let temp = add_slices(&shift_left_slices(&q, 2 * m), &shift_left_slices(&mid, m));
+    add_slices(&temp, &p)
+}
+
+/// Below this many limbs `mul_karatsuba_par_slices` stops spawning threads:
+/// the three sub-products are too cheap to pay for a thread each.
+/// `BIGMUL_PAR_KARATSUBA_THRESHOLD` overrides it; see
+/// [`par_karatsuba_threshold`].
+pub const PAR_KARATSUBA_MIN_LIMBS: usize = 1024;
+
+/// Karatsuba spread over up to `threads` threads. The three sub-products of
+/// each level run concurrently, dividing the thread budget between them,
+/// until the budget runs out or operands drop below
+/// [`par_karatsuba_threshold`]; from there each thread continues with
+/// `mul_karatsuba_slices`. With `threads <= 1` this is exactly
+/// `mul_karatsuba_slices`.
+pub fn mul_karatsuba_par_slices(a: &[u32], b: &[u32], threads: usize) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return vec![0];
+    }
+    let n = cmp::max(a.len(), b.len());
+    if threads <= 1 || n < par_karatsuba_threshold() {
+        return mul_karatsuba_slices(a, b);
+    }
+    let m = n / 2;
+    if cmp::min(a.len(), b.len()) <= m {
+        return mul_unbalanced(a, b, |x, y| mul_karatsuba_par_slices(x, y, threads));
+    }
+    let a0 = &a[0..m];
+    let a1 = &a[m..];
+    let b0 = &b[0..m];
+    let b1 = &b[m..];
+    let sum_a = add_slices(a0, a1);
+    let sum_b = add_slices(b0, b1);
+    // p always gets a thread of its own; with only two threads q shares the
+    // calling thread with u.
+    let threads_p = cmp::max(threads / 3, 1);
+    let threads_q = threads / 3;
+    let threads_u = threads - threads_p - threads_q;
+    // Spawned threads start counting from zero; their totals are added to
+    // this thread's.
+    let counted = |x, y, threads| {
+        let product = mul_karatsuba_par_slices(x, y, threads);
+        (product, instrument::limb_ops())
+    };
+    let (p, q, u) = std::thread::scope(|scope| {
+        let p = scope.spawn(|| counted(a0, b0, threads_p));
+        let q = (threads_q > 0).then(|| scope.spawn(|| counted(a1, b1, threads_q)));
+        let u = mul_karatsuba_par_slices(&sum_a, &sum_b, threads_u);
+        let q = match q {
+            Some(handle) => {
+                let (q, ops) = handle
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e));
+                instrument::add_limb_ops(ops as usize);
+                q
+            }
+            None => mul_karatsuba_par_slices(a1, b1, threads_u),
+        };
+        let (p, ops) = p.join().unwrap_or_else(|e| std::panic::resume_unwind(e));
+        instrument::add_limb_ops(ops as usize);
+        (p, q, u)
+    });
+    let sum_pq = add_slices(&p, &q);
+    let mid = sub_slices(&u, &sum_pq);
+    let q_shifted = shift_left_slices(&q, 2 * m);
+    let mid_shifted = shift_left_slices(&mid, m);
+    let temp = add_slices(&q_shifted, &mid_shifted);
+    add_slices(&temp, &p)
+}
+
+/// Subtractive Karatsuba: the middle term comes from
+/// `a0*b1 + a1*b0 = p + q - (a1 - a0)(b1 - b0)`, multiplying the absolute
+/// differences and tracking their signs. Unlike the additive form the factors
+/// never grow past `m` limbs. Falls back to `mul_direct_slices` at
+/// [`karatsuba_threshold`] limbs.
+pub fn mul_karatsuba_sub_slices(a: &[u32], b: &[u32]) -> Vec<u32> {
+    mul_karatsuba_sub_depth_slices(a, b, MAX_KARATSUBA_DEPTH)
+}
+
+/// `mul_karatsuba_sub_slices` with at most `max_depth` levels of recursion,
+/// as in `mul_karatsuba_depth_slices`.
+pub fn mul_karatsuba_sub_depth_slices(a: &[u32], b: &[u32], max_depth: usize) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return vec![0];
+    }
+    let n = cmp::max(a.len(), b.len());
+    if n <= karatsuba_threshold() || max_depth == 0 {
+        return mul_direct_slices(a, b);
+    }
+    let m = n / 2;
+    if cmp::min(a.len(), b.len()) <= m {
+        return mul_unbalanced(a, b, |x, y| {
+            mul_karatsuba_sub_depth_slices(x, y, max_depth - 1)
+        });
+    }
+    let a0 = &a[0..m];
+    let a1 = &a[m..];
+    let b0 = &b[0..m];
+    let b1 = &b[m..];
+    let p = mul_karatsuba_sub_depth_slices(a0, b0, max_depth - 1);
+    let q = mul_karatsuba_sub_depth_slices(a1, b1, max_depth - 1);
+    let (sign_a, diff_a) = abs_diff_slices(a1, a0);
+    let (sign_b, diff_b) = abs_diff_slices(b1, b0);
+    invariant!(
+        p.len() <= 2 * m,
+        "karatsuba_sub: low product overlaps the high one"
+    );
+    let sum_pq = add_slices(&p, &q);
+    let mid = if sign_a == Ordering::Equal || sign_b == Ordering::Equal {
+        sum_pq
+    } else {
+        let d = mul_karatsuba_sub_depth_slices(&diff_a, &diff_b, max_depth - 1);
+        if sign_a == sign_b {
+            sub_slices(&sum_pq, &d)
+        } else {
+            add_slices(&sum_pq, &d)
+        }
+    };
+    let q_shifted = shift_left_slices(&q, 2 * m);
+    let mid_shifted = shift_left_slices(&mid, m);
+    let temp = add_slices(&q_shifted, &mid_shifted);
+    add_slices(&temp, &p)
+}
+
+/// Computes `(a * b) mod BASE^n`, the lowest `n` limbs of the product.
+///
+/// Uses Mulders' short product: the low `h ~ 0.7n` limbs of each operand
+/// are multiplied in full, and the two cross terms that can still reach
+/// below `BASE^n` are short products of size `n - h`. Terms with both
+/// indices `>= h` are never formed.
+pub fn mul_low_slices(a: &[u32], b: &[u32], n: usize) -> Vec<u32> {
+    let a = &a[..cmp::min(n, a.len())];
+    let b = &b[..cmp::min(n, b.len())];
+    if a.is_empty() || b.is_empty() {
+        return vec![0];
+    }
+    if a.len() + b.len() <= n {
+        return mul_karatsuba_slices(a, b);
+    }
+    if n <= karatsuba_threshold() {
+        let mut result = vec![0u32; n];
+        for i in 0..a.len() {
+            let mut carry: u64 = 0;
+            for j in 0..cmp::min(b.len(), n - i) {
+                let temp = a[i] as u64 * b[j] as u64 + result[i + j] as u64 + carry;
+                result[i + j] = (temp % BASE) as u32;
+                carry = temp / BASE;
+            }
+            let mut k = i + b.len();
+            while carry > 0 && k < n {
+                let temp = result[k] as u64 + carry;
+                result[k] = (temp % BASE) as u32;
+                carry = temp / BASE;
+                k += 1;
+            }
+        }
+        normalize(&mut result);
+        return result;
+    }
+    let h = (n * 7).div_ceil(10);
+    let full = mul_karatsuba_slices(&a[..cmp::min(h, a.len())], &b[..cmp::min(h, b.len())]);
+    let cross_a = if a.len() > h {
+        mul_low_slices(&a[h..], b, n - h)
+    } else {
+        vec![0]
+    };
+    let cross_b = if b.len() > h {
+        mul_low_slices(a, &b[h..], n - h)
+    } else {
+        vec![0]
+    };
+    let cross = shift_left_slices(&add_slices(&cross_a, &cross_b), h);
+    let mut result = add_slices(&full, &cross);
+    result.truncate(n);
+    normalize(&mut result);
+    result
+}
+
+/// Middle product: with `A = a.len() >= L = b.len()`, computes
+/// `sum(a[i] * b[j] * BASE^(i + j - (L - 1)))` over the band
+/// `L - 1 <= i + j <= A - 1`, the `A - L + 1` diagonals where every limb of
+/// `b` meets a limb of `a`. Carries out of the band are kept, so the result
+/// has up to `A - L + 3` limbs.
+///
+/// This is the product Newton iterations need when the low diagonals are
+/// already known. For the balanced shape `A = 2L - 1` it costs about one
+/// `L x L` Karatsuba product instead of a `2L x L` one, using the transposed
+/// Karatsuba recursion of Hanrot, Quercia and Zimmermann on carry-free
+/// coefficients with a single carry pass at the end.
+pub fn mul_mid_slices(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if b.is_empty() || a.len() < b.len() {
+        return vec![0];
+    }
+    let a: Vec<i64> = a.iter().map(|&d| d as i64).collect();
+    let b: Vec<i64> = b.iter().map(|&d| d as i64).collect();
+    let coeffs = mul_mid_coeffs(&a, &b);
+    let mut result = Vec::with_capacity(coeffs.len() + 3);
+    let mut carry: i128 = 0;
+    for c in coeffs {
+        let v = c + carry;
+        result.push(v.rem_euclid(BASE as i128) as u32);
+        carry = v.div_euclid(BASE as i128);
+    }
+    while carry > 0 {
+        result.push((carry % BASE as i128) as u32);
+        carry /= BASE as i128;
+    }
+    normalize(&mut result);
+    result
+}
+
+/// Band product on raw coefficients, `A - L + 1` outputs. Coefficients may
+/// be negative inside the recursion.
+fn mul_mid_coeffs(a: &[i64], b: &[i64]) -> Vec<i128> {
+    let len_b = b.len();
+    let width = a.len() - len_b + 1;
+    // The transposed recursion pays off where Karatsuba's does.
+    let threshold = karatsuba_threshold();
+    if len_b <= threshold || width <= threshold {
+        return (0..width)
+            .map(|w| {
+                b.iter()
+                    .enumerate()
+                    .map(|(j, &bj)| bj as i128 * a[len_b - 1 - j + w] as i128)
+                    .sum()
+            })
+            .collect();
+    }
+    if width > len_b {
+        // Output chunks of `len_b` diagonals, each a balanced problem.
+        let mut result = Vec::with_capacity(width);
+        let mut start = 0;
+        while start < width {
+            let chunk = cmp::min(len_b, width - start);
+            result.extend(mul_mid_coeffs(&a[start..start + chunk + len_b - 1], b));
+            start += chunk;
+        }
+        return result;
+    }
+    if width < len_b {
+        // Peel `width` limbs of `b` at a time:
+        // MP(a, b) = MP(a[L - h..], b[..h]) + MP(a[..A - h], b[h..]).
+        let h = width;
+        let mut result = mul_mid_coeffs(&a[len_b - h..], &b[..h]);
+        let rest = mul_mid_coeffs(&a[..a.len() - h], &b[h..]);
+        for (r, x) in result.iter_mut().zip(rest) {
+            *r += x;
+        }
+        return result;
+    }
+    if len_b % 2 == 1 {
+        // Peel the top limb of `b` to reach an even length.
+        let top = b[len_b - 1] as i128;
+        let mut result = mul_mid_coeffs(&a[1..], &b[..len_b - 1]);
+        for (r, &x) in result.iter_mut().zip(&a[..width]) {
+            *r += top * x as i128;
+        }
+        return result;
+    }
+    // Balanced, even: with k = L/2, A0 = a[..2k-1], A1 = a[k..3k-1],
+    // A2 = a[2k..4k-1] and P = MP(A1, b0 + b1),
+    //   low half  = P + MP(A0 - A1, b1)
+    //   high half = P + MP(A2 - A1, b0).
+    let k = len_b / 2;
+    let (b0, b1) = b.split_at(k);
+    let a0 = &a[..2 * k - 1];
+    let a1 = &a[k..3 * k - 1];
+    let a2 = &a[2 * k..4 * k - 1];
+    let sum_b: Vec<i64> = b0.iter().zip(b1).map(|(x, y)| x + y).collect();
+    let diff_01: Vec<i64> = a0.iter().zip(a1).map(|(x, y)| x - y).collect();
+    let diff_21: Vec<i64> = a2.iter().zip(a1).map(|(x, y)| x - y).collect();
+    let p = mul_mid_coeffs(a1, &sum_b);
+    let low = mul_mid_coeffs(&diff_01, b1);
+    let high = mul_mid_coeffs(&diff_21, b0);
+    p.iter()
+        .zip(&low)
+        .map(|(x, y)| x + y)
+        .chain(p.iter().zip(&high).map(|(x, y)| x + y))
+        .collect()
+}
+
+/// Multiplies by a single limb-sized factor.
+fn mul_small(a: &[u32], f: u32) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len() + 1);
+    let mut carry: u64 = 0;
+    for &d in a {
+        let temp = d as u64 * f as u64 + carry;
+        result.push((temp % BASE) as u32);
+        carry = temp / BASE;
+    }
+    result.push(carry as u32);
+    result
+}
+
+/// Divides by a single limb, returning the quotient and remainder. Panics if
+/// `d` is zero.
+pub fn div_rem_small(a: &[u32], d: u32) -> (Vec<u32>, u32) {
+    assert!(d != 0, "division by zero");
+    let mut quotient = vec![0u32; a.len()];
+    let mut rem: u64 = 0;
+    for i in (0..a.len()).rev() {
+        let cur = rem * BASE + a[i] as u64;
+        quotient[i] = (cur / d as u64) as u32;
+        rem = cur % d as u64;
+    }
+    if quotient.is_empty() {
+        quotient.push(0);
+    }
+    normalize(&mut quotient);
+    (quotient, rem as u32)
+}
+
+/// Returns `(a / 2^bits, a % 2^bits)`. Powers of two do not line up with
+/// base-10^9 limbs, so this peels off up to 29 bits at a time with
+/// single-limb divisions, `O(len(a) * bits / 29)` in all, and stops early
+/// once the quotient reaches zero.
+pub fn div_rem_pow2_slices(a: &[u32], bits: usize) -> (Vec<u32>, Vec<u32>) {
+    const STEP: usize = 29;
+    let mut quotient = a.to_vec();
+    if quotient.is_empty() {
+        quotient.push(0);
+    }
+    normalize(&mut quotient);
+    let mut rem = vec![0];
+    // 2^(bits consumed so far): the weight of the next remainder chunk.
+    let mut weight = vec![1];
+    let mut left = bits;
+    while left > 0 && quotient != [0] {
+        let step = cmp::min(left, STEP);
+        let (q, r) = div_rem_small(&quotient, 1 << step);
+        rem = add_slices(&rem, &mul_small(&weight, r));
+        weight = mul_small(&weight, 1 << step);
+        quotient = q;
+        left -= step;
+    }
+    (quotient, rem)
+}
+
+/// Long division (Knuth, TAOCP 4.3.1, Algorithm D), returning
+/// `(a / b, a % b)`. Panics if `b` is zero.
+pub fn div_rem_slices(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    let a = &a[..a.iter().rposition(|&d| d != 0).map_or(0, |i| i + 1)];
+    let b = &b[..b.iter().rposition(|&d| d != 0).map_or(0, |i| i + 1)];
+    assert!(!b.is_empty(), "division by zero");
+    if cmp_slices(a, b) == Ordering::Less {
+        let mut rem = a.to_vec();
+        if rem.is_empty() {
+            rem.push(0);
+        }
+        return (vec![0], rem);
+    }
+    if b.len() == 1 {
+        let (q, r) = div_rem_small(a, b[0]);
+        return (q, vec![r]);
+    }
+
+    // Scale so the top limb of the divisor is at least BASE / 2, which keeps
+    // each estimated quotient limb at most two too large.
+    let f = (BASE / (b[b.len() - 1] as u64 + 1)) as u32;
+    let mut u = mul_small(a, f);
+    let mut v = mul_small(b, f);
+    v.pop();
+    let n = v.len();
+    let m = u.len() - n;
+    let v_top = v[n - 1] as u64;
+    let v_next = v[n - 2] as u64;
+    let mut quotient = vec![0u32; m];
+
+    for j in (0..m).rev() {
+        let num = u[j + n] as u64 * BASE + u[j + n - 1] as u64;
+        let mut qhat = num / v_top;
+        let mut rhat = num % v_top;
+        while qhat >= BASE || qhat * v_next > rhat * BASE + u[j + n - 2] as u64 {
+            qhat -= 1;
+            rhat += v_top;
+            if rhat >= BASE {
+                break;
+            }
+        }
+
+        let mut carry: u64 = 0;
+        let mut borrow: i64 = 0;
+        for i in 0..n {
+            let p = qhat * v[i] as u64 + carry;
+            carry = p / BASE;
+            let mut t = u[i + j] as i64 - (p % BASE) as i64 - borrow;
+            borrow = if t < 0 {
+                t += BASE as i64;
+                1
+            } else {
+                0
+            };
+            u[i + j] = t as u32;
+        }
+        let t = u[j + n] as i64 - carry as i64 - borrow;
+        if t < 0 {
+            // qhat was one too large: add the divisor back.
+            qhat -= 1;
+            u[j + n] = (t + BASE as i64) as u32;
+            let mut carry: u64 = 0;
+            for i in 0..n {
+                let sum = u[i + j] as u64 + v[i] as u64 + carry;
+                u[i + j] = (sum % BASE) as u32;
+                carry = sum / BASE;
+            }
+            u[j + n] = ((u[j + n] as u64 + carry) % BASE) as u32;
+        } else {
+            u[j + n] = t as u32;
+        }
+        quotient[j] = qhat as u32;
+    }
+
+    normalize(&mut quotient);
+    let (rem, _) = div_rem_small(&u[..n], f);
+    invariant!(
+        cmp_slices(&rem, b) == Ordering::Less,
+        "div_rem_slices: remainder not below divisor"
+    );
+    (quotient, rem)
+}
+
+/// Returns `a / b` for a `b` known to divide `a` exactly (Jebelean's exact
+/// division). Quotient limbs come out lowest first as `r_i * b_0^-1 mod BASE`,
+/// with no trial quotients or corrections, and only the low limbs of `a` that
+/// hold the quotient are ever updated. If `b` does not divide `a` the result
+/// is meaningless. Panics if `b` is zero.
+pub fn div_exact_slices(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut a = a[..a.iter().rposition(|&d| d != 0).map_or(0, |i| i + 1)].to_vec();
+    let mut b = b[..b.iter().rposition(|&d| d != 0).map_or(0, |i| i + 1)].to_vec();
+    assert!(!b.is_empty(), "division by zero");
+
+    // The low limb of the divisor must be invertible modulo BASE = 2^9 5^9.
+    // Cancel whole zero limbs, then factors of 2 and 5 (at most 2^9 5^9 at a
+    // time, so the factor divides every higher limb's weight too), until it
+    // is.
+    loop {
+        let zeros = b.iter().take_while(|&&d| d == 0).count();
+        b.drain(..zeros);
+        a.drain(..cmp::min(zeros, a.len()));
+        let mut f = 1;
+        for p in [2, 5] {
+            let mut k = 0;
+            while k < 9 && (b[0] / f).is_multiple_of(p) {
+                f *= p;
+                k += 1;
+            }
+        }
+        if f == 1 {
+            break;
+        }
+        a = div_rem_small(&a, f).0;
+        b = div_rem_small(&b, f).0;
+    }
+    if cmp_slices(&a, &b) == Ordering::Less {
+        return vec![0];
+    }
+    if b.len() == 1 {
+        return div_rem_small(&a, b[0]).0;
+    }
+
+    let inv = inverse_mod_base(b[0]);
+    let len = a.len() - b.len() + 1;
+    let mut r = a[..len].to_vec();
+    let mut quotient = vec![0u32; len];
+    for i in 0..len {
+        let q = r[i] as u64 * inv % BASE;
+        quotient[i] = q as u32;
+        let mut carry: u64 = 0;
+        let mut borrow: i64 = 0;
+        for j in 0..len - i {
+            let bj = b.get(j).copied();
+            if bj.is_none() && carry == 0 && borrow == 0 {
+                break;
+            }
+            let p = q * bj.unwrap_or(0) as u64 + carry;
+            carry = p / BASE;
+            let mut t = r[i + j] as i64 - (p % BASE) as i64 - borrow;
+            borrow = if t < 0 {
+                t += BASE as i64;
+                1
+            } else {
+                0
+            };
+            r[i + j] = t as u32;
+        }
+    }
+    normalize(&mut quotient);
+    quotient
+}
+
+/// Inverse of `x` modulo `BASE` by the extended Euclidean algorithm; `x` must
+/// be coprime to 10.
+fn inverse_mod_base(x: u32) -> u64 {
+    let (mut r0, mut r1) = (BASE as i64, x as i64);
+    let (mut t0, mut t1) = (0i64, 1i64);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (t0, t1) = (t1, t0 - q * t1);
+    }
+    debug_assert_eq!(r0, 1, "{} is not invertible modulo BASE", x);
+    t0.rem_euclid(BASE as i64) as u64
+}
+
+// In-place kernels over caller-provided buffers, for `FixedUint` and the
+// arena, which must not allocate.
+
+/// Below this many limbs `mul_karatsuba_into` multiplies directly.
+const IN_PLACE_KARATSUBA_CUTOFF: usize = 16;
+
+/// `dst += src` over `dst.len()` limbs (`src` no longer), returning the
+/// carry out of the top.
+pub(crate) fn add_in_place(dst: &mut [u32], src: &[u32]) -> bool {
+    instrument::add_limb_ops(dst.len());
+    let mut carry = 0;
+    for (i, d) in dst.iter_mut().enumerate() {
+        if i >= src.len() && carry == 0 {
+            return false;
+        }
+        let sum = *d + src.get(i).copied().unwrap_or(0) + carry;
+        carry = (sum as u64 >= BASE) as u32;
+        *d = sum - carry * BASE as u32;
+    }
+    carry != 0
+}
+
+/// `dst -= src` over `dst.len()` limbs, returning the borrow out of the top.
+pub(crate) fn sub_in_place(dst: &mut [u32], src: &[u32]) -> bool {
+    instrument::add_limb_ops(dst.len());
+    let mut borrow = 0;
+    for (i, d) in dst.iter_mut().enumerate() {
+        if i >= src.len() && borrow == 0 {
+            return false;
+        }
+        let sub = src.get(i).copied().unwrap_or(0) + borrow;
+        borrow = (*d < sub) as u32;
+        *d = *d + borrow * BASE as u32 - sub;
+    }
+    borrow != 0
+}
+
+/// Writes `a * b` into `out[..a.len() + b.len()]`, which must be zeroed.
+pub(crate) fn mul_direct_into(a: &[u32], b: &[u32], out: &mut [u32]) {
+    instrument::add_limb_ops(a.len() * b.len());
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let t = out[i + j] as u64 + x as u64 * y as u64 + carry;
+            out[i + j] = (t % BASE) as u32;
+            carry = t / BASE;
+        }
+        out[i + b.len()] = carry as u32;
+    }
+}
+
+/// Writes `a * b` for equal-length `a` and `b` into `out[..2n]`, which must
+/// be zeroed. `scratch` needs about `4n` limbs plus a few per level; `6n`
+/// always suffices.
+pub(crate) fn mul_karatsuba_into(a: &[u32], b: &[u32], out: &mut [u32], scratch: &mut [u32]) {
+    let n = a.len();
+    if n <= IN_PLACE_KARATSUBA_CUTOFF {
+        mul_direct_into(a, b, out);
+        return;
+    }
+    let m = n / 2;
+    let h = n - m;
+    let (a0, a1) = a.split_at(m);
+    let (b0, b1) = b.split_at(m);
+
+    let (low, high) = out[..2 * n].split_at_mut(2 * m);
+    mul_karatsuba_into(a0, b0, low, scratch);
+    mul_karatsuba_into(a1, b1, high, scratch);
+
+    // u = (a0 + a1)(b0 + b1), with both sums padded to h + 1 limbs.
+    let (sa, rest) = scratch.split_at_mut(h + 1);
+    let (sb, rest) = rest.split_at_mut(h + 1);
+    let (u, rest) = rest.split_at_mut(2 * h + 2);
+    clear(sa);
+    clear(sb);
+    sa[..h].copy_from_slice(a1);
+    sb[..h].copy_from_slice(b1);
+    add_in_place(sa, a0);
+    add_in_place(sb, b0);
+    clear(u);
+    mul_karatsuba_into(sa, sb, u, rest);
+
+    // out += (u - p - q) * BASE^m
+    sub_in_place(u, &out[..2 * m]);
+    sub_in_place(u, &out[2 * m..2 * n]);
+    let top = 2 * n - m;
+    let u_len = u.iter().rposition(|&d| d != 0).map_or(0, |i| i + 1);
+    debug_assert!(u_len <= top);
+    add_in_place(&mut out[m..2 * n], &u[..u_len]);
+}
+
+fn clear(s: &mut [u32]) {
+    s.iter_mut().for_each(|d| *d = 0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn limbs(len: usize, seed: u64) -> Vec<u32> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut v: Vec<u32> = (0..len).map(|_| rng.gen_range(0..BASE as u32)).collect();
+        if let Some(top) = v.last_mut() {
+            *top = rng.gen_range(1..BASE as u32);
+        }
+        v
+    }
+
+    fn all_products(a: &[u32], b: &[u32]) -> [Vec<u32>; 4] {
+        [
+            mul_direct_slices(a, b),
+            mul_dc_slices(a, b),
+            mul_karatsuba_slices(a, b),
+            mul_karatsuba_sub_slices(a, b),
+        ]
+    }
+
+    #[test]
+    fn normalize_keeps_one_zero_limb() {
+        let mut v = vec![0, 0, 0];
+        normalize(&mut v);
+        assert_eq!(v, vec![0]);
+        let mut v = vec![5, 0, 7, 0, 0];
+        normalize(&mut v);
+        assert_eq!(v, vec![5, 0, 7]);
+    }
+
+    #[test]
+    fn validation_names_the_problem() {
+        assert!(is_normalized(&[0]));
+        assert!(is_normalized(&[0, 0, 3]));
+        assert!(!is_normalized(&[]));
+        let high_zero = validate(&[5, 0, 7, 0]).unwrap_err();
+        assert!(high_zero.contains("limb 3 of 4"), "{}", high_zero);
+        let out_of_range = validate(&[5, 1_000_000_000, 0]).unwrap_err();
+        assert!(
+            out_of_range.contains("limb 1 is 1000000000"),
+            "{}",
+            out_of_range
+        );
+        let mut v = vec![5, 0, 7, 0, 0];
+        normalize(&mut v);
+        assert!(is_normalized(&v));
+    }
+
+    #[test]
+    fn cmp_ignores_leading_zero_limbs() {
+        assert_eq!(cmp_slices(&[5, 0, 0], &[5]), Ordering::Equal);
+        assert_eq!(cmp_slices(&[], &[0]), Ordering::Equal);
+        assert_eq!(cmp_slices(&[0, 1], &[999_999_999]), Ordering::Greater);
+        assert_eq!(cmp_slices(&[1, 2], &[2, 2]), Ordering::Less);
+    }
+
+    #[test]
+    fn add_and_sub_propagate_carries() {
+        let max = (BASE - 1) as u32;
+        assert_eq!(add_slices(&[max, max], &[1]), vec![0, 0, 1]);
+        assert_eq!(sub_slices(&[0, 0, 1], &[1]), vec![max, max]);
+        assert_eq!(sub_slices(&[4, 2], &[4, 2]), vec![0]);
+        assert_eq!(add_slices(&[], &[]), vec![0]);
+        assert_eq!(add_slices(&[], &[5]), vec![5]);
+    }
+
+    #[test]
+    fn shift_left_of_zero_stays_zero() {
+        assert_eq!(shift_left_slices(&[0], 3), vec![0]);
+        assert_eq!(shift_left_slices(&[7], 2), vec![0, 0, 7]);
+    }
+
+    #[test]
+    fn shift_right_drops_low_limbs() {
+        assert_eq!(shift_right_slices(&[1, 2, 3], 1), vec![2, 3]);
+        assert_eq!(shift_right_slices(&[1, 2, 0], 1), vec![2]);
+        assert_eq!(shift_right_slices(&[1, 2, 3], 3), vec![0]);
+        assert_eq!(shift_right_slices(&[], 0), vec![0]);
+        assert_eq!(shift_left_slices(&[], 2), vec![0]);
+    }
+
+    #[test]
+    fn empty_slices_multiply_to_zero() {
+        for p in all_products(&[], &[]) {
+            assert_eq!(p, vec![0]);
+        }
+        for p in all_products(&[], &limbs(100, 1)) {
+            assert_eq!(p, vec![0]);
+        }
+    }
+
+    #[test]
+    fn zero_operand_gives_normalized_zero() {
+        let big = limbs(200, 2);
+        for p in all_products(&[0], &big) {
+            assert_eq!(p, vec![0]);
+        }
+        for p in all_products(&big, &[0]) {
+            assert_eq!(p, vec![0]);
+        }
+    }
+
+    #[test]
+    fn single_limb_operands() {
+        let max = (BASE - 1) as u32;
+        for p in all_products(&[max], &[max]) {
+            assert_eq!(p, vec![1, max - 1]);
+        }
+        let big = limbs(150, 3);
+        for p in all_products(&[1], &big) {
+            assert_eq!(p, big);
+        }
+    }
+
+    #[test]
+    fn squares_and_scalar_products_match_direct() {
+        let max = (BASE - 1) as u32;
+        for n in [0, 1, 2, 31, 32, 33, 100, 257] {
+            for a in [limbs(n, n as u64), vec![max; n]] {
+                let expected = mul_direct_slices(&a, &a);
+                assert_eq!(square_direct_slices(&a), expected, "n={}", n);
+                assert_eq!(square_karatsuba_slices(&a), expected, "n={}", n);
+                for d in [0, 1, 7, max] {
+                    assert_eq!(mul_small_slices(&a, d), mul_direct_slices(&a, &[d]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn limb_operations_are_counted_across_threads() {
+        let a = limbs(3000, 1);
+        let b = limbs(3000, 2);
+        let ops = |f: &dyn Fn() -> Vec<u32>| {
+            instrument::reset();
+            f();
+            instrument::limb_ops()
+        };
+        assert_eq!(ops(&|| mul_direct_slices(&a[..10], &b[..7])), 70);
+        assert_eq!(ops(&|| add_slices(&a[..10], &b[..7])), 10);
+        let kara = ops(&|| mul_karatsuba_slices(&a, &b));
+        assert_eq!(kara, ops(&|| mul_karatsuba_slices(&a, &b)));
+        assert_eq!(kara, ops(&|| mul_karatsuba_par_slices(&a, &b, 4)));
+        assert!(3 * kara < ops(&|| mul_direct_slices(&a, &b)));
+    }
+
+    #[test]
+    fn wildly_different_lengths_agree() {
+        for (len_a, len_b) in [
+            (1, 1000),
+            (2, 513),
+            (33, 2000),
+            (31, 64),
+            (65, 64),
+            (700, 9),
+        ] {
+            let a = limbs(len_a, len_a as u64);
+            let b = limbs(len_b, len_b as u64 + 1);
+            let [direct, dc, kara, kara_sub] = all_products(&a, &b);
+            assert_eq!(direct, dc, "{} x {}", len_a, len_b);
+            assert_eq!(direct, kara, "{} x {}", len_a, len_b);
+            assert_eq!(direct, kara_sub, "{} x {}", len_a, len_b);
+            assert_eq!(direct, mul_karatsuba_slices(&b, &a));
+            assert!(direct.len() == len_a + len_b || direct.len() == len_a + len_b - 1);
+        }
+    }
+
+    #[test]
+    fn subtractive_karatsuba_handles_every_sign_case() {
+        let max = (BASE - 1) as u32;
+        let low = vec![max; 40];
+        let high = vec![1; 40];
+        let mut cases = Vec::new();
+        for a in [
+            [low.clone(), high.clone()].concat(),
+            [high.clone(), low.clone()].concat(),
+        ] {
+            for b in [
+                [low.clone(), high.clone()].concat(),
+                [high.clone(), low.clone()].concat(),
+            ] {
+                cases.push((a.clone(), b));
+            }
+        }
+        cases.push(([high.clone(), high.clone()].concat(), limbs(80, 7)));
+        for (a, b) in cases {
+            assert_eq!(mul_karatsuba_sub_slices(&a, &b), mul_direct_slices(&a, &b));
+        }
+    }
+
+    fn truncated(mut v: Vec<u32>, n: usize) -> Vec<u32> {
+        v.truncate(n);
+        if v.is_empty() {
+            v.push(0);
+        }
+        normalize(&mut v);
+        v
+    }
+
+    #[test]
+    fn mul_low_matches_truncated_product() {
+        for (len_a, len_b, n) in [
+            (10, 10, 5),
+            (40, 40, 40),
+            (40, 40, 79),
+            (40, 40, 200),
+            (300, 300, 300),
+            (500, 37, 100),
+            (3, 400, 401),
+            (257, 300, 411),
+        ] {
+            let a = limbs(len_a, 100 + len_a as u64);
+            let b = limbs(len_b, 200 + len_b as u64);
+            let expected = truncated(mul_direct_slices(&a, &b), n);
+            assert_eq!(
+                mul_low_slices(&a, &b, n),
+                expected,
+                "{} x {} mod B^{}",
+                len_a,
+                len_b,
+                n
+            );
+        }
+        assert_eq!(mul_low_slices(&[5], &[7], 0), vec![0]);
+        assert_eq!(mul_low_slices(&[0, 0, 1], &[0, 0, 1], 4), vec![0]);
+    }
+
+    fn naive_mid(a: &[u32], b: &[u32]) -> Vec<u32> {
+        let (len_a, len_b) = (a.len(), b.len());
+        let mut result = vec![0];
+        for (i, &x) in a.iter().enumerate() {
+            for (j, &y) in b.iter().enumerate() {
+                if i + j >= len_b - 1 && i + j < len_a {
+                    let term = mul_direct_slices(&[x], &[y]);
+                    result = add_slices(&result, &shift_left_slices(&term, i + j + 1 - len_b));
+                }
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn mul_mid_matches_band_sum() {
+        for (len_a, len_b) in [
+            (1, 1),
+            (9, 5),
+            (63, 32),
+            (127, 64),
+            (199, 100),
+            (250, 41),
+            (90, 77),
+        ] {
+            let a = limbs(len_a, 300 + len_a as u64);
+            let b = limbs(len_b, 400 + len_b as u64);
+            assert_eq!(
+                mul_mid_slices(&a, &b),
+                naive_mid(&a, &b),
+                "{} x {}",
+                len_a,
+                len_b
+            );
+        }
+        let max = (BASE - 1) as u32;
+        let a = vec![max; 255];
+        let b = vec![max; 128];
+        assert_eq!(mul_mid_slices(&a, &b), naive_mid(&a, &b));
+    }
+
+    #[test]
+    fn depth_limit_keeps_products_exact() {
+        for (len_a, len_b) in [(300, 300), (1000, 40), (17, 700), (257, 129)] {
+            let a = limbs(len_a, 700 + len_a as u64);
+            let b = limbs(len_b, 800 + len_b as u64);
+            let expected = mul_direct_slices(&a, &b);
+            for depth in [0, 1, 2, 5, MAX_KARATSUBA_DEPTH] {
+                assert_eq!(mul_karatsuba_depth_slices(&a, &b, depth), expected);
+                assert_eq!(mul_karatsuba_sub_depth_slices(&a, &b, depth), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_karatsuba_matches_sequential() {
+        for (la, lb) in [(3000, 3000), (2500, 1100), (4000, 700), (50, 5000)] {
+            let a = limbs(la, la as u64);
+            let b = limbs(lb, lb as u64 + 1);
+            let expected = mul_karatsuba_slices(&a, &b);
+            for threads in [1, 2, 3, 4, 7] {
+                assert_eq!(mul_karatsuba_par_slices(&a, &b, threads), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn multiplying_by_power_of_base_is_a_shift() {
+        let a = limbs(90, 4);
+        let power = shift_left_slices(&[1], 120);
+        for p in all_products(&a, &power) {
+            assert_eq!(p, shift_left_slices(&a, 120));
+        }
+    }
+
+    #[test]
+    fn unnormalized_inputs_give_normalized_products() {
+        let mut a = limbs(40, 5);
+        a.extend([0, 0, 0]);
+        let b = limbs(70, 6);
+        let mut a_norm = a.clone();
+        normalize(&mut a_norm);
+        let expected = mul_direct_slices(&a_norm, &b);
+        for p in all_products(&a, &b) {
+            assert_eq!(p, expected);
+            assert_ne!(p.last(), Some(&0));
+        }
+    }
+
+    #[test]
+    fn div_rem_reconstructs_dividend() {
+        for (len_a, len_b) in [
+            (1, 1),
+            (5, 1),
+            (5, 2),
+            (40, 17),
+            (120, 119),
+            (300, 64),
+            (3, 7),
+        ] {
+            let a = limbs(len_a, 500 + len_a as u64);
+            let b = limbs(len_b, 600 + len_b as u64);
+            let (q, r) = div_rem_slices(&a, &b);
+            assert_eq!(cmp_slices(&r, &b), Ordering::Less, "{} / {}", len_a, len_b);
+            let back = add_slices(&mul_direct_slices(&q, &b), &r);
+            assert_eq!(
+                cmp_slices(&back, &a),
+                Ordering::Equal,
+                "{} / {}",
+                len_a,
+                len_b
+            );
+        }
+    }
+
+    #[test]
+    fn div_rem_hits_add_back_and_exact_cases() {
+        let max = (BASE - 1) as u32;
+        // Divisor with a small top limb forces heavy normalization.
+        let (q, r) = div_rem_slices(&[0, 0, 0, 1], &[1, 1]);
+        assert_eq!(
+            add_slices(&mul_direct_slices(&q, &[1, 1]), &r),
+            vec![0, 0, 0, 1]
+        );
+        // qhat overestimates on operands like these (Knuth's test values).
+        let a = [0, 0, max, max - 1, max];
+        let b = [max, 0, max];
+        let (q, r) = div_rem_slices(&a, &b);
+        assert_eq!(
+            cmp_slices(&add_slices(&mul_direct_slices(&q, &b), &r), &a),
+            Ordering::Equal
+        );
+        let b = limbs(30, 7);
+        let p = mul_karatsuba_slices(&b, &limbs(20, 8));
+        assert_eq!(div_rem_slices(&p, &b), (limbs(20, 8), vec![0]));
+        assert_eq!(div_rem_small(&[], 3), (vec![0], 0));
+    }
+
+    #[test]
+    fn div_rem_by_powers_matches_long_division() {
+        let a = limbs(30, 31);
+        for k in [0, 1, 7, 29, 30, 45] {
+            let power = shift_left_slices(&[1], k);
+            assert_eq!(div_rem_pow_base_slices(&a, k), div_rem_slices(&a, &power));
+        }
+        for bits in [0, 1, 28, 29, 30, 100, 500, 2000] {
+            let mut power = vec![1];
+            for _ in 0..bits {
+                power = mul_small(&power, 2);
+            }
+            assert_eq!(div_rem_pow2_slices(&a, bits), div_rem_slices(&a, &power));
+        }
+        assert_eq!(div_rem_pow2_slices(&[], 10), (vec![0], vec![0]));
+    }
+
+    #[test]
+    #[should_panic(expected = "sub_slices: a < b")]
+    fn invariant_checks_catch_underflow() {
+        with_paranoid(true, || sub_slices(&[1, 2], &[1, 3]));
+    }
+
+    #[test]
+    #[should_panic(expected = "limb out of range")]
+    fn invariant_checks_catch_bad_limbs() {
+        with_paranoid(true, || add_slices(&[BASE as u32], &[1]));
+    }
+
+    #[test]
+    fn abs_diff_reports_order() {
+        let a = limbs(12, 41);
+        let b = limbs(9, 42);
+        let diff = sub_slices(&a, &b);
+        assert_eq!(abs_diff_slices(&a, &b), (Ordering::Greater, diff.clone()));
+        assert_eq!(abs_diff_slices(&b, &a), (Ordering::Less, diff));
+        let mut padded = a.clone();
+        padded.extend([0, 0]);
+        assert_eq!(abs_diff_slices(&padded, &a), (Ordering::Equal, vec![0]));
+    }
+
+    #[test]
+    fn addmul_matches_separate_passes() {
+        let max = (BASE - 1) as u32;
+        for (len_x, len_y, a, b) in [
+            (1, 1, 3, 5),
+            (30, 29, max, max),
+            (12, 40, 1, max),
+            (25, 25, 7, 7),
+            (1, 9, 0, 2),
+        ] {
+            let x = limbs(len_x, 500 + len_x as u64);
+            let y = limbs(len_y, 600 + len_y as u64);
+            let (ax, by) = (mul_small_slices(&x, a), mul_small_slices(&y, b));
+            assert_eq!(addmul_slices(a, &x, b, &y), add_slices(&ax, &by));
+            assert_eq!(addmul_sub_slices(a, &x, b, &y), abs_diff_slices(&ax, &by));
+            assert_eq!(addmul_sub_slices(b, &y, a, &x), abs_diff_slices(&by, &ax));
+        }
+        let x = vec![max; 20];
+        assert_eq!(addmul_slices(max, &x, max, &x), {
+            let p = mul_small_slices(&x, max);
+            add_slices(&p, &p)
+        });
+        assert_eq!(
+            addmul_sub_slices(4, &[5], 2, &[10]),
+            (Ordering::Equal, vec![0])
+        );
+    }
+
+    #[test]
+    fn div_exact_matches_long_division() {
+        let cases = [
+            (limbs(40, 21), limbs(25, 22)),
+            (limbs(3, 23), limbs(60, 24)),
+            (limbs(80, 25), vec![7]),
+            // Divisors with factors of 2, 5 and BASE.
+            (limbs(30, 26), vec![0, 0, 512_000_000, 3]),
+            (limbs(30, 27), mul_small(&limbs(12, 28), 390_625)),
+            (
+                limbs(30, 29),
+                mul_small(&mul_small(&limbs(12, 30), 1 << 29), 1 << 20),
+            ),
+        ];
+        for (q, b) in cases {
+            let a = mul_direct_slices(&q, &b);
+            assert_eq!(div_exact_slices(&a, &b), div_rem_slices(&a, &b).0);
+            assert_eq!(div_exact_slices(&a, &b), q);
+        }
+        assert_eq!(div_exact_slices(&[0], &[5]), vec![0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "division by zero")]
+    fn div_rem_by_zero_panics() {
+        div_rem_slices(&[1], &[0, 0]);
+    }
+
+    #[test]
+    fn result_lengths_are_checked() {
+        with_max_result_limbs(MAX_RESULT_LIMBS, || {
+            assert_eq!(product_len(3, 4).unwrap(), 7);
+            assert!(matches!(
+                product_len(usize::MAX, 1),
+                Err(Error::SizeOverflow)
+            ));
+            assert!(matches!(
+                product_len(MAX_RESULT_LIMBS, 1),
+                Err(Error::TooLarge { limbs, max: MAX_RESULT_LIMBS }) if limbs == MAX_RESULT_LIMBS + 1
+            ));
+        });
+    }
+
+    #[test]
+    fn threshold_overrides_fall_back_on_bad_values() {
+        assert_eq!(parse_threshold(None, 32), 32);
+        assert_eq!(parse_threshold(Some(" 48 "), 32), 48);
+        assert_eq!(parse_threshold(Some("4"), 32), 4);
+        assert_eq!(parse_threshold(Some("3"), 32), 32);
+        assert_eq!(parse_threshold(Some("fast"), 32), 32);
+        assert_eq!(parse_threshold(Some("-8"), 32), 32);
+    }
+
+    #[test]
+    fn carry_save_matches_sequential_sums() {
+        for n in [1, 2, 7, 40] {
+            let (p, q, r) = (limbs(n, 1), limbs(n, 2), limbs(n + 1, 3));
+            let mut sum = CarrySave::new(0);
+            sum.add(&p, 0);
+            sum.add(&q, n / 2);
+            sum.add(&r, n);
+            sum.sub(&p, 1);
+            let expected = add_slices(
+                &add_slices(&p, &shift_left_slices(&q, n / 2)),
+                &shift_left_slices(&r, n),
+            );
+            assert_eq!(
+                sum.finish(),
+                sub_slices(&expected, &shift_left_slices(&p, 1)),
+                "n={}",
+                n
+            );
+        }
+        let mut carries = CarrySave::new(1);
+        for _ in 0..3 {
+            carries.add(&[999_999_999, 999_999_999], 0);
+        }
+        assert_eq!(carries.finish(), vec![999_999_997, 999_999_999, 2]);
+        assert_eq!(CarrySave::new(4).finish(), vec![0]);
+    }
+
+    /// Operands at every boundary the kernels branch on: empty, zero with
+    /// and without high zero limbs, one limb, a zero low limb, and lengths
+    /// either side of the thresholds.
+    fn degenerate_operands() -> Vec<Vec<u32>> {
+        let mut ops = vec![
+            vec![],
+            vec![0],
+            vec![0, 0, 0],
+            vec![7],
+            vec![999_999_999],
+            vec![0, 7],
+            vec![0, 0, 1],
+        ];
+        for (len, seed) in [(2, 60), (31, 61), (32, 62), (33, 63), (65, 64), (150, 65)] {
+            ops.push(limbs(len, seed));
+        }
+        let mut padded = limbs(40, 66);
+        padded.extend([0, 0]);
+        ops.push(padded);
+        ops
+    }
+
+    #[test]
+    fn every_kernel_survives_degenerate_lengths() {
+        let norm = |v: &[u32]| {
+            let mut v = v.to_vec();
+            if v.is_empty() {
+                v.push(0);
+            }
+            normalize(&mut v);
+            v
+        };
+        let ops = degenerate_operands();
+        for a in &ops {
+            assert_eq!(square_karatsuba_slices(a), mul_direct_slices(a, a));
+            assert_eq!(shift_right_slices(a, a.len() + 1), vec![0]);
+            assert_eq!(div_rem_pow_base_slices(a, 0), (norm(a), vec![0]));
+            assert_eq!(div_rem_pow2_slices(a, 0), (norm(a), vec![0]));
+            for b in &ops {
+                let p = mul_direct_slices(a, b);
+                for q in all_products(a, b) {
+                    assert_eq!(q, p, "{} x {} limbs", a.len(), b.len());
+                }
+                assert_eq!(mul_karatsuba_par_slices(a, b, 4), p);
+                for n in [0, 1, a.len() + b.len()] {
+                    assert_eq!(mul_low_slices(a, b, n), norm(&p[..cmp::min(n, p.len())]));
+                }
+                mul_mid_slices(a, b);
+                let sum = add_slices(a, b);
+                assert_eq!(sub_slices(&sum, b), norm(a));
+                assert_eq!(abs_diff_slices(&sum, a), (cmp_slices(b, &[]), norm(b)));
+                if cmp_slices(b, &[]) == Ordering::Greater {
+                    let (q, r) = div_rem_slices(a, b);
+                    assert_eq!(add_slices(&mul_direct_slices(&q, b), &r), norm(a));
+                    assert_eq!(div_exact_slices(&p, b), norm(a));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+/// Limbs a [`LimbVec`] holds without allocating: every value below 10^36,
+/// which covers all of `u64`.
+pub(crate) const INLINE: usize = 4;
+
+/// The limb storage behind `BigUint`: up to [`INLINE`] limbs live in the
+/// value itself, longer numbers on the heap. Small values (counters,
+/// scalars, the zero and one that every algorithm starts from) are created,
+/// cloned and dropped without touching the allocator.
+#[derive(Clone)]
+pub(crate) enum LimbVec {
+    Inline { len: u8, buf: [u32; INLINE] },
+    Heap(Vec<u32>),
+}
+
+impl LimbVec {
+    /// `len` zero limbs, inline when they fit.
+    pub(crate) fn zeroed(len: usize) -> Self {
+        if len <= INLINE {
+            LimbVec::Inline {
+                len: len as u8,
+                buf: [0; INLINE],
+            }
+        } else {
+            LimbVec::Heap(vec![0; len])
+        }
+    }
+
+    pub(crate) fn from_slice(s: &[u32]) -> Self {
+        let mut v = LimbVec::zeroed(s.len());
+        v.copy_from_slice(s);
+        v
+    }
+
+    pub(crate) fn as_slice(&self) -> &[u32] {
+        self
+    }
+
+    #[cfg(test)]
+    pub(crate) fn is_inline(&self) -> bool {
+        matches!(self, LimbVec::Inline { .. })
+    }
+
+    /// Drops high zero limbs, keeping at least one.
+    pub(crate) fn normalize(&mut self) {
+        let len = self.iter().rposition(|&d| d != 0).map_or(1, |i| i + 1);
+        match self {
+            LimbVec::Inline { len: l, .. } => *l = len as u8,
+            LimbVec::Heap(v) => v.truncate(len),
+        }
+    }
+
+    pub(crate) fn into_vec(self) -> Vec<u32> {
+        match self {
+            LimbVec::Inline { len, buf } => buf[..len as usize].to_vec(),
+            LimbVec::Heap(v) => v,
+        }
+    }
+}
+
+impl From<Vec<u32>> for LimbVec {
+    /// Moves short vectors inline, so the result clones without allocating.
+    fn from(v: Vec<u32>) -> Self {
+        if v.len() <= INLINE {
+            LimbVec::from_slice(&v)
+        } else {
+            LimbVec::Heap(v)
+        }
+    }
+}
+
+impl Deref for LimbVec {
+    type Target = [u32];
+
+    fn deref(&self) -> &[u32] {
+        match self {
+            LimbVec::Inline { len, buf } => &buf[..*len as usize],
+            LimbVec::Heap(v) => v,
+        }
+    }
+}
+
+impl DerefMut for LimbVec {
+    fn deref_mut(&mut self) -> &mut [u32] {
+        match self {
+            LimbVec::Inline { len, buf } => &mut buf[..*len as usize],
+            LimbVec::Heap(v) => v,
+        }
+    }
+}
+
+impl fmt::Debug for LimbVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+impl PartialEq for LimbVec {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for LimbVec {}
+
+impl<const N: usize> PartialEq<[u32; N]> for LimbVec {
+    fn eq(&self, other: &[u32; N]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl PartialEq<Vec<u32>> for LimbVec {
+    fn eq(&self, other: &Vec<u32>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_values_stay_inline() {
+        let small = LimbVec::from(vec![1, 2, 3, 4]);
+        assert!(small.is_inline());
+        assert!(small.clone().is_inline());
+        let large = LimbVec::from(vec![1, 2, 3, 4, 5]);
+        assert!(!large.is_inline());
+        assert_eq!(large, vec![1, 2, 3, 4, 5]);
+
+        let mut v = LimbVec::zeroed(INLINE);
+        v[0] = 7;
+        v.normalize();
+        assert_eq!(v, [7]);
+        assert_eq!(v.into_vec(), [7]);
+        let mut zero = LimbVec::zeroed(3);
+        zero.normalize();
+        assert_eq!(zero, [0]);
+    }
+}
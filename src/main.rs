@@ -1,362 +1,1358 @@
-use plotters::prelude::*;
-use rand::Rng;
-use std::cmp;
+mod bases_bench;
+mod carry_save_bench;
+mod check;
+mod cli;
+mod completions;
+mod dlog;
+mod end_to_end;
+mod energy;
+mod export;
+mod external;
+mod factor;
+mod fixed_bench;
+#[cfg(any(feature = "ibig", feature = "malachite"))]
+mod foreign;
+#[cfg(feature = "cuda")]
+mod gpu;
+mod heap;
+mod heatmap;
+mod history;
+mod inputs;
+mod interrupt;
+mod json;
+mod matrix_bench;
+mod memory;
+mod merge;
+mod mixed_bench;
+mod multiplier;
+mod operands;
+mod out_of_core;
+mod overhead;
+mod panels;
+mod perf;
+mod pi;
+mod pow_bench;
+mod primality_bench;
+mod primitives;
+mod profile;
+mod rsa;
+mod scaling;
+mod shared_bench;
+mod square_bench;
+mod timer;
+mod verify;
+
+use bigmul::report::{Chart, Kind, Scale, Style, crossover};
+use bigmul::{BigInt, BigUint, Sign};
+use check::Check;
+use cli::{Command, Config, Output};
+use energy::Rapl;
+use inputs::{Axis, InputClass};
+use multiplier::Multiplier;
+use operands::Point;
+use perf::Counters;
+use std::fmt::Write as _;
 use std::time::Instant;
+use timer::Timer;
 
-#[derive(Clone, Debug)]
-struct BigInt {
-    digits: Vec<u32>,
-}
+const NUM_INSTANCES: usize = 10;
+const LARGE_NUM_INSTANCES: usize = 3;
 
-const BASE: u64 = 1_000_000_000;
+/// Digit counts `--quick-check` covers: single limbs, both sides of the
+/// Karatsuba and divide and conquer thresholds, and past the parallel one.
+const QUICK_CHECK_DIGITS: [usize; 8] = [1, 10, 100, 300, 1000, 3000, 10_000, 20_000];
 
-impl BigInt {
-    fn new() -> Self {
-        BigInt { digits: vec![0] }
+/// Number of timed multiplications averaged per point and algorithm.
+fn num_instances(config: &Config) -> usize {
+    if config.quick_check {
+        1
+    } else if config.large {
+        LARGE_NUM_INSTANCES
+    } else {
+        NUM_INSTANCES
     }
+}
 
-    fn from_str(s: &str) -> Self {
-        if s.is_empty() {
-            return BigInt::new();
-        }
-        let mut digits = Vec::new();
-        let mut ss = s.to_string();
-        while !ss.is_empty() {
-            let chunk_size = cmp::min(9, ss.len());
-            let chunk = &ss[ss.len() - chunk_size..];
-            let digit = chunk.parse::<u32>().unwrap_or(0);
-            digits.push(digit);
-            ss.truncate(ss.len() - chunk_size);
-        }
-        BigInt::normalize(&mut digits);
-        if digits.is_empty() {
-            digits.push(0);
+/// Average times for each measured point, one series per active
+/// multiplier in registry order. `joules` and `counters` hold average energy
+/// and hardware counts per multiplication in the same layout when `--energy`
+/// or `--perf` is on and are empty otherwise. `labels` names the corpus file
+/// behind each point and is empty for generated sweeps. `samples` keeps every
+/// individual timing, per series and then per point. `phases` splits each
+/// point's wall time.
+#[derive(Default)]
+struct Measurements {
+    ns: Vec<usize>,
+    labels: Vec<String>,
+    phases: Vec<Phases>,
+    avgs: Vec<Vec<f64>>,
+    samples: Vec<Vec<Vec<f64>>>,
+    joules: Vec<Vec<f64>>,
+    counters: Vec<Vec<[f64; 4]>>,
+}
+
+/// Wall seconds one point took in each phase of the sweep: producing its
+/// operands, the multipliers' runs (with whatever the probes add), and
+/// checking the products.
+#[derive(Clone, Copy, Default)]
+struct Phases {
+    generation: f64,
+    multiplication: f64,
+    checking: f64,
+}
+
+/// Running totals for one point.
+struct Totals {
+    seconds: Vec<f64>,
+    samples: Vec<Vec<f64>>,
+    joules: Vec<f64>,
+    counters: Vec<[u64; 4]>,
+    checking: f64,
+}
+
+impl Totals {
+    /// Totals for `series` multipliers with room for `rounds` samples each,
+    /// so recording a sample never reallocates between timed regions.
+    fn new(series: usize, rounds: usize) -> Self {
+        Totals {
+            seconds: vec![0.0; series],
+            samples: vec![Vec::with_capacity(rounds); series],
+            joules: vec![0.0; series],
+            counters: vec![[0; 4]; series],
+            checking: 0.0,
         }
-        BigInt { digits }
     }
+}
+
+/// How each multiplication is measured: the clock, plus whatever optional
+/// probes are on.
+#[derive(Clone, Copy, Default)]
+struct Probes<'a> {
+    timer: Timer,
+    rapl: Option<&'a Rapl>,
+    perf: Option<&'a Counters>,
+}
 
-    fn normalize(digits: &mut Vec<u32>) {
-        while digits.len() > 1 && *digits.last().unwrap() == 0 {
-            digits.pop();
+impl Measurements {
+    /// Room for `points` points in every series.
+    fn new(series: usize, probes: Probes, points: usize) -> Self {
+        let per_series = |on: bool| if on { series } else { 0 };
+        Measurements {
+            ns: Vec::with_capacity(points),
+            phases: Vec::with_capacity(points),
+            avgs: vec![Vec::with_capacity(points); series],
+            samples: vec![Vec::with_capacity(points); series],
+            joules: vec![Vec::with_capacity(points); per_series(probes.rapl.is_some())],
+            counters: vec![Vec::with_capacity(points); per_series(probes.perf.is_some())],
+            ..Default::default()
         }
     }
 
-    fn to_string(&self) -> String {
-        if self.digits.is_empty() || self.digits == vec![0] {
-            return "0".to_string();
+    /// Averages each series over the number of times it actually ran, and
+    /// keeps the point's samples.
+    fn push(&mut self, n: usize, totals: Totals, phases: Phases) {
+        self.ns.push(n);
+        self.phases.push(phases);
+        let runs: Vec<f64> = totals.samples.iter().map(|s| s.len() as f64).collect();
+        for ((avgs, total), runs) in self.avgs.iter_mut().zip(&totals.seconds).zip(&runs) {
+            avgs.push(total / runs);
         }
-        let mut s = self.digits.last().unwrap().to_string();
-        for &d in self.digits.iter().rev().skip(1) {
-            s.push_str(&format!("{:09}", d));
+        for (samples, times) in self.samples.iter_mut().zip(totals.samples) {
+            samples.push(times);
         }
-        s
-    }
-
-    fn add(&self, other: &BigInt) -> BigInt {
-        BigInt {
-            digits: Self::add_slices(&self.digits, &other.digits),
+        for ((joules, total), runs) in self.joules.iter_mut().zip(&totals.joules).zip(&runs) {
+            joules.push(total / runs);
+        }
+        for ((counters, total), runs) in self.counters.iter_mut().zip(&totals.counters).zip(&runs) {
+            counters.push(total.map(|c| c as f64 / runs));
         }
     }
+}
 
-    fn add_slices(a: &[u32], b: &[u32]) -> Vec<u32> {
-        let max_len = cmp::max(a.len(), b.len());
-        let mut result = vec![0u32; max_len + 1];
-        let mut carry: u64 = 0;
-        for i in 0..max_len {
-            let ai = if i < a.len() { a[i] as u64 } else { 0 };
-            let bi = if i < b.len() { b[i] as u64 } else { 0 };
-            let sum = ai + bi + carry;
-            result[i] = (sum % BASE) as u32;
-            carry = sum / BASE;
+/// One algorithm's results as written to the console, CSV and JSON.
+struct Series<'a> {
+    name: &'a str,
+    times: &'a [f64],
+    samples: &'a [Vec<f64>],
+    joules: Option<&'a [f64]>,
+    counters: Option<&'a [[f64; 4]]>,
+}
+
+/// Multiplies `a * b` with every multiplier whose `active` entry is set,
+/// adding each one's elapsed time (and whatever `probes` measure) to
+/// `totals`, and checks the products as `check` says, outside the timed
+/// regions, adding the wall time that takes to `totals.checking`. Each
+/// product is dropped as soon as it is checked; only the first one is kept
+/// until the pair is done, as the reference for `full`. Returns which
+/// multiplier failed the check, if one did.
+fn time_pair(
+    multipliers: &[Box<dyn Multiplier>],
+    active: &[bool],
+    probes: Probes,
+    check: Check,
+    a: &BigUint,
+    b: &BigUint,
+    totals: &mut Totals,
+) -> Result<(), String> {
+    let checking = Instant::now();
+    let expected = (check == Check::Residue).then(|| check::expected(a, b));
+    totals.checking += checking.elapsed().as_secs_f64();
+    let mut reference: Option<(&str, Vec<u32>)> = None;
+    for (k, m) in multipliers.iter().enumerate() {
+        if !active[k] {
+            continue;
         }
-        if carry > 0 {
-            result[max_len] = carry as u32;
+        let energy_start = probes.rapl.map(|r| r.read());
+        if let Some(perf) = probes.perf {
+            perf.start();
         }
-        Self::normalize(&mut result);
-        result
-    }
-
-    fn sub(&self, other: &BigInt) -> BigInt {
-        BigInt {
-            digits: Self::sub_slices(&self.digits, &other.digits),
+        let start = probes.timer.now();
+        let product = m.mul(a.as_limbs(), b.as_limbs());
+        let seconds = probes.timer.seconds_since(start);
+        totals.seconds[k] += seconds;
+        totals.samples[k].push(seconds);
+        if let Some(perf) = probes.perf {
+            for (total, count) in totals.counters[k].iter_mut().zip(perf.stop()) {
+                *total += count;
+            }
         }
-    }
-
-    fn sub_slices(a: &[u32], b: &[u32]) -> Vec<u32> {
-        let max_len = a.len();
-        let mut result = vec![0u32; max_len];
-        let mut borrow: i64 = 0;
-        for i in 0..max_len {
-            let ai = a[i] as i64;
-            let bi = if i < b.len() { b[i] as i64 } else { 0 };
-            let mut diff = ai - bi - borrow;
-            if diff < 0 {
-                diff += BASE as i64;
-                borrow = 1;
-            } else {
-                borrow = 0;
+        if let (Some(r), Some(energy_start)) = (probes.rapl, energy_start) {
+            totals.joules[k] += r.joules_between(&energy_start, &r.read());
+        }
+        let checking = Instant::now();
+        match (check, &reference) {
+            (Check::Full, Some((first, expected))) if &product != expected => {
+                return Err(format!("{} disagrees with {}", m.name(), first));
+            }
+            (Check::Full, None) => reference = Some((m.name(), product)),
+            (Check::Residue, _) if check::fingerprint(product) != expected => {
+                return Err(format!("{} fails the residue check", m.name()));
             }
-            result[i] = diff as u32;
+            _ => {}
         }
-        Self::normalize(&mut result);
-        result
+        totals.checking += checking.elapsed().as_secs_f64();
     }
+    Ok(())
+}
 
-    fn shift_left(&self, k: usize) -> BigInt {
-        BigInt {
-            digits: Self::shift_left_slices(&self.digits, k),
-        }
+fn default_sizes(axis: Axis) -> Vec<usize> {
+    let min_d: usize = axis.digits_to_unit(1000);
+    let max_d: usize = axis.digits_to_unit(10000);
+    let num_sizes: usize = 100;
+    let step = (max_d - min_d) / (num_sizes - 1);
+    let mut ns: Vec<usize> = (0..num_sizes).map(|i| min_d + i * step).collect();
+
+    if ns.last().unwrap() != &max_d {
+        *ns.last_mut().unwrap() = max_d;
     }
+    ns
+}
+
+/// Geometrically spaced sizes from 10^4 to 2 * 10^6 digits for `--large`.
+fn large_sizes(axis: Axis) -> Vec<usize> {
+    let min_d = axis.digits_to_unit(10_000) as f64;
+    let max_d = axis.digits_to_unit(2_000_000) as f64;
+    let num_sizes = 24;
+    let ratio = (max_d / min_d).powf(1.0 / (num_sizes - 1) as f64);
+    (0..num_sizes)
+        .map(|i| (min_d * ratio.powi(i)).round() as usize)
+        .collect()
+}
 
-    fn shift_left_slices(digits: &[u32], k: usize) -> Vec<u32> {
-        if digits == &[0] {
-            return vec![0];
+/// Upper bound on rounds per point under `--budget`, so a multiplier much
+/// faster than the timer's resolution still finishes.
+const MAX_BUDGET_ROUNDS: usize = 100_000;
+
+/// Times every multiplier on each point's operands, cycling through the
+/// point's pairs. Without `--budget` every multiplier runs `num_instances`
+/// rounds; with it each one keeps running until it has spent the budget at
+/// that point (at least once), so fast sizes get many samples and slow ones
+/// few. Stops at the first product that fails the check, naming it and the
+/// point.
+fn sweep(
+    config: &Config,
+    multipliers: &[Box<dyn Multiplier>],
+    probes: Probes,
+    points: &[Point],
+) -> Result<Measurements, String> {
+    let instances = num_instances(config);
+    let mut results = Measurements::new(multipliers.len(), probes, points.len());
+    for point in points {
+        if interrupt::interrupted() {
+            break;
+        }
+        let start = Instant::now();
+        let mut totals = Totals::new(multipliers.len(), instances);
+        for (round, (a, b)) in point.pairs.iter().cycle().enumerate() {
+            let active: Vec<bool> = match config.budget {
+                Some(budget) if round < MAX_BUDGET_ROUNDS => {
+                    totals.seconds.iter().map(|&t| t < budget).collect()
+                }
+                None if round < instances => vec![true; multipliers.len()],
+                _ => break,
+            };
+            if !active.contains(&true) {
+                break;
+            }
+            time_pair(
+                multipliers,
+                &active,
+                probes,
+                config.check,
+                a,
+                b,
+                &mut totals,
+            )
+            .map_err(|msg| format!("{} at {} {}", msg, point.size, config.x_axis.name()))?;
+        }
+        let phases = Phases {
+            generation: point.generation,
+            multiplication: start.elapsed().as_secs_f64() - totals.checking,
+            checking: totals.checking,
+        };
+        results.push(point.size, totals, phases);
+        if let Some(label) = &point.label {
+            results.labels.push(label.clone());
         }
-        let mut res = vec![0u32; k + digits.len()];
-        res[k..].copy_from_slice(digits);
-        res
     }
+    Ok(results)
+}
 
-    fn mul_direct(&self, other: &BigInt) -> BigInt {
-        BigInt {
-            digits: Self::mul_direct_slices(&self.digits, &other.digits),
+fn write_csv(path: &str, config: &Config, results: &Measurements, series: &[Series]) {
+    let mut out = String::new();
+    if !results.labels.is_empty() {
+        out.push_str("file,");
+    }
+    out.push_str(config.x_axis.name());
+    for s in series {
+        write!(out, ",{}", s.name).unwrap();
+        if s.joules.is_some() {
+            write!(out, ",{}_joules", s.name).unwrap();
+        }
+        if s.counters.is_some() {
+            for event in perf::EVENTS {
+                write!(out, ",{}_{}", s.name, event).unwrap();
+            }
         }
     }
-
-    fn mul_direct_slices(a: &[u32], b: &[u32]) -> Vec<u32> {
-        let len_a = a.len();
-        let len_b = b.len();
-        if len_a == 0 || len_b == 0 {
-            return vec![0];
+    out.push('\n');
+    for (i, n) in results.ns.iter().enumerate() {
+        if let Some(label) = results.labels.get(i) {
+            write!(out, "{},", label).unwrap();
         }
-        let mut result = vec![0u32; len_a + len_b];
-        for i in 0..len_a {
-            let mut carry: u64 = 0;
-            for j in 0..len_b {
-                let temp = a[i] as u64 * b[j] as u64 + result[i + j] as u64 + carry;
-                result[i + j] = (temp % BASE) as u32;
-                carry = temp / BASE;
+        write!(out, "{}", n).unwrap();
+        for s in series {
+            write!(out, ",{:.9}", s.times[i]).unwrap();
+            if let Some(joules) = s.joules {
+                write!(out, ",{:.9}", joules[i]).unwrap();
             }
-            let mut k = i + len_b;
-            while carry > 0 {
-                if k == result.len() {
-                    result.push(0);
+            if let Some(counters) = s.counters {
+                for c in counters[i] {
+                    write!(out, ",{:.1}", c).unwrap();
                 }
-                let temp = result[k] as u64 + carry;
-                result[k] = (temp % BASE) as u32;
-                carry = temp / BASE;
-                k += 1;
             }
         }
-        Self::normalize(&mut result);
-        result
+        out.push('\n');
     }
+    std::fs::write(path, out).expect("Failed to write CSV results");
+}
 
-    fn mul_dc(&self, other: &BigInt) -> BigInt {
-        BigInt {
-            digits: Self::mul_dc_slices(&self.digits, &other.digits),
+/// Writes each point's wall seconds per phase.
+fn write_phases_csv(path: &str, config: &Config, results: &Measurements) {
+    let mut out = String::new();
+    if !results.labels.is_empty() {
+        out.push_str("file,");
+    }
+    writeln!(
+        out,
+        "{},generation,multiplication,checking",
+        config.x_axis.name()
+    )
+    .unwrap();
+    for (i, (n, p)) in results.ns.iter().zip(&results.phases).enumerate() {
+        if let Some(label) = results.labels.get(i) {
+            write!(out, "{},", label).unwrap();
         }
+        writeln!(
+            out,
+            "{},{:.6},{:.6},{:.6}",
+            n, p.generation, p.multiplication, p.checking
+        )
+        .unwrap();
     }
+    std::fs::write(path, out).expect("Failed to write phase times");
+}
 
-    fn mul_dc_slices(a: &[u32], b: &[u32]) -> Vec<u32> {
-        if a.is_empty() || b.is_empty() {
-            return vec![0];
+/// Where each series overtakes `direct` for good, as marked on the chart;
+/// `None` if `direct` was not run.
+fn crossover_summary(config: &Config, ns: &[usize], series: &[Series]) -> Option<String> {
+    let points = |s: &Series| -> Vec<(f64, f64)> {
+        ns.iter()
+            .map(|&n| n as f64)
+            .zip(s.times.iter().copied())
+            .collect()
+    };
+    let direct = points(series.iter().find(|s| s.name == "direct")?);
+    let mut out = String::from("Faster than direct from:\n");
+    for s in series.iter().filter(|s| s.name != "direct") {
+        let from = match crossover(&direct, &points(s)) {
+            Some(n) => format!("{}={}", config.x_axis.name(), n),
+            None => "never, in this sweep".to_string(),
+        };
+        writeln!(out, "  {:<12} {}", s.name, from).unwrap();
+    }
+    Some(out)
+}
+
+/// The sweep's wall time per phase, summed over the points, with each
+/// phase's share.
+fn phase_summary(phases: &[Phases]) -> String {
+    let sum = |f: fn(&Phases) -> f64| phases.iter().map(f).sum::<f64>();
+    let rows = [
+        ("generation", sum(|p| p.generation)),
+        ("multiplication", sum(|p| p.multiplication)),
+        ("checking", sum(|p| p.checking)),
+    ];
+    let total: f64 = rows.iter().map(|(_, t)| t).sum();
+    let mut out = String::from("Time by phase (wall clock):\n");
+    for (name, t) in rows {
+        let share = if total > 0.0 { 100.0 * t / total } else { 0.0 };
+        writeln!(
+            out,
+            "  {:<15} {:>10}  {:5.1}%",
+            name,
+            format_seconds(t),
+            share
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// The results document written to `results.json` and printed by
+/// `--output json`.
+fn results_json(
+    config: &Config,
+    results: &Measurements,
+    series: &[Series],
+    complete: bool,
+) -> String {
+    let join = |v: Vec<String>| v.join(", ");
+    let mut out = String::from("{\n");
+    writeln!(out, "  \"complete\": {},", complete).unwrap();
+    writeln!(out, "  \"host\": \"{}\",", json::escape(&merge::hostname())).unwrap();
+    match &config.corpus {
+        Some(dir) => {
+            writeln!(
+                out,
+                "  \"corpus\": \"{}\",",
+                json::escape(&dir.display().to_string())
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "  \"files\": [{}],",
+                join(
+                    results
+                        .labels
+                        .iter()
+                        .map(|l| format!("\"{}\"", json::escape(l)))
+                        .collect()
+                )
+            )
+            .unwrap();
         }
-        let n = cmp::max(a.len(), b.len());
-        if n <= 32 {
-            return Self::mul_direct_slices(a, b);
+        None => {
+            writeln!(out, "  \"input_class\": \"{}\",", config.input_class.name()).unwrap();
         }
-        let m = n / 2;
-        let a0 = &a[0..cmp::min(m, a.len())];
-        let a1 = if a.len() > m { &a[m..] } else { &[] };
-        let b0 = &b[0..cmp::min(m, b.len())];
-        let b1 = if b.len() > m { &b[m..] } else { &[] };
-        let p = Self::mul_dc_slices(a0, b0);
-        let q = Self::mul_dc_slices(a1, b1);
-        let r = Self::mul_dc_slices(a0, b1);
-        let s = Self::mul_dc_slices(a1, b0);
-        let mid = Self::add_slices(&r, &s);
-        let q_shifted = Self::shift_left_slices(&q, 2 * m);
-        let mid_shifted = Self::shift_left_slices(&mid, m);
-        let temp = Self::add_slices(&q_shifted, &mid_shifted);
-        Self::add_slices(&temp, &p)
     }
+    writeln!(out, "  \"x_axis\": \"{}\",", config.x_axis.name()).unwrap();
+    writeln!(
+        out,
+        "  \"sizes\": [{}],",
+        join(results.ns.iter().map(|n| n.to_string()).collect())
+    )
+    .unwrap();
+    out.push_str("  \"algorithms\": [\n");
+    for (k, s) in series.iter().enumerate() {
+        let times = join(s.times.iter().map(|t| format!("{:.9}", t)).collect());
+        let joules = match s.joules {
+            Some(joules) => format!(
+                ", \"joules\": [{}]",
+                join(joules.iter().map(|j| format!("{:.9}", j)).collect())
+            ),
+            None => String::new(),
+        };
+        let counters = match s.counters {
+            Some(counters) => perf::EVENTS
+                .iter()
+                .enumerate()
+                .map(|(e, event)| {
+                    let values = join(counters.iter().map(|c| format!("{:.1}", c[e])).collect());
+                    format!(", \"{}\": [{}]", event, values)
+                })
+                .collect(),
+            None => String::new(),
+        };
+        let sep = if k + 1 < series.len() { "," } else { "" };
+        writeln!(
+            out,
+            "    {{\"name\": \"{}\", \"times\": [{}]{}{}}}{}",
+            json::escape(s.name),
+            times,
+            joules,
+            counters,
+            sep
+        )
+        .unwrap();
+    }
+    out.push_str("  ]\n}\n");
+    out
+}
 
-    fn mul_karatsuba(&self, other: &BigInt) -> BigInt {
-        BigInt {
-            digits: Self::mul_karatsuba_slices(&self.digits, &other.digits),
+/// Renders a GitHub-flavored Markdown table of median times per point, each
+/// non-reference cell followed by its speedup over the first series.
+fn markdown_table(config: &Config, results: &Measurements, series: &[Series]) -> String {
+    let mut out = String::from("|");
+    if !results.labels.is_empty() {
+        out.push_str(" file |");
+    }
+    write!(out, " {} |", config.x_axis.name()).unwrap();
+    for s in series {
+        write!(out, " {} |", s.name).unwrap();
+    }
+    out.push_str("\n|");
+    if !results.labels.is_empty() {
+        out.push_str(" --- |");
+    }
+    out.push_str(&" ---: |".repeat(series.len() + 1));
+    out.push('\n');
+    for (i, n) in results.ns.iter().enumerate() {
+        out.push('|');
+        if let Some(label) = results.labels.get(i) {
+            write!(out, " {} |", label).unwrap();
+        }
+        write!(out, " {} |", n).unwrap();
+        let reference = median(&series[0].samples[i]);
+        for (k, s) in series.iter().enumerate() {
+            let t = median(&s.samples[i]);
+            if k == 0 {
+                write!(out, " {} |", format_measure(config, t)).unwrap();
+            } else {
+                write!(
+                    out,
+                    " {} ({:.2}×) |",
+                    format_measure(config, t),
+                    reference / t
+                )
+                .unwrap();
+            }
         }
+        out.push('\n');
     }
+    if let Some(first) = series.first() {
+        write!(
+            out,
+            "\nMedian {} per multiplication; speedups are relative to {}.\n",
+            if config.timer.deterministic() {
+                "limb operations"
+            } else {
+                "time"
+            },
+            first.name
+        )
+        .unwrap();
+    }
+    out
+}
 
-    fn mul_karatsuba_slices(a: &[u32], b: &[u32]) -> Vec<u32> {
-        if a.is_empty() || b.is_empty() {
-            return vec![0];
+/// Scores each series by the geometric mean over all points of its time
+/// divided by the fastest time at that point, so 1.0 means fastest
+/// everywhere. Returns `(series index, score)`, best first.
+fn scores(series: &[Series]) -> Vec<(usize, f64)> {
+    let points = series.iter().map(|s| s.times.len()).min().unwrap_or(0);
+    let mut log_sums = vec![0.0; series.len()];
+    let mut counted = 0;
+    for i in 0..points {
+        let fastest = series.iter().map(|s| s.times[i]).fold(f64::MAX, f64::min);
+        if fastest <= 0.0 {
+            continue;
         }
-        let n = cmp::max(a.len(), b.len());
-        if n <= 32 {
-            return Self::mul_direct_slices(a, b);
+        for (sum, s) in log_sums.iter_mut().zip(series) {
+            *sum += (s.times[i] / fastest).ln();
         }
-        let m = n / 2;
-        let a0 = &a[0..cmp::min(m, a.len())];
-        let a1 = if a.len() > m { &a[m..] } else { &[] };
-        let b0 = &b[0..cmp::min(m, b.len())];
-        let b1 = if b.len() > m { &b[m..] } else { &[] };
-        let p = Self::mul_karatsuba_slices(a0, b0);
-        let q = Self::mul_karatsuba_slices(a1, b1);
-        let sum_a = Self::add_slices(a0, a1);
-        let sum_b = Self::add_slices(b0, b1);
-        let u = Self::mul_karatsuba_slices(&sum_a, &sum_b);
-        let sum_pq = Self::add_slices(&p, &q);
-        let mid = Self::sub_slices(&u, &sum_pq);
-        let q_shifted = Self::shift_left_slices(&q, 2 * m);
-        let mid_shifted = Self::shift_left_slices(&mid, m);
-        let temp = Self::add_slices(&q_shifted, &mid_shifted);
-        Self::add_slices(&temp, &p)
+        counted += 1;
     }
+    if counted == 0 {
+        return Vec::new();
+    }
+    let mut ranked: Vec<(usize, f64)> = log_sums
+        .iter()
+        .map(|sum| (sum / counted as f64).exp())
+        .enumerate()
+        .collect();
+    ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+    ranked
 }
 
-impl PartialEq for BigInt {
-    fn eq(&self, other: &Self) -> bool {
-        self.digits == other.digits
+fn median(samples: &[f64]) -> f64 {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
     }
 }
 
-fn random_bigint(d: usize) -> BigInt {
-    if d == 0 {
-        return BigInt::new();
+/// A median for the Markdown table: a time, or an operation count under
+/// `--timer ops`.
+fn format_measure(config: &Config, t: f64) -> String {
+    if config.timer.deterministic() {
+        format!("{:.0} ops", t)
+    } else {
+        format_seconds(t)
     }
-    let mut rng = rand::thread_rng();
-    let mut s = rng.gen_range(1..=9).to_string();
-    for _ in 1..d {
-        s.push_str(&rng.gen_range(0..=9).to_string());
+}
+
+fn format_seconds(t: f64) -> String {
+    if t >= 1.0 {
+        format!("{:.3} s", t)
+    } else if t >= 1e-3 {
+        format!("{:.3} ms", t * 1e3)
+    } else {
+        format!("{:.1} µs", t * 1e6)
     }
-    BigInt::from_str(&s)
 }
 
-fn main() {
-    let min_d: usize = 1000;
-    let max_d: usize = 10000;
-    let num_sizes: usize = 100;
-    let step = (max_d - min_d) / (num_sizes - 1);
-    let mut ns: Vec<usize> = (0..num_sizes).map(|i| min_d + i * step).collect();
+/// Title of the comparison chart, naming the input class or corpus.
+fn caption(config: &Config) -> String {
+    match (&config.corpus, config.input_class) {
+        (Some(_), _) => "Multiplication Algorithms Comparison (corpus)".to_string(),
+        (None, InputClass::Random) => "Multiplication Algorithms Comparison".to_string(),
+        (None, class) => format!("Multiplication Algorithms Comparison ({})", class.name()),
+    }
+}
 
-    if ns.last().unwrap() != &max_d {
-        *ns.last_mut().unwrap() = max_d;
+/// Vertical axis title: time, or limb operations under `--timer ops`.
+fn y_desc(config: &Config) -> &'static str {
+    if config.timer.deterministic() {
+        "Average Limb Operations"
+    } else {
+        "Average Execution Time (seconds)"
+    }
+}
+
+/// `chart` smoothed and thinned out as `--smooth` and `--max-points` ask.
+fn legible(chart: Chart, config: &Config) -> Chart {
+    let chart = chart.smooth(config.smooth);
+    match config.max_points {
+        Some(max) => chart.max_points(max),
+        None => chart,
     }
+}
 
-    let num_instances = 10;
+/// Draws one line per `(legend label, averages, style)` entry of `series`,
+/// log-log under `--large`, marking where each overtakes the `baseline`
+/// line if it is among them.
+fn plot(
+    path: &str,
+    title: &str,
+    config: &Config,
+    ns: &[usize],
+    series: &[(&str, &[f64], Style)],
+    baseline: Option<&str>,
+) -> Result<(), String> {
+    let chart = Chart::new()
+        .title(title)
+        .x_desc(format!("Input Size (number of {})", config.x_axis.name()))
+        .y_desc(y_desc(config))
+        .scale(if config.large {
+            Scale::LogLog
+        } else {
+            Scale::Linear
+        });
+    let chart = match baseline {
+        Some(label) if series.iter().any(|s| s.0 == label) => chart.crossovers(label),
+        _ => chart,
+    };
+    series
+        .iter()
+        .fold(legible(chart, config), |chart, &(label, avgs, style)| {
+            let points = ns.iter().map(|&n| n as f64).zip(avgs.iter().copied());
+            chart.series_styled(label, points, style)
+        })
+        .draw_png(path.as_ref())
+}
 
-    let mut avgs_direct: Vec<f64> = Vec::with_capacity(num_sizes);
-    let mut avgs_dc: Vec<f64> = Vec::with_capacity(num_sizes);
-    let mut avgs_kara: Vec<f64> = Vec::with_capacity(num_sizes);
+/// Stacks each point's wall seconds per phase, so the top edge is the
+/// point's whole cost and the bands show how much of it the products were.
+fn plot_phases(path: &str, config: &Config, results: &Measurements) -> Result<(), String> {
+    let names = ["generation", "multiplication", "checking"];
+    let split = |p: &Phases| [p.generation, p.multiplication, p.checking];
+    let chart = Chart::new()
+        .title("Time by Phase")
+        .x_desc(format!("Input Size (number of {})", config.x_axis.name()))
+        .y_desc("Wall Time per Point (seconds)")
+        .kind(Kind::Stacked)
+        .scale(if config.large {
+            Scale::LogLog
+        } else {
+            Scale::Linear
+        });
+    names
+        .iter()
+        .enumerate()
+        .fold(legible(chart, config), |chart, (k, name)| {
+            let points = results.ns.iter().zip(&results.phases);
+            chart.series(*name, points.map(|(&n, p)| (n as f64, split(p)[k])))
+        })
+        .draw_png(path.as_ref())
+}
 
-    for &n in &ns {
-        let mut times_direct = 0.0;
-        let mut times_dc = 0.0;
-        let mut times_kara = 0.0;
-        for _ in 0..num_instances {
-            let a = random_bigint(n);
-            let b = random_bigint(n);
+fn main() {
+    let config = match Command::from_env() {
+        Command::Bench(config) => config,
+        Command::Completions { shell } => {
+            print!("{}", completions::script(shell));
+            return;
+        }
+        Command::Man => {
+            print!("{}", completions::man_page());
+            return;
+        }
+        Command::RsaDemo { bits } => {
+            rsa::run(bits);
+            return;
+        }
+        Command::Mul {
+            a,
+            b,
+            notation,
+            algorithm,
+            estimate,
+            fallback,
+        } => {
+            let [a, b] = [a, b].map(|operand| {
+                operand.value().unwrap_or_else(|msg| {
+                    eprintln!("error: {}", msg);
+                    std::process::exit(1);
+                })
+            });
+            let algorithm = if estimate {
+                memory::guard(algorithm, &a, &b, &notation, fallback).unwrap_or_else(|msg| {
+                    eprintln!("error: {}", msg);
+                    std::process::exit(1);
+                })
+            } else {
+                algorithm
+            };
+            let product = match algorithm {
+                Some(name) => {
+                    let m = profile::find(&name).expect("algorithm validated by the CLI");
+                    let limbs = m.mul(a.magnitude().as_limbs(), b.magnitude().as_limbs());
+                    let sign = if a.sign() == b.sign() {
+                        Sign::Plus
+                    } else {
+                        Sign::Minus
+                    };
+                    BigInt::from_biguint(
+                        sign,
+                        BigUint::from_limbs(limbs).expect("limbs below BASE"),
+                    )
+                }
+                None => a.mul(&b),
+            };
+            println!("{}", product.format(&notation));
+            return;
+        }
+        Command::MulDisk {
+            a,
+            b,
+            out,
+            block,
+            max_limbs,
+        } => {
+            interrupt::install();
+            if let Some(max) = max_limbs {
+                bigmul::limbs::set_max_result_limbs(max);
+            }
+            #[cfg(unix)]
+            match out_of_core::run(&a, &b, &out, block) {
+                Ok(true) => {}
+                Ok(false) => std::process::exit(interrupt::EXIT_INTERRUPTED),
+                Err(msg) => {
+                    eprintln!("error: {}", msg);
+                    std::process::exit(1);
+                }
+            }
+            #[cfg(not(unix))]
+            {
+                let _ = (a, b, out, block);
+                eprintln!("error: mul-disk needs memory-mapped files (Unix only)");
+                std::process::exit(1);
+            }
+            return;
+        }
+        Command::Factor { n } => {
+            interrupt::install();
+            if !factor::run(&n) {
+                std::process::exit(interrupt::EXIT_INTERRUPTED);
+            }
+            return;
+        }
+        Command::Dlog { g, h, modulus } => {
+            interrupt::install();
+            if !dlog::run(&g, &h, &modulus) {
+                std::process::exit(interrupt::EXIT_INTERRUPTED);
+            }
+            return;
+        }
+        Command::ComputePi { digits } => {
+            if !pi::run(digits) {
+                std::process::exit(interrupt::EXIT_INTERRUPTED);
+            }
+            return;
+        }
+        Command::BenchPrimitives => {
+            interrupt::install();
+            if !primitives::run() {
+                std::process::exit(interrupt::EXIT_INTERRUPTED);
+            }
+            return;
+        }
+        Command::Gen {
+            input_class,
+            digits,
+            count,
+            seed,
+            out,
+        } => {
+            match operands::write_dataset(input_class, digits, count, seed, &out) {
+                Ok(seed) => println!(
+                    "Wrote {} pairs of {}-digit operands to {} (seed {})",
+                    count,
+                    digits,
+                    out.display(),
+                    seed
+                ),
+                Err(msg) => {
+                    eprintln!("error: {}", msg);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Command::Profile {
+            algorithm,
+            digits,
+            seconds,
+        } => {
+            interrupt::install();
+            if !profile::run(&algorithm, digits, seconds) {
+                std::process::exit(interrupt::EXIT_INTERRUPTED);
+            }
+            return;
+        }
+        Command::History {
+            db,
+            algorithm,
+            digits,
+        } => {
+            if let Err(msg) = history::show(&db, &algorithm, digits) {
+                eprintln!("error: {}", msg);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Command::Merge {
+            inputs,
+            out,
+            algorithm,
+        } => {
+            if let Err(msg) = merge::run(&inputs, &out, algorithm.as_deref()) {
+                eprintln!("error: {}", msg);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Command::Verify {
+            iterations,
+            max_digits,
+            seed,
+            paranoid,
+            oracles,
+            quiet,
+            output,
+        } => {
+            interrupt::install();
+            match verify::run(
+                iterations, max_digits, seed, paranoid, &oracles, quiet, output,
+            ) {
+                Ok(verify::Verdict::Passed) => return,
+                Ok(verify::Verdict::Failed) => std::process::exit(check::EXIT_MISMATCH),
+                Ok(verify::Verdict::Interrupted) => std::process::exit(interrupt::EXIT_INTERRUPTED),
+                Err(msg) => {
+                    eprintln!("error: {}", msg);
+                    std::process::exit(1);
+                }
+            }
+        }
+    };
+    interrupt::install();
 
-            let start = Instant::now();
-            let prod1 = a.mul_direct(&b);
-            times_direct += start.elapsed().as_secs_f64();
+    if config.mod_pow {
+        if !pow_bench::run() {
+            std::process::exit(interrupt::EXIT_INTERRUPTED);
+        }
+        return;
+    }
+    if config.primality {
+        if !primality_bench::run() {
+            std::process::exit(interrupt::EXIT_INTERRUPTED);
+        }
+        return;
+    }
+    if config.mod_mul {
+        if !pow_bench::run_mod_mul() {
+            std::process::exit(interrupt::EXIT_INTERRUPTED);
+        }
+        return;
+    }
 
-            let start = Instant::now();
-            let prod2 = a.mul_dc(&b);
-            times_dc += start.elapsed().as_secs_f64();
+    if config.fixed_width {
+        if !fixed_bench::run() {
+            std::process::exit(interrupt::EXIT_INTERRUPTED);
+        }
+        return;
+    }
+    if config.wrapper_overhead {
+        if !overhead::run() {
+            std::process::exit(interrupt::EXIT_INTERRUPTED);
+        }
+        return;
+    }
+    if config.shared_clone {
+        if !shared_bench::run() {
+            std::process::exit(interrupt::EXIT_INTERRUPTED);
+        }
+        return;
+    }
+    if config.mixed_sizes {
+        if !mixed_bench::run() {
+            std::process::exit(interrupt::EXIT_INTERRUPTED);
+        }
+        return;
+    }
+    if config.square {
+        if !square_bench::run() {
+            std::process::exit(interrupt::EXIT_INTERRUPTED);
+        }
+        return;
+    }
+    if config.carry_save {
+        if !carry_save_bench::run() {
+            std::process::exit(interrupt::EXIT_INTERRUPTED);
+        }
+        return;
+    }
+    if config.matrix {
+        if !matrix_bench::run() {
+            std::process::exit(interrupt::EXIT_INTERRUPTED);
+        }
+        return;
+    }
+    if config.bases {
+        if !bases_bench::run() {
+            std::process::exit(interrupt::EXIT_INTERRUPTED);
+        }
+        return;
+    }
+    if config.end_to_end {
+        if !end_to_end::run() {
+            std::process::exit(interrupt::EXIT_INTERRUPTED);
+        }
+        return;
+    }
+    if config.scaling {
+        if !scaling::run() {
+            std::process::exit(interrupt::EXIT_INTERRUPTED);
+        }
+        return;
+    }
 
-            let start = Instant::now();
-            let prod3 = a.mul_karatsuba(&b);
-            times_kara += start.elapsed().as_secs_f64();
+    let corpus = config.corpus.as_ref().map(|dir| {
+        inputs::load_corpus(dir, config.x_axis).unwrap_or_else(|msg| {
+            eprintln!("error: {}", msg);
+            std::process::exit(1);
+        })
+    });
+    let saved = config.operands.as_ref().map(|dir| {
+        operands::load(dir, config.x_axis).unwrap_or_else(|msg| {
+            eprintln!("error: {}", msg);
+            std::process::exit(1);
+        })
+    });
 
-            assert_eq!(prod1, prod2);
-            assert_eq!(prod1, prod3);
+    let mut multipliers: Vec<Box<dyn Multiplier>> = multiplier::registry()
+        .into_iter()
+        .filter(|m| !(config.large && m.quadratic()))
+        .collect();
+    for (name, path) in &config.external {
+        if multipliers.iter().any(|m| m.name() == name) {
+            eprintln!("error: multiplier name '{}' is already taken", name);
+            std::process::exit(1);
+        }
+        match external::External::spawn(name, path) {
+            Ok(ext) => multipliers.push(Box::new(ext)),
+            Err(msg) => {
+                eprintln!("error: {}", msg);
+                std::process::exit(1);
+            }
+        }
+    }
+    if config.heatmap {
+        if !heatmap::run(&multipliers) {
+            std::process::exit(interrupt::EXIT_INTERRUPTED);
+        }
+        return;
+    }
+    let points = match (corpus, saved) {
+        (Some(corpus), _) => operands::from_corpus(corpus),
+        (None, Some(points)) => points,
+        (None, None) if config.quick_check => InputClass::ALL
+            .iter()
+            .flat_map(|&class| {
+                QUICK_CHECK_DIGITS.iter().map(move |&d| {
+                    let n = config.x_axis.digits_to_unit(d);
+                    operands::generate_seeded(class, config.x_axis, n, 1, n as u64)
+                })
+            })
+            .collect(),
+        (None, None) => {
+            let ns = if config.large {
+                large_sizes(config.x_axis)
+            } else {
+                default_sizes(config.x_axis)
+            };
+            if config.timer.deterministic() {
+                // Operation counts depend slightly on the values (carries
+                // lengthen sums), so fix them too.
+                ns.iter()
+                    .map(|&n| {
+                        operands::generate_seeded(
+                            config.input_class,
+                            config.x_axis,
+                            n,
+                            num_instances(&config),
+                            n as u64,
+                        )
+                    })
+                    .collect()
+            } else {
+                operands::generate(
+                    config.input_class,
+                    config.x_axis,
+                    &ns,
+                    num_instances(&config),
+                )
+            }
+        }
+    };
+    if let Some(dir) = &config.save_operands {
+        let meta = [("class", config.input_class.name().to_string())];
+        if let Err(msg) = operands::save(dir, config.x_axis, &meta, &points) {
+            eprintln!("error: {}", msg);
+            std::process::exit(1);
+        }
+        if config.reports() {
+            println!("Operands saved to {}", dir.display());
         }
-        avgs_direct.push(times_direct / num_instances as f64);
-        avgs_dc.push(times_dc / num_instances as f64);
-        avgs_kara.push(times_kara / num_instances as f64);
     }
+    let rapl = config.energy.then(|| {
+        Rapl::open().unwrap_or_else(|msg| {
+            eprintln!("error: {}", msg);
+            std::process::exit(1);
+        })
+    });
+    let counters = config.perf.then(|| {
+        Counters::open().unwrap_or_else(|msg| {
+            eprintln!("error: {}", msg);
+            std::process::exit(1);
+        })
+    });
+    let db = config.db.as_ref().map(|path| {
+        history::Db::open(path).unwrap_or_else(|msg| {
+            eprintln!("error: {}", msg);
+            std::process::exit(1);
+        })
+    });
+    let probes = Probes {
+        timer: config.timer,
+        rapl: rapl.as_ref(),
+        perf: counters.as_ref(),
+    };
+    let resolution = timer::announce(&config);
+    let results = sweep(&config, &multipliers, probes, &points).unwrap_or_else(|msg| {
+        eprintln!("error: {}", msg);
+        std::process::exit(check::EXIT_MISMATCH);
+    });
+    if config.quick_check {
+        if results.ns.len() < points.len() {
+            std::process::exit(interrupt::EXIT_INTERRUPTED);
+        }
+        match config.output {
+            Output::Text if !config.quiet => println!(
+                "Quick check passed: {} algorithms agree on {} pairs",
+                multipliers.len(),
+                points.len()
+            ),
+            Output::Text => {}
+            Output::Json => println!(
+                "{{\"passed\": true, \"algorithms\": {}, \"pairs\": {}}}",
+                multipliers.len(),
+                points.len()
+            ),
+        }
+        return;
+    }
+    let floor = timer::RESOLUTION_MARGIN * resolution;
+    for (m, avgs) in multipliers.iter().zip(&results.avgs) {
+        if config.timer.deterministic() || config.quiet {
+            break;
+        }
+        let short = avgs.iter().filter(|&&t| t < floor).count();
+        if short > 0 {
+            eprintln!(
+                "warning: {} averaged under {:.1} us ({} clock steps) at {} points; \
+                 those times are dominated by timer resolution",
+                m.name(),
+                floor * 1e6,
+                timer::RESOLUTION_MARGIN,
+                short
+            );
+        }
+    }
+
+    let planned = points.len();
+    let complete = results.ns.len() == planned;
+    if !complete && !config.quiet {
+        eprintln!(
+            "Interrupted: writing partial results for {} of {} points",
+            results.ns.len(),
+            planned
+        );
+    }
+
+    let series: Vec<Series> = multipliers
+        .iter()
+        .enumerate()
+        .map(|(k, m)| Series {
+            name: m.name(),
+            times: &results.avgs[k],
+            samples: &results.samples[k],
+            joules: results.joules.get(k).map(Vec::as_slice),
+            counters: results.counters.get(k).map(Vec::as_slice),
+        })
+        .collect();
 
     // Print data
-    for i in 0..ns.len() {
+    for i in 0..results.ns.len() {
+        if !config.reports() {
+            break;
+        }
+        let mut line = String::new();
+        if let Some(label) = results.labels.get(i) {
+            write!(line, "file={}, ", label).unwrap();
+        }
+        write!(line, "n={}", results.ns[i]).unwrap();
+        for s in &series {
+            write!(line, ", {}={:.6}", s.name, s.times[i]).unwrap();
+            if let Some(joules) = s.joules {
+                write!(line, " ({:.6} J)", joules[i]).unwrap();
+            }
+            if let Some(counters) = s.counters {
+                let [instructions, cycles, ..] = counters[i];
+                write!(line, " ({:.0} insn, {:.0} cyc)", instructions, cycles).unwrap();
+            }
+        }
+        println!("{}", line);
+    }
+
+    let ranking = scores(&series);
+    if config.reports() && !ranking.is_empty() {
+        println!("\nScore (geometric mean of time relative to the fastest; 1.000 is best):");
+        for (place, &(k, score)) in ranking.iter().enumerate() {
+            println!("{:>3}. {:<12} {:.3}", place + 1, series[k].name, score);
+        }
+        println!();
+    }
+
+    if config.reports() {
+        if let Some(summary) = crossover_summary(&config, &results.ns, &series) {
+            println!("{}", summary);
+        }
+        if !results.ns.is_empty() {
+            println!("{}", phase_summary(&results.phases));
+        }
+    }
+
+    std::fs::create_dir_all("./assets").expect("Failed to create ./assets directory");
+    write_csv("./assets/results.csv", &config, &results, &series);
+    write_phases_csv("./assets/phases.csv", &config, &results);
+    let json = results_json(&config, &results, &series, complete);
+    std::fs::write("./assets/results.json", &json).expect("Failed to write JSON results");
+    if config.output == Output::Json {
+        print!("{}", json);
+    }
+    if config.reports() {
         println!(
-            "n={}, direct={:.6}, dc={:.6}, kara={:.6}",
-            ns[i], avgs_direct[i], avgs_dc[i], avgs_kara[i]
+            "Results saved to ./assets/results.csv and ./assets/results.json, \
+             phase times to ./assets/phases.csv"
         );
     }
+    if config.markdown && !results.ns.is_empty() {
+        let table = markdown_table(&config, &results, &series);
+        std::fs::write("./assets/results.md", &table).expect("Failed to write Markdown results");
+        if config.reports() {
+            print!("\n{}\n", table);
+            println!("Table saved to ./assets/results.md");
+        }
+    }
+    if let Some(data) = &config.gnuplot
+        && !results.ns.is_empty()
+    {
+        match export::gnuplot(data, &config, &results, &series) {
+            Ok(script) if config.reports() => println!(
+                "gnuplot data saved to {}; run `gnuplot {}` to draw it",
+                data.display(),
+                script
+            ),
+            Ok(_) => {}
+            Err(msg) => eprintln!("error: {}", msg),
+        }
+    }
+    if let Some(path) = &config.pgfplots
+        && !results.ns.is_empty()
+    {
+        match export::pgfplots(path, &config, &results, &series) {
+            Ok(()) if config.reports() => {
+                println!("pgfplots figure saved to {}", path.display())
+            }
+            Ok(()) => {}
+            Err(msg) => eprintln!("error: {}", msg),
+        }
+    }
+    if let (Some(db), Some(path)) = (&db, &config.db) {
+        let info = history::RunInfo {
+            input_class: if config.corpus.is_some() {
+                "corpus"
+            } else {
+                config.input_class.name()
+            },
+            x_axis: config.x_axis.name(),
+            complete,
+        };
+        let samples: Vec<history::Sample> = multipliers
+            .iter()
+            .zip(&results.samples)
+            .flat_map(|(m, samples)| {
+                results
+                    .ns
+                    .iter()
+                    .zip(samples)
+                    .flat_map(move |(&size, times)| {
+                        times.iter().map(move |&seconds| history::Sample {
+                            algorithm: m.name(),
+                            size,
+                            seconds,
+                        })
+                    })
+            })
+            .collect();
+        match db.record_run(&info, &samples) {
+            Ok(run) if config.reports() => {
+                println!("Run {} appended to {}", run, path.display())
+            }
+            Ok(_) => {}
+            Err(msg) => eprintln!("error: {}", msg),
+        }
+    }
 
     // Plot graph
-    std::fs::create_dir_all("./assets").expect("Failed to create ./assets directory");
-    let root = BitMapBackend::new("./assets/multiplication_times.png", (800, 600)).into_drawing_area();
-    root.fill(&WHITE).unwrap();
-    let max_time = avgs_direct
+    let direct = multipliers
         .iter()
-        .chain(avgs_dc.iter())
-        .chain(avgs_kara.iter())
-        .fold(f64::MIN, |m, &v| m.max(v));
-    let mut chart = ChartBuilder::on(&root)
-        .caption(
-            "Multiplication Algorithms Comparison",
-            ("sans-serif", 50).into_font(),
-        )
-        .margin(10)
-        .x_label_area_size(40)
-        .y_label_area_size(50)
-        .build_cartesian_2d(
-            ns[0] as f32..*ns.last().unwrap() as f32 + 1.0,
-            0f32..(max_time * 1.1) as f32,
-        )
-        .unwrap();
-
-    chart
-        .configure_mesh()
-        .x_desc("Input Size (number of digits)")
-        .y_desc("Average Execution Time (seconds)")
-        .draw()
-        .unwrap();
-
-    chart
-        .draw_series(LineSeries::new(
-            ns.iter()
-                .zip(avgs_direct.iter())
-                .map(|(&x, &y)| (x as f32, y as f32)),
-            &RED,
-        ))
-        .unwrap()
-        .label("Direct Multiplication")
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &RED));
-
-    chart
-        .draw_series(LineSeries::new(
-            ns.iter()
-                .zip(avgs_dc.iter())
-                .map(|(&x, &y)| (x as f32, y as f32)),
-            &GREEN,
-        ))
-        .unwrap()
-        .label("Simple Divide & Conquer")
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &GREEN));
-
-    chart
-        .draw_series(LineSeries::new(
-            ns.iter()
-                .zip(avgs_kara.iter())
-                .map(|(&x, &y)| (x as f32, y as f32)),
-            &BLUE,
-        ))
-        .unwrap()
-        .label("Karatsuba")
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &BLUE));
-
-    chart
-        .configure_series_labels()
-        .background_style(&WHITE.mix(0.8))
-        .border_style(&BLACK)
-        .draw()
-        .unwrap();
+        .find(|m| m.name() == "direct")
+        .map(|m| m.label());
+    if !results.ns.is_empty() {
+        let legend: Vec<(&str, &[f64], Style)> = multipliers
+            .iter()
+            .zip(&results.avgs)
+            .map(|(m, avgs)| (m.label(), avgs.as_slice(), Style::of(m.name())))
+            .collect();
+        match plot(
+            "./assets/multiplication_times.png",
+            &caption(&config),
+            &config,
+            &results.ns,
+            &legend,
+            direct,
+        ) {
+            Ok(()) if config.reports() => {
+                println!("Graph saved to ./assets/multiplication_times.png")
+            }
+            Ok(()) => {}
+            Err(msg) => eprintln!("error: {}", msg),
+        }
+        match plot_phases("./assets/phases.png", &config, &results) {
+            Ok(()) if config.reports() => {
+                println!("Phase breakdown saved to ./assets/phases.png")
+            }
+            Ok(()) => {}
+            Err(msg) => eprintln!("error: {}", msg),
+        }
+        if config.pedagogical {
+            // Quadratic baselines dwarf the practical algorithms on a shared
+            // axis; each group gets its own chart and scale, keeping the
+            // colors of the combined one.
+            for (path, title, quadratic) in [
+                (
+                    "./assets/multiplication_times_baselines.png",
+                    "Educational Baselines",
+                    true,
+                ),
+                (
+                    "./assets/multiplication_times_practical.png",
+                    "Practical Algorithms",
+                    false,
+                ),
+            ] {
+                let group: Vec<_> = multipliers
+                    .iter()
+                    .zip(&legend)
+                    .filter(|(m, _)| m.quadratic() == quadratic)
+                    .map(|(_, &entry)| entry)
+                    .collect();
+                if !group.is_empty() {
+                    match plot(path, title, &config, &results.ns, &group, direct) {
+                        Ok(()) if config.reports() => println!("Graph saved to {}", path),
+                        Ok(()) => {}
+                        Err(msg) => eprintln!("error: {}", msg),
+                    }
+                }
+            }
+        }
+    }
 
-    root.present().unwrap();
+    if config.panels
+        && !results.ns.is_empty()
+        && !panels::run(&config, &multipliers, &points, &results)
+    {
+        std::process::exit(interrupt::EXIT_INTERRUPTED);
+    }
 
-    println!("Graph saved to ./assets/multiplication_times.png");
+    if !complete {
+        std::process::exit(interrupt::EXIT_INTERRUPTED);
+    }
 }
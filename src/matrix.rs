@@ -0,0 +1,231 @@
+//! Matrices of `BigInt`, multiplied naively or by Strassen's method: the
+//! divide-and-conquer trade of the integer kernels one level up. Strassen
+//! forms a product of block matrices from seven block products instead of
+//! eight at the price of eighteen block additions, and with big entries,
+//! where one entry product costs far more than one entry addition, the
+//! trade pays off at much smaller sizes than it does for machine numbers.
+
+use crate::bigint::BigInt;
+
+/// Dimensions at or below which `mul_strassen` multiplies naively.
+const STRASSEN_THRESHOLD: usize = 8;
+
+/// A dense `rows x cols` matrix, stored row by row.
+///
+/// ```
+/// use bigmul::{BigInt, BigMatrix};
+///
+/// let big = |s: &str| BigInt::from_str(s).unwrap();
+/// let m = BigMatrix::new(2, 2, vec![big("1"), big("1"), big("1"), big("0")]);
+/// // The eighth power of the Fibonacci matrix holds F(8).
+/// let m2 = m.mul(&m);
+/// let m4 = m2.mul(&m2);
+/// let m8 = m4.mul(&m4);
+/// assert_eq!(m8.get(0, 1).to_string(), "21");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BigMatrix {
+    rows: usize,
+    cols: usize,
+    entries: Vec<BigInt>,
+}
+
+impl BigMatrix {
+    /// The matrix with `entries` in row-major order. Panics unless there
+    /// are `rows * cols` of them.
+    pub fn new(rows: usize, cols: usize, entries: Vec<BigInt>) -> Self {
+        assert_eq!(
+            entries.len(),
+            rows * cols,
+            "BigMatrix::new: {} entries for a {}x{} matrix",
+            entries.len(),
+            rows,
+            cols
+        );
+        BigMatrix {
+            rows,
+            cols,
+            entries,
+        }
+    }
+
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        BigMatrix::new(rows, cols, vec![BigInt::zero(); rows * cols])
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut m = BigMatrix::zeros(n, n);
+        for i in 0..n {
+            m.entries[i * n + i] = BigInt::one();
+        }
+        m
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> &BigInt {
+        assert!(
+            row < self.rows && col < self.cols,
+            "BigMatrix::get out of range"
+        );
+        &self.entries[row * self.cols + col]
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(&self, other: &BigMatrix) -> BigMatrix {
+        self.zip(other, BigInt::add)
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn sub(&self, other: &BigMatrix) -> BigMatrix {
+        self.zip(other, BigInt::sub)
+    }
+
+    fn zip(&self, other: &BigMatrix, f: fn(&BigInt, &BigInt) -> BigInt) -> BigMatrix {
+        assert!(
+            self.rows == other.rows && self.cols == other.cols,
+            "BigMatrix: {}x{} and {}x{} differ in shape",
+            self.rows,
+            self.cols,
+            other.rows,
+            other.cols
+        );
+        let entries = self
+            .entries
+            .iter()
+            .zip(&other.entries)
+            .map(|(a, b)| f(a, b));
+        BigMatrix::new(self.rows, self.cols, entries.collect())
+    }
+
+    /// Multiplies by Strassen's method.
+    #[allow(clippy::should_implement_trait)]
+    pub fn mul(&self, other: &BigMatrix) -> BigMatrix {
+        self.mul_strassen(other)
+    }
+
+    /// Row by column: `rows * cols * other.cols` entry products.
+    pub fn mul_naive(&self, other: &BigMatrix) -> BigMatrix {
+        self.check_product(other);
+        let mut out = BigMatrix::zeros(self.rows, other.cols);
+        for i in 0..self.rows {
+            for k in 0..self.cols {
+                let a = &self.entries[i * self.cols + k];
+                if a.is_zero() {
+                    continue;
+                }
+                let row = &other.entries[k * other.cols..(k + 1) * other.cols];
+                for (o, b) in out.entries[i * other.cols..].iter_mut().zip(row) {
+                    *o = o.add(&a.mul(b));
+                }
+            }
+        }
+        out
+    }
+
+    /// Seven half-size products per level, until a dimension is 8 or less and
+    /// the naive product takes over. Odd dimensions are padded with a zero
+    /// row or column for the split.
+    pub fn mul_strassen(&self, other: &BigMatrix) -> BigMatrix {
+        self.check_product(other);
+        if [self.rows, self.cols, other.cols]
+            .iter()
+            .any(|&d| d <= STRASSEN_THRESHOLD)
+        {
+            return self.mul_naive(other);
+        }
+        let (h, k, l) = (
+            self.rows.div_ceil(2),
+            self.cols.div_ceil(2),
+            other.cols.div_ceil(2),
+        );
+        let a = |r, c| self.block(r * h, c * k, h, k);
+        let b = |r, c| other.block(r * k, c * l, k, l);
+        let (a11, a12, a21, a22) = (a(0, 0), a(0, 1), a(1, 0), a(1, 1));
+        let (b11, b12, b21, b22) = (b(0, 0), b(0, 1), b(1, 0), b(1, 1));
+
+        let m1 = a11.add(&a22).mul_strassen(&b11.add(&b22));
+        let m2 = a21.add(&a22).mul_strassen(&b11);
+        let m3 = a11.mul_strassen(&b12.sub(&b22));
+        let m4 = a22.mul_strassen(&b21.sub(&b11));
+        let m5 = a11.add(&a12).mul_strassen(&b22);
+        let m6 = a21.sub(&a11).mul_strassen(&b11.add(&b12));
+        let m7 = a12.sub(&a22).mul_strassen(&b21.add(&b22));
+
+        let mut out = BigMatrix::zeros(self.rows, other.cols);
+        out.place(0, 0, &m1.add(&m4).sub(&m5).add(&m7));
+        out.place(0, l, &m3.add(&m5));
+        out.place(h, 0, &m2.add(&m4));
+        out.place(h, l, &m1.sub(&m2).add(&m3).add(&m6));
+        out
+    }
+
+    fn check_product(&self, other: &BigMatrix) {
+        assert_eq!(
+            self.cols, other.rows,
+            "BigMatrix: cannot multiply {}x{} by {}x{}",
+            self.rows, self.cols, other.rows, other.cols
+        );
+    }
+
+    /// The `rows x cols` block from `(row, col)`, zero past the edges.
+    fn block(&self, row: usize, col: usize, rows: usize, cols: usize) -> BigMatrix {
+        let mut out = BigMatrix::zeros(rows, cols);
+        for i in 0..rows.min(self.rows.saturating_sub(row)) {
+            let from = (row + i) * self.cols + col;
+            let width = cols.min(self.cols.saturating_sub(col));
+            out.entries[i * cols..i * cols + width]
+                .clone_from_slice(&self.entries[from..from + width]);
+        }
+        out
+    }
+
+    /// Copies `block` in at `(row, col)`, dropping what falls past the edges.
+    fn place(&mut self, row: usize, col: usize, block: &BigMatrix) {
+        for i in 0..block.rows.min(self.rows.saturating_sub(row)) {
+            let width = block.cols.min(self.cols.saturating_sub(col));
+            let to = (row + i) * self.cols + col;
+            self.entries[to..to + width]
+                .clone_from_slice(&block.entries[i * block.cols..i * block.cols + width]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bigint::Sign;
+    use crate::random_bits;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    fn random_matrix(rows: usize, cols: usize, rng: &mut StdRng) -> BigMatrix {
+        let entries = (0..rows * cols)
+            .map(|_| {
+                let sign = [Sign::Minus, Sign::Plus][rng.gen_range(0..2)];
+                let bits = rng.gen_range(0..300);
+                BigInt::from_biguint(sign, random_bits(bits, rng))
+            })
+            .collect();
+        BigMatrix::new(rows, cols, entries)
+    }
+
+    #[test]
+    fn strassen_matches_naive() {
+        let mut rng = StdRng::seed_from_u64(85);
+        for (n, m, p) in [(1, 1, 1), (20, 20, 20), (17, 23, 9), (33, 18, 27)] {
+            let a = random_matrix(n, m, &mut rng);
+            let b = random_matrix(m, p, &mut rng);
+            let expected = a.mul_naive(&b);
+            assert_eq!(a.mul_strassen(&b), expected, "{}x{} by {}x{}", n, m, m, p);
+            assert_eq!(BigMatrix::identity(n).mul(&a), a);
+        }
+        let a = random_matrix(3, 4, &mut rng);
+        assert_eq!(a.sub(&a), BigMatrix::zeros(3, 4));
+    }
+}
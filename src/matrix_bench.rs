@@ -0,0 +1,87 @@
+//! `--matrix`: naive against Strassen multiplication of square `BigMatrix`
+//! values with 500-digit entries, from 8x8 (where Strassen is the naive
+//! method) to 64x64. Each Strassen level replaces one block product in
+//! eight with eighteen block additions, so the ratio of their times shows
+//! how cheap additions are next to big-entry products.
+
+use crate::interrupt;
+use crate::primitives::seconds_per_call;
+use bigmul::report::{Chart, Scale};
+use bigmul::{BigInt, BigMatrix, Sign, random_bits};
+use std::fmt::Write as _;
+use std::hint::black_box;
+
+/// Matrix dimensions.
+const SIZES: [usize; 4] = [8, 16, 32, 64];
+/// Bits per entry, about 500 digits.
+const ENTRY_BITS: usize = 1661;
+
+fn random_matrix(n: usize) -> BigMatrix {
+    let mut rng = rand::thread_rng();
+    let entries = (0..n * n)
+        .map(|_| BigInt::from_biguint(Sign::Plus, random_bits(ENTRY_BITS, &mut rng)))
+        .collect();
+    BigMatrix::new(n, n, entries)
+}
+
+/// Runs the sweep, printing both times and their ratio, and writes
+/// `./assets/matrix.csv` and `./assets/matrix.png`. Returns `false` if
+/// interrupted.
+pub fn run() -> bool {
+    let mut rows: Vec<(usize, f64, f64)> = Vec::new();
+    for &n in &SIZES {
+        if interrupt::interrupted() {
+            break;
+        }
+        let (a, b) = (random_matrix(n), random_matrix(n));
+        let naive = seconds_per_call(|| {
+            black_box(black_box(&a).mul_naive(black_box(&b)));
+        });
+        let strassen = seconds_per_call(|| {
+            black_box(black_box(&a).mul_strassen(black_box(&b)));
+        });
+        println!(
+            "size={}x{}, naive={:.3e} s, strassen={:.3e} s ({:.2}x)",
+            n,
+            n,
+            naive,
+            strassen,
+            naive / strassen
+        );
+        rows.push((n, naive, strassen));
+    }
+    if rows.is_empty() {
+        return false;
+    }
+
+    let mut out = String::from("size,naive_seconds,strassen_seconds,speedup\n");
+    for (n, naive, strassen) in &rows {
+        writeln!(
+            out,
+            "{},{:.9},{:.9},{:.3}",
+            n,
+            naive,
+            strassen,
+            naive / strassen
+        )
+        .unwrap();
+    }
+    std::fs::create_dir_all("./assets").expect("Failed to create ./assets directory");
+    std::fs::write("./assets/matrix.csv", out).expect("Failed to write CSV results");
+    let series = |pick: fn(&(usize, f64, f64)) -> f64| {
+        rows.iter()
+            .map(move |row| (row.0 as f64, pick(row)))
+            .collect::<Vec<_>>()
+    };
+    let chart = Chart::new()
+        .title("BigMatrix Multiplication")
+        .x_desc("Matrix Size (rows)")
+        .scale(Scale::LogLog)
+        .series("naive", series(|row| row.1))
+        .series("strassen", series(|row| row.2));
+    if let Err(msg) = chart.draw_png("./assets/matrix.png".as_ref()) {
+        eprintln!("error: {}", msg);
+    }
+    println!("Results saved to ./assets/matrix.csv and ./assets/matrix.png");
+    rows.len() == SIZES.len()
+}
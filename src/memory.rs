@@ -0,0 +1,202 @@
+//! `mul --digits-estimate`: a peak-memory estimate for one huge product,
+//! checked against the memory the system has available before any work
+//! starts, so a 100-million-digit multiplication fails in a second instead
+//! of an hour into swapping.
+//!
+//! The estimate follows each kernel's allocations level by level: Karatsuba
+//! keeps its three sub-products, the operand sums and the recombination
+//! temporaries alive until it returns, about 14 operand lengths of limbs at
+//! the top level, and the parallel variant holds up to three such recursions
+//! at once. The operands, the product and its decimal text are added on top.
+
+use crate::multiplier::available_threads;
+use bigmul::limbs::par_karatsuba_threshold;
+use bigmul::{BigInt, Notation};
+
+/// Lower-memory algorithms `--fallback` may switch to, fastest first.
+const FALLBACKS: [&str; 3] = ["kara", "dc", "direct"];
+
+/// An estimate above this share of available memory draws a warning.
+const WARN_SHARE: f64 = 0.8;
+
+/// Peak limbs a kernel holds while multiplying operands of `n` and `m`
+/// limbs (`n >= m`), its product included. `None` is `BigUint::mul`, which
+/// takes the Karatsuba path for large operands.
+fn kernel_limbs(algorithm: Option<&str>, n: usize, m: usize, threads: usize) -> f64 {
+    let (n, m) = (n as f64, m as f64);
+    match algorithm {
+        Some("direct") => n + m,
+        // Four sub-products and a carry-save sum of 64-bit words.
+        Some("dc") => 10.0 * n,
+        Some("kara_par") => parallel_limbs(n, m, threads),
+        // 128-bit column sums copied back from the device, then the product.
+        Some("gpu") => 5.0 * (n + m),
+        _ => karatsuba_limbs(n, m),
+    }
+}
+
+fn karatsuba_limbs(n: f64, m: f64) -> f64 {
+    if m <= n / 2.0 {
+        // The unbalanced split: a carry-save sum of 64-bit words, the
+        // product, and one balanced product of the shorter length.
+        3.0 * (n + m) + karatsuba_limbs(m, m)
+    } else {
+        14.0 * n
+    }
+}
+
+/// As `karatsuba_limbs`, with the three sub-products of each level running
+/// at once while the thread budget lasts, split as the kernel splits it.
+fn parallel_limbs(n: f64, m: f64, threads: usize) -> f64 {
+    if threads <= 1 || n < par_karatsuba_threshold() as f64 {
+        return karatsuba_limbs(n, m);
+    }
+    if m <= n / 2.0 {
+        return 3.0 * (n + m) + parallel_limbs(m, m, threads);
+    }
+    let half = |t| parallel_limbs(n / 2.0, n / 2.0, t);
+    let threads_p = (threads / 3).max(1);
+    let threads_q = threads / 3;
+    let threads_u = threads - threads_p - threads_q;
+    let concurrent = if threads_q > 0 {
+        half(threads_p) + half(threads_q) + half(threads_u)
+    } else {
+        half(threads_p) + half(threads_u)
+    };
+    (n + concurrent).max(14.0 * n)
+}
+
+/// Estimated peak bytes for multiplying `a` and `b` with `algorithm` and
+/// printing the product as `notation` says.
+pub fn estimate(algorithm: Option<&str>, a: &BigInt, b: &BigInt, notation: &Notation) -> u64 {
+    let (la, lb) = (
+        a.magnitude().as_limbs().len(),
+        b.magnitude().as_limbs().len(),
+    );
+    let (n, m) = (la.max(lb), la.min(lb));
+    let product = (n + m) as f64;
+    let kernel = kernel_limbs(algorithm, n, m, available_threads());
+    // Every notation renders all the digits first; grouping copies them.
+    let copies = if matches!(notation, Notation::Grouped(_)) {
+        2.0
+    } else {
+        1.0
+    };
+    let text = 9.0 * product * copies;
+    let limb = std::mem::size_of::<u32>() as f64;
+    ((n + m) as f64 * limb + (kernel * limb).max(product * limb + text)) as u64
+}
+
+/// `MemAvailable` from `/proc/meminfo` in bytes.
+fn parse_meminfo(text: &str) -> Option<u64> {
+    let line = text.lines().find(|l| l.starts_with("MemAvailable:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// Memory the system can hand out without swapping, if it says.
+pub fn available() -> Option<u64> {
+    parse_meminfo(&std::fs::read_to_string("/proc/meminfo").ok()?)
+}
+
+/// `bytes` in binary units.
+fn format_bytes(bytes: u64) -> String {
+    let mut value = bytes as f64;
+    for unit in ["B", "KiB", "MiB", "GiB"] {
+        if value < 1024.0 {
+            return format!("{:.1} {}", value, unit);
+        }
+        value /= 1024.0;
+    }
+    format!("{:.1} TiB", value)
+}
+
+/// Reports the estimate for `algorithm` on stderr and returns the algorithm
+/// to multiply with: `algorithm` itself if it fits in available memory, or
+/// with `fallback` the fastest of [`FALLBACKS`] that needs less and fits.
+/// Fails if nothing fits.
+pub fn guard(
+    algorithm: Option<String>,
+    a: &BigInt,
+    b: &BigInt,
+    notation: &Notation,
+    fallback: bool,
+) -> Result<Option<String>, String> {
+    let name = |algorithm: Option<&str>| algorithm.unwrap_or("auto").to_string();
+    let needed = estimate(algorithm.as_deref(), a, b, notation);
+    let Some(available) = available() else {
+        eprintln!(
+            "Estimated peak memory with {}: {} (available memory unknown)",
+            name(algorithm.as_deref()),
+            format_bytes(needed)
+        );
+        return Ok(algorithm);
+    };
+    eprintln!(
+        "Estimated peak memory with {}: {} of {} available",
+        name(algorithm.as_deref()),
+        format_bytes(needed),
+        format_bytes(available)
+    );
+    if needed <= available {
+        if needed as f64 > WARN_SHARE * available as f64 {
+            eprintln!("warning: the product needs most of the available memory");
+        }
+        return Ok(algorithm);
+    }
+    if !fallback {
+        return Err(format!(
+            "{} needs an estimated {} but only {} is available; \
+             pass --fallback to try a lower-memory algorithm",
+            name(algorithm.as_deref()),
+            format_bytes(needed),
+            format_bytes(available)
+        ));
+    }
+    for candidate in FALLBACKS {
+        let lower = estimate(Some(candidate), a, b, notation);
+        if lower < needed && lower <= available {
+            eprintln!(
+                "Switching to {}: estimated peak memory {}",
+                candidate,
+                format_bytes(lower)
+            );
+            return Ok(Some(candidate.to_string()));
+        }
+    }
+    Err(format!(
+        "no algorithm fits in {} of available memory",
+        format_bytes(available)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimates_order_the_algorithms() {
+        let a = BigInt::from_str(&"7".repeat(90_000)).unwrap();
+        let b = BigInt::from_str(&"3".repeat(90_000)).unwrap();
+        let bytes = |alg| estimate(alg, &a, &b, &Notation::Plain);
+        assert!(bytes(Some("direct")) < bytes(Some("dc")));
+        assert!(bytes(Some("dc")) < bytes(Some("kara")));
+        assert_eq!(bytes(None), bytes(Some("kara")));
+        // 10,000 limbs per operand: the operands plus 14 lengths of limbs.
+        assert_eq!(bytes(Some("kara")), 4 * (20_000 + 140_000));
+        assert!(parallel_limbs(1e6, 1e6, 8) > karatsuba_limbs(1e6, 1e6));
+        assert_eq!(parallel_limbs(1e6, 1e6, 1), karatsuba_limbs(1e6, 1e6));
+        let grouped = estimate(Some("direct"), &a, &b, &Notation::Grouped(",".into()));
+        assert!(grouped > bytes(Some("direct")));
+    }
+
+    #[test]
+    fn reads_available_memory() {
+        let text = "MemTotal:       16303428 kB\nMemFree:         1234 kB\n\
+                    MemAvailable:   10485760 kB\n";
+        assert_eq!(parse_meminfo(text), Some(10 * 1024 * 1024 * 1024));
+        assert_eq!(parse_meminfo("MemTotal: 1 kB\n"), None);
+        assert_eq!(format_bytes(512), "512.0 B");
+        assert_eq!(format_bytes(3 << 30), "3.0 GiB");
+    }
+}
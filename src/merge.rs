@@ -0,0 +1,233 @@
+//! `bigmul merge`: combines `results.json` files from several machines into
+//! one file and charts each algorithm once per host, to compare how the
+//! crossovers move between machines.
+//!
+//! Every sweep records the host it ran on; files from older versions fall
+//! back to their file name. Merged files can be merged again.
+
+use crate::json::{self, Value};
+use bigmul::report::Chart;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// One machine's sweep.
+struct Machine {
+    host: String,
+    sizes: Vec<f64>,
+    algorithms: Vec<(String, Vec<f64>)>,
+}
+
+/// The name of this machine, for tagging results.
+#[cfg(unix)]
+pub fn hostname() -> String {
+    let mut buf = [0u8; 256];
+    let rc = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) };
+    if rc != 0 {
+        return "unknown".to_string();
+    }
+    let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+    String::from_utf8_lossy(&buf[..len]).into_owned()
+}
+
+#[cfg(not(unix))]
+pub fn hostname() -> String {
+    std::env::var("COMPUTERNAME").unwrap_or_else(|_| "unknown".to_string())
+}
+
+fn numbers(v: Option<&Value>, what: &str) -> Result<Vec<f64>, String> {
+    v.and_then(Value::as_array)
+        .ok_or_else(|| format!("missing {}", what))?
+        .iter()
+        .map(|x| x.as_f64().ok_or_else(|| format!("non-numeric {}", what)))
+        .collect()
+}
+
+/// Reads one sweep object: `host` (optional), `sizes` and `algorithms`.
+fn machine(v: &Value, fallback_host: &str) -> Result<Machine, String> {
+    let sizes = numbers(v.get("sizes"), "sizes")?;
+    let mut algorithms = Vec::new();
+    for a in v
+        .get("algorithms")
+        .and_then(Value::as_array)
+        .ok_or("missing algorithms")?
+    {
+        let name = a
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or("unnamed algorithm")?;
+        let times = numbers(a.get("times"), "times")?;
+        if times.len() > sizes.len() {
+            return Err(format!("{} has more times than sizes", name));
+        }
+        algorithms.push((name.to_string(), times));
+    }
+    let host = v
+        .get("host")
+        .and_then(Value::as_str)
+        .unwrap_or(fallback_host);
+    Ok(Machine {
+        host: host.to_string(),
+        sizes,
+        algorithms,
+    })
+}
+
+/// Loads a results file or a merged file, returning its size unit and
+/// machines.
+fn load(path: &Path) -> Result<(String, Vec<Machine>), String> {
+    let context = |msg: String| format!("{}: {}", path.display(), msg);
+    let text = std::fs::read_to_string(path).map_err(|e| context(format!("cannot read: {}", e)))?;
+    let doc = json::parse(&text).map_err(context)?;
+    let x_axis = doc
+        .get("x_axis")
+        .and_then(Value::as_str)
+        .ok_or_else(|| context("missing x_axis".to_string()))?
+        .to_string();
+    let stem = path
+        .file_stem()
+        .map_or("unknown".into(), |s| s.to_string_lossy());
+    let machines = match doc.get("machines").and_then(Value::as_array) {
+        Some(list) => list
+            .iter()
+            .map(|m| machine(m, &stem))
+            .collect::<Result<_, _>>(),
+        None => machine(&doc, &stem).map(|m| vec![m]),
+    }
+    .map_err(context)?;
+    Ok((x_axis, machines))
+}
+
+fn to_json(x_axis: &str, machines: &[Machine]) -> String {
+    let join = |v: &[f64]| {
+        v.iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut out = String::from("{\n");
+    writeln!(out, "  \"x_axis\": \"{}\",", json::escape(x_axis)).unwrap();
+    out.push_str("  \"machines\": [\n");
+    for (i, m) in machines.iter().enumerate() {
+        writeln!(
+            out,
+            "    {{\"host\": \"{}\", \"sizes\": [{}], \"algorithms\": [",
+            json::escape(&m.host),
+            join(&m.sizes)
+        )
+        .unwrap();
+        for (k, (name, times)) in m.algorithms.iter().enumerate() {
+            let sep = if k + 1 < m.algorithms.len() { "," } else { "" };
+            writeln!(
+                out,
+                "      {{\"name\": \"{}\", \"times\": [{}]}}{}",
+                json::escape(name),
+                join(times),
+                sep
+            )
+            .unwrap();
+        }
+        let sep = if i + 1 < machines.len() { "," } else { "" };
+        writeln!(out, "    ]}}{}", sep).unwrap();
+    }
+    out.push_str("  ]\n}\n");
+    out
+}
+
+/// Draws one line per (algorithm, host), optionally for one algorithm only.
+fn plot(
+    path: &Path,
+    x_axis: &str,
+    machines: &[Machine],
+    algorithm: Option<&str>,
+) -> Result<(), String> {
+    let title = match algorithm {
+        Some(a) => format!("{} across machines", a),
+        None => "Algorithms across machines".to_string(),
+    };
+    let mut chart = Chart::new()
+        .title(title)
+        .x_desc(format!("Input Size (number of {})", x_axis))
+        .dimensions(1000, 600);
+    for m in machines {
+        for (name, times) in &m.algorithms {
+            if algorithm.is_none_or(|a| a == name) {
+                let points = m.sizes.iter().copied().zip(times.iter().copied());
+                chart = chart.series(format!("{} @ {}", name, m.host), points);
+            }
+        }
+    }
+    chart.draw_png(path)
+}
+
+/// Merges `inputs` into `out` and charts them next to it as a PNG.
+pub fn run(inputs: &[PathBuf], out: &Path, algorithm: Option<&str>) -> Result<(), String> {
+    let mut x_axis: Option<String> = None;
+    let mut machines: Vec<Machine> = Vec::new();
+    for path in inputs {
+        let (axis, loaded) = load(path)?;
+        match &x_axis {
+            Some(first) if *first != axis => {
+                return Err(format!(
+                    "{} measures {} but earlier files measure {}",
+                    path.display(),
+                    axis,
+                    first
+                ));
+            }
+            _ => x_axis = Some(axis),
+        }
+        for mut m in loaded {
+            // Two sweeps from the same host stay apart as host#2, host#3, ...
+            let base = m.host.clone();
+            let mut n = 1;
+            while machines.iter().any(|other| other.host == m.host) {
+                n += 1;
+                m.host = format!("{}#{}", base, n);
+            }
+            machines.push(m);
+        }
+    }
+    let x_axis = x_axis.expect("at least one input");
+    if let Some(a) = algorithm
+        && !machines
+            .iter()
+            .any(|m| m.algorithms.iter().any(|(name, _)| name == a))
+    {
+        return Err(format!("no input has results for '{}'", a));
+    }
+
+    std::fs::write(out, to_json(&x_axis, &machines))
+        .map_err(|e| format!("cannot write {}: {}", out.display(), e))?;
+    let chart = out.with_extension("png");
+    plot(&chart, &x_axis, &machines, algorithm)?;
+    println!(
+        "Merged {} machines into {}; chart saved to {}",
+        machines.len(),
+        out.display(),
+        chart.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merged_output_reloads() {
+        let doc = json::parse(
+            r#"{"complete": true, "host": "laptop", "x_axis": "digits",
+                "sizes": [1000, 2000],
+                "algorithms": [{"name": "kara", "times": [0.5, 1.5]}]}"#,
+        )
+        .unwrap();
+        let m = machine(&doc, "fallback").unwrap();
+        assert_eq!(m.host, "laptop");
+        let merged = json::parse(&to_json("digits", &[m])).unwrap();
+        let machines = merged.get("machines").and_then(Value::as_array).unwrap();
+        let again = machine(&machines[0], "fallback").unwrap();
+        assert_eq!(again.host, "laptop");
+        assert_eq!(again.sizes, [1000.0, 2000.0]);
+        assert_eq!(again.algorithms, [("kara".to_string(), vec![0.5, 1.5])]);
+    }
+}
@@ -0,0 +1,103 @@
+//! `--mixed-sizes`: what inline limb storage saves on workloads that mix
+//! small values (counters, scalars) with big ones. The same bookkeeping loop
+//! runs over `BigUint`, which keeps values of up to four limbs inline, and
+//! over plain `Vec<u32>` limbs through the slice kernels, which always
+//! allocate, with a growing share of the values small.
+
+use crate::interrupt;
+use crate::primitives::{random_limbs, seconds_per_call};
+use bigmul::{BigUint, limbs};
+use std::fmt::Write as _;
+use std::hint::black_box;
+
+/// Percent of the values that are small.
+const SMALL_SHARES: [usize; 5] = [0, 50, 90, 99, 100];
+const VALUES: usize = 100;
+const SMALL_LIMBS: usize = 2;
+const LARGE_LIMBS: usize = 100;
+
+/// Limbs of each value: the first `small_share` percent small.
+fn sizes(small_share: usize) -> Vec<usize> {
+    (0..VALUES)
+        .map(|i| {
+            if i * 100 < small_share * VALUES {
+                SMALL_LIMBS
+            } else {
+                LARGE_LIMBS
+            }
+        })
+        .collect()
+}
+
+/// Seconds per round with heap limbs and with `BigUint`. A round copies
+/// every value, bumps a counter per value and scales each by a one-limb
+/// factor.
+fn measure(small_share: usize) -> [f64; 2] {
+    let values: Vec<Vec<u32>> = sizes(small_share).into_iter().map(random_limbs).collect();
+    let scalar = random_limbs(1);
+    let heap = seconds_per_call(|| {
+        let mut counter = vec![0u32];
+        let mut held = Vec::with_capacity(2 * VALUES);
+        for v in black_box(&values) {
+            counter = limbs::add_slices(&counter, &[1]);
+            held.push(limbs::mul_karatsuba_slices(v, &scalar));
+            held.push(v.clone());
+        }
+        black_box((counter, held));
+    });
+
+    let values: Vec<BigUint> = values
+        .into_iter()
+        .map(|v| BigUint::from_limbs(v).unwrap())
+        .collect();
+    let (scalar, one) = (BigUint::from_limbs(scalar).unwrap(), BigUint::one());
+    let inline = seconds_per_call(|| {
+        let mut counter = BigUint::new();
+        let mut held = Vec::with_capacity(2 * VALUES);
+        for v in black_box(&values) {
+            counter = counter.add(&one);
+            held.push(v.mul_karatsuba(&scalar));
+            held.push(v.clone());
+        }
+        black_box((counter, held));
+    });
+    [heap, inline]
+}
+
+/// Runs the comparison, printing seconds per round at each share of small
+/// values and writing `./assets/mixed_sizes.csv`. Returns `false` if
+/// interrupted before the end.
+pub fn run() -> bool {
+    let mut rows: Vec<(usize, [f64; 2])> = Vec::new();
+    for &share in &SMALL_SHARES {
+        if interrupt::interrupted() {
+            break;
+        }
+        let [heap, inline] = measure(share);
+        println!(
+            "small={}%, heap_limbs={:.3e} s, biguint={:.3e} s, speedup={:.2}x",
+            share,
+            heap,
+            inline,
+            heap / inline
+        );
+        rows.push((share, [heap, inline]));
+    }
+
+    let mut out = String::from("small_percent,heap_limbs_seconds,biguint_seconds,speedup\n");
+    for (share, [heap, inline]) in &rows {
+        writeln!(
+            out,
+            "{},{:.12},{:.12},{:.3}",
+            share,
+            heap,
+            inline,
+            heap / inline
+        )
+        .unwrap();
+    }
+    std::fs::create_dir_all("./assets").expect("Failed to create ./assets directory");
+    std::fs::write("./assets/mixed_sizes.csv", out).expect("Failed to write CSV results");
+    println!("Results saved to ./assets/mixed_sizes.csv");
+    rows.len() == SMALL_SHARES.len()
+}
@@ -0,0 +1,306 @@
+use crate::biguint::{BigUint, PowWindow, pow_windowed};
+use crate::error::Error;
+use crate::limbs;
+use std::cmp::Ordering;
+
+/// Constants for Montgomery multiplication with `R = BASE^k`, where `k` is
+/// the modulus length in limbs.
+#[derive(Clone, Debug)]
+struct Montgomery {
+    /// `-m^-1 mod R`.
+    m_neg_inv: Vec<u32>,
+    /// `R^2 mod m`, for converting into Montgomery form.
+    r2: BigUint,
+    /// `R mod m`, the Montgomery form of one.
+    one: BigUint,
+}
+
+/// Arithmetic modulo a fixed `m`, with the Barrett (and, when `m` is coprime
+/// to 10, Montgomery) constants derived once in `new`.
+///
+/// Elements are plain `BigUint` residues in `[0, m)`. `mul` and `pow` reduce
+/// with Barrett or Montgomery instead of long division; the Montgomery form
+/// is exposed through `to_montgomery`/`mont_mul`/`from_montgomery` for
+/// callers that chain many products.
+#[derive(Clone, Debug)]
+pub struct ModRing {
+    modulus: BigUint,
+    /// Limb count of the modulus.
+    k: usize,
+    /// Barrett constant `floor(BASE^(2k) / m)`.
+    mu: Vec<u32>,
+    montgomery: Option<Montgomery>,
+}
+
+impl ModRing {
+    /// Panics if `modulus` is zero; see [`try_new`](ModRing::try_new).
+    pub fn new(modulus: BigUint) -> Self {
+        ModRing::try_new(modulus).expect("modulus must be non-zero")
+    }
+
+    /// As [`new`](ModRing::new), failing with [`Error::DivisionByZero`]
+    /// instead of panicking on a zero modulus.
+    pub fn try_new(modulus: BigUint) -> Result<Self, Error> {
+        if modulus.is_zero() {
+            return Err(Error::DivisionByZero);
+        }
+        let k = modulus.digits.len();
+        let b2k = limbs::shift_left_slices(&[1], 2 * k);
+        let mu = limbs::div_rem_slices(&b2k, &modulus.digits).0;
+        // R = BASE^k is invertible modulo m exactly when m is coprime to 10.
+        let r = BigUint {
+            digits: limbs::shift_left_slices(&[1], k).into(),
+        };
+        let montgomery = modulus.mod_inverse(&r).ok().map(|inv| Montgomery {
+            m_neg_inv: r.sub(&inv).into_limbs(),
+            r2: BigUint { digits: b2k.into() }.div_rem(&modulus).1,
+            one: r.div_rem(&modulus).1,
+        });
+        Ok(ModRing {
+            modulus,
+            k,
+            mu,
+            montgomery,
+        })
+    }
+
+    pub fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+
+    /// Whether Montgomery multiplication is available (`m` coprime to 10).
+    pub fn has_montgomery(&self) -> bool {
+        self.montgomery.is_some()
+    }
+
+    /// Reduces any `x` into `[0, m)`. Values below `BASE^(2k)`, which
+    /// includes every product of two residues, take the Barrett path.
+    pub fn reduce(&self, x: &BigUint) -> BigUint {
+        let k = self.k;
+        if x.digits.len() > 2 * k {
+            return x.div_rem(&self.modulus).1;
+        }
+        // q = floor(floor(x / B^(k-1)) * mu / B^(k+1)) undershoots x / m by
+        // at most 2, so r = x - q*m needs at most two corrections and fits
+        // in k + 1 limbs.
+        let q1 = limbs::shift_right_slices(&x.digits, k - 1);
+        let q = limbs::shift_right_slices(&limbs::mul_karatsuba_slices(&q1, &self.mu), k + 1);
+        let low = &x.digits[..x.digits.len().min(k + 1)];
+        let qm = limbs::mul_low_slices(&q, &self.modulus.digits, k + 1);
+        let mut r = if limbs::cmp_slices(low, &qm) == Ordering::Less {
+            let wrapped = limbs::add_slices(low, &limbs::shift_left_slices(&[1], k + 1));
+            limbs::sub_slices(&wrapped, &qm)
+        } else {
+            limbs::sub_slices(low, &qm)
+        };
+        while limbs::cmp_slices(&r, &self.modulus.digits) != Ordering::Less {
+            r = limbs::sub_slices(&r, &self.modulus.digits);
+        }
+        BigUint { digits: r.into() }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        let sum = a.add(b);
+        if sum >= self.modulus {
+            sum.sub(&self.modulus)
+        } else {
+            sum
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn sub(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        if a >= b {
+            a.sub(b)
+        } else {
+            a.add(&self.modulus).sub(b)
+        }
+    }
+
+    /// Product of two residues, Barrett-reduced.
+    #[allow(clippy::should_implement_trait)]
+    pub fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        self.reduce(&a.mul_karatsuba(b))
+    }
+
+    /// `base^exp mod m` with sliding windows, in Montgomery form when
+    /// available and with Barrett reduction otherwise.
+    pub fn pow(&self, base: &BigUint, exp: &BigUint) -> BigUint {
+        let bits = exp.bits_msb_first();
+        let base = self.reduce(base);
+        match &self.montgomery {
+            Some(mont) => {
+                let x = pow_windowed(
+                    self.redc(mont, &base.mul_karatsuba(&mont.r2).digits),
+                    mont.one.clone(),
+                    &bits,
+                    PowWindow::Sliding,
+                    |a, b| self.redc(mont, &a.mul_karatsuba(b).digits),
+                );
+                self.redc(mont, &x.digits)
+            }
+            None => {
+                let one = self.reduce(&BigUint::one());
+                pow_windowed(base, one, &bits, PowWindow::Sliding, |a, b| self.mul(a, b))
+            }
+        }
+    }
+
+    /// Inverse of `a`; fails with `NotInvertible` if it shares a factor
+    /// with `m`.
+    pub fn inv(&self, a: &BigUint) -> Result<BigUint, Error> {
+        a.mod_inverse(&self.modulus)
+    }
+
+    /// The Montgomery constants; `R` is not invertible modulo an `m` that
+    /// shares a factor with 10.
+    fn mont(&self) -> Result<&Montgomery, Error> {
+        self.montgomery.as_ref().ok_or(Error::NotInvertible)
+    }
+
+    /// Montgomery reduction `t * R^-1 mod m` for `t < m * R`: with
+    /// `q = t * (-m^-1) mod R`, `t + q*m` is divisible by `R`.
+    fn redc(&self, mont: &Montgomery, t: &[u32]) -> BigUint {
+        let q = limbs::mul_low_slices(t, &mont.m_neg_inv, self.k);
+        let sum = limbs::add_slices(t, &limbs::mul_karatsuba_slices(&q, &self.modulus.digits));
+        let mut r = limbs::shift_right_slices(&sum, self.k);
+        if limbs::cmp_slices(&r, &self.modulus.digits) != Ordering::Less {
+            r = limbs::sub_slices(&r, &self.modulus.digits);
+        }
+        BigUint { digits: r.into() }
+    }
+
+    /// `a * R mod m`. Fails with `NotInvertible` unless `has_montgomery()`,
+    /// as do the other Montgomery operations.
+    pub fn to_montgomery(&self, a: &BigUint) -> Result<BigUint, Error> {
+        let mont = self.mont()?;
+        Ok(self.redc(mont, &a.mul_karatsuba(&mont.r2).digits))
+    }
+
+    /// `a * R^-1 mod m`, undoing `to_montgomery`.
+    pub fn from_montgomery(&self, a: &BigUint) -> Result<BigUint, Error> {
+        Ok(self.redc(self.mont()?, &a.digits))
+    }
+
+    /// Product of two values in Montgomery form, staying in Montgomery form.
+    pub fn mont_mul(&self, a: &BigUint, b: &BigUint) -> Result<BigUint, Error> {
+        Ok(self.redc(self.mont()?, &a.mul_karatsuba(b).digits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    fn random_below(m: &BigUint, rng: &mut StdRng) -> BigUint {
+        let digits: String = (0..m.to_string().len() + 5)
+            .map(|_| char::from(b'0' + rng.gen_range(0..10)))
+            .collect();
+        BigUint::from_str(&digits).unwrap().div_rem(m).1
+    }
+
+    fn moduli() -> Vec<BigUint> {
+        [
+            "7",
+            "1000000000",
+            "999999999999999999999999999",
+            "1000000000000000000000000000057",
+            "2305843009213693951",
+            &"31415926535897932384626433832795".repeat(9),
+            &format!("{}1", "27182818284590452353602874713527".repeat(12)),
+        ]
+        .iter()
+        .map(|s| BigUint::from_str(s).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn barrett_matches_division() {
+        let mut rng = StdRng::seed_from_u64(17);
+        for m in moduli() {
+            let ring = ModRing::new(m.clone());
+            for _ in 0..20 {
+                let a = random_below(&m, &mut rng);
+                let b = random_below(&m, &mut rng);
+                let product = a.mul_karatsuba(&b);
+                assert_eq!(ring.mul(&a, &b), product.div_rem(&m).1, "m = {}", m);
+                assert_eq!(ring.add(&a, &b), a.add(&b).div_rem(&m).1);
+                assert_eq!(ring.sub(&a, &b).add(&b).div_rem(&m).1, a);
+            }
+            let max = m.sub(&BigUint::from_str("1").unwrap());
+            assert_eq!(ring.mul(&max, &max), max.mul_karatsuba(&max).div_rem(&m).1);
+            let big = max.mul_karatsuba(&max).mul_karatsuba(&max);
+            assert_eq!(ring.reduce(&big), big.div_rem(&m).1);
+        }
+    }
+
+    #[test]
+    fn montgomery_round_trips_and_multiplies() {
+        let mut rng = StdRng::seed_from_u64(18);
+        for m in moduli() {
+            let ring = ModRing::new(m.clone());
+            let coprime = !m.digits[0].is_multiple_of(2) && !m.digits[0].is_multiple_of(5);
+            assert_eq!(ring.has_montgomery(), coprime, "m = {}", m);
+            if !coprime {
+                assert!(matches!(
+                    ring.to_montgomery(&BigUint::one()),
+                    Err(Error::NotInvertible)
+                ));
+                continue;
+            }
+            for _ in 0..20 {
+                let a = random_below(&m, &mut rng);
+                let b = random_below(&m, &mut rng);
+                let am = ring.to_montgomery(&a).unwrap();
+                let bm = ring.to_montgomery(&b).unwrap();
+                assert_eq!(ring.from_montgomery(&am).unwrap(), a);
+                assert_eq!(
+                    ring.from_montgomery(&ring.mont_mul(&am, &bm).unwrap())
+                        .unwrap(),
+                    ring.mul(&a, &b)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn try_new_refuses_a_zero_modulus() {
+        assert!(matches!(
+            ModRing::try_new(BigUint::zero()),
+            Err(Error::DivisionByZero)
+        ));
+        let ring = ModRing::try_new(BigUint::from(7u64)).unwrap();
+        assert_eq!(ring.reduce(&BigUint::from(30u64)), BigUint::from(2u64));
+    }
+
+    #[test]
+    fn pow_and_inv() {
+        let mut rng = StdRng::seed_from_u64(19);
+        for m in moduli() {
+            let ring = ModRing::new(m.clone());
+            let a = random_below(&m, &mut rng);
+            let e = random_below(&m, &mut rng);
+            assert_eq!(
+                ring.pow(&a, &e),
+                a.mod_pow(&e, &m, PowWindow::Binary),
+                "m = {}",
+                m
+            );
+            assert_eq!(
+                ring.pow(&a, &BigUint::new()),
+                BigUint::from_str("1").unwrap().div_rem(&m).1
+            );
+            if let Ok(inv) = ring.inv(&a) {
+                assert_eq!(ring.mul(&a, &inv).to_string(), "1");
+            }
+        }
+        let ring = ModRing::new(BigUint::from_str("10").unwrap());
+        assert!(ring.inv(&BigUint::from_str("4").unwrap()).is_err());
+        assert_eq!(
+            ring.inv(&BigUint::from_str("3").unwrap()).unwrap(),
+            BigUint::from_str("7").unwrap()
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! The algorithms under comparison, behind one trait so the sweep, the
+//! result files and the chart never name them individually.
+
+use bigmul::limbs;
+
+/// One algorithm in the comparison. Operands and products follow the
+/// `bigmul::limbs` conventions.
+pub trait Multiplier {
+    fn mul(&self, a: &[u32], b: &[u32]) -> Vec<u32>;
+
+    /// Short identifier for CSV/JSON columns and console output.
+    fn name(&self) -> &str;
+
+    /// Legend text for the chart.
+    fn label(&self) -> &str {
+        self.name()
+    }
+
+    /// Quadratic algorithms are left out of `--large` sweeps.
+    fn quadratic(&self) -> bool {
+        false
+    }
+}
+
+/// A `limbs` kernel registered by function pointer.
+struct Kernel {
+    name: &'static str,
+    label: &'static str,
+    quadratic: bool,
+    mul: fn(&[u32], &[u32]) -> Vec<u32>,
+}
+
+impl Multiplier for Kernel {
+    fn mul(&self, a: &[u32], b: &[u32]) -> Vec<u32> {
+        (self.mul)(a, b)
+    }
+
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn label(&self) -> &str {
+        self.label
+    }
+
+    fn quadratic(&self) -> bool {
+        self.quadratic
+    }
+}
+
+/// Every algorithm the harness knows, in chart order. The first entry that
+/// runs in a sweep is the reference the others are checked against.
+pub fn registry() -> Vec<Box<dyn Multiplier>> {
+    #[allow(unused_mut)]
+    let mut multipliers: Vec<Box<dyn Multiplier>> = vec![
+        Box::new(Kernel {
+            name: "direct",
+            label: "Direct Multiplication",
+            quadratic: true,
+            mul: limbs::mul_direct_slices,
+        }),
+        Box::new(Kernel {
+            name: "dc",
+            label: "Simple Divide & Conquer",
+            quadratic: true,
+            mul: limbs::mul_dc_slices,
+        }),
+        Box::new(Kernel {
+            name: "kara",
+            label: "Karatsuba",
+            quadratic: false,
+            mul: limbs::mul_karatsuba_slices,
+        }),
+        Box::new(Kernel {
+            name: "kara_sub",
+            label: "Karatsuba (subtractive)",
+            quadratic: false,
+            mul: limbs::mul_karatsuba_sub_slices,
+        }),
+        Box::new(Kernel {
+            name: "kara_par",
+            label: "Karatsuba (parallel)",
+            quadratic: false,
+            mul: |a, b| limbs::mul_karatsuba_par_slices(a, b, available_threads()),
+        }),
+    ];
+    #[cfg(feature = "cuda")]
+    multipliers.extend(crate::gpu::multiplier());
+    #[cfg(any(feature = "ibig", feature = "malachite"))]
+    multipliers.extend(crate::foreign::multipliers());
+    multipliers
+}
+
+/// A multithreaded kernel, run with an explicit thread count by `--scaling`.
+pub struct Parallel {
+    pub name: &'static str,
+    pub label: &'static str,
+    pub mul: fn(&[u32], &[u32], usize) -> Vec<u32>,
+}
+
+/// The algorithms whose thread count can be varied, in chart order.
+pub fn parallel_registry() -> Vec<Parallel> {
+    vec![Parallel {
+        name: "kara_par",
+        label: "Karatsuba (parallel)",
+        mul: limbs::mul_karatsuba_par_slices,
+    }]
+}
+
+/// Threads the machine can run at once, falling back to one.
+pub fn available_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registered_kernels_agree() {
+        let a: Vec<u32> = (1..=100).map(|i| i * 9_999_991 % 1_000_000_000).collect();
+        let b: Vec<u32> = (1..=70).map(|i| i * 7_777_777 % 1_000_000_000).collect();
+        let multipliers = registry();
+        let expected = multipliers[0].mul(&a, &b);
+        for m in &multipliers {
+            assert_eq!(m.mul(&a, &b), expected, "{}", m.name());
+        }
+        let mut names: Vec<&str> = multipliers.iter().map(|m| m.name()).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), multipliers.len(), "names must be unique");
+    }
+}
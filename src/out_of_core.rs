@@ -0,0 +1,81 @@
+//! `bigmul mul-disk`: multiplies two decimal files through `bigmul::disk`,
+//! for operands and products too large for memory. The limb files live
+//! next to the output as `<OUT>.a.limbs`, `<OUT>.b.limbs` and
+//! `<OUT>.limbs` and are removed when the product is written, or when the
+//! run fails or is interrupted.
+
+#![cfg_attr(not(unix), allow(dead_code, unused_imports))]
+
+use crate::interrupt;
+#[cfg(unix)]
+use bigmul::disk;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Limbs per block by default: 4 MiB pieces, about 9.4 million digits.
+pub const DEFAULT_BLOCK: usize = 1 << 20;
+
+/// Scratch files removed on drop, however the run ends.
+struct Scratch(Vec<PathBuf>);
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        for path in &self.0 {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Runs the multiplication and prints the time spent in each phase. Returns
+/// `Ok(false)` if interrupted.
+#[cfg(unix)]
+pub fn run(a: &Path, b: &Path, out: &Path, block: usize) -> Result<bool, String> {
+    let scratch = |ext: &str| {
+        let mut name = out.as_os_str().to_owned();
+        name.push(ext);
+        PathBuf::from(name)
+    };
+    let files = Scratch(vec![
+        scratch(".a.limbs"),
+        scratch(".b.limbs"),
+        scratch(".limbs"),
+    ]);
+    let [la, lb, lp] = [&files.0[0], &files.0[1], &files.0[2]];
+
+    let start = Instant::now();
+    let limbs_a = disk::decimal_to_limbs(a, la).map_err(|e| e.to_string())?;
+    let limbs_b = disk::decimal_to_limbs(b, lb).map_err(|e| e.to_string())?;
+    let convert = start.elapsed();
+    println!(
+        "Operands: {} and {} limbs, in blocks of {} limbs",
+        limbs_a, limbs_b, block
+    );
+
+    let start = Instant::now();
+    let tty = std::io::stderr().is_terminal();
+    let finished = disk::mul_files(la, lb, lp, block, |done, total| {
+        if tty {
+            eprint!("\rBlock products: {}/{}", done, total);
+        }
+        !interrupt::interrupted()
+    })
+    .map_err(|e| e.to_string())?;
+    if tty {
+        eprintln!();
+    }
+    if !finished {
+        println!("interrupted");
+        return Ok(false);
+    }
+    let multiply = start.elapsed();
+
+    let start = Instant::now();
+    let digits = disk::limbs_to_decimal(lp, out).map_err(|e| e.to_string())?;
+    let write = start.elapsed();
+    println!("Product of {} digits written to {}", digits, out.display());
+    println!("conversion:     {:.6} s", convert.as_secs_f64());
+    println!("block products: {:.6} s", multiply.as_secs_f64());
+    println!("output:         {:.6} s", write.as_secs_f64());
+    Ok(true)
+}
@@ -0,0 +1,138 @@
+//! `--wrapper-overhead`: what the owned-value API costs on top of the slice
+//! kernels at small sizes, where the multiplication itself is cheapest. The
+//! same Karatsuba product is timed through `limbs`, `BigUint`, `BigInt`, and
+//! `BigInt` with both operands cloned first, as code passing values by value
+//! would.
+
+use crate::interrupt;
+use crate::primitives::{random_limbs, seconds_per_call};
+use bigmul::{BigInt, BigUint, Sign, limbs};
+use plotters::prelude::*;
+use std::fmt::Write as _;
+use std::hint::black_box;
+
+/// Operand sizes in limbs: powers of two from 1 to 256.
+const SIZES: [usize; 9] = [1, 2, 4, 8, 16, 32, 64, 128, 256];
+const LAYERS: [&str; 4] = ["slices", "biguint", "bigint", "bigint_clone"];
+
+/// Seconds per product through each layer.
+fn measure(n: usize) -> [f64; 4] {
+    let a = random_limbs(n);
+    let b = random_limbs(n);
+    let ua = BigUint::from_limbs(a.clone()).unwrap();
+    let ub = BigUint::from_limbs(b.clone()).unwrap();
+    let ia = BigInt::from_biguint(Sign::Minus, ua.clone());
+    let ib = BigInt::from_biguint(Sign::Plus, ub.clone());
+    [
+        seconds_per_call(|| {
+            black_box(limbs::mul_karatsuba_slices(black_box(&a), black_box(&b)));
+        }),
+        seconds_per_call(|| {
+            black_box(black_box(&ua).mul_karatsuba(black_box(&ub)));
+        }),
+        seconds_per_call(|| {
+            black_box(black_box(&ia).mul_karatsuba(black_box(&ib)));
+        }),
+        seconds_per_call(|| {
+            let (x, y) = (black_box(&ia).clone(), black_box(&ib).clone());
+            black_box(x.mul_karatsuba(&y));
+        }),
+    ]
+}
+
+/// Runs the sweep, printing each layer's time and overhead over the slice
+/// kernel, and writes `./assets/wrapper_overhead.csv` and
+/// `./assets/wrapper_overhead.png`. Returns `false` if interrupted.
+pub fn run() -> bool {
+    let mut rows: Vec<(usize, [f64; 4])> = Vec::new();
+    for &n in &SIZES {
+        if interrupt::interrupted() {
+            break;
+        }
+        let times = measure(n);
+        let mut line = format!("limbs={}, slices={:.1} ns", n, times[0] * 1e9);
+        for (name, t) in LAYERS.iter().zip(&times).skip(1) {
+            write!(line, ", {}={:+.1}%", name, overhead(*t, times[0])).unwrap();
+        }
+        println!("{}", line);
+        rows.push((n, times));
+    }
+    if rows.is_empty() {
+        return false;
+    }
+
+    let mut out = String::from("limbs");
+    for name in LAYERS {
+        write!(out, ",{}_seconds", name).unwrap();
+    }
+    for name in &LAYERS[1..] {
+        write!(out, ",{}_overhead_pct", name).unwrap();
+    }
+    out.push('\n');
+    for (n, times) in &rows {
+        write!(out, "{}", n).unwrap();
+        for t in times {
+            write!(out, ",{:.12}", t).unwrap();
+        }
+        for t in &times[1..] {
+            write!(out, ",{:.2}", overhead(*t, times[0])).unwrap();
+        }
+        out.push('\n');
+    }
+    std::fs::create_dir_all("./assets").expect("Failed to create ./assets directory");
+    std::fs::write("./assets/wrapper_overhead.csv", out).expect("Failed to write CSV results");
+    plot("./assets/wrapper_overhead.png", &rows);
+    println!("Results saved to ./assets/wrapper_overhead.csv and ./assets/wrapper_overhead.png");
+    rows.len() == SIZES.len()
+}
+
+/// Extra time of `t` over `base`, in percent.
+fn overhead(t: f64, base: f64) -> f64 {
+    (t / base - 1.0) * 100.0
+}
+
+/// Nanoseconds per product against size, both on log scales.
+fn plot(path: &str, rows: &[(usize, [f64; 4])]) {
+    let root = BitMapBackend::new(path, (800, 600)).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+    let series = |k: usize| -> Vec<(f32, f32)> {
+        rows.iter()
+            .map(|(n, times)| ((*n as f64).log10() as f32, (times[k] * 1e9).log10() as f32))
+            .collect()
+    };
+    let ys = || (0..LAYERS.len()).flat_map(series).map(|(_, y)| y);
+    let (y_min, y_max) = (ys().fold(f32::MAX, f32::min), ys().fold(f32::MIN, f32::max));
+    let x_max = (rows.last().unwrap().0 as f64).log10() as f32 + 0.01;
+    let mut chart = ChartBuilder::on(&root)
+        .caption("API Layer Overhead", ("sans-serif", 40).into_font())
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(0f32..x_max, y_min - 0.1..y_max + 0.1)
+        .unwrap();
+    chart
+        .configure_mesh()
+        .x_desc("Operand Size (limbs, log scale)")
+        .y_desc("Time per Product (ns, log scale)")
+        .x_label_formatter(&|x| format!("{:.0}", 10f64.powf(*x as f64)))
+        .y_label_formatter(&|y| format!("{:.0}", 10f64.powf(*y as f64)))
+        .draw()
+        .unwrap();
+    for (k, name) in LAYERS.iter().enumerate() {
+        let color = Palette99::pick(k).to_rgba();
+        chart
+            .draw_series(LineSeries::new(series(k), color))
+            .unwrap()
+            .label(*name)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperLeft)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .unwrap();
+
+    root.present().unwrap();
+}
@@ -0,0 +1,109 @@
+//! `--panels`: the sweep's times next to what else each product costs,
+//! peak heap and limb operations, as three panels of one figure over the
+//! same sizes. Memory and operations are measured in one extra, untimed
+//! product per algorithm and point on the point's first pair, since both
+//! are the same on every run of the same operands.
+
+use crate::cli::Config;
+use crate::multiplier::Multiplier;
+use crate::operands::Point;
+use crate::{Measurements, heap, interrupt};
+use bigmul::instrument;
+use bigmul::report::{Chart, Figure, Style};
+use std::fmt::Write as _;
+use std::hint::black_box;
+
+/// Peak heap bytes and limb operations of one product.
+#[derive(Clone, Copy)]
+struct Footprint {
+    peak_bytes: usize,
+    limb_ops: u64,
+}
+
+fn footprint(m: &dyn Multiplier, a: &[u32], b: &[u32]) -> Footprint {
+    instrument::reset();
+    let peak_bytes = heap::peak_bytes(|| {
+        black_box(m.mul(black_box(a), black_box(b)));
+    });
+    Footprint {
+        peak_bytes,
+        limb_ops: instrument::limb_ops(),
+    }
+}
+
+/// Measures every swept point's footprints and writes them to
+/// `./assets/panels.csv`, and the figure to `./assets/panels.png`. Returns
+/// `false` if interrupted before every point was measured.
+pub fn run(
+    config: &Config,
+    multipliers: &[Box<dyn Multiplier>],
+    points: &[Point],
+    results: &Measurements,
+) -> bool {
+    // Per swept point, per multiplier.
+    let mut footprints: Vec<Vec<Footprint>> = Vec::new();
+    for point in &points[..results.ns.len()] {
+        if interrupt::interrupted() {
+            break;
+        }
+        let (a, b) = &point.pairs[0];
+        footprints.push(
+            multipliers
+                .iter()
+                .map(|m| footprint(m.as_ref(), a.as_limbs(), b.as_limbs()))
+                .collect(),
+        );
+    }
+    if footprints.is_empty() {
+        return false;
+    }
+
+    let mut out = String::from(config.x_axis.name());
+    for m in multipliers {
+        write!(out, ",{}_peak_bytes,{}_limb_ops", m.name(), m.name()).unwrap();
+    }
+    out.push('\n');
+    for (n, row) in results.ns.iter().zip(&footprints) {
+        write!(out, "{}", n).unwrap();
+        for f in row {
+            write!(out, ",{},{}", f.peak_bytes, f.limb_ops).unwrap();
+        }
+        out.push('\n');
+    }
+    std::fs::write("./assets/panels.csv", out).expect("Failed to write panel data");
+
+    let x_desc = format!("Input Size (number of {})", config.x_axis.name());
+    let panel = |title: &str, y_desc: &str, value: &dyn Fn(usize, usize) -> f64| {
+        multipliers.iter().enumerate().fold(
+            Chart::new()
+                .title(title)
+                .x_desc(x_desc.clone())
+                .y_desc(y_desc),
+            |chart, (k, m)| {
+                let points = (0..footprints.len()).map(|i| (results.ns[i] as f64, value(i, k)));
+                chart.series_styled(m.label(), points, Style::of(m.name()))
+            },
+        )
+    };
+    let figure = Figure::new()
+        .title(crate::caption(config))
+        .panel(panel("Time", crate::y_desc(config), &|i, k| {
+            results.avgs[k][i]
+        }))
+        .panel(panel("Peak Heap", "Peak Heap (MiB)", &|i, k| {
+            footprints[i][k].peak_bytes as f64 / (1 << 20) as f64
+        }))
+        .panel(panel(
+            "Limb Operations",
+            "Limb Operations (millions)",
+            &|i, k| footprints[i][k].limb_ops as f64 / 1e6,
+        ));
+    match figure.draw_png("./assets/panels.png".as_ref()) {
+        Ok(()) if config.reports() => {
+            println!("Panels saved to ./assets/panels.png, their data to ./assets/panels.csv")
+        }
+        Ok(()) => {}
+        Err(msg) => eprintln!("error: {}", msg),
+    }
+    footprints.len() == results.ns.len()
+}
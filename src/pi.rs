@@ -0,0 +1,234 @@
+//! `compute-pi`: digits of pi by the Chudnovsky series with binary
+//! splitting, timed phase by phase over a doubling sweep of digit counts.
+//!
+//! The series phase is dominated by multiplying the large partial products
+//! near the root of the splitting tree. The square root and final division
+//! run on schoolbook long division and grow quadratically, so at large digit
+//! counts they overtake it.
+
+use crate::interrupt;
+use bigmul::{BigInt, BigUint};
+use plotters::prelude::*;
+use std::fmt::Write as _;
+use std::time::Instant;
+
+/// Decimal digits gained per series term, `log10(640320^3 / 1728)`.
+const DIGITS_PER_TERM: f64 = 14.181647462725477;
+const GUARD_DIGITS: usize = 10;
+const MIN_DIGITS: usize = 1000;
+
+fn int(v: u64) -> BigInt {
+    BigInt::from(BigUint::from(v))
+}
+
+/// Returns `(P(a, b), Q(a, b), T(a, b))` for the Chudnovsky terms
+/// `a..b`.
+fn split(a: u64, b: u64) -> (BigInt, BigInt, BigInt) {
+    if b - a == 1 {
+        let (p, q) = if a == 0 {
+            (int(1), int(1))
+        } else {
+            // 640320^3 / 24 = 10939058860032000
+            let p = int(6 * a - 5)
+                .mul_karatsuba(&int(2 * a - 1))
+                .mul_karatsuba(&int(6 * a - 1));
+            let q = int(a * a)
+                .mul_karatsuba(&int(a))
+                .mul_karatsuba(&int(10939058860032000));
+            (p, q)
+        };
+        let t = p.mul_karatsuba(&int(13591409).add(&int(545140134).mul_karatsuba(&int(a))));
+        return (p.clone(), q, if a % 2 == 1 { -t } else { t });
+    }
+    let m = (a + b) / 2;
+    let (p_am, q_am, t_am) = split(a, m);
+    let (p_mb, q_mb, t_mb) = split(m, b);
+    let t = q_mb.mul_karatsuba(&t_am).add(&p_am.mul_karatsuba(&t_mb));
+    (p_am.mul_karatsuba(&p_mb), q_am.mul_karatsuba(&q_mb), t)
+}
+
+/// Seconds spent in each phase of one computation.
+struct Phases {
+    series: f64,
+    sqrt: f64,
+    divide: f64,
+}
+
+/// Returns pi to `digits` decimal places as `"31415..."` (no point).
+fn pi_digits(digits: usize) -> (String, Phases) {
+    let precision = digits + GUARD_DIGITS;
+    let terms = (precision as f64 / DIGITS_PER_TERM) as u64 + 2;
+
+    let start = Instant::now();
+    let (_, q, t) = split(0, terms);
+    let series = start.elapsed().as_secs_f64();
+
+    // pi = 426880 * sqrt(10005) * Q / T, scaled by 10^precision.
+    let start = Instant::now();
+    let scaled = BigUint::from_str(&format!("10005{}", "0".repeat(2 * precision)))
+        .expect("a power of ten times 10005 is decimal");
+    let root = scaled.sqrt();
+    let sqrt = start.elapsed().as_secs_f64();
+
+    let start = Instant::now();
+    let numerator = root
+        .mul_karatsuba(&BigUint::from(426_880u64))
+        .mul_karatsuba(q.magnitude());
+    let (pi, _) = numerator.div_rem(t.magnitude());
+    let divide = start.elapsed().as_secs_f64();
+
+    let mut s = pi.to_string();
+    s.truncate(digits + 1);
+    (
+        s,
+        Phases {
+            series,
+            sqrt,
+            divide,
+        },
+    )
+}
+
+/// Digit counts from `MIN_DIGITS` doubling up to `digits`, always ending at
+/// `digits`.
+fn sweep_digits(digits: usize) -> Vec<usize> {
+    let mut ds: Vec<usize> = std::iter::successors(Some(MIN_DIGITS), |&d| Some(d * 2))
+        .take_while(|&d| d < digits)
+        .collect();
+    ds.push(digits);
+    ds
+}
+
+/// Runs the sweep up to `digits`, printing per-phase times, and writes
+/// `./assets/compute_pi.csv` and `./assets/compute_pi.png`. Returns `false`
+/// if interrupted before the end.
+pub fn run(digits: usize) -> bool {
+    interrupt::install();
+    let planned = sweep_digits(digits);
+    let mut rows: Vec<(usize, Phases)> = Vec::new();
+    let mut last = String::new();
+    for &d in &planned {
+        if interrupt::interrupted() {
+            break;
+        }
+        let (s, phases) = pi_digits(d);
+        println!(
+            "digits={}, series={:.6}, sqrt={:.6}, divide={:.6}, total={:.6}",
+            d,
+            phases.series,
+            phases.sqrt,
+            phases.divide,
+            phases.series + phases.sqrt + phases.divide
+        );
+        rows.push((d, phases));
+        last = s;
+    }
+    if !last.is_empty() {
+        let tail = &last[last.len().saturating_sub(10)..];
+        println!(
+            "pi = {}.{}...{}",
+            &last[..1],
+            &last[1..51.min(last.len())],
+            tail
+        );
+    }
+
+    let mut out = String::from("digits,series,sqrt,divide,total\n");
+    for (d, p) in &rows {
+        writeln!(
+            out,
+            "{},{:.9},{:.9},{:.9},{:.9}",
+            d,
+            p.series,
+            p.sqrt,
+            p.divide,
+            p.series + p.sqrt + p.divide
+        )
+        .unwrap();
+    }
+    std::fs::create_dir_all("./assets").expect("Failed to create ./assets directory");
+    std::fs::write("./assets/compute_pi.csv", out).expect("Failed to write CSV results");
+    println!("Results saved to ./assets/compute_pi.csv");
+    if !rows.is_empty() {
+        plot("./assets/compute_pi.png", &rows);
+        println!("Graph saved to ./assets/compute_pi.png");
+    }
+    rows.len() == planned.len()
+}
+
+fn plot(path: &str, rows: &[(usize, Phases)]) {
+    let root = BitMapBackend::new(path, (800, 600)).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+    let total: fn(&Phases) -> f64 = |p| p.series + p.sqrt + p.divide;
+    let max_time = rows.iter().map(|(_, p)| total(p)).fold(f64::MIN, f64::max);
+    let max_digits = rows.last().unwrap().0;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(
+            "Computing Pi (Chudnovsky, binary splitting)",
+            ("sans-serif", 36).into_font(),
+        )
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d(
+            0f32..max_digits as f32 * 1.02,
+            0f32..(max_time * 1.1) as f32,
+        )
+        .unwrap();
+    chart
+        .configure_mesh()
+        .x_desc("Decimal Digits")
+        .y_desc("Execution Time (seconds)")
+        .draw()
+        .unwrap();
+
+    let column = |f: fn(&Phases) -> f64| rows.iter().map(|(_, p)| f(p)).collect::<Vec<f64>>();
+    let series = [
+        ("Total", BLACK, column(total)),
+        ("Binary splitting", BLUE, column(|p| p.series)),
+        ("Square root", GREEN, column(|p| p.sqrt)),
+        ("Final division", RED, column(|p| p.divide)),
+    ];
+    for (label, color, times) in series {
+        chart
+            .draw_series(LineSeries::new(
+                rows.iter()
+                    .zip(times)
+                    .map(|((d, _), t)| (*d as f32, t as f32)),
+                color,
+            ))
+            .unwrap()
+            .label(label)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .unwrap();
+
+    root.present().unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_hundred_digits() {
+        let (s, _) = pi_digits(100);
+        assert_eq!(
+            s,
+            "31415926535897932384626433832795028841971693993751\
+             058209749445923078164062862089986280348253421170679"
+        );
+    }
+
+    #[test]
+    fn sweep_ends_at_request() {
+        assert_eq!(sweep_digits(5000), [1000, 2000, 4000, 5000]);
+        assert_eq!(sweep_digits(500), [500]);
+    }
+}
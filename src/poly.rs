@@ -0,0 +1,295 @@
+//! Polynomials with `BigInt` coefficients, multiplied with the same kernels
+//! as the integers. `mul_karatsuba` runs Karatsuba's three-product
+//! recursion over coefficient vectors, and `mul_kronecker` (what `mul`
+//! uses) substitutes a large power of the base for the variable: each
+//! polynomial becomes one integer with its coefficients in fixed-width limb
+//! slots, one integer product does all the work, and the product's
+//! coefficients are read back slot by slot. Slots are wide enough that no
+//! coefficient spills into its neighbor, and signed coefficients are read
+//! as balanced digits in `(-X/2, X/2)`.
+
+use crate::bigint::{BigInt, Sign};
+use crate::biguint::BigUint;
+use crate::limbs::BASE;
+
+/// Coefficients at or below which `mul_karatsuba` multiplies directly.
+const KARATSUBA_THRESHOLD: usize = 16;
+
+/// A polynomial `c[0] + c[1] x + c[2] x^2 + ...` with no trailing zero
+/// coefficients; the zero polynomial has none at all.
+///
+/// ```
+/// use bigmul::Poly;
+///
+/// let p = Poly::from_u64(&[1, 1]); // 1 + x
+/// let square = p.mul(&p);
+/// assert_eq!(square, Poly::from_u64(&[1, 2, 1]));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Poly {
+    coeffs: Vec<BigInt>,
+}
+
+impl Poly {
+    /// The polynomial with `coeffs`, lowest degree first.
+    pub fn new(mut coeffs: Vec<BigInt>) -> Self {
+        while coeffs.last().is_some_and(BigInt::is_zero) {
+            coeffs.pop();
+        }
+        Poly { coeffs }
+    }
+
+    pub fn from_u64(coeffs: &[u64]) -> Self {
+        Poly::new(
+            coeffs
+                .iter()
+                .map(|&c| {
+                    let limbs = vec![
+                        (c % BASE) as u32,
+                        (c / BASE % BASE) as u32,
+                        (c / BASE / BASE) as u32,
+                    ];
+                    BigInt::from_kernel(Sign::Plus, limbs)
+                })
+                .collect(),
+        )
+    }
+
+    pub fn zero() -> Self {
+        Poly { coeffs: Vec::new() }
+    }
+
+    pub fn coeffs(&self) -> &[BigInt] {
+        &self.coeffs
+    }
+
+    /// `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.coeffs.len().checked_sub(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coeffs.is_empty()
+    }
+
+    /// The value at `x`, by Horner's rule.
+    pub fn eval(&self, x: &BigInt) -> BigInt {
+        self.coeffs
+            .iter()
+            .rev()
+            .fold(BigInt::zero(), |acc, c| acc.mul(x).add(c))
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(&self, other: &Poly) -> Poly {
+        Poly::new(add_coeffs(&self.coeffs, &other.coeffs))
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn sub(&self, other: &Poly) -> Poly {
+        let negated: Vec<BigInt> = other.coeffs.iter().map(|c| -c).collect();
+        Poly::new(add_coeffs(&self.coeffs, &negated))
+    }
+
+    /// Multiplies by Kronecker substitution.
+    #[allow(clippy::should_implement_trait)]
+    pub fn mul(&self, other: &Poly) -> Poly {
+        self.mul_kronecker(other)
+    }
+
+    /// Every coefficient of one by every coefficient of the other.
+    pub fn mul_schoolbook(&self, other: &Poly) -> Poly {
+        if self.is_zero() || other.is_zero() {
+            return Poly::zero();
+        }
+        Poly::new(schoolbook(&self.coeffs, &other.coeffs))
+    }
+
+    /// Karatsuba over coefficients: three half-size polynomial products per
+    /// level instead of four.
+    pub fn mul_karatsuba(&self, other: &Poly) -> Poly {
+        if self.is_zero() || other.is_zero() {
+            return Poly::zero();
+        }
+        Poly::new(karatsuba(&self.coeffs, &other.coeffs))
+    }
+
+    /// One integer product of the two polynomials evaluated at
+    /// `X = BASE^w`, with `w` limbs per coefficient slot.
+    pub fn mul_kronecker(&self, other: &Poly) -> Poly {
+        if self.is_zero() || other.is_zero() {
+            return Poly::zero();
+        }
+        let widest = |p: &Poly| {
+            p.coeffs
+                .iter()
+                .map(|c| c.magnitude().as_limbs().len())
+                .max()
+                .unwrap_or(1)
+        };
+        // |c_k| < n BASE^(p + q) <= BASE^w / 2 with 2n <= BASE^r.
+        let terms = 2 * self.coeffs.len().min(other.coeffs.len()) as u128;
+        let mut r = 1;
+        while (BASE as u128).pow(r) < terms {
+            r += 1;
+        }
+        let w = widest(self) + widest(other) + r as usize;
+        let product = pack(&self.coeffs, w).mul(&pack(&other.coeffs, w));
+        Poly::new(unpack(
+            product,
+            w,
+            self.coeffs.len() + other.coeffs.len() - 1,
+        ))
+    }
+}
+
+fn add_coeffs(a: &[BigInt], b: &[BigInt]) -> Vec<BigInt> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut sum = long.to_vec();
+    add_at(&mut sum, 0, short);
+    sum
+}
+
+/// Adds `p` into `out` from index `offset`. Coefficients of `p` past the end
+/// of `out` are zero in every caller.
+fn add_at(out: &mut [BigInt], offset: usize, p: &[BigInt]) {
+    for (o, c) in out[offset..].iter_mut().zip(p) {
+        *o = o.add(c);
+    }
+}
+
+fn schoolbook(a: &[BigInt], b: &[BigInt]) -> Vec<BigInt> {
+    let mut out = vec![BigInt::zero(); a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (o, y) in out[i..].iter_mut().zip(b) {
+            *o = o.add(&x.mul(y));
+        }
+    }
+    out
+}
+
+/// The `a.len() + b.len() - 1` coefficients of `a * b`, both non-empty.
+fn karatsuba(a: &[BigInt], b: &[BigInt]) -> Vec<BigInt> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    if short.len() <= KARATSUBA_THRESHOLD {
+        return schoolbook(long, short);
+    }
+    let mut out = vec![BigInt::zero(); a.len() + b.len() - 1];
+    if 2 * short.len() <= long.len() {
+        // Pieces of the longer one as long as the shorter, as in the
+        // integer kernels' unbalanced split.
+        for (i, piece) in long.chunks(short.len()).enumerate() {
+            add_at(&mut out, i * short.len(), &karatsuba(piece, short));
+        }
+        return out;
+    }
+    let m = long.len() / 2;
+    let (a0, a1) = long.split_at(m);
+    let (b0, b1) = short.split_at(m);
+    let p = karatsuba(a0, b0);
+    let q = karatsuba(a1, b1);
+    let u = karatsuba(&add_coeffs(a0, a1), &add_coeffs(b0, b1));
+    // u - p - q; `u` is at least as long as either.
+    let mut mid = u;
+    for part in [&p, &q] {
+        for (d, c) in mid.iter_mut().zip(part) {
+            *d = d.sub(c);
+        }
+    }
+    add_at(&mut out, 0, &p);
+    add_at(&mut out, 2 * m, &q);
+    add_at(&mut out, m, &mid);
+    out
+}
+
+/// `sum c_i X^i` for `X = BASE^w`; every `|c_i| < BASE^w`.
+fn pack(coeffs: &[BigInt], w: usize) -> BigInt {
+    let mut positive = vec![0u32; coeffs.len() * w];
+    let mut negative = vec![0u32; coeffs.len() * w];
+    for (i, c) in coeffs.iter().enumerate() {
+        let slots = if c.sign() == Sign::Minus {
+            &mut negative
+        } else {
+            &mut positive
+        };
+        let limbs = c.magnitude().as_limbs();
+        slots[i * w..i * w + limbs.len()].copy_from_slice(limbs);
+    }
+    let part = |limbs| BigInt::from_kernel(Sign::Plus, limbs);
+    part(positive).sub(&part(negative))
+}
+
+/// The first `count` balanced base-`X` digits of `value`, `X = BASE^w`.
+fn unpack(value: BigInt, w: usize, count: usize) -> Vec<BigInt> {
+    let (sign, magnitude) = value.into_parts();
+    let limbs = magnitude.as_limbs();
+    let x = BigUint::one().shift_left(w);
+    let mut carry = false;
+    let mut coeffs = Vec::with_capacity(count);
+    for k in 0..count {
+        let slot = limbs
+            .get(k * w..limbs.len().min((k + 1) * w))
+            .unwrap_or(&[]);
+        let mut v = BigUint::from_kernel(slot.to_vec());
+        if carry {
+            v = v.add(&BigUint::one());
+        }
+        // v >= X / 2: the digit is v - X and one carries into the next slot.
+        let digits = v.as_limbs();
+        carry = digits.len() > w || (digits.len() == w && digits[w - 1] as u64 >= BASE / 2);
+        let digit = if carry {
+            BigInt::from_biguint(-sign, x.sub(&v))
+        } else {
+            BigInt::from_biguint(sign, v)
+        };
+        coeffs.push(digit);
+    }
+    debug_assert!(!carry, "unpack: carry out of the last coefficient");
+    coeffs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random_bits;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    fn random_poly(len: usize, bits: usize, rng: &mut StdRng) -> Poly {
+        Poly::new(
+            (0..len)
+                .map(|_| {
+                    let sign = [Sign::Minus, Sign::Plus][rng.gen_range(0..2)];
+                    let bits = rng.gen_range(0..=bits);
+                    BigInt::from_biguint(sign, random_bits(bits, rng))
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn products_agree_and_evaluate() {
+        let mut rng = StdRng::seed_from_u64(84);
+        for (la, lb, bits) in [
+            (1, 1, 10),
+            (5, 3, 64),
+            (40, 37, 200),
+            (90, 20, 40),
+            (33, 70, 1),
+        ] {
+            let a = random_poly(la, bits, &mut rng);
+            let b = random_poly(lb, bits, &mut rng);
+            let expected = a.mul_schoolbook(&b);
+            assert_eq!(a.mul_karatsuba(&b), expected, "{} x {}", la, lb);
+            assert_eq!(a.mul_kronecker(&b), expected, "{} x {}", la, lb);
+            let x = BigInt::from_str("-1234567").unwrap();
+            assert_eq!(expected.eval(&x), a.eval(&x).mul(&b.eval(&x)));
+        }
+        // Every coefficient at the slot's limit: all carries, all borrows.
+        let top = Poly::from_u64(&[u64::MAX; 9]);
+        let low = top.sub(&top.add(&top));
+        assert_eq!(top.mul(&low), top.mul_schoolbook(&low));
+        assert!(top.mul(&Poly::zero()).is_zero());
+        assert_eq!(Poly::from_u64(&[3, 0, 0]).degree(), Some(0));
+        assert_eq!(Poly::zero().degree(), None);
+    }
+}
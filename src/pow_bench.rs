@@ -0,0 +1,148 @@
+//! Modular arithmetic benchmarks.
+//!
+//! `--mod-pow` times each exponent window strategy at a fixed modulus size
+//! over a range of exponent sizes. `--mod-mul` measures modular
+//! multiplication throughput for long division, Barrett and Montgomery
+//! reduction over a range of modulus sizes.
+
+use crate::inputs;
+use crate::interrupt;
+use bigmul::{BigUint, ModRing, PowWindow};
+use std::fmt::Write as _;
+use std::time::Instant;
+
+const MODULUS_BITS: usize = 2048;
+const EXPONENT_BITS: [usize; 8] = [16, 32, 64, 128, 256, 512, 1024, 2048];
+const NUM_INSTANCES: usize = 3;
+const MOD_MUL_BITS: [usize; 6] = [256, 512, 1024, 2048, 4096, 8192];
+const MOD_MUL_METHODS: [&str; 3] = ["division", "barrett", "montgomery"];
+
+/// Runs the sweep, prints one line per exponent size and writes
+/// `./assets/mod_pow.csv`. Returns `false` if interrupted before the end.
+pub fn run() -> bool {
+    let mut rows: Vec<(usize, Vec<f64>)> = Vec::new();
+    for &bits in &EXPONENT_BITS {
+        if interrupt::interrupted() {
+            break;
+        }
+        let mut totals = vec![0.0; PowWindow::ALL.len()];
+        for _ in 0..NUM_INSTANCES {
+            let modulus = inputs::random_bits(MODULUS_BITS);
+            let base = inputs::random_bits(MODULUS_BITS - 1);
+            let exp = inputs::random_bits(bits);
+            let mut first: Option<BigUint> = None;
+            for (total, window) in totals.iter_mut().zip(PowWindow::ALL) {
+                let start = Instant::now();
+                let r = base.mod_pow(&exp, &modulus, window);
+                *total += start.elapsed().as_secs_f64();
+                match &first {
+                    Some(expected) => assert_eq!(&r, expected),
+                    None => first = Some(r),
+                }
+            }
+        }
+        let avgs: Vec<f64> = totals.iter().map(|t| t / NUM_INSTANCES as f64).collect();
+        let mut line = format!("exp_bits={}", bits);
+        for (window, avg) in PowWindow::ALL.iter().zip(&avgs) {
+            write!(line, ", {}={:.6}", window.name(), avg).unwrap();
+        }
+        println!("{}", line);
+        rows.push((bits, avgs));
+    }
+
+    let mut out = String::from("exp_bits");
+    for window in PowWindow::ALL {
+        write!(out, ",{}", window.name()).unwrap();
+    }
+    out.push('\n');
+    for (bits, avgs) in &rows {
+        write!(out, "{}", bits).unwrap();
+        for avg in avgs {
+            write!(out, ",{:.9}", avg).unwrap();
+        }
+        out.push('\n');
+    }
+    std::fs::create_dir_all("./assets").expect("Failed to create ./assets directory");
+    std::fs::write("./assets/mod_pow.csv", out).expect("Failed to write CSV results");
+    println!("Results saved to ./assets/mod_pow.csv");
+    rows.len() == EXPONENT_BITS.len()
+}
+
+/// Chained multiplications per modulus size: enough for a stable rate at
+/// small sizes without dragging out the large ones.
+fn mod_mul_count(bits: usize) -> usize {
+    (2_000_000 / bits).max(100)
+}
+
+/// Runs the `--mod-mul` sweep, printing modular multiplications per second
+/// for each method and writing `./assets/mod_mul.csv`. Returns `false` if
+/// interrupted before the end.
+pub fn run_mod_mul() -> bool {
+    let one = BigUint::one();
+    let mut rows: Vec<(usize, [f64; 3])> = Vec::new();
+    for &bits in &MOD_MUL_BITS {
+        if interrupt::interrupted() {
+            break;
+        }
+        // Montgomery needs a modulus coprime to the limb base 10^9.
+        let mut modulus = inputs::random_bits(bits);
+        while modulus.gcd(&BigUint::from(10u64)) != one {
+            modulus = modulus.add(&one);
+        }
+        let ring = ModRing::new(modulus.clone());
+        let x = inputs::random_bits(bits - 1);
+        let y = inputs::random_bits(bits - 1);
+        let count = mod_mul_count(bits);
+
+        let start = Instant::now();
+        let mut by_division = x.clone();
+        for _ in 0..count {
+            by_division = by_division.mul_karatsuba(&y).div_rem(&modulus).1;
+        }
+        let t_division = start.elapsed().as_secs_f64();
+
+        let start = Instant::now();
+        let mut by_barrett = x.clone();
+        for _ in 0..count {
+            by_barrett = ring.mul(&by_barrett, &y);
+        }
+        let t_barrett = start.elapsed().as_secs_f64();
+
+        // The modulus is coprime to 10, so odd, and Montgomery form exists.
+        let odd = "odd modulus";
+        let y_mont = ring.to_montgomery(&y).expect(odd);
+        let start = Instant::now();
+        let mut by_montgomery = ring.to_montgomery(&x).expect(odd);
+        for _ in 0..count {
+            by_montgomery = ring.mont_mul(&by_montgomery, &y_mont).expect(odd);
+        }
+        let t_montgomery = start.elapsed().as_secs_f64();
+
+        assert_eq!(by_division, by_barrett);
+        assert_eq!(
+            by_division,
+            ring.from_montgomery(&by_montgomery).expect(odd)
+        );
+
+        let rates = [t_division, t_barrett, t_montgomery].map(|t| count as f64 / t);
+        let mut line = format!("modulus_bits={}", bits);
+        for (name, rate) in MOD_MUL_METHODS.iter().zip(&rates) {
+            write!(line, ", {}={:.0}", name, rate).unwrap();
+        }
+        println!("{} (modmuls/s)", line);
+        rows.push((bits, rates));
+    }
+
+    let mut out = format!("modulus_bits,{}\n", MOD_MUL_METHODS.join(","));
+    for (bits, rates) in &rows {
+        write!(out, "{}", bits).unwrap();
+        for rate in rates {
+            write!(out, ",{:.1}", rate).unwrap();
+        }
+        out.push('\n');
+    }
+    std::fs::create_dir_all("./assets").expect("Failed to create ./assets directory");
+    std::fs::write("./assets/mod_mul.csv", out).expect("Failed to write CSV results");
+    println!("Results saved to ./assets/mod_mul.csv");
+    rows.len() == MOD_MUL_BITS.len()
+}
@@ -0,0 +1,104 @@
+//! `--primality`: Miller-Rabin against Solovay-Strassen as the number of
+//! rounds grows. Cost is the time to accept a 512-bit prime, which runs
+//! every round; error is how many Carmichael numbers each test accepts.
+//! The Carmichael numbers are Chernick's `(6k+1)(12k+1)(18k+1)` with all
+//! three factors prime and above the trial-division bound, so only the
+//! probabilistic rounds can reject them.
+
+use crate::interrupt;
+use crate::primitives::seconds_per_call;
+use bigmul::{BigUint, random_bits};
+use std::fmt::Write as _;
+use std::hint::black_box;
+
+const ROUNDS: [usize; 6] = [1, 2, 3, 4, 6, 8];
+const CARMICHAEL_COUNT: usize = 100;
+const PRIME_BITS: usize = 512;
+
+/// The first `CARMICHAEL_COUNT` Chernick numbers with `6k + 1 > 1000`.
+fn carmichael_numbers() -> Vec<BigUint> {
+    (167u64..)
+        .filter_map(|k| {
+            let factors = [6 * k + 1, 12 * k + 1, 18 * k + 1].map(BigUint::from);
+            factors
+                .iter()
+                .all(|f| f.is_probable_prime(16))
+                .then(|| factors[0].mul(&factors[1]).mul(&factors[2]))
+        })
+        .take(CARMICHAEL_COUNT)
+        .collect()
+}
+
+fn random_prime() -> BigUint {
+    let mut rng = rand::thread_rng();
+    loop {
+        let p = random_bits(PRIME_BITS, &mut rng);
+        if p.is_probable_prime(16) {
+            return p;
+        }
+    }
+}
+
+/// Runs the comparison, printing cost and error at each round count, and
+/// writes `./assets/primality.csv`. Returns `false` if interrupted before
+/// the end.
+pub fn run() -> bool {
+    let carmichael = carmichael_numbers();
+    let prime = random_prime();
+    let mut rows: Vec<(usize, [f64; 2], [usize; 2])> = Vec::new();
+    for &rounds in &ROUNDS {
+        if interrupt::interrupted() {
+            break;
+        }
+        let times = [
+            seconds_per_call(|| {
+                black_box(black_box(&prime).is_probable_prime(rounds));
+            }),
+            seconds_per_call(|| {
+                black_box(black_box(&prime).is_probable_prime_solovay_strassen(rounds));
+            }),
+        ];
+        let passed = [
+            carmichael
+                .iter()
+                .filter(|n| n.is_probable_prime(rounds))
+                .count(),
+            carmichael
+                .iter()
+                .filter(|n| n.is_probable_prime_solovay_strassen(rounds))
+                .count(),
+        ];
+        println!(
+            "rounds={}, miller_rabin={:.3e} s ({} of {} Carmichael numbers pass), solovay_strassen={:.3e} s ({} pass)",
+            rounds,
+            times[0],
+            passed[0],
+            carmichael.len(),
+            times[1],
+            passed[1]
+        );
+        rows.push((rounds, times, passed));
+    }
+
+    let mut out = String::from(
+        "rounds,miller_rabin_seconds,solovay_strassen_seconds,\
+         miller_rabin_false_positives,solovay_strassen_false_positives,carmichael_numbers\n",
+    );
+    for (rounds, [mr, ss], [mr_passed, ss_passed]) in &rows {
+        writeln!(
+            out,
+            "{},{:.12},{:.12},{},{},{}",
+            rounds,
+            mr,
+            ss,
+            mr_passed,
+            ss_passed,
+            carmichael.len()
+        )
+        .unwrap();
+    }
+    std::fs::create_dir_all("./assets").expect("Failed to create ./assets directory");
+    std::fs::write("./assets/primality.csv", out).expect("Failed to write CSV results");
+    println!("Results saved to ./assets/primality.csv");
+    rows.len() == ROUNDS.len()
+}
@@ -0,0 +1,408 @@
+//! Probable-prime testing for `BigUint`: Miller-Rabin, Solovay-Strassen on
+//! top of the Jacobi symbol, and the strong Lucas test that completes
+//! Baillie-PSW.
+
+use crate::biguint::{BigUint, PowWindow};
+use crate::limbs;
+use crate::limbvec::LimbVec;
+use crate::modring::ModRing;
+
+/// Odd primes below 1000, for trial division before Miller-Rabin.
+fn small_primes() -> Vec<u32> {
+    (3..1000u32)
+        .step_by(2)
+        .filter(|&n| {
+            (3..)
+                .step_by(2)
+                .take_while(|d| d * d <= n)
+                .all(|d| n % d != 0)
+        })
+        .collect()
+}
+
+impl BigUint {
+    /// Miller-Rabin with the first `rounds` primes as bases, after trial
+    /// division by the primes below 1000. Composites that pass are possible
+    /// but the bases are fixed, so the answer is deterministic; 13 rounds
+    /// already decide every `n < 3.3 * 10^24`.
+    pub fn is_probable_prime(&self, rounds: usize) -> bool {
+        let primes = small_primes();
+        if let Some(decided) = self.trial_division(&primes) {
+            return decided;
+        }
+
+        let one = BigUint::one();
+        let n_1 = self.sub(&one);
+        // n - 1 = d * 2^s with d odd.
+        let mut d = n_1.clone();
+        let mut s = 0;
+        while d.digits[0].is_multiple_of(2) {
+            d = BigUint {
+                digits: limbs::div_rem_small(&d.digits, 2).0.into(),
+            };
+            s += 1;
+        }
+        let bases = std::iter::once(2)
+            .chain(primes.iter().copied())
+            .take(rounds);
+        'witness: for a in bases {
+            let a = BigUint {
+                digits: LimbVec::from_slice(&[a]),
+            };
+            if a >= n_1 {
+                break;
+            }
+            let mut x = a.mod_pow(&d, self, PowWindow::Sliding);
+            if x == one || x == n_1 {
+                continue;
+            }
+            for _ in 1..s {
+                x = x.mul_karatsuba(&x).div_rem(self).1;
+                if x == n_1 {
+                    continue 'witness;
+                }
+            }
+            return false;
+        }
+        true
+    }
+
+    /// Solovay-Strassen with the same fixed bases as
+    /// [`is_probable_prime`](BigUint::is_probable_prime): `n` passes base `a`
+    /// if `a^((n-1)/2) = (a/n) mod n` (Euler's criterion). Each round costs
+    /// about the same exponentiation as a Miller-Rabin round, but catches
+    /// fewer composites: every strong pseudoprime to a base is an Euler
+    /// pseudoprime to it, not conversely.
+    pub fn is_probable_prime_solovay_strassen(&self, rounds: usize) -> bool {
+        let primes = small_primes();
+        if let Some(decided) = self.trial_division(&primes) {
+            return decided;
+        }
+        let one = BigUint::one();
+        let n_1 = self.sub(&one);
+        let (e, _) = limbs::div_rem_small(&n_1.digits, 2);
+        let e = BigUint { digits: e.into() };
+        let bases = std::iter::once(2)
+            .chain(primes.iter().copied())
+            .take(rounds);
+        for a in bases {
+            let a = BigUint {
+                digits: LimbVec::from_slice(&[a]),
+            };
+            if a >= n_1 {
+                break;
+            }
+            let expected = match a.jacobi(self) {
+                0 => return false,
+                1 => &one,
+                _ => &n_1,
+            };
+            if a.mod_pow(&e, self, PowWindow::Sliding) != *expected {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// The Jacobi symbol `(self/n)`: 0, 1 or -1. For prime `n` it is the
+    /// Legendre symbol, 1 exactly when `self` is a nonzero square mod `n`.
+    /// Panics if `n` is even.
+    pub fn jacobi(&self, n: &BigUint) -> i8 {
+        assert!(n.digits[0] % 2 == 1, "jacobi: n must be odd");
+        // BASE is a multiple of 8, so residues mod 4 and 8 come from the low
+        // limb.
+        let mut a = self.div_rem(n).1;
+        let mut n = n.clone();
+        let mut result = 1;
+        while !a.is_zero() {
+            while a.digits[0].is_multiple_of(2) {
+                a = BigUint {
+                    digits: limbs::div_rem_small(&a.digits, 2).0.into(),
+                };
+                if matches!(n.digits[0] % 8, 3 | 5) {
+                    result = -result;
+                }
+            }
+            std::mem::swap(&mut a, &mut n);
+            if a.digits[0] % 4 == 3 && n.digits[0] % 4 == 3 {
+                result = -result;
+            }
+            a = a.div_rem(&n).1;
+        }
+        if n.is_one() { result } else { 0 }
+    }
+
+    /// Baillie-PSW: Miller-Rabin to base 2, then a strong Lucas test with
+    /// Selfridge's parameters. No composite is known to pass both, so unlike
+    /// [`is_probable_prime`](BigUint::is_probable_prime) there are no rounds
+    /// to choose; it costs about three Miller-Rabin rounds.
+    pub fn is_probable_prime_bpsw(&self) -> bool {
+        self.is_probable_prime(1) && self.is_strong_lucas_probable_prime()
+    }
+
+    /// The strong Lucas probable-prime test with Selfridge's parameters:
+    /// `D` is the first of 5, -7, 9, -11, ... with `(D/n) = -1`, `P = 1` and
+    /// `Q = (1 - D)/4`. Its pseudoprimes are largely disjoint from base-2
+    /// strong pseudoprimes, which is what Baillie-PSW relies on.
+    pub fn is_strong_lucas_probable_prime(&self) -> bool {
+        match self.trial_division(&small_primes()) {
+            Some(decided) => decided,
+            None => self.strong_lucas(),
+        }
+    }
+
+    /// The strong Lucas test proper, for odd `self > 1`.
+    fn strong_lucas(&self) -> bool {
+        // No D exists for a perfect square.
+        let root = self.sqrt();
+        if root.mul(&root) == *self {
+            return false;
+        }
+        let ring = ModRing::new(self.clone());
+        let residue = |x: i64| {
+            let r = ring.reduce(&BigUint::from(x.unsigned_abs()));
+            if x < 0 {
+                ring.sub(&BigUint::zero(), &r)
+            } else {
+                r
+            }
+        };
+        let mut d: i64 = 5;
+        loop {
+            match residue(d).jacobi(self) {
+                -1 => break,
+                // A common factor with D: composite unless n is |D| itself.
+                0 => return *self == BigUint::from(d.unsigned_abs()),
+                _ => d = if d > 0 { -(d + 2) } else { -d + 2 },
+            }
+        }
+        let (big_d, q) = (residue(d), residue((1 - d) / 4));
+        // x / 2 mod n.
+        let half = |x: BigUint| {
+            let x = if x.digits[0] % 2 == 1 { x.add(self) } else { x };
+            BigUint {
+                digits: limbs::div_rem_small(&x.digits, 2).0.into(),
+            }
+        };
+        // n + 1 = k * 2^s with k odd.
+        let mut k = self.add(&BigUint::one());
+        let mut s = 0;
+        while k.digits[0].is_multiple_of(2) {
+            k = BigUint {
+                digits: limbs::div_rem_small(&k.digits, 2).0.into(),
+            };
+            s += 1;
+        }
+        // U_1 = 1 and V_1 = P = 1; doubling and incrementing the index along
+        // the bits of k, with qk = Q^index.
+        let (mut u, mut v, mut qk) = (BigUint::one(), BigUint::one(), q.clone());
+        for &bit in &k.bits_msb_first()[1..] {
+            u = ring.mul(&u, &v);
+            v = ring.sub(&ring.mul(&v, &v), &ring.add(&qk, &qk));
+            qk = ring.mul(&qk, &qk);
+            if bit == 1 {
+                let next_u = half(ring.add(&u, &v));
+                v = half(ring.add(&ring.mul(&big_d, &u), &v));
+                u = next_u;
+                qk = ring.mul(&qk, &q);
+            }
+        }
+        if u.is_zero() || v.is_zero() {
+            return true;
+        }
+        for _ in 1..s {
+            v = ring.sub(&ring.mul(&v, &v), &ring.add(&qk, &qk));
+            if v.is_zero() {
+                return true;
+            }
+            qk = ring.mul(&qk, &qk);
+        }
+        false
+    }
+
+    /// Settles `self` by size, parity and division by `primes` (the odd
+    /// primes below 1000), or returns `None` when a probabilistic test is
+    /// needed.
+    fn trial_division(&self, primes: &[u32]) -> Option<bool> {
+        let two = BigUint::from(2u64);
+        if *self < two {
+            return Some(false);
+        }
+        if self.digits[0].is_multiple_of(2) {
+            return Some(*self == two);
+        }
+        for &p in primes {
+            if limbs::div_rem_small(&self.digits, p).1 == 0 {
+                return Some(self.digits == [p]);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_values() {
+        let primes: Vec<u32> = (0..60)
+            .filter(|&n| {
+                BigUint::from_str(&n.to_string())
+                    .unwrap()
+                    .is_probable_prime(8)
+            })
+            .collect();
+        assert_eq!(
+            primes,
+            [
+                2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59
+            ]
+        );
+        assert!(BigUint::from_str("997").unwrap().is_probable_prime(8));
+        assert!(
+            !BigUint::from_str("1009")
+                .unwrap()
+                .mul_karatsuba(&BigUint::from_str("1013").unwrap())
+                .is_probable_prime(8)
+        );
+    }
+
+    #[test]
+    fn large_primes_and_pseudoprimes() {
+        // 2^127 - 1 and the RSA-100 factor 37975227936943673922808872755445627854565536638199.
+        assert!(
+            BigUint::from_str("170141183460469231731687303715884105727")
+                .unwrap()
+                .is_probable_prime(16)
+        );
+        assert!(
+            BigUint::from_str("37975227936943673922808872755445627854565536638199")
+                .unwrap()
+                .is_probable_prime(16)
+        );
+        // 149491 * 747451 * 34233211 is a strong pseudoprime to every prime
+        // base up to 31 but not to 37.
+        let spsp = BigUint::from_str("3825123056546413051").unwrap();
+        assert!(spsp.is_probable_prime(11));
+        assert!(!spsp.is_probable_prime(12));
+        // 2^128 + 1 is composite.
+        assert!(
+            !BigUint::from_str("340282366920938463463374607431768211457")
+                .unwrap()
+                .is_probable_prime(16)
+        );
+    }
+
+    #[test]
+    fn jacobi_matches_euler_criterion() {
+        for n in (3u64..200).step_by(2) {
+            let big_n = BigUint::from_str(&n.to_string()).unwrap();
+            let prime = (2..n).all(|d| n % d != 0);
+            for a in 0..2 * n {
+                let j = BigUint::from_str(&a.to_string()).unwrap().jacobi(&big_n);
+                if prime {
+                    let euler = (0..(n - 1) / 2).fold(1, |x, _| x * a % n);
+                    let expected = match euler {
+                        0 => 0,
+                        1 => 1,
+                        _ => -1,
+                    };
+                    assert_eq!(j, expected, "({}/{})", a, n);
+                } else {
+                    // Multiplicative in the top argument.
+                    let j2 = BigUint::from_str(&(a * a % n).to_string())
+                        .unwrap()
+                        .jacobi(&big_n);
+                    assert_eq!(j2, j * j, "({}^2/{})", a, n);
+                }
+            }
+        }
+        // (2/15) = (2/3)(2/5) = (-1)(-1); (7/15) = (7/3)(7/5) = (1)(-1).
+        let n = BigUint::from_str("15").unwrap();
+        assert_eq!(BigUint::from_str("2").unwrap().jacobi(&n), 1);
+        assert_eq!(BigUint::from_str("7").unwrap().jacobi(&n), -1);
+    }
+
+    #[test]
+    fn solovay_strassen_agrees_with_miller_rabin() {
+        for n in 0..3000u32 {
+            let x = BigUint::from_str(&n.to_string()).unwrap();
+            assert_eq!(
+                x.is_probable_prime_solovay_strassen(8),
+                x.is_probable_prime(8),
+                "{}",
+                n
+            );
+        }
+        assert!(
+            BigUint::from_str("170141183460469231731687303715884105727")
+                .unwrap()
+                .is_probable_prime_solovay_strassen(16)
+        );
+        assert!(
+            !BigUint::from_str("340282366920938463463374607431768211457")
+                .unwrap()
+                .is_probable_prime_solovay_strassen(16)
+        );
+        // The Carmichael number 1171 * 2341 * 3511 is an Euler pseudoprime
+        // to bases 2, 3 and 5, but not a strong pseudoprime to base 2.
+        let carmichael = BigUint::from_str("9624742921").unwrap();
+        assert!(carmichael.is_probable_prime_solovay_strassen(3));
+        assert!(!carmichael.is_probable_prime_solovay_strassen(4));
+        assert!(!carmichael.is_probable_prime(1));
+    }
+
+    #[test]
+    fn strong_lucas_pseudoprimes_fail_baillie_psw() {
+        // The smallest strong Lucas pseudoprimes; none is a strong
+        // pseudoprime to base 2.
+        for n in ["5459", "5777", "10877", "16109", "18971", "22499", "24569"] {
+            let n = BigUint::from_str(n).unwrap();
+            assert!(n.strong_lucas(), "{}", n);
+            assert!(!n.is_probable_prime(1), "{}", n);
+            assert!(!n.is_probable_prime_bpsw(), "{}", n);
+        }
+        // Every odd prime passes the Lucas test alone.
+        for n in (3..3000u32).step_by(2) {
+            let x = BigUint::from_str(&n.to_string()).unwrap();
+            if x.is_probable_prime(8) {
+                assert!(x.strong_lucas(), "{}", n);
+            }
+        }
+    }
+
+    #[test]
+    fn baillie_psw_agrees_with_miller_rabin() {
+        for n in 0..3000u32 {
+            let x = BigUint::from_str(&n.to_string()).unwrap();
+            assert_eq!(x.is_probable_prime_bpsw(), x.is_probable_prime(8), "{}", n);
+        }
+        assert!(
+            BigUint::from_str("170141183460469231731687303715884105727")
+                .unwrap()
+                .is_probable_prime_bpsw()
+        );
+        assert!(
+            BigUint::from_str("37975227936943673922808872755445627854565536638199")
+                .unwrap()
+                .is_probable_prime_bpsw()
+        );
+        // Strong pseudoprimes to every prime base up to 31, and a square.
+        assert!(
+            !BigUint::from_str("3825123056546413051")
+                .unwrap()
+                .is_probable_prime_bpsw()
+        );
+        assert!(
+            !BigUint::from_str("1194649")
+                .unwrap()
+                .is_probable_prime_bpsw()
+        );
+        assert!(
+            !BigUint::from_str("9624742921")
+                .unwrap()
+                .is_probable_prime_bpsw()
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! `bigmul bench-primitives`: throughput of the linear-time kernels the
+//! multiplication algorithms are assembled from, in limbs per second, plus
+//! how many additions one Karatsuba product costs at the same size. That
+//! ratio is what the Karatsuba threshold trades against.
+
+use crate::interrupt;
+use bigmul::limbs::{self, BASE};
+use plotters::prelude::*;
+use rand::Rng;
+use std::fmt::Write as _;
+use std::hint::black_box;
+use std::time::Instant;
+
+/// Operand sizes in limbs: powers of two from 16 to 65536.
+const SIZES: [usize; 13] = [
+    16, 32, 64, 128, 256, 512, 1024, 2048, 4096, 8192, 16384, 32768, 65536,
+];
+const PRIMITIVES: [&str; 4] = ["add", "sub", "shift_left", "shift_right"];
+/// Minimum wall time per measurement, so short kernels repeat enough.
+const MIN_SECONDS: f64 = 0.05;
+
+/// Seconds per call of `f`, repeating until `MIN_SECONDS` has elapsed.
+pub fn seconds_per_call<F: FnMut()>(mut f: F) -> f64 {
+    let start = Instant::now();
+    let mut calls = 0u64;
+    loop {
+        f();
+        calls += 1;
+        let elapsed = start.elapsed().as_secs_f64();
+        if elapsed >= MIN_SECONDS {
+            return elapsed / calls as f64;
+        }
+    }
+}
+
+pub fn random_limbs(n: usize) -> Vec<u32> {
+    let mut rng = rand::thread_rng();
+    let mut v: Vec<u32> = (0..n).map(|_| rng.gen_range(0..BASE as u32)).collect();
+    v[n - 1] = rng.gen_range(1..BASE as u32);
+    v
+}
+
+/// Seconds per call for each primitive and for one Karatsuba product.
+fn measure(n: usize) -> ([f64; 4], f64) {
+    let a = random_limbs(n);
+    let mut b = random_limbs(n);
+    // Keep a >= b so the subtraction is valid.
+    b[n - 1] = b[n - 1].min(a[n - 1].saturating_sub(1));
+    let shift = n / 2;
+    let times = [
+        seconds_per_call(|| {
+            black_box(limbs::add_slices(black_box(&a), black_box(&b)));
+        }),
+        seconds_per_call(|| {
+            black_box(limbs::sub_slices(black_box(&a), black_box(&b)));
+        }),
+        seconds_per_call(|| {
+            black_box(limbs::shift_left_slices(black_box(&a), shift));
+        }),
+        seconds_per_call(|| {
+            black_box(limbs::shift_right_slices(black_box(&a), shift));
+        }),
+    ];
+    let kara = seconds_per_call(|| {
+        black_box(limbs::mul_karatsuba_slices(black_box(&a), black_box(&b)));
+    });
+    (times, kara)
+}
+
+/// Runs the sweep, printing throughput per size and writing
+/// `./assets/primitives.csv` and `./assets/primitives.png`. Returns `false`
+/// if interrupted before the end.
+pub fn run() -> bool {
+    let mut rows: Vec<(usize, [f64; 4], f64)> = Vec::new();
+    for &n in &SIZES {
+        if interrupt::interrupted() {
+            break;
+        }
+        let (times, kara) = measure(n);
+        let mut line = format!("limbs={}", n);
+        for (name, t) in PRIMITIVES.iter().zip(&times) {
+            write!(line, ", {}={:.3e} limbs/s", name, n as f64 / t).unwrap();
+        }
+        write!(line, ", kara/add={:.1}", kara / times[0]).unwrap();
+        println!("{}", line);
+        rows.push((n, times, kara));
+    }
+    if rows.is_empty() {
+        return false;
+    }
+
+    let mut out = String::from("limbs");
+    for name in PRIMITIVES {
+        write!(out, ",{0}_seconds,{0}_limbs_per_second", name).unwrap();
+    }
+    out.push_str(",kara_seconds,kara_per_add\n");
+    for (n, times, kara) in &rows {
+        write!(out, "{}", n).unwrap();
+        for t in times {
+            write!(out, ",{:.12},{:.1}", t, *n as f64 / t).unwrap();
+        }
+        writeln!(out, ",{:.12},{:.2}", kara, kara / times[0]).unwrap();
+    }
+    std::fs::create_dir_all("./assets").expect("Failed to create ./assets directory");
+    std::fs::write("./assets/primitives.csv", out).expect("Failed to write CSV results");
+    plot("./assets/primitives.png", &rows);
+    println!("Results saved to ./assets/primitives.csv and ./assets/primitives.png");
+    rows.len() == SIZES.len()
+}
+
+/// Throughput in limbs per second against size, size on a log scale.
+fn plot(path: &str, rows: &[(usize, [f64; 4], f64)]) {
+    let root = BitMapBackend::new(path, (800, 600)).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+    let throughput = |k: usize| -> Vec<(f32, f32)> {
+        rows.iter()
+            .map(|(n, times, _)| {
+                (
+                    (*n as f64).log10() as f32,
+                    (*n as f64 / times[k] / 1e9) as f32,
+                )
+            })
+            .collect()
+    };
+    let max = (0..PRIMITIVES.len())
+        .flat_map(throughput)
+        .map(|(_, y)| y)
+        .fold(0f32, f32::max);
+    let x_min = (rows[0].0 as f64).log10() as f32;
+    let x_max = (rows.last().unwrap().0 as f64).log10() as f32 + 0.01;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(
+            "Linear Primitive Throughput",
+            ("sans-serif", 40).into_font(),
+        )
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d(x_min..x_max, 0f32..max * 1.1)
+        .unwrap();
+    chart
+        .configure_mesh()
+        .x_desc("Operand Size (limbs, log scale)")
+        .y_desc("Throughput (billion limbs per second)")
+        .x_label_formatter(&|x| format!("{:.0}", 10f64.powf(*x as f64)))
+        .draw()
+        .unwrap();
+    for (k, name) in PRIMITIVES.iter().enumerate() {
+        let color = Palette99::pick(k).to_rgba();
+        chart
+            .draw_series(LineSeries::new(throughput(k), color))
+            .unwrap()
+            .label(*name)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .unwrap();
+
+    root.present().unwrap();
+}
@@ -0,0 +1,273 @@
+//! Products of many factors. Folding a list left to right multiplies an
+//! ever-longer running product by one small factor at a time, which keeps
+//! the kernels in their most lopsided and least efficient shape; pairing the
+//! two smallest values at every step instead keeps both operands of each
+//! product about the same size, so Karatsuba's savings apply throughout.
+//! [`ProductAccumulator`] does the same for factors that arrive one at a
+//! time.
+
+use crate::bigint::{BigInt, Sign};
+use crate::biguint::BigUint;
+use crate::instrument;
+use crate::limbs;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Multiplies all of `factors`, always combining the two smallest values
+/// left. The empty product is one.
+pub fn product(factors: &[BigInt]) -> BigInt {
+    match split_signs(factors) {
+        Some((sign, magnitudes)) => BigInt::from_biguint(sign, balanced(magnitudes)),
+        None => BigInt::zero(),
+    }
+}
+
+/// [`product`] on up to `threads` threads: the factors are dealt into two
+/// halves of about equal total size, multiplied concurrently, and the
+/// halves' products are combined with
+/// [`mul_karatsuba_par`](BigUint::mul_karatsuba_par). Below
+/// [`par_karatsuba_threshold`](limbs::par_karatsuba_threshold) limbs in total
+/// this is exactly `product`.
+pub fn product_par(factors: &[BigInt], threads: usize) -> BigInt {
+    match split_signs(factors) {
+        Some((sign, magnitudes)) => BigInt::from_biguint(sign, balanced_par(magnitudes, threads)),
+        None => BigInt::zero(),
+    }
+}
+
+/// The sign of the product and the magnitudes of the factors, or `None` if
+/// a factor is zero.
+fn split_signs(factors: &[BigInt]) -> Option<(Sign, Vec<BigUint>)> {
+    let mut sign = Sign::Plus;
+    let mut magnitudes = Vec::with_capacity(factors.len());
+    for f in factors {
+        match f.sign() {
+            Sign::NoSign => return None,
+            Sign::Minus => sign = -sign,
+            Sign::Plus => {}
+        }
+        magnitudes.push(f.magnitude().clone());
+    }
+    Some((sign, magnitudes))
+}
+
+/// Multiplies the two shortest values until one is left.
+fn balanced(values: Vec<BigUint>) -> BigUint {
+    let mut heap: BinaryHeap<Reverse<(usize, usize)>> = values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| Reverse((v.as_limbs().len(), i)))
+        .collect();
+    // Each slot is taken once, when its index leaves the heap.
+    let mut slots = values;
+    loop {
+        let Some(Reverse((_, i))) = heap.pop() else {
+            return BigUint::one();
+        };
+        let a = std::mem::take(&mut slots[i]);
+        let Some(Reverse((_, j))) = heap.pop() else {
+            return a;
+        };
+        let p = a.mul(&std::mem::take(&mut slots[j]));
+        heap.push(Reverse((p.as_limbs().len(), slots.len())));
+        slots.push(p);
+    }
+}
+
+fn balanced_par(mut values: Vec<BigUint>, threads: usize) -> BigUint {
+    let total: usize = values.iter().map(|v| v.as_limbs().len()).sum();
+    if threads <= 1 || values.len() < 2 || total < limbs::par_karatsuba_threshold() {
+        return balanced(values);
+    }
+    // Largest first, each to the lighter half.
+    values.sort_by_key(|v| Reverse(v.as_limbs().len()));
+    let (mut left, mut right) = (Vec::new(), Vec::new());
+    let (mut left_limbs, mut right_limbs) = (0, 0);
+    for v in values {
+        if left_limbs <= right_limbs {
+            left_limbs += v.as_limbs().len();
+            left.push(v);
+        } else {
+            right_limbs += v.as_limbs().len();
+            right.push(v);
+        }
+    }
+    let threads_right = threads / 2;
+    let (l, r) = std::thread::scope(|scope| {
+        // Spawned threads start counting from zero; their totals are added
+        // to this thread's.
+        let r = scope.spawn(|| {
+            let r = balanced_par(right, threads_right);
+            (r, instrument::limb_ops())
+        });
+        let l = balanced_par(left, threads - threads_right);
+        let (r, ops) = r.join().unwrap_or_else(|e| std::panic::resume_unwind(e));
+        instrument::add_limb_ops(ops as usize);
+        (l, r)
+    });
+    l.mul_karatsuba_par(&r, threads)
+}
+
+/// A running product for factors that arrive one at a time, as in
+/// factorial-like loops. Pushed factors wait on a stack of partial products
+/// whose lengths shrink towards the top; a factor is multiplied in only once
+/// the top is at least as long as the entry below it, so, as in [`product`],
+/// every multiplication pairs operands of similar size.
+///
+/// ```
+/// use bigmul::{BigInt, ProductAccumulator};
+///
+/// let mut acc = ProductAccumulator::new();
+/// for k in 1..=20 {
+///     acc.push(&BigInt::from_str(&k.to_string()).unwrap());
+/// }
+/// assert_eq!(acc.finish().to_string(), "2432902008176640000");
+/// ```
+#[derive(Clone, Debug)]
+pub struct ProductAccumulator {
+    sign: Sign,
+    stack: Vec<BigUint>,
+}
+
+impl ProductAccumulator {
+    /// An empty accumulator, whose product is one.
+    pub fn new() -> Self {
+        ProductAccumulator {
+            sign: Sign::Plus,
+            stack: Vec::new(),
+        }
+    }
+
+    /// Multiplies `factor` into the running product. After a zero factor the
+    /// product stays zero and later factors are dropped.
+    pub fn push(&mut self, factor: &BigInt) {
+        match (self.sign, factor.sign()) {
+            (Sign::NoSign, _) => return,
+            (_, Sign::NoSign) => {
+                self.sign = Sign::NoSign;
+                self.stack.clear();
+                return;
+            }
+            (_, Sign::Minus) => self.sign = -self.sign,
+            (_, Sign::Plus) => {}
+        }
+        let mut top = factor.magnitude().clone();
+        while let Some(below) = self
+            .stack
+            .pop_if(|below| below.as_limbs().len() <= top.as_limbs().len())
+        {
+            top = below.mul(&top);
+        }
+        self.stack.push(top);
+    }
+
+    /// The product of the factors pushed so far, leaving the accumulator as
+    /// it is.
+    pub fn product(&self) -> BigInt {
+        BigInt::from_biguint(self.sign, balanced(self.stack.clone()))
+    }
+
+    /// The product of every factor pushed.
+    pub fn finish(self) -> BigInt {
+        BigInt::from_biguint(self.sign, balanced(self.stack))
+    }
+}
+
+impl Default for ProductAccumulator {
+    fn default() -> Self {
+        ProductAccumulator::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    fn random_factors(rng: &mut StdRng, count: usize) -> Vec<BigInt> {
+        (0..count)
+            .map(|_| {
+                let digits: String = (0..rng.gen_range(1..400))
+                    .map(|_| char::from(b'0' + rng.gen_range(0..10)))
+                    .collect();
+                let sign = if rng.gen_bool(0.5) { "-" } else { "" };
+                BigInt::from_str(&format!("{}1{}", sign, digits)).unwrap()
+            })
+            .collect()
+    }
+
+    fn folded(factors: &[BigInt]) -> BigInt {
+        factors.iter().fold(BigInt::one(), |acc, f| acc.mul(f))
+    }
+
+    #[test]
+    fn matches_left_fold() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for count in [0, 1, 2, 3, 7, 64] {
+            let factors = random_factors(&mut rng, count);
+            assert_eq!(product(&factors), folded(&factors), "{} factors", count);
+        }
+        let factorial: Vec<BigInt> = (1..=300)
+            .map(|k| BigInt::from_str(&k.to_string()).unwrap())
+            .collect();
+        assert_eq!(product(&factorial), folded(&factorial));
+    }
+
+    #[test]
+    fn zero_factor_and_signs() {
+        let factors: Vec<BigInt> = ["-2", "3", "-5", "-7"]
+            .into_iter()
+            .map(BigInt::from_str)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(product(&factors).to_string(), "-210");
+        assert_eq!(product(&factors[..3]).to_string(), "30");
+        let mut with_zero = factors.clone();
+        with_zero.push(BigInt::zero());
+        assert!(product(&with_zero).is_zero());
+        assert!(product_par(&with_zero, 4).is_zero());
+        assert!(product(&[]).is_one());
+    }
+
+    #[test]
+    fn accumulator_matches_product() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let factors = random_factors(&mut rng, 100);
+        let mut acc = ProductAccumulator::new();
+        assert!(acc.product().is_one());
+        for (k, f) in factors.iter().enumerate() {
+            acc.push(f);
+            if k % 25 == 24 {
+                assert_eq!(acc.product(), product(&factors[..=k]));
+            }
+        }
+        // Partial products stay shorter towards the top of the stack.
+        assert!(
+            acc.stack
+                .windows(2)
+                .all(|w| w[0].as_limbs().len() > w[1].as_limbs().len())
+        );
+        assert_eq!(acc.finish(), product(&factors));
+
+        let mut acc = ProductAccumulator::default();
+        for f in ["-3", "0", "-5"] {
+            acc.push(&BigInt::from_str(f).unwrap());
+        }
+        assert!(acc.finish().is_zero());
+    }
+
+    #[test]
+    fn parallel_matches_sequential() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let factors = random_factors(&mut rng, 200);
+        let expected = product(&factors);
+        for threads in [1, 2, 3, 4] {
+            assert_eq!(
+                product_par(&factors, threads),
+                expected,
+                "{} threads",
+                threads
+            );
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! `bigmul profile`: one algorithm, one operand size, multiplied in a loop
+//! for a fixed wall-clock time so an external profiler (`perf record`,
+//! `cargo flamegraph`) sees nothing but that algorithm's hot path.
+//!
+//! Build with `cargo build --profile profiling` to keep debug symbols in an
+//! otherwise optimized binary.
+
+use crate::inputs::{Axis, InputClass};
+use crate::interrupt;
+use crate::multiplier::{self, Multiplier};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Finds a registered multiplier by name (`kara`) or, ignoring case, by
+/// label (`karatsuba`).
+pub fn find(algorithm: &str) -> Option<Box<dyn Multiplier>> {
+    multiplier::registry()
+        .into_iter()
+        .find(|m| m.name() == algorithm || m.label().eq_ignore_ascii_case(algorithm))
+}
+
+/// Kept out of line so profiles show a single frame owning the loop.
+#[inline(never)]
+fn hot_loop(m: &dyn Multiplier, a: &[u32], b: &[u32], duration: Duration) -> (u64, Duration) {
+    let start = Instant::now();
+    let mut iterations = 0;
+    while start.elapsed() < duration && !interrupt::interrupted() {
+        black_box(m.mul(black_box(a), black_box(b)));
+        iterations += 1;
+    }
+    (iterations, start.elapsed())
+}
+
+/// Runs the loop and prints the iteration count and mean time per
+/// multiplication. Returns `false` if interrupted.
+pub fn run(algorithm: &str, digits: usize, seconds: f64) -> bool {
+    let m = find(algorithm).expect("algorithm validated by the CLI");
+    let a = InputClass::Random.generate(digits, Axis::Digits);
+    let b = InputClass::Random.generate(digits, Axis::Digits);
+    println!(
+        "profiling {} on {}-digit operands for {:.1} s (pid {})",
+        m.name(),
+        digits,
+        seconds,
+        std::process::id()
+    );
+    let (iterations, elapsed) = hot_loop(
+        m.as_ref(),
+        a.as_limbs(),
+        b.as_limbs(),
+        Duration::from_secs_f64(seconds),
+    );
+    if iterations > 0 {
+        println!(
+            "{} multiplications, {:.6} s each",
+            iterations,
+            elapsed.as_secs_f64() / iterations as f64
+        );
+    }
+    !interrupt::interrupted()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_by_name_or_label() {
+        assert_eq!(find("kara").unwrap().name(), "kara");
+        assert_eq!(find("Karatsuba").unwrap().name(), "kara");
+        assert_eq!(find("karatsuba").unwrap().name(), "kara");
+        assert!(find("fft").is_none());
+    }
+}
@@ -0,0 +1,291 @@
+//! Integers in a base chosen at runtime, for showing what the choice of
+//! base does to the kernels. The crate itself fixes `10^9`: nine digits per
+//! limb, the most that leaves a limb product with carries in a `u64`, and
+//! printing is a plain copy. `10^4` limbs hold fewer than half as many
+//! digits, so every product has over four times the limb products; `2^32`
+//! holds about a tenth more than `10^9` and reduces modulo the base with a
+//! shift instead of a division, but converting to decimal is quadratic
+//! work of its own. [`RadixUint`] runs the same direct and Karatsuba
+//! kernels in each base, so their times and crossovers can be compared.
+
+use crate::biguint::BigUint;
+use crate::binary::{from_words, to_words};
+use crate::error::Error;
+use crate::limbs;
+use std::fmt;
+
+/// A limb base for [`RadixUint`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Base {
+    /// `10^4`, four decimal digits per limb.
+    Decimal4,
+    /// `10^9`, the crate's own base.
+    Decimal9,
+    /// `2^32`, a full machine word per limb.
+    Binary32,
+}
+
+impl Base {
+    pub const ALL: [Base; 3] = [Base::Decimal4, Base::Decimal9, Base::Binary32];
+
+    /// The base as a number.
+    pub fn radix(self) -> u64 {
+        match self {
+            Base::Decimal4 => 10_000,
+            Base::Decimal9 => limbs::BASE,
+            Base::Binary32 => 1 << 32,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Base::Decimal4 => "10^4",
+            Base::Decimal9 => "10^9",
+            Base::Binary32 => "2^32",
+        }
+    }
+}
+
+/// A non-negative integer as little-endian limbs in a [`Base`], with no
+/// high zero limbs beyond the first. Both operands of a product must share
+/// a base.
+///
+/// ```
+/// use bigmul::{Base, BigUint, RadixUint};
+///
+/// let x = BigUint::from_str("123456789012345678901234567890").unwrap();
+/// let a = RadixUint::from_biguint(&x, Base::Decimal4);
+/// assert_eq!(a.as_limbs()[..2], [7890, 3456]);
+/// assert_eq!(a.mul_karatsuba(&a).to_biguint(), x.mul(&x));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RadixUint {
+    base: Base,
+    limbs: Vec<u32>,
+}
+
+impl RadixUint {
+    pub fn from_biguint(x: &BigUint, base: Base) -> Self {
+        let mut limbs = match base {
+            Base::Decimal9 => x.as_limbs().to_vec(),
+            Base::Binary32 => to_words(x),
+            Base::Decimal4 => decimal_limbs(x.to_string().as_bytes(), 4),
+        };
+        limbs::normalize(&mut limbs);
+        if limbs.is_empty() {
+            limbs.push(0);
+        }
+        RadixUint { base, limbs }
+    }
+
+    pub fn from_str(s: &str, base: Base) -> Result<Self, Error> {
+        Ok(RadixUint::from_biguint(&BigUint::from_str(s)?, base))
+    }
+
+    pub fn to_biguint(&self) -> BigUint {
+        match self.base {
+            Base::Decimal9 => BigUint::from_kernel(self.limbs.clone()),
+            Base::Binary32 => from_words(&self.limbs),
+            Base::Decimal4 => {
+                let digits: String = self
+                    .limbs
+                    .iter()
+                    .rev()
+                    .map(|l| format!("{:04}", l))
+                    .collect();
+                BigUint::from_kernel(decimal_limbs(digits.as_bytes(), 9))
+            }
+        }
+    }
+
+    pub fn base(&self) -> Base {
+        self.base
+    }
+
+    pub fn as_limbs(&self) -> &[u32] {
+        &self.limbs
+    }
+
+    /// Multiplies with Karatsuba.
+    #[allow(clippy::should_implement_trait)]
+    pub fn mul(&self, other: &RadixUint) -> RadixUint {
+        self.mul_karatsuba(other)
+    }
+
+    pub fn mul_direct(&self, other: &RadixUint) -> RadixUint {
+        let r = self.common_radix(other);
+        self.with_limbs(mul_direct(&self.limbs, &other.limbs, r))
+    }
+
+    /// Karatsuba down to [`limbs::karatsuba_threshold`] limbs, whatever the
+    /// base, so that a crossover in digits moves with the digits per limb.
+    pub fn mul_karatsuba(&self, other: &RadixUint) -> RadixUint {
+        let r = self.common_radix(other);
+        self.with_limbs(mul_karatsuba(&self.limbs, &other.limbs, r))
+    }
+
+    fn common_radix(&self, other: &RadixUint) -> u64 {
+        assert_eq!(
+            self.base, other.base,
+            "RadixUint: operands in different bases"
+        );
+        self.base.radix()
+    }
+
+    fn with_limbs(&self, mut limbs: Vec<u32>) -> RadixUint {
+        limbs::normalize(&mut limbs);
+        RadixUint {
+            base: self.base,
+            limbs,
+        }
+    }
+}
+
+impl fmt::Display for RadixUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.to_biguint(), f)
+    }
+}
+
+/// Every product `x * y <= (r - 1)^2` plus two carries below `r` stays
+/// under `r^2 <= 2^64`.
+/// Little-endian limbs of `width` decimal digits each from the ASCII
+/// digits `digits`.
+fn decimal_limbs(digits: &[u8], width: usize) -> Vec<u32> {
+    digits
+        .rchunks(width)
+        .map(|chunk| chunk.iter().fold(0, |acc, &d| acc * 10 + (d - b'0') as u32))
+        .collect()
+}
+
+fn mul_direct(a: &[u32], b: &[u32], r: u64) -> Vec<u32> {
+    let mut out = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let t = out[i + j] as u64 + x as u64 * y as u64 + carry;
+            out[i + j] = (t % r) as u32;
+            carry = t / r;
+        }
+        out[i + b.len()] = carry as u32;
+    }
+    out
+}
+
+fn mul_karatsuba(a: &[u32], b: &[u32], r: u64) -> Vec<u32> {
+    let n = a.len().max(b.len());
+    if a.len().min(b.len()) <= limbs::karatsuba_threshold() {
+        return mul_direct(a, b, r);
+    }
+    let m = n / 2;
+    let ((a0, a1), (b0, b1)) = (split(a, m), split(b, m));
+    let p = mul_karatsuba(a0, b0, r);
+    let q = mul_karatsuba(a1, b1, r);
+    let u = mul_karatsuba(&add(a0, a1, r), &add(b0, b1, r), r);
+    let mid = sub(&sub(&u, &p, r), &q, r);
+    let mut out = vec![0u32; a.len() + b.len() + 1];
+    add_at(&mut out, 0, &p, r);
+    add_at(&mut out, 2 * m, &q, r);
+    add_at(&mut out, m, &mid, r);
+    out
+}
+
+/// The low `m` limbs of `x` and the rest, each trimmed; the rest is empty
+/// if `x` has no more than `m` limbs.
+fn split(x: &[u32], m: usize) -> (&[u32], &[u32]) {
+    let (low, high) = x.split_at(m.min(x.len()));
+    (trimmed(low), trimmed(high))
+}
+
+/// `x` without high zero limbs, keeping one.
+fn trimmed(x: &[u32]) -> &[u32] {
+    let len = x.iter().rposition(|&d| d != 0).map_or(1, |i| i + 1);
+    &x[..len.min(x.len())]
+}
+
+fn add(a: &[u32], b: &[u32], r: u64) -> Vec<u32> {
+    let mut out = vec![0u32; a.len().max(b.len()) + 1];
+    add_at(&mut out, 0, a, r);
+    add_at(&mut out, 0, b, r);
+    out
+}
+
+/// `a - b` for `a >= b`.
+fn sub(a: &[u32], b: &[u32], r: u64) -> Vec<u32> {
+    let mut out = a.to_vec();
+    let mut borrow = 0u64;
+    for (i, o) in out.iter_mut().enumerate() {
+        let d = b.get(i).copied().unwrap_or(0) as u64 + borrow;
+        if i >= b.len() && borrow == 0 {
+            break;
+        }
+        if (*o as u64) < d {
+            *o = (*o as u64 + r - d) as u32;
+            borrow = 1;
+        } else {
+            *o = (*o as u64 - d) as u32;
+            borrow = 0;
+        }
+    }
+    debug_assert_eq!(borrow, 0, "radix sub: negative result");
+    out
+}
+
+/// Adds `x` into `out` at limb `offset`; high zero limbs of `x` past the
+/// end of `out` are skipped.
+fn add_at(out: &mut [u32], offset: usize, x: &[u32], r: u64) {
+    let mut carry = 0u64;
+    let mut i = offset;
+    for &d in trimmed(x) {
+        let t = out[i] as u64 + d as u64 + carry;
+        out[i] = (t % r) as u32;
+        carry = t / r;
+        i += 1;
+    }
+    while carry > 0 {
+        let t = out[i] as u64 + carry;
+        out[i] = (t % r) as u32;
+        carry = t / r;
+        i += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random_bits;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn every_base_multiplies_alike() {
+        let mut rng = StdRng::seed_from_u64(86);
+        for (bits_a, bits_b) in [(1, 1), (200, 90), (5000, 4000), (9000, 700), (0, 3000)] {
+            let x = random_bits(bits_a, &mut rng);
+            let y = random_bits(bits_b, &mut rng);
+            let expected = x.mul(&y);
+            for base in Base::ALL {
+                let (a, b) = (
+                    RadixUint::from_biguint(&x, base),
+                    RadixUint::from_biguint(&y, base),
+                );
+                assert_eq!(a.to_biguint(), x, "{}", base.name());
+                assert!(a.as_limbs().iter().all(|&d| (d as u64) < base.radix()));
+                assert_eq!(a.mul_direct(&b).to_biguint(), expected, "{}", base.name());
+                assert_eq!(
+                    a.mul_karatsuba(&b).to_biguint(),
+                    expected,
+                    "{}",
+                    base.name()
+                );
+            }
+        }
+        let max = RadixUint::from_str(&"9".repeat(200), Base::Binary32).unwrap();
+        assert_eq!(
+            max.mul(&max).to_string(),
+            BigUint::from_str(&"9".repeat(200))
+                .unwrap()
+                .square()
+                .to_string()
+        );
+    }
+}
@@ -0,0 +1,320 @@
+//! Random `BigUint`s specified in bits, as cryptographic sizes are, or
+//! bounded by another `BigUint`, as witnesses and residues are. The bits are
+//! drawn as 32-bit words and converted to decimal limbs as in
+//! `from_bytes_le`.
+
+use crate::bigint::BigInt;
+use crate::biguint::BigUint;
+use crate::binary::from_words;
+use crate::limbs::BASE;
+use rand::Rng;
+use rand::distributions::uniform::{SampleBorrow, SampleUniform, UniformSampler};
+
+/// A number drawn uniformly from the `bits`-bit numbers, `[2^(bits-1),
+/// 2^bits)`: the top bit is always set. Zero bits gives zero.
+///
+/// ```
+/// use bigmul::random_bits;
+/// use rand::SeedableRng;
+///
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+/// assert_eq!(random_bits(2048, &mut rng).bits(), 2048);
+/// ```
+pub fn random_bits<R: Rng + ?Sized>(bits: usize, rng: &mut R) -> BigUint {
+    if bits == 0 {
+        return BigUint::zero();
+    }
+    let mut words = random_words(bits, rng);
+    if let Some(top) = words.last_mut() {
+        *top |= 1 << ((bits - 1) % 32);
+    }
+    from_words(&words)
+}
+
+/// A number drawn uniformly from `[0, bound)`, by drawing from the
+/// `bound.bits()`-bit range and rejecting values not below `bound`; fewer
+/// than two draws are needed on average. Panics if `bound` is zero.
+///
+/// ```
+/// use bigmul::{BigUint, random_below};
+/// use rand::SeedableRng;
+///
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+/// let bound = BigUint::from_str("1000000000000000000000").unwrap();
+/// assert!(random_below(&bound, &mut rng) < bound);
+/// ```
+pub fn random_below<R: Rng + ?Sized>(bound: &BigUint, rng: &mut R) -> BigUint {
+    assert!(!bound.is_zero(), "random_below: bound is zero");
+    let bits = bound.bits();
+    loop {
+        let x = from_words(&random_words(bits, rng));
+        if x < *bound {
+            return x;
+        }
+    }
+}
+
+/// The [`UniformSampler`] behind `rng.gen_range(low..high)` and
+/// `Uniform::new(low, high)` for `BigUint`: `low` plus a draw below the
+/// width of the range. The draw works limb by limb from the top, so it is
+/// `O(n)` with no conversion from binary, and a decimal-aligned range such
+/// as `10^(d-1)..10^d` costs about one pass over its limbs.
+///
+/// ```
+/// use bigmul::BigUint;
+/// use rand::{Rng, SeedableRng};
+///
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+/// let (low, high) = (BigUint::from_str("10").unwrap().pow(30), BigUint::from_str("10").unwrap().pow(31));
+/// let x = rng.gen_range(low.clone()..high.clone());
+/// assert!(low <= x && x < high);
+/// ```
+#[derive(Clone, Debug)]
+pub struct UniformBigUint {
+    low: BigUint,
+    width: Vec<u32>,
+}
+
+impl UniformSampler for UniformBigUint {
+    type X = BigUint;
+
+    /// Panics if `low >= high`.
+    fn new<B1, B2>(low: B1, high: B2) -> Self
+    where
+        B1: SampleBorrow<BigUint> + Sized,
+        B2: SampleBorrow<BigUint> + Sized,
+    {
+        let (low, high) = (low.borrow(), high.borrow());
+        assert!(low < high, "UniformBigUint::new: low >= high");
+        UniformBigUint {
+            low: low.clone(),
+            width: high.sub(low).into_limbs(),
+        }
+    }
+
+    /// Panics if `low > high`.
+    fn new_inclusive<B1, B2>(low: B1, high: B2) -> Self
+    where
+        B1: SampleBorrow<BigUint> + Sized,
+        B2: SampleBorrow<BigUint> + Sized,
+    {
+        let (low, high) = (low.borrow(), high.borrow());
+        assert!(low <= high, "UniformBigUint::new_inclusive: low > high");
+        UniformBigUint::new(low, high.add(&BigUint::one()))
+    }
+
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> BigUint {
+        self.low.add(&below_limbs(&self.width, rng))
+    }
+}
+
+impl SampleUniform for BigUint {
+    type Sampler = UniformBigUint;
+}
+
+/// [`UniformBigUint`] for `BigInt` ranges, which may straddle zero.
+///
+/// ```
+/// use bigmul::BigInt;
+/// use rand::{Rng, SeedableRng};
+///
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+/// let bound = BigInt::from_str("-1000000000000").unwrap();
+/// let x = rng.gen_range(bound.clone()..=BigInt::zero());
+/// assert!(bound <= x && x <= BigInt::zero());
+/// ```
+#[derive(Clone, Debug)]
+pub struct UniformBigInt {
+    low: BigInt,
+    width: Vec<u32>,
+}
+
+impl UniformSampler for UniformBigInt {
+    type X = BigInt;
+
+    /// Panics if `low >= high`.
+    fn new<B1, B2>(low: B1, high: B2) -> Self
+    where
+        B1: SampleBorrow<BigInt> + Sized,
+        B2: SampleBorrow<BigInt> + Sized,
+    {
+        let (low, high) = (low.borrow(), high.borrow());
+        assert!(low < high, "UniformBigInt::new: low >= high");
+        UniformBigInt {
+            low: low.clone(),
+            width: high.abs_diff(low).into_limbs(),
+        }
+    }
+
+    /// Panics if `low > high`.
+    fn new_inclusive<B1, B2>(low: B1, high: B2) -> Self
+    where
+        B1: SampleBorrow<BigInt> + Sized,
+        B2: SampleBorrow<BigInt> + Sized,
+    {
+        let (low, high) = (low.borrow(), high.borrow());
+        assert!(low <= high, "UniformBigInt::new_inclusive: low > high");
+        UniformBigInt::new(low, high.add(&BigInt::one()))
+    }
+
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> BigInt {
+        self.low.add(&BigInt::from(below_limbs(&self.width, rng)))
+    }
+}
+
+impl SampleUniform for BigInt {
+    type Sampler = UniformBigInt;
+}
+
+/// A number drawn uniformly from `[0, width)` for normalized, nonzero
+/// `width` limbs. Limbs are drawn from the top, the first bounded by the
+/// top limb of `width`; a draw is rejected as soon as its prefix exceeds
+/// `width`'s, and once it falls below, the remaining limbs are free. Each
+/// attempt succeeds with probability at least one half.
+fn below_limbs<R: Rng + ?Sized>(width: &[u32], rng: &mut R) -> BigUint {
+    let base = BASE as u32;
+    let mut limbs = vec![0; width.len()];
+    'attempt: loop {
+        let mut bounded = true;
+        for (i, &w) in width.iter().enumerate().rev() {
+            let top = if i + 1 == width.len() { w } else { base - 1 };
+            let d = rng.gen_range(0..=top);
+            limbs[i] = d;
+            if bounded {
+                if d > w || (i == 0 && d == w) {
+                    continue 'attempt;
+                }
+                bounded = d == w;
+            }
+        }
+        return BigUint::from_kernel(limbs);
+    }
+}
+
+/// Uniformly random little-endian 32-bit words of a number below
+/// `2^bits`.
+fn random_words<R: Rng + ?Sized>(bits: usize, rng: &mut R) -> Vec<u32> {
+    let mut words: Vec<u32> = (0..bits.div_ceil(32))
+        .map(|_| rng.gen_range(0..=u32::MAX))
+        .collect();
+    if let Some(top) = words.last_mut() {
+        *top &= u32::MAX >> (32 - ((bits - 1) % 32 + 1));
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn random_bits_sets_exactly_the_top_bit() {
+        let mut rng = StdRng::seed_from_u64(6);
+        assert!(random_bits(0, &mut rng).is_zero());
+        assert!(random_bits(1, &mut rng).is_one());
+        for bits in [2, 28, 29, 30, 58, 64, 1000, 4096] {
+            for _ in 0..5 {
+                let x = random_bits(bits, &mut rng);
+                assert_eq!(x.bits(), bits);
+                assert!(x.div_rem_pow2(bits - 1).0.is_one(), "bits={}", bits);
+            }
+        }
+    }
+
+    #[test]
+    fn random_bits_is_uniform_over_small_ranges() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut counts = [0; 8];
+        for _ in 0..8000 {
+            let x: usize = random_bits(4, &mut rng).to_string().parse().unwrap();
+            counts[x - 8] += 1;
+        }
+        assert!(
+            counts.iter().all(|&c| (850..1150).contains(&c)),
+            "{:?}",
+            counts
+        );
+    }
+
+    #[test]
+    fn random_below_stays_below_and_covers_the_range() {
+        let mut rng = StdRng::seed_from_u64(8);
+        assert!(random_below(&BigUint::one(), &mut rng).is_zero());
+        let six = BigUint::from_str("6").unwrap();
+        let mut counts = [0; 6];
+        for _ in 0..6000 {
+            let x: usize = random_below(&six, &mut rng).to_string().parse().unwrap();
+            counts[x] += 1;
+        }
+        assert!(
+            counts.iter().all(|&c| (850..1150).contains(&c)),
+            "{:?}",
+            counts
+        );
+        let bound = BigUint::from_str(&format!("1{}", "0".repeat(300))).unwrap();
+        let mut high = 0;
+        for _ in 0..50 {
+            let x = random_below(&bound, &mut rng);
+            assert!(x < bound);
+            high += (x.to_string().len() == 300) as usize;
+        }
+        // About nine in ten values below 10^300 have 300 digits.
+        assert!(high > 35, "{}", high);
+    }
+
+    #[test]
+    fn gen_range_covers_signed_and_unsigned_ranges() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut counts = [0; 7];
+        for _ in 0..7000 {
+            let x = rng.gen_range(BigInt::from_str("-3").unwrap()..=BigInt::from_str("3").unwrap());
+            let x: i64 = x.to_string().parse().unwrap();
+            counts[(x + 3) as usize] += 1;
+        }
+        assert!(
+            counts.iter().all(|&c| (850..1150).contains(&c)),
+            "{:?}",
+            counts
+        );
+        let low = BigUint::from_str(&format!("1{}", "0".repeat(50))).unwrap();
+        let high = low.add(&BigUint::from_str("5").unwrap());
+        for _ in 0..100 {
+            let x = rng.gen_range(low.clone()..high.clone());
+            assert!(low <= x && x < high);
+        }
+        let same = BigUint::from_str("42").unwrap();
+        assert_eq!(rng.gen_range(same.clone()..=same.clone()), same);
+    }
+
+    #[test]
+    fn gen_range_draws_limb_by_limb() {
+        let mut rng = StdRng::seed_from_u64(12);
+        // 3 * 10^9: the top limb is uniform over 0, 1 and 2.
+        let high = BigUint::from(3 * BASE);
+        let mut counts = [0; 3];
+        for _ in 0..3000 {
+            let x = rng.gen_range(BigUint::zero()..high.clone());
+            counts[x.div_rem_pow_base(1).0.as_limbs()[0] as usize] += 1;
+        }
+        assert!(
+            counts.iter().all(|&c| (850..1150).contains(&c)),
+            "{:?}",
+            counts
+        );
+        let ten = BigUint::from(10u64);
+        for d in [1, 9, 10, 100] {
+            let (low, high) = (ten.pow(d - 1), ten.pow(d));
+            for _ in 0..20 {
+                let x = rng.gen_range(low.clone()..high.clone());
+                assert_eq!(x.to_string().len(), d as usize);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "bound is zero")]
+    fn random_below_zero_panics() {
+        random_below(&BigUint::zero(), &mut StdRng::seed_from_u64(0));
+    }
+}
@@ -0,0 +1,275 @@
+use crate::bigint::{BigInt, Sign};
+use crate::biguint::BigUint;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Neg;
+
+/// An exact fraction: a `BigInt` numerator over a positive `BigUint`
+/// denominator, always in lowest terms. Zero is `0/1`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BigRational {
+    numer: BigInt,
+    denom: BigUint,
+}
+
+impl BigRational {
+    /// Builds `numer / denom` in lowest terms. Panics if `denom` is zero.
+    pub fn new(numer: BigInt, denom: BigInt) -> Self {
+        assert!(!denom.is_zero(), "denominator must be non-zero");
+        let sign = match denom.sign() {
+            Sign::Minus => -numer.sign(),
+            _ => numer.sign(),
+        };
+        let (_, n) = numer.into_parts();
+        let (_, d) = denom.into_parts();
+        BigRational::reduced(sign, n, d)
+    }
+
+    pub fn from_integer(n: BigInt) -> Self {
+        BigRational {
+            numer: n,
+            denom: BigUint::one(),
+        }
+    }
+
+    fn reduced(sign: Sign, n: BigUint, d: BigUint) -> Self {
+        let g = n.gcd(&d);
+        let (n, d) = if g.digits == [1] {
+            (n, d)
+        } else {
+            (n.div_rem(&g).0, d.div_rem(&g).0)
+        };
+        let numer = BigInt::from_biguint(sign, n);
+        let denom = if numer.is_zero() { BigUint::one() } else { d };
+        BigRational { numer, denom }
+    }
+
+    pub fn numer(&self) -> &BigInt {
+        &self.numer
+    }
+
+    pub fn denom(&self) -> &BigUint {
+        &self.denom
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numer.is_zero()
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denom.digits == [1]
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(&self, other: &BigRational) -> BigRational {
+        let d = BigInt::from(self.denom.clone());
+        let od = BigInt::from(other.denom.clone());
+        let n = self
+            .numer
+            .mul_karatsuba(&od)
+            .add(&other.numer.mul_karatsuba(&d));
+        BigRational::new(n, d.mul_karatsuba(&od))
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn sub(&self, other: &BigRational) -> BigRational {
+        self.add(&-other)
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn mul(&self, other: &BigRational) -> BigRational {
+        let n = self.numer.mul_karatsuba(&other.numer);
+        let (sign, n) = n.into_parts();
+        BigRational::reduced(sign, n, self.denom.mul_karatsuba(&other.denom))
+    }
+
+    /// Computes `self / other`. Panics if `other` is zero.
+    #[allow(clippy::should_implement_trait)]
+    pub fn div(&self, other: &BigRational) -> BigRational {
+        self.mul(&other.recip())
+    }
+
+    /// Returns `1 / self`. Panics if `self` is zero.
+    pub fn recip(&self) -> BigRational {
+        assert!(!self.is_zero(), "reciprocal of zero");
+        BigRational {
+            numer: BigInt::from_biguint(self.numer.sign(), self.denom.clone()),
+            denom: self.numer.magnitude().clone(),
+        }
+    }
+
+    /// Computes `self^exp` for any signed exponent, negative exponents
+    /// giving the reciprocal power. The result needs no reduction: powers of
+    /// coprime numbers stay coprime. Panics if `self` is zero and `exp` is
+    /// negative.
+    pub fn pow(&self, exp: i64) -> BigRational {
+        let k = exp.unsigned_abs();
+        let power = BigRational {
+            numer: self.numer.pow(k),
+            denom: self.denom.pow(k),
+        };
+        if exp < 0 {
+            assert!(!self.is_zero(), "zero to a negative power");
+            power.recip()
+        } else {
+            power
+        }
+    }
+
+    /// Decimal expansion truncated toward zero after `digits` places, e.g.
+    /// `-1/3` to 4 places is `"-0.3333"`.
+    pub fn to_decimal(&self, digits: usize) -> String {
+        let scaled = self
+            .numer
+            .magnitude()
+            .mul_karatsuba(&BigUint::from(10u64).pow(digits as u64));
+        let q = scaled.div_rem(&self.denom).0.to_string();
+        let q = format!("{:0>width$}", q, width = digits + 1);
+        let (int, frac) = q.split_at(q.len() - digits);
+        let sign = if self.numer.sign() == Sign::Minus && q.bytes().any(|b| b != b'0') {
+            "-"
+        } else {
+            ""
+        };
+        if digits == 0 {
+            format!("{}{}", sign, int)
+        } else {
+            format!("{}{}.{}", sign, int, frac)
+        }
+    }
+}
+
+impl Default for BigRational {
+    fn default() -> Self {
+        BigRational::from_integer(BigInt::new())
+    }
+}
+
+impl From<BigInt> for BigRational {
+    fn from(n: BigInt) -> Self {
+        BigRational::from_integer(n)
+    }
+}
+
+impl Neg for BigRational {
+    type Output = BigRational;
+
+    fn neg(self) -> BigRational {
+        BigRational {
+            numer: -self.numer,
+            denom: self.denom,
+        }
+    }
+}
+
+impl Neg for &BigRational {
+    type Output = BigRational;
+
+    fn neg(self) -> BigRational {
+        -self.clone()
+    }
+}
+
+impl PartialOrd for BigRational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigRational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let lhs = self.numer.mul_karatsuba(&BigInt::from(other.denom.clone()));
+        let rhs = other.numer.mul_karatsuba(&BigInt::from(self.denom.clone()));
+        lhs.cmp(&rhs)
+    }
+}
+
+impl fmt::Display for BigRational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn q(n: &str, d: &str) -> BigRational {
+        BigRational::new(BigInt::from_str(n).unwrap(), BigInt::from_str(d).unwrap())
+    }
+
+    #[test]
+    fn construction_reduces_and_moves_sign() {
+        assert_eq!(q("6", "-4").to_string(), "-3/2");
+        assert_eq!(q("-6", "-4").to_string(), "3/2");
+        assert_eq!(q("0", "-7").to_string(), "0");
+        assert_eq!(q("0", "-7"), BigRational::default());
+        assert_eq!(q("10000000000", "5").to_string(), "2000000000");
+    }
+
+    #[test]
+    #[should_panic(expected = "denominator must be non-zero")]
+    fn zero_denominator_panics() {
+        q("1", "0");
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = q("1", "3");
+        let b = q("-1", "6");
+        assert_eq!(a.add(&b), q("1", "6"));
+        assert_eq!(a.sub(&b), q("1", "2"));
+        assert_eq!(a.mul(&b), q("-1", "18"));
+        assert_eq!(a.div(&b), q("-2", "1"));
+        assert_eq!(b.recip(), q("-6", "1"));
+        assert!(a.sub(&a).is_zero());
+        // sum of 1/(k(k+1)) for k = 1..50 telescopes to 50/51
+        let sum = (1..=50u32).fold(BigRational::default(), |acc, k| {
+            acc.add(&q("1", &(k * (k + 1)).to_string()))
+        });
+        assert_eq!(sum, q("50", "51"));
+    }
+
+    #[test]
+    fn signed_powers() {
+        assert_eq!(q("2", "3").pow(-3).to_string(), "27/8");
+        assert_eq!(q("-2", "3").pow(3).to_string(), "-8/27");
+        assert_eq!(q("-2", "3").pow(-2).to_string(), "9/4");
+        assert_eq!(q("-2", "3").pow(0).to_string(), "1");
+        assert_eq!(q("0", "1").pow(5).to_string(), "0");
+    }
+
+    #[test]
+    #[should_panic(expected = "zero to a negative power")]
+    fn zero_to_negative_power_panics() {
+        q("0", "1").pow(-1);
+    }
+
+    #[test]
+    fn ordering() {
+        let mut v = [
+            q("1", "2"),
+            q("-1", "3"),
+            q("2", "3"),
+            q("-1", "2"),
+            q("0", "1"),
+        ];
+        v.sort();
+        let sorted: Vec<String> = v.iter().map(|x| x.to_string()).collect();
+        assert_eq!(sorted, ["-1/2", "-1/3", "0", "1/2", "2/3"]);
+    }
+
+    #[test]
+    fn decimal_expansion_truncates() {
+        assert_eq!(q("1", "3").to_decimal(5), "0.33333");
+        assert_eq!(q("-2", "3").to_decimal(3), "-0.666");
+        assert_eq!(q("-1", "3000").to_decimal(2), "0.00");
+        assert_eq!(q("22", "7").to_decimal(0), "3");
+        assert_eq!(q("1", "7").to_decimal(24), "0.142857142857142857142857");
+        assert_eq!(q("123456789012", "1000").to_decimal(4), "123456789.0120");
+    }
+}
@@ -0,0 +1,846 @@
+//! Charts of measured comparisons, drawn the same way by the `bigmul`
+//! binary and by code that runs a [`Sweep`](crate::Sweep): one line per
+//! algorithm over the operand size, as raw times or as speedups over a
+//! baseline, on linear or log-log axes, or as stacked areas showing how a
+//! total splits into parts. Each algorithm keeps one [`Style`], color and
+//! marker, in every chart. Charts render to PNG files or to SVG text for
+//! embedding in HTML, alone or as the panels of a [`Figure`].
+
+use crate::sweep::Results;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::path::Path;
+
+/// Line colors, assigned to series in order.
+pub const PALETTE: [RGBColor; 8] = [
+    RED,
+    GREEN,
+    BLUE,
+    MAGENTA,
+    CYAN,
+    BLACK,
+    RGBColor(255, 140, 0),
+    RGBColor(128, 0, 128),
+];
+
+/// A point marker drawn along a line, so lines stay apart where colors
+/// are hard to tell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Marker {
+    Circle,
+    Square,
+    Triangle,
+    Cross,
+}
+
+impl Marker {
+    pub const ALL: [Marker; 4] = [
+        Marker::Circle,
+        Marker::Square,
+        Marker::Triangle,
+        Marker::Cross,
+    ];
+}
+
+/// How one algorithm is drawn, in every chart and every run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Style {
+    pub color: RGBColor,
+    pub marker: Marker,
+}
+
+/// The algorithms of this crate and of its optional features, in
+/// [`PALETTE`] order.
+const STYLES: [(&str, Style); 8] = [
+    ("direct", Style::new(PALETTE[0], Marker::Circle)),
+    ("dc", Style::new(PALETTE[1], Marker::Square)),
+    ("kara", Style::new(PALETTE[2], Marker::Triangle)),
+    ("kara_sub", Style::new(PALETTE[3], Marker::Cross)),
+    ("kara_par", Style::new(PALETTE[4], Marker::Circle)),
+    ("gpu", Style::new(PALETTE[5], Marker::Square)),
+    ("ibig", Style::new(PALETTE[6], Marker::Triangle)),
+    ("malachite", Style::new(PALETTE[7], Marker::Cross)),
+];
+
+impl Style {
+    pub const fn new(color: RGBColor, marker: Marker) -> Self {
+        Style { color, marker }
+    }
+
+    /// The style of the algorithm named `name`. Known algorithms have fixed
+    /// styles; any other name, such as an `--external` program's, gets one
+    /// picked by a hash of the name, so it too looks the same in every
+    /// chart whatever else is drawn with it.
+    ///
+    /// ```
+    /// use bigmul::report::{PALETTE, Style};
+    ///
+    /// assert_eq!(Style::of("direct").color, PALETTE[0]);
+    /// assert_eq!(Style::of("my-plugin"), Style::of("my-plugin"));
+    /// ```
+    pub fn of(name: &str) -> Style {
+        if let Some((_, style)) = STYLES.iter().find(|(known, _)| *known == name) {
+            return *style;
+        }
+        // FNV-1a, which unlike std's hasher is the same in every build.
+        let hash = name.bytes().fold(0xcbf2_9ce4_8422_2325u64, |h, b| {
+            (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+        }) as usize;
+        Style::new(
+            PALETTE[hash % PALETTE.len()],
+            Marker::ALL[hash / PALETTE.len() % Marker::ALL.len()],
+        )
+    }
+}
+
+/// What the vertical axis shows.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Kind {
+    /// Each series' values as given.
+    Times,
+    /// The named series' value divided by each series' value at the same
+    /// size, so higher is faster and the baseline is a flat line at 1.
+    Speedup { baseline: String },
+    /// Each series added onto the ones before it and filled down to them,
+    /// so the bands show the parts of a total and the top edge the total.
+    Stacked,
+}
+
+/// How both axes are scaled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Scale {
+    #[default]
+    Linear,
+    /// Both coordinates are plotted as their base-10 logarithms, for
+    /// sweeps spanning orders of magnitude.
+    LogLog,
+}
+
+/// One line: its legend label, color, marker if any and `(size, value)`
+/// points.
+#[derive(Clone, Debug)]
+struct Line {
+    label: String,
+    color: RGBColor,
+    marker: Option<Marker>,
+    points: Vec<(f64, f64)>,
+}
+
+/// Markers drawn along one line at most, however many points it has.
+const MAX_MARKERS: usize = 12;
+
+/// A line and its points in plotting coordinates.
+type Plotted<'a> = (&'a Line, Vec<(f32, f32)>);
+
+/// A line chart, built up with the methods below and drawn by
+/// [`Chart::draw_png`] or [`Chart::to_svg`].
+///
+/// ```no_run
+/// use bigmul::Sweep;
+/// use bigmul::report::{Chart, Kind};
+///
+/// let results = Sweep::new().run();
+/// Chart::from_results(&results).draw_png("times.png".as_ref()).unwrap();
+/// let speedups = Chart::from_results(&results).kind(Kind::Speedup {
+///     baseline: "direct".to_string(),
+/// });
+/// let svg = speedups.to_svg().unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Chart {
+    title: String,
+    x_desc: String,
+    y_desc: Option<String>,
+    kind: Kind,
+    scale: Scale,
+    dimensions: (u32, u32),
+    smoothing: usize,
+    max_points: Option<usize>,
+    crossovers: Option<String>,
+    lines: Vec<Line>,
+}
+
+impl Default for Chart {
+    fn default() -> Self {
+        Chart::new()
+    }
+}
+
+impl Chart {
+    /// An empty 800x600 chart of times on linear axes.
+    pub fn new() -> Self {
+        Chart {
+            title: "Multiplication Algorithms Comparison".to_string(),
+            x_desc: "Input Size".to_string(),
+            y_desc: None,
+            kind: Kind::Times,
+            scale: Scale::Linear,
+            dimensions: (800, 600),
+            smoothing: 1,
+            max_points: None,
+            crossovers: None,
+            lines: Vec::new(),
+        }
+    }
+
+    /// One line per algorithm of `results` in its [`Style`], over sizes in
+    /// limbs.
+    pub fn from_results(results: &Results) -> Self {
+        let sizes: Vec<f64> = results.sizes.iter().map(|&n| n as f64).collect();
+        results.series.iter().fold(
+            Chart::new().x_desc("Input Size (number of limbs)"),
+            |chart, (name, times)| {
+                let points = sizes.iter().copied().zip(times.iter().copied());
+                chart.series_styled(*name, points, Style::of(name))
+            },
+        )
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn x_desc(mut self, desc: impl Into<String>) -> Self {
+        self.x_desc = desc.into();
+        self
+    }
+
+    /// The vertical axis title, which otherwise follows the [`Kind`].
+    pub fn y_desc(mut self, desc: impl Into<String>) -> Self {
+        self.y_desc = Some(desc.into());
+        self
+    }
+
+    pub fn kind(mut self, kind: Kind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn scale(mut self, scale: Scale) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Width and height in pixels.
+    pub fn dimensions(mut self, width: u32, height: u32) -> Self {
+        self.dimensions = (width, height);
+        self
+    }
+
+    /// Draws each value as the mean of the `window` values around it, to
+    /// calm noisy dense sweeps; 1 (the default) draws them as measured.
+    pub fn smooth(mut self, window: usize) -> Self {
+        self.smoothing = window.max(1);
+        self
+    }
+
+    /// Draws at most `max` evenly spaced points of each line, always
+    /// including the last, so hundreds of sizes stay legible.
+    pub fn max_points(mut self, max: usize) -> Self {
+        self.max_points = Some(max.max(2));
+        self
+    }
+
+    /// Marks where each line overtakes the one labeled `baseline`, as found
+    /// by [`crossover`] on the measured values, with a vertical line and
+    /// the size in the line's color.
+    pub fn crossovers(mut self, baseline: impl Into<String>) -> Self {
+        self.crossovers = Some(baseline.into());
+        self
+    }
+
+    /// Adds a line in the next [`PALETTE`] color.
+    pub fn series(
+        self,
+        label: impl Into<String>,
+        points: impl IntoIterator<Item = (f64, f64)>,
+    ) -> Self {
+        let color = PALETTE[self.lines.len() % PALETTE.len()];
+        self.series_with_color(label, points, color)
+    }
+
+    /// Adds a line in `color`, for keeping colors fixed across charts that
+    /// show different subsets of the same algorithms.
+    pub fn series_with_color(
+        mut self,
+        label: impl Into<String>,
+        points: impl IntoIterator<Item = (f64, f64)>,
+        color: RGBColor,
+    ) -> Self {
+        self.lines.push(Line {
+            label: label.into(),
+            color,
+            marker: None,
+            points: points.into_iter().collect(),
+        });
+        self
+    }
+
+    /// Adds a line in `style`, normally an algorithm's [`Style::of`].
+    pub fn series_styled(
+        mut self,
+        label: impl Into<String>,
+        points: impl IntoIterator<Item = (f64, f64)>,
+        style: Style,
+    ) -> Self {
+        self = self.series_with_color(label, points, style.color);
+        self.lines.last_mut().unwrap().marker = Some(style.marker);
+        self
+    }
+
+    /// Writes the chart to `path` as a PNG.
+    pub fn draw_png(&self, path: &Path) -> Result<(), String> {
+        let root = BitMapBackend::new(path, self.dimensions).into_drawing_area();
+        self.draw(&root)
+            .map_err(|e| format!("failed to draw {}: {}", path.display(), e))
+    }
+
+    /// The chart as an SVG document.
+    pub fn to_svg(&self) -> Result<String, String> {
+        let mut svg = String::new();
+        {
+            let root = SVGBackend::with_string(&mut svg, self.dimensions).into_drawing_area();
+            self.draw(&root)
+                .map_err(|e| format!("failed to draw SVG: {}", e))?;
+        }
+        Ok(svg)
+    }
+
+    /// The lines as plotted: speedups divided out, stacked values summed,
+    /// smoothed and thinned out, and sizes and values logged under
+    /// [`Scale::LogLog`].
+    fn plotted(&self) -> Result<Vec<Plotted<'_>>, String> {
+        let baseline = match &self.kind {
+            Kind::Times | Kind::Stacked => None,
+            Kind::Speedup { baseline } => Some(
+                self.lines
+                    .iter()
+                    .find(|l| l.label == *baseline)
+                    .ok_or_else(|| format!("no series named '{}'", baseline))?,
+            ),
+        };
+        let scale = |v: f64| match self.scale {
+            Scale::Linear => v as f32,
+            Scale::LogLog => v.log10() as f32,
+        };
+        Ok(self
+            .lines
+            .iter()
+            .map(|line| {
+                let points: Vec<(f64, f64)> = line
+                    .points
+                    .iter()
+                    .filter_map(|&(x, y)| {
+                        let y = match (&self.kind, baseline) {
+                            (Kind::Stacked, _) => self.stacked_at(line, x)?,
+                            (_, None) => y,
+                            (_, Some(base)) => base.points.iter().find(|p| p.0 == x)?.1 / y,
+                        };
+                        Some((x, y))
+                    })
+                    .collect();
+                let points = moving_average(&points, self.smoothing);
+                let points = decimate(points, self.max_points);
+                (
+                    line,
+                    points.iter().map(|&(x, y)| (scale(x), scale(y))).collect(),
+                )
+            })
+            .collect())
+    }
+
+    /// The sum at size `x` of `line` and every line before it, or `None` if
+    /// any of them has no point there.
+    fn stacked_at(&self, line: &Line, x: f64) -> Option<f64> {
+        let mut total = 0.0;
+        for below in &self.lines {
+            total += below.points.iter().find(|p| p.0 == x)?.1;
+            if std::ptr::eq(below, line) {
+                break;
+            }
+        }
+        Some(total)
+    }
+
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> Result<(), String>
+    where
+        DB::ErrorType: 'static,
+    {
+        let lines = self.plotted()?;
+        let all = || lines.iter().flat_map(|(_, points)| points.iter());
+        if all().next().is_none() {
+            return Err("nothing to chart".to_string());
+        }
+        let log = self.scale == Scale::LogLog;
+        let x_min = all().map(|p| p.0).fold(f32::MAX, f32::min);
+        let x_max = all().map(|p| p.0).fold(f32::MIN, f32::max);
+        let y_min = all().map(|p| p.1).fold(f32::MAX, f32::min);
+        let y_max = all().map(|p| p.1).fold(f32::MIN, f32::max);
+        let (x_range, y_range) = if log {
+            // Margins of a factor 1.5 either way, in log space.
+            let margin = 1.5f32.log10();
+            (
+                x_min..x_max + 0.01,
+                y_min.max(-9.0) - margin..y_max + margin,
+            )
+        } else {
+            (x_min..x_max + 1.0, 0f32..y_max * 1.1)
+        };
+
+        let e = |e: DrawingAreaErrorKind<DB::ErrorType>| e.to_string();
+        root.fill(&WHITE).map_err(e)?;
+        let mut chart = ChartBuilder::on(root)
+            .caption(&self.title, ("sans-serif", caption_size(root)).into_font())
+            .margin(10)
+            .x_label_area_size(40)
+            .y_label_area_size(60)
+            .build_cartesian_2d(x_range, y_range)
+            .map_err(e)?;
+
+        let y_desc = match (&self.y_desc, &self.kind) {
+            (Some(desc), _) => desc.clone(),
+            (None, Kind::Times) => "Average Execution Time (seconds)".to_string(),
+            (None, Kind::Speedup { baseline }) => format!("Speedup over {}", baseline),
+            (None, Kind::Stacked) => "Cumulative Execution Time (seconds)".to_string(),
+        };
+        let (x_desc, y_desc) = if log {
+            (log_desc(&self.x_desc), log_desc(&y_desc))
+        } else {
+            (self.x_desc.clone(), y_desc)
+        };
+        let x_unlog = |x: &f32| format!("{:.0}", 10f64.powf(*x as f64));
+        let y_unlog = |y: &f32| format!("{:.0e}", 10f64.powf(*y as f64));
+        let mut mesh = chart.configure_mesh();
+        mesh.x_desc(x_desc).y_desc(y_desc);
+        if log {
+            mesh.x_label_formatter(&x_unlog).y_label_formatter(&y_unlog);
+        }
+        mesh.draw().map_err(e)?;
+
+        if let Kind::Speedup { .. } = self.kind {
+            let one = if log { 0.0 } else { 1.0 };
+            chart
+                .draw_series(LineSeries::new(
+                    [(x_min, one), (x_max, one)],
+                    BLACK.mix(0.2),
+                ))
+                .map_err(e)?;
+        }
+        let stacked = self.kind == Kind::Stacked;
+        let y_floor = chart.y_range().start;
+        let mut below: Option<Vec<(f32, f32)>> = None;
+        if let Some(baseline) = &self.crossovers {
+            let base = self
+                .lines
+                .iter()
+                .find(|l| l.label == *baseline)
+                .ok_or_else(|| format!("no series named '{}'", baseline))?;
+            let marked = self
+                .lines
+                .iter()
+                .filter(|l| !std::ptr::eq(*l, base))
+                .filter_map(|l| Some((l, crossover(&base.points, &l.points)?)));
+            let (bottom, top) = (chart.y_range().start, chart.y_range().end);
+            // Labels stacked down from the top, one line each.
+            let step = (top - bottom) / 20.0;
+            for (k, (line, x)) in marked.enumerate() {
+                let at = if log { x.log10() as f32 } else { x as f32 };
+                let y = top - step * (k + 1) as f32;
+                chart
+                    .draw_series(DashedLineSeries::new(
+                        [(at, bottom), (at, top)],
+                        6,
+                        4,
+                        line.color.into(),
+                    ))
+                    .map_err(e)?;
+                chart
+                    .draw_series(std::iter::once(Text::new(
+                        format!(" {}", x),
+                        (at, y),
+                        ("sans-serif", 15).into_font().color(&line.color),
+                    )))
+                    .map_err(e)?;
+            }
+        }
+        for (line, points) in lines {
+            let color = line.color;
+            if stacked {
+                // The band between this edge and the one below, or the axis.
+                let floor = below
+                    .take()
+                    .unwrap_or_else(|| points.iter().map(|&(x, _)| (x, y_floor)).collect());
+                let band: Vec<(f32, f32)> = points
+                    .iter()
+                    .copied()
+                    .chain(floor.into_iter().rev())
+                    .collect();
+                chart
+                    .draw_series(std::iter::once(Polygon::new(band, color.mix(0.3))))
+                    .map_err(e)?;
+                below = Some(points.clone());
+            }
+            if let Some(marker) = line.marker {
+                let stride = points.len().div_ceil(MAX_MARKERS).max(1);
+                let at = points.iter().step_by(stride).map(|&p| EmptyElement::at(p));
+                let (filled, stroke) = (color.filled(), color.stroke_width(2));
+                match marker {
+                    Marker::Circle => {
+                        chart.draw_series(at.map(|p| p + Circle::new((0, 0), 3, filled)))
+                    }
+                    Marker::Square => chart
+                        .draw_series(at.map(|p| p + Rectangle::new([(-3, -3), (3, 3)], filled))),
+                    Marker::Triangle => {
+                        chart.draw_series(at.map(|p| p + TriangleMarker::new((0, 0), 4, filled)))
+                    }
+                    Marker::Cross => {
+                        chart.draw_series(at.map(|p| p + Cross::new((0, 0), 3, stroke)))
+                    }
+                }
+                .map_err(e)?;
+            }
+            let marker = line.marker;
+            chart
+                .draw_series(LineSeries::new(points, color))
+                .map_err(e)?
+                .label(&line.label)
+                .legend(move |(x, y)| {
+                    if stacked {
+                        let band = color.mix(0.3).filled();
+                        return Rectangle::new([(x, y - 5), (x + 20, y + 5)], band).into_dyn();
+                    }
+                    // A styled line is keyed by its marker, slightly larger
+                    // than along the line.
+                    let (at, filled, stroke) = ((x + 10, y), color.filled(), color.stroke_width(2));
+                    match marker {
+                        None => PathElement::new(vec![(x, y), (x + 20, y)], color).into_dyn(),
+                        Some(Marker::Circle) => Circle::new(at, 4, filled).into_dyn(),
+                        Some(Marker::Square) => {
+                            Rectangle::new([(x + 6, y - 4), (x + 14, y + 4)], filled).into_dyn()
+                        }
+                        Some(Marker::Triangle) => TriangleMarker::new(at, 5, filled).into_dyn(),
+                        Some(Marker::Cross) => Cross::new(at, 4, stroke).into_dyn(),
+                    }
+                });
+        }
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()
+            .map_err(e)?;
+        root.present().map_err(e)
+    }
+}
+
+/// Caption font size for an area: 50 on a full 600-pixel chart, smaller on
+/// a panel of a [`Figure`].
+fn caption_size<DB: DrawingBackend>(area: &DrawingArea<DB, Shift>) -> u32 {
+    (area.dim_in_pixel().1 / 12).clamp(16, 50)
+}
+
+/// Several charts of the same sweep stacked one above another at the same
+/// width, such as time, memory and operation counts, so one image shows
+/// every measure. Charts over the same sizes line up on the shared
+/// horizontal axis.
+///
+/// ```no_run
+/// use bigmul::Sweep;
+/// use bigmul::report::{Chart, Figure, Kind};
+///
+/// let results = Sweep::new().run();
+/// Figure::new()
+///     .title("Sweep")
+///     .panel(Chart::from_results(&results).title("Time"))
+///     .panel(Chart::from_results(&results).title("Speedup").kind(Kind::Speedup {
+///         baseline: "direct".to_string(),
+///     }))
+///     .draw_png("sweep.png".as_ref())
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Figure {
+    title: String,
+    width: u32,
+    panel_height: u32,
+    panels: Vec<Chart>,
+}
+
+impl Default for Figure {
+    fn default() -> Self {
+        Figure::new()
+    }
+}
+
+impl Figure {
+    /// An untitled figure of 800x360 panels.
+    pub fn new() -> Self {
+        Figure {
+            title: String::new(),
+            width: 800,
+            panel_height: 360,
+            panels: Vec::new(),
+        }
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Width and height in pixels of each panel.
+    pub fn panel_dimensions(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.panel_height = height;
+        self
+    }
+
+    /// Adds `chart` below the panels so far; its own dimensions are
+    /// ignored.
+    pub fn panel(mut self, chart: Chart) -> Self {
+        self.panels.push(chart);
+        self
+    }
+
+    /// Writes the figure to `path` as a PNG.
+    pub fn draw_png(&self, path: &Path) -> Result<(), String> {
+        let root = BitMapBackend::new(path, self.dimensions()).into_drawing_area();
+        self.draw(&root)
+            .map_err(|e| format!("failed to draw {}: {}", path.display(), e))
+    }
+
+    /// The figure as an SVG document.
+    pub fn to_svg(&self) -> Result<String, String> {
+        let mut svg = String::new();
+        {
+            let root = SVGBackend::with_string(&mut svg, self.dimensions()).into_drawing_area();
+            self.draw(&root)
+                .map_err(|e| format!("failed to draw SVG: {}", e))?;
+        }
+        Ok(svg)
+    }
+
+    /// The whole figure: the panels plus a band for the title.
+    fn dimensions(&self) -> (u32, u32) {
+        let title = if self.title.is_empty() { 0 } else { 60 };
+        (
+            self.width,
+            self.panel_height * self.panels.len() as u32 + title,
+        )
+    }
+
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> Result<(), String>
+    where
+        DB::ErrorType: 'static,
+    {
+        if self.panels.is_empty() {
+            return Err("no panels to draw".to_string());
+        }
+        let e = |e: DrawingAreaErrorKind<DB::ErrorType>| e.to_string();
+        root.fill(&WHITE).map_err(e)?;
+        let body = if self.title.is_empty() {
+            root.clone()
+        } else {
+            root.titled(&self.title, ("sans-serif", 40)).map_err(e)?
+        };
+        for (chart, area) in self
+            .panels
+            .iter()
+            .zip(body.split_evenly((self.panels.len(), 1)))
+        {
+            chart.draw(&area)?;
+        }
+        root.present().map_err(e)
+    }
+}
+
+/// The smallest size from which `other` is below `baseline` at every size
+/// both were measured at, so noise at one small size cannot claim the
+/// crossover; `None` if `other` is not below at the largest shared size.
+///
+/// ```
+/// use bigmul::report::crossover;
+///
+/// let direct = [(100.0, 1.0), (200.0, 4.0), (300.0, 9.0), (400.0, 16.0)];
+/// let karatsuba = [(100.0, 0.9), (200.0, 5.0), (300.0, 7.0), (400.0, 11.0)];
+/// assert_eq!(crossover(&direct, &karatsuba), Some(300.0));
+/// ```
+pub fn crossover(baseline: &[(f64, f64)], other: &[(f64, f64)]) -> Option<f64> {
+    let mut from = None;
+    for &(x, y) in other {
+        let Some(&(_, base)) = baseline.iter().find(|p| p.0 == x) else {
+            continue;
+        };
+        from = if y < base { from.or(Some(x)) } else { None };
+    }
+    from
+}
+
+/// Each value replaced by the mean of the `window` values centered on it,
+/// fewer at the ends.
+fn moving_average(points: &[(f64, f64)], window: usize) -> Vec<(f64, f64)> {
+    let (before, after) = ((window - 1) / 2, window / 2);
+    (0..points.len())
+        .map(|i| {
+            let around = &points[i.saturating_sub(before)..(i + after + 1).min(points.len())];
+            let mean = around.iter().map(|p| p.1).sum::<f64>() / around.len() as f64;
+            (points[i].0, mean)
+        })
+        .collect()
+}
+
+/// Every `k`-th point and the last, for the smallest `k` leaving at most
+/// `max`.
+fn decimate(points: Vec<(f64, f64)>, max: Option<usize>) -> Vec<(f64, f64)> {
+    let Some(max) = max.filter(|&max| points.len() > max) else {
+        return points;
+    };
+    let stride = (points.len() - 1).div_ceil(max - 1);
+    let last = points.len() - 1;
+    points
+        .into_iter()
+        .enumerate()
+        .filter(|&(i, _)| i % stride == 0 || i == last)
+        .map(|(_, p)| p)
+        .collect()
+}
+
+/// `desc` marked as log scale, inside its trailing parenthesis if it has
+/// one: `Time (seconds)` becomes `Time (seconds, log scale)`.
+fn log_desc(desc: &str) -> String {
+    match desc.strip_suffix(')') {
+        Some(open) if open.contains('(') => format!("{}, log scale)", open),
+        _ => format!("{} (log scale)", desc),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results() -> Results {
+        Results {
+            sizes: vec![10, 100, 1000],
+            series: vec![
+                ("direct", vec![1e-6, 1e-4, 1e-2]),
+                ("kara", vec![2e-6, 5e-5, 2e-3]),
+            ],
+        }
+    }
+
+    #[test]
+    fn speedups_divide_by_the_baseline() {
+        let chart = Chart::from_results(&results()).kind(Kind::Speedup {
+            baseline: "direct".to_string(),
+        });
+        let plotted = chart.plotted().unwrap();
+        assert_eq!(plotted[0].1, [(10.0, 1.0), (100.0, 1.0), (1000.0, 1.0)]);
+        assert_eq!(plotted[1].1, [(10.0, 0.5), (100.0, 2.0), (1000.0, 5.0)]);
+        let log = chart.clone().scale(Scale::LogLog);
+        assert_eq!(log.plotted().unwrap()[1].1[2], (3.0, 5f64.log10() as f32));
+        let missing = Chart::from_results(&results()).kind(Kind::Speedup {
+            baseline: "fft".to_string(),
+        });
+        assert!(missing.plotted().is_err());
+    }
+
+    #[test]
+    fn svg_names_every_series() {
+        let svg = Chart::from_results(&results())
+            .title("Sweep")
+            .scale(Scale::LogLog)
+            .to_svg()
+            .unwrap();
+        assert!(svg.starts_with("<svg"));
+        for text in ["Sweep", "direct", "kara", "log scale"] {
+            assert!(svg.contains(text), "{}", text);
+        }
+        assert!(Chart::new().to_svg().is_err());
+        assert_eq!(log_desc("Speedup"), "Speedup (log scale)");
+    }
+
+    #[test]
+    fn smoothing_and_thinning_keep_the_ends() {
+        let points: Vec<(f64, f64)> = (0..10).map(|i| (i as f64, (i % 2) as f64)).collect();
+        let smoothed = moving_average(&points, 3);
+        assert_eq!(smoothed[0], (0.0, 0.5));
+        assert_eq!(smoothed[4], (4.0, 2.0 / 3.0));
+        assert_eq!(smoothed[9], (9.0, 0.5));
+        assert_eq!(moving_average(&points, 1), points);
+        let xs = |points: Vec<(f64, f64)>| points.iter().map(|p| p.0).collect::<Vec<_>>();
+        assert_eq!(xs(decimate(points.clone(), Some(4))), [0.0, 3.0, 6.0, 9.0]);
+        assert_eq!(xs(decimate(points.clone(), Some(3))), [0.0, 5.0, 9.0]);
+        assert_eq!(decimate(points.clone(), Some(10)), points);
+        let chart = Chart::from_results(&results()).smooth(3).max_points(2);
+        let plotted = chart.plotted().unwrap();
+        assert_eq!(plotted[0].1, [(10.0, 5.05e-5), (1000.0, 5.05e-3)]);
+    }
+
+    #[test]
+    fn styles_are_fixed_per_name() {
+        let known: Vec<Style> = STYLES.iter().map(|(name, _)| Style::of(name)).collect();
+        for (i, a) in known.iter().enumerate() {
+            assert!(known[i + 1..].iter().all(|b| b != a), "{:?}", a);
+        }
+        assert_eq!(Style::of("kara"), Style::new(BLUE, Marker::Triangle));
+        // Pinned so a change to the hash, which would restyle every plugin
+        // between runs, is noticed.
+        assert_eq!(Style::of("plugin"), Style::new(PALETTE[2], Marker::Cross));
+        assert_eq!(Style::of("plugin2"), Style::new(PALETTE[0], Marker::Cross));
+        let svg = Chart::from_results(&results()).to_svg().unwrap();
+        assert!(svg.contains("<circle"));
+    }
+
+    #[test]
+    fn figures_stack_their_panels() {
+        let figure = Figure::new()
+            .title("Everything")
+            .panel(Chart::from_results(&results()).title("Time"))
+            .panel(Chart::from_results(&results()).title("Again"));
+        assert_eq!(figure.dimensions(), (800, 780));
+        let svg = figure.to_svg().unwrap();
+        for text in ["Everything", "Time", "Again"] {
+            assert!(svg.contains(text), "{}", text);
+        }
+        assert!(Figure::new().to_svg().is_err());
+        let empty_panel = Figure::new().panel(Chart::new());
+        assert!(empty_panel.to_svg().is_err());
+    }
+
+    #[test]
+    fn crossovers_need_to_hold_to_the_end() {
+        let base = [(1.0, 1.0), (2.0, 2.0), (3.0, 3.0)];
+        assert_eq!(
+            crossover(&base, &[(1.0, 0.5), (2.0, 1.0), (3.0, 2.0)]),
+            Some(1.0)
+        );
+        assert_eq!(
+            crossover(&base, &[(1.0, 0.5), (2.0, 3.0), (3.0, 2.0)]),
+            Some(3.0)
+        );
+        assert_eq!(
+            crossover(&base, &[(1.0, 0.5), (2.0, 1.0), (3.0, 4.0)]),
+            None
+        );
+        assert_eq!(crossover(&base, &[(2.5, 0.1)]), None);
+        let svg = Chart::from_results(&results())
+            .crossovers("direct")
+            .to_svg()
+            .unwrap();
+        assert!(svg.contains("fill=\"#0000FF\">\n 100\n"));
+        let missing = Chart::from_results(&results()).crossovers("fft");
+        assert!(missing.to_svg().is_err());
+    }
+
+    #[test]
+    fn stacked_series_sum_the_ones_below() {
+        let chart = Chart::from_results(&results()).kind(Kind::Stacked);
+        let plotted = chart.plotted().unwrap();
+        assert_eq!(plotted[0].1, [(10.0, 1e-6), (100.0, 1e-4), (1000.0, 1e-2)]);
+        assert_eq!(plotted[1].1[1], (100.0, 1.5e-4));
+        let ragged = chart.series("verify", [(10.0, 1e-6)]);
+        assert_eq!(ragged.plotted().unwrap()[2].1, [(10.0, 4e-6)]);
+        assert!(ragged.to_svg().unwrap().contains("<polygon"));
+    }
+}
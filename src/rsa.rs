@@ -0,0 +1,102 @@
+//! `rsa-demo`: textbook RSA key generation, encryption and decryption, timed
+//! phase by phase.
+
+use crate::inputs;
+use bigmul::{BigUint, PowWindow};
+use std::time::Instant;
+
+const MESSAGE: &str = "bigmul rsa-demo: the quick brown fox jumps over the lazy dog";
+const PUBLIC_EXPONENT: u64 = 65537;
+const MR_ROUNDS: usize = 32;
+
+/// Returns a random probable prime with exactly `bits` bits whose
+/// predecessor is coprime to `e`, and the number of candidates tried.
+fn random_prime(bits: usize, e: &BigUint) -> (BigUint, usize) {
+    let one = BigUint::one();
+    let two = BigUint::from(2u64);
+    let mut candidates = 0;
+    loop {
+        let mut p = inputs::random_bits(bits);
+        if p.div_rem(&two).1.is_zero() {
+            p = p.add(&one);
+        }
+        // Step through odd numbers without leaving the bit length.
+        while p.bits() == bits {
+            candidates += 1;
+            if p.is_probable_prime(MR_ROUNDS) && p.sub(&one).gcd(e) == one {
+                return (p, candidates);
+            }
+            p = p.add(&two);
+        }
+    }
+}
+
+fn encode(message: &str) -> BigUint {
+    let byte = BigUint::from(256u64);
+    message.bytes().fold(BigUint::new(), |acc, b| {
+        acc.mul_karatsuba(&byte).add(&BigUint::from(u64::from(b)))
+    })
+}
+
+fn decode(mut m: BigUint) -> String {
+    let byte = BigUint::from(256u64);
+    let mut bytes = Vec::new();
+    while !m.is_zero() {
+        let (q, r) = m.div_rem(&byte);
+        bytes.push(r.to_string().parse::<u8>().unwrap());
+        m = q;
+    }
+    bytes.reverse();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Runs the demo for an RSA modulus of `bits` bits and prints each phase's
+/// time.
+pub fn run(bits: usize) {
+    let e = BigUint::from(PUBLIC_EXPONENT);
+    let total = Instant::now();
+
+    // Two primes of half the size give a modulus one bit short about half
+    // the time; draw again until it has exactly `bits` bits.
+    let start = Instant::now();
+    let mut tried = 0;
+    let (p, q, n) = loop {
+        let (p, tried_p) = random_prime(bits / 2, &e);
+        let (q, tried_q) = random_prime(bits - bits / 2, &e);
+        tried += tried_p + tried_q;
+        let n = p.mul_karatsuba(&q);
+        if p != q && n.bits() == bits {
+            break (p, q, n);
+        }
+    };
+    let t_primes = start.elapsed().as_secs_f64();
+
+    let start = Instant::now();
+    let one = BigUint::one();
+    let phi = p.sub(&one).mul_karatsuba(&q.sub(&one));
+    let d = e
+        .mod_inverse(&phi)
+        .expect("e is coprime to phi by construction");
+    let t_keys = start.elapsed().as_secs_f64();
+
+    let m = encode(MESSAGE);
+
+    let start = Instant::now();
+    let c = m.mod_pow(&e, &n, PowWindow::Sliding);
+    let t_encrypt = start.elapsed().as_secs_f64();
+
+    let start = Instant::now();
+    let recovered = c.mod_pow(&d, &n, PowWindow::Sliding);
+    let t_decrypt = start.elapsed().as_secs_f64();
+
+    let text = decode(recovered.clone());
+    assert_eq!(recovered, m, "decryption did not recover the message");
+
+    println!("modulus bits: {}", n.bits());
+    println!("prime generation: {:.6} s ({} candidates)", t_primes, tried);
+    println!("key construction: {:.6} s", t_keys);
+    println!("encryption:       {:.6} s", t_encrypt);
+    println!("decryption:       {:.6} s", t_decrypt);
+    println!("total:            {:.6} s", total.elapsed().as_secs_f64());
+    println!("message: {}", text);
+}
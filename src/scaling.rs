@@ -0,0 +1,167 @@
+//! `--scaling`: fixes the operand size and sweeps thread counts from one to
+//! the number of available CPUs for every parallel algorithm, reporting
+//! speedup over the single-threaded run and parallel efficiency
+//! (speedup divided by threads).
+
+use crate::inputs::{Axis, InputClass};
+use crate::interrupt;
+use crate::multiplier::{self, Parallel};
+use bigmul::report::Style;
+use plotters::prelude::*;
+use std::fmt::Write as _;
+use std::time::Instant;
+
+const SCALING_DIGITS: usize = 200_000;
+const NUM_INSTANCES: usize = 3;
+
+/// Mean seconds per multiplication for one algorithm at one thread count.
+fn time_threads(kernel: &Parallel, a: &[u32], b: &[u32], threads: usize, expected: &[u32]) -> f64 {
+    let mut total = 0.0;
+    for _ in 0..NUM_INSTANCES {
+        let start = Instant::now();
+        let product = (kernel.mul)(a, b, threads);
+        total += start.elapsed().as_secs_f64();
+        assert_eq!(
+            product, expected,
+            "{} with {} threads",
+            kernel.name, threads
+        );
+    }
+    total / NUM_INSTANCES as f64
+}
+
+/// Runs the sweep, prints one line per thread count and writes
+/// `./assets/scaling.csv` and `./assets/scaling.png`. Returns `false` if
+/// interrupted before the end.
+pub fn run() -> bool {
+    let kernels = multiplier::parallel_registry();
+    let max_threads = multiplier::available_threads();
+    let a = InputClass::Random.generate(SCALING_DIGITS, Axis::Digits);
+    let b = InputClass::Random.generate(SCALING_DIGITS, Axis::Digits);
+    let expected = a.mul_karatsuba(&b);
+    println!(
+        "{}-digit operands, 1..={} threads",
+        SCALING_DIGITS, max_threads
+    );
+
+    let mut rows: Vec<(usize, Vec<f64>)> = Vec::new();
+    for threads in 1..=max_threads {
+        if interrupt::interrupted() {
+            break;
+        }
+        let times: Vec<f64> = kernels
+            .iter()
+            .map(|k| time_threads(k, a.as_limbs(), b.as_limbs(), threads, expected.as_limbs()))
+            .collect();
+        let mut line = format!("threads={}", threads);
+        for (k, (kernel, t)) in kernels.iter().zip(&times).enumerate() {
+            let speedup = rows.first().map_or(1.0, |(_, base)| base[k] / t);
+            write!(line, ", {}={:.6} (x{:.2})", kernel.name, t, speedup).unwrap();
+        }
+        println!("{}", line);
+        rows.push((threads, times));
+    }
+    if rows.is_empty() {
+        return false;
+    }
+
+    let mut out = String::from("threads");
+    for kernel in &kernels {
+        write!(out, ",{0}_seconds,{0}_speedup,{0}_efficiency", kernel.name).unwrap();
+    }
+    out.push('\n');
+    for (threads, times) in &rows {
+        write!(out, "{}", threads).unwrap();
+        for (t, base) in times.iter().zip(&rows[0].1) {
+            let speedup = base / t;
+            write!(
+                out,
+                ",{:.9},{:.4},{:.4}",
+                t,
+                speedup,
+                speedup / *threads as f64
+            )
+            .unwrap();
+        }
+        out.push('\n');
+    }
+    std::fs::create_dir_all("./assets").expect("Failed to create ./assets directory");
+    std::fs::write("./assets/scaling.csv", out).expect("Failed to write CSV results");
+    plot("./assets/scaling.png", &kernels, &rows);
+    println!("Results saved to ./assets/scaling.csv and ./assets/scaling.png");
+    rows.len() == max_threads
+}
+
+/// Speedup (with the ideal linear line) on the left, efficiency on the right.
+fn plot(path: &str, kernels: &[Parallel], rows: &[(usize, Vec<f64>)]) {
+    let root = BitMapBackend::new(path, (1200, 600)).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+    let (left, right) = root.split_horizontally(600);
+    let max_threads = rows.last().unwrap().0.max(2) as f32;
+    let speedup = |k: usize| -> Vec<(f32, f32)> {
+        rows.iter()
+            .map(|(n, times)| (*n as f32, (rows[0].1[k] / times[k]) as f32))
+            .collect()
+    };
+    let max_speedup = (0..kernels.len())
+        .flat_map(speedup)
+        .map(|(_, s)| s)
+        .fold(max_threads, f32::max);
+
+    let mut chart = ChartBuilder::on(&left)
+        .caption("Speedup", ("sans-serif", 30).into_font())
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d(1f32..max_threads, 0f32..max_speedup * 1.1)
+        .unwrap();
+    chart
+        .configure_mesh()
+        .x_desc("Threads")
+        .y_desc("Speedup over 1 thread")
+        .draw()
+        .unwrap();
+    chart
+        .draw_series(LineSeries::new(
+            [(1.0, 1.0), (max_threads, max_threads)],
+            BLACK.mix(0.4),
+        ))
+        .unwrap()
+        .label("Ideal")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLACK.mix(0.4)));
+    for (k, kernel) in kernels.iter().enumerate() {
+        let color = Style::of(kernel.name).color;
+        chart
+            .draw_series(LineSeries::new(speedup(k), color))
+            .unwrap()
+            .label(kernel.label)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .unwrap();
+
+    let mut chart = ChartBuilder::on(&right)
+        .caption("Parallel efficiency", ("sans-serif", 30).into_font())
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d(1f32..max_threads, 0f32..1.2f32)
+        .unwrap();
+    chart
+        .configure_mesh()
+        .x_desc("Threads")
+        .y_desc("Speedup / threads")
+        .draw()
+        .unwrap();
+    for (k, kernel) in kernels.iter().enumerate() {
+        let color = Style::of(kernel.name).color;
+        let points = speedup(k).into_iter().map(|(n, s)| (n, s / n));
+        chart.draw_series(LineSeries::new(points, color)).unwrap();
+    }
+
+    root.present().unwrap();
+}
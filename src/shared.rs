@@ -0,0 +1,96 @@
+use crate::bigint::BigInt;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// A `BigInt` behind an `Arc`, so `clone` is O(1) however large the value:
+/// clones share one limb buffer, and it is copied only when a shared value is
+/// changed through [`make_mut`](SharedBigInt::make_mut) or taken apart with
+/// [`into_bigint`](SharedBigInt::into_bigint).
+///
+/// Derefs to `BigInt`, so every arithmetic method is available and returns
+/// an ordinary `BigInt`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SharedBigInt(Arc<BigInt>);
+
+impl SharedBigInt {
+    pub fn new(value: BigInt) -> Self {
+        SharedBigInt(Arc::new(value))
+    }
+
+    /// Mutable access, copying the value first if another clone shares it.
+    pub fn make_mut(&mut self) -> &mut BigInt {
+        Arc::make_mut(&mut self.0)
+    }
+
+    /// The owned value, copied only if another clone shares it.
+    pub fn into_bigint(self) -> BigInt {
+        Arc::unwrap_or_clone(self.0)
+    }
+
+    /// Whether another clone currently shares this value's buffer.
+    pub fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.0) > 1
+    }
+
+    pub fn ptr_eq(&self, other: &SharedBigInt) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Deref for SharedBigInt {
+    type Target = BigInt;
+
+    fn deref(&self) -> &BigInt {
+        &self.0
+    }
+}
+
+impl From<BigInt> for SharedBigInt {
+    fn from(value: BigInt) -> Self {
+        SharedBigInt::new(value)
+    }
+}
+
+impl PartialOrd for SharedBigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SharedBigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl fmt::Display for SharedBigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_share_until_mutated() {
+        let a = SharedBigInt::new(BigInt::from_str("-123456789012345678901234567890").unwrap());
+        let mut b = a.clone();
+        assert!(a.ptr_eq(&b) && a.is_shared());
+
+        *b.make_mut() = b.mul_karatsuba(&b);
+        assert!(!a.ptr_eq(&b) && !a.is_shared());
+        assert_eq!(a.to_string(), "-123456789012345678901234567890");
+        assert_eq!(*b, a.mul_karatsuba(&a));
+
+        let c = a.clone();
+        assert_eq!(c.into_bigint(), *a);
+        assert_eq!(
+            a.into_bigint().to_string(),
+            "-123456789012345678901234567890"
+        );
+    }
+}
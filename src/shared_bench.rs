@@ -0,0 +1,86 @@
+//! `--shared-clone`: what `SharedBigInt`'s O(1) clone saves over deep-copying
+//! `BigInt`, alone and in a workload that hands one value to many holders
+//! (a cache, an expression tree) and modifies one copy in eight.
+
+use crate::inputs::{Axis, InputClass};
+use crate::interrupt;
+use crate::primitives::seconds_per_call;
+use bigmul::{BigInt, SharedBigInt};
+use std::fmt::Write as _;
+use std::hint::black_box;
+
+const DIGITS: [usize; 4] = [1_000, 10_000, 100_000, 1_000_000];
+/// Copies handed out per workload round.
+const FANOUT: usize = 64;
+const METHODS: [&str; 4] = [
+    "bigint_clone",
+    "shared_clone",
+    "bigint_workload",
+    "shared_workload",
+];
+
+/// Hands `FANOUT` copies of `value` out, negating every eighth in place.
+fn workload<T: Clone>(value: &T, negate: impl Fn(&mut T)) {
+    let mut held: Vec<T> = Vec::with_capacity(FANOUT);
+    for i in 0..FANOUT {
+        let mut v = value.clone();
+        if i % 8 == 0 {
+            negate(&mut v);
+        }
+        held.push(v);
+    }
+    black_box(held);
+}
+
+fn measure(digits: usize) -> [f64; 4] {
+    let owned = BigInt::from(InputClass::Random.generate(digits, Axis::Digits));
+    let shared = SharedBigInt::new(owned.clone());
+    [
+        seconds_per_call(|| {
+            black_box(black_box(&owned).clone());
+        }),
+        seconds_per_call(|| {
+            black_box(black_box(&shared).clone());
+        }),
+        seconds_per_call(|| workload(&owned, |v| *v = -std::mem::take(v))),
+        seconds_per_call(|| {
+            workload(&shared, |v| {
+                let v = v.make_mut();
+                *v = -std::mem::take(v);
+            })
+        }),
+    ]
+}
+
+/// Runs the comparison, printing seconds per operation at each size and
+/// writing `./assets/shared_clone.csv`. Returns `false` if interrupted
+/// before the end.
+pub fn run() -> bool {
+    let mut rows: Vec<(usize, [f64; 4])> = Vec::new();
+    for &digits in &DIGITS {
+        if interrupt::interrupted() {
+            break;
+        }
+        let times = measure(digits);
+        let mut line = format!("digits={}", digits);
+        for (name, t) in METHODS.iter().zip(&times) {
+            write!(line, ", {}={:.3e} s", name, t).unwrap();
+        }
+        write!(line, ", workload speedup={:.1}x", times[2] / times[3]).unwrap();
+        println!("{}", line);
+        rows.push((digits, times));
+    }
+
+    let mut out = format!("digits,{},workload_speedup\n", METHODS.join(","));
+    for (digits, times) in &rows {
+        write!(out, "{}", digits).unwrap();
+        for t in times {
+            write!(out, ",{:.12}", t).unwrap();
+        }
+        writeln!(out, ",{:.2}", times[2] / times[3]).unwrap();
+    }
+    std::fs::create_dir_all("./assets").expect("Failed to create ./assets directory");
+    std::fs::write("./assets/shared_clone.csv", out).expect("Failed to write CSV results");
+    println!("Results saved to ./assets/shared_clone.csv");
+    rows.len() == DIGITS.len()
+}
@@ -0,0 +1,148 @@
+//! `--square`: squaring `a` with the general product `a * a` against the
+//! dedicated squaring kernels, schoolbook and Karatsuba. Schoolbook squaring
+//! forms each cross product once, so it should approach half the time of
+//! the general product; Karatsuba squaring saves less, since only the base
+//! cases get cheaper.
+
+use crate::interrupt;
+use crate::primitives::{random_limbs, seconds_per_call};
+use bigmul::limbs;
+use bigmul::report::PALETTE;
+use plotters::prelude::*;
+use std::fmt::Write as _;
+use std::hint::black_box;
+
+/// Operand sizes in limbs: powers of two from 8 to 8192.
+const SIZES: [usize; 11] = [8, 16, 32, 64, 128, 256, 512, 1024, 2048, 4096, 8192];
+const METHODS: [&str; 4] = [
+    "direct_mul",
+    "direct_square",
+    "karatsuba_mul",
+    "karatsuba_square",
+];
+
+fn measure(n: usize) -> [f64; 4] {
+    let a = random_limbs(n);
+    [
+        seconds_per_call(|| {
+            black_box(limbs::mul_direct_slices(black_box(&a), black_box(&a)));
+        }),
+        seconds_per_call(|| {
+            black_box(limbs::square_direct_slices(black_box(&a)));
+        }),
+        seconds_per_call(|| {
+            black_box(limbs::mul_karatsuba_slices(black_box(&a), black_box(&a)));
+        }),
+        seconds_per_call(|| {
+            black_box(limbs::square_karatsuba_slices(black_box(&a)));
+        }),
+    ]
+}
+
+/// Runs the sweep, printing each method's time and the squaring ratios,
+/// and writes `./assets/square.csv` and `./assets/square.png`. Returns
+/// `false` if interrupted.
+pub fn run() -> bool {
+    let mut rows: Vec<(usize, [f64; 4])> = Vec::new();
+    for &n in &SIZES {
+        if interrupt::interrupted() {
+            break;
+        }
+        let times = measure(n);
+        println!(
+            "limbs={}, direct: mul={:.3e} s square={:.3e} s ({:.2}), karatsuba: mul={:.3e} s square={:.3e} s ({:.2})",
+            n,
+            times[0],
+            times[1],
+            times[1] / times[0],
+            times[2],
+            times[3],
+            times[3] / times[2]
+        );
+        rows.push((n, times));
+    }
+    if rows.is_empty() {
+        return false;
+    }
+
+    let mut out = String::from("limbs");
+    for name in METHODS {
+        write!(out, ",{}_seconds", name).unwrap();
+    }
+    out.push_str(",direct_ratio,karatsuba_ratio\n");
+    for (n, times) in &rows {
+        write!(out, "{}", n).unwrap();
+        for t in times {
+            write!(out, ",{:.12}", t).unwrap();
+        }
+        writeln!(
+            out,
+            ",{:.3},{:.3}",
+            times[1] / times[0],
+            times[3] / times[2]
+        )
+        .unwrap();
+    }
+    std::fs::create_dir_all("./assets").expect("Failed to create ./assets directory");
+    std::fs::write("./assets/square.csv", out).expect("Failed to write CSV results");
+    plot("./assets/square.png", &rows);
+    println!("Results saved to ./assets/square.csv and ./assets/square.png");
+    rows.len() == SIZES.len()
+}
+
+/// Time of each squaring kernel as a fraction of the general product of the
+/// same family, against size on a log scale.
+fn plot(path: &str, rows: &[(usize, [f64; 4])]) {
+    let root = BitMapBackend::new(path, (800, 600)).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+    let x_max = (rows.last().unwrap().0 as f64).log10() as f32 + 0.05;
+    let x_min = (rows[0].0 as f64).log10() as f32 - 0.05;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(
+            "Squaring vs General Multiplication",
+            ("sans-serif", 40).into_font(),
+        )
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(x_min..x_max, 0f32..1.2f32)
+        .unwrap();
+    chart
+        .configure_mesh()
+        .x_desc("Operand Size (limbs, log scale)")
+        .y_desc("Square Time / Multiply Time")
+        .x_label_formatter(&|x| format!("{:.0}", 10f64.powf(*x as f64)))
+        .draw()
+        .unwrap();
+    // Schoolbook squaring's ideal ratio.
+    chart
+        .draw_series(LineSeries::new(
+            [(x_min, 0.5), (x_max, 0.5)],
+            BLACK.mix(0.2),
+        ))
+        .unwrap();
+    for (k, (name, (mul, square))) in [("direct", (0, 1)), ("karatsuba", (2, 3))]
+        .into_iter()
+        .enumerate()
+    {
+        let points: Vec<(f32, f32)> = rows
+            .iter()
+            .map(|(n, t)| ((*n as f64).log10() as f32, (t[square] / t[mul]) as f32))
+            .collect();
+        let color = PALETTE[k % PALETTE.len()];
+        chart
+            .draw_series(LineSeries::new(points, color))
+            .unwrap()
+            .label(name)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::LowerRight)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .unwrap();
+
+    root.present().unwrap();
+}
@@ -0,0 +1,190 @@
+//! Sums of many values at once. Adding a list pairwise carries through the
+//! running total once per term; here every term's limbs are added into
+//! 64-bit columns with no carries at all, and the columns are carried into
+//! limbs once at the end. A column has room for about a billion limbs below
+//! `BASE` before it could overflow, so the carry pass is needed only that
+//! rarely. Signed terms are summed as two magnitudes, positive and
+//! negative, and subtracted once.
+//!
+//! [`addmul_sub`] is the two-term case with small factors, `a*x - b*y`,
+//! the step of Lehmer's GCD and of Toom interpolation. Scaling each term and
+//! then combining takes three passes and two temporaries; here both
+//! products and the combination share one pass over the limbs.
+
+use crate::bigint::{BigInt, Sign};
+use crate::biguint::BigUint;
+use crate::limbs::{self, BASE};
+use std::cmp::Ordering;
+
+/// Terms added into the columns between carry passes: `2^30` limbs below
+/// `BASE` stay under `2^60`, leaving room for the previous pass's limbs.
+const TERMS_PER_PASS: usize = 1 << 30;
+
+/// The sum of `values`; zero for an empty slice.
+///
+/// ```
+/// use bigmul::{BigInt, sum};
+///
+/// let values: Vec<BigInt> = ["999999999999", "1", "-500", "-499"]
+///     .iter()
+///     .map(|s| BigInt::from_str(s).unwrap())
+///     .collect();
+/// assert_eq!(sum(&values).to_string(), "999999999001");
+/// ```
+pub fn sum(values: &[BigInt]) -> BigInt {
+    let of_sign = |sign| {
+        let magnitudes = values
+            .iter()
+            .filter(|v| v.sign() == sign)
+            .map(|v| v.magnitude().as_limbs());
+        sum_magnitudes(magnitudes)
+    };
+    let positive = BigInt::from_biguint(Sign::Plus, of_sign(Sign::Plus));
+    let negative = BigInt::from_biguint(Sign::Plus, of_sign(Sign::Minus));
+    positive.sub(&negative)
+}
+
+/// `a*x - b*y` for factors below `10^9` in absolute value; a negative `b`
+/// gives `a*x + |b|*y`. Panics if either factor is out of range.
+///
+/// ```
+/// use bigmul::{BigInt, addmul_sub};
+///
+/// let x = BigInt::from_str("1000000000000").unwrap();
+/// let y = BigInt::from_str("-7").unwrap();
+/// assert_eq!(addmul_sub(3, &x, 2, &y).to_string(), "3000000000014");
+/// assert_eq!(addmul_sub(3, &x, -2, &y).to_string(), "2999999999986");
+/// ```
+pub fn addmul_sub(a: i64, x: &BigInt, b: i64, y: &BigInt) -> BigInt {
+    assert!(
+        a.unsigned_abs() < BASE && b.unsigned_abs() < BASE,
+        "addmul_sub: factors {} and {} are not below 10^9",
+        a,
+        b
+    );
+    let signum = |v: &BigInt| match v.sign() {
+        Sign::Minus => -1,
+        Sign::NoSign => 0,
+        Sign::Plus => 1,
+    };
+    // The signs the two terms enter the result with.
+    let (p, q) = (a.signum() * signum(x), -b.signum() * signum(y));
+    let (a, x) = (a.unsigned_abs() as u32, x.as_limbs());
+    let (b, y) = (b.unsigned_abs() as u32, y.as_limbs());
+    let (sign, magnitude) = if p * q >= 0 {
+        (p + q, limbs::addmul_slices(a, x, b, y))
+    } else {
+        let (order, magnitude) = limbs::addmul_sub_slices(a, x, b, y);
+        let sign = match order {
+            Ordering::Less => q,
+            Ordering::Equal => 0,
+            Ordering::Greater => p,
+        };
+        (sign, magnitude)
+    };
+    let sign = match sign.signum() {
+        -1 => Sign::Minus,
+        0 => Sign::NoSign,
+        _ => Sign::Plus,
+    };
+    BigInt::from_kernel(sign, magnitude)
+}
+
+fn sum_magnitudes<'a>(terms: impl Iterator<Item = &'a [u32]>) -> BigUint {
+    let mut columns: Vec<u64> = Vec::new();
+    let mut pending = 0;
+    for limbs in terms {
+        if limbs.len() > columns.len() {
+            columns.resize(limbs.len(), 0);
+        }
+        for (c, &d) in columns.iter_mut().zip(limbs) {
+            *c += d as u64;
+        }
+        pending += 1;
+        if pending == TERMS_PER_PASS {
+            carry(&mut columns);
+            pending = 0;
+        }
+    }
+    carry(&mut columns);
+    let digits = columns.into_iter().map(|c| c as u32).collect();
+    BigUint::from_kernel(digits)
+}
+
+/// Carries every column into the next, extending as needed, so that each
+/// is below `BASE`.
+fn carry(columns: &mut Vec<u64>) {
+    let mut carry = 0;
+    for c in columns.iter_mut() {
+        let t = *c + carry;
+        *c = t % BASE;
+        carry = t / BASE;
+    }
+    while carry > 0 {
+        columns.push(carry % BASE);
+        carry /= BASE;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random_bits;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn matches_pairwise_addition() {
+        let mut rng = StdRng::seed_from_u64(88);
+        let values: Vec<BigInt> = (0..500)
+            .map(|_| {
+                let sign = [Sign::Minus, Sign::Plus][rng.gen_range(0..2)];
+                let bits = rng.gen_range(0..2000);
+                BigInt::from_biguint(sign, random_bits(bits, &mut rng))
+            })
+            .collect();
+        let pairwise = values.iter().fold(BigInt::zero(), |acc, v| acc.add(v));
+        assert_eq!(sum(&values), pairwise);
+        let nines = vec![BigInt::from_str(&"9".repeat(50)).unwrap(); 1000];
+        let expected = BigInt::from_str(&"9".repeat(50))
+            .unwrap()
+            .mul(&BigInt::from_str("1000").unwrap());
+        assert_eq!(sum(&nines), expected);
+        assert!(sum(&[]).is_zero());
+        let mut columns = vec![3 * BASE + 7, BASE - 1];
+        carry(&mut columns);
+        assert_eq!(columns, [7, 2, 1]);
+    }
+
+    #[test]
+    fn addmul_sub_matches_separate_products() {
+        let mut rng = StdRng::seed_from_u64(89);
+        let factor = |rng: &mut StdRng| rng.gen_range(-(BASE as i64) + 1..BASE as i64);
+        for _ in 0..300 {
+            let x = BigInt::from_biguint(
+                [Sign::Minus, Sign::Plus][rng.gen_range(0..2)],
+                random_bits(rng.gen_range(0..600), &mut rng),
+            );
+            let y = BigInt::from_biguint(
+                [Sign::Minus, Sign::Plus][rng.gen_range(0..2)],
+                random_bits(rng.gen_range(0..600), &mut rng),
+            );
+            let (a, b) = (
+                factor(&mut rng),
+                [0, 1, -1, factor(&mut rng)][rng.gen_range(0..4)],
+            );
+            let small = |v: i64| BigInt::from_str(&v.to_string()).unwrap();
+            let expected = small(a).mul(&x).sub(&small(b).mul(&y));
+            assert_eq!(addmul_sub(a, &x, b, &y), expected);
+        }
+        let x = BigInt::from_str("-123456789123456789").unwrap();
+        assert!(addmul_sub(6, &x, 3, &x.add(&x)).is_zero());
+        assert!(addmul_sub(0, &x, 0, &x).is_zero());
+    }
+
+    #[test]
+    #[should_panic(expected = "not below 10^9")]
+    fn addmul_sub_rejects_large_factors() {
+        let one = BigInt::from_str("1").unwrap();
+        addmul_sub(1_000_000_000, &one, 1, &one);
+    }
+}
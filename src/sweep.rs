@@ -0,0 +1,187 @@
+use crate::limbs;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::time::Instant;
+
+/// A multiplication kernel to compare in a [`Sweep`], over limbs as in
+/// [`limbs`].
+#[derive(Clone, Copy)]
+pub struct Algorithm {
+    pub name: &'static str,
+    pub mul: fn(&[u32], &[u32]) -> Vec<u32>,
+}
+
+impl Algorithm {
+    pub const DIRECT: Algorithm = Algorithm {
+        name: "direct",
+        mul: limbs::mul_direct_slices,
+    };
+    pub const DC: Algorithm = Algorithm {
+        name: "dc",
+        mul: limbs::mul_dc_slices,
+    };
+    pub const KARATSUBA: Algorithm = Algorithm {
+        name: "kara",
+        mul: limbs::mul_karatsuba_slices,
+    };
+    pub const KARATSUBA_SUB: Algorithm = Algorithm {
+        name: "kara_sub",
+        mul: limbs::mul_karatsuba_sub_slices,
+    };
+
+    /// The single-threaded kernels, under the names the `bigmul` binary
+    /// uses for them.
+    pub const ALL: [Algorithm; 4] = [
+        Algorithm::DIRECT,
+        Algorithm::DC,
+        Algorithm::KARATSUBA,
+        Algorithm::KARATSUBA_SUB,
+    ];
+
+    pub fn by_name(name: &str) -> Option<Algorithm> {
+        Algorithm::ALL.into_iter().find(|a| a.name == name)
+    }
+}
+
+/// A measured comparison: every algorithm multiplies the same random
+/// operands at every size, interleaved round by round so drift in machine
+/// state affects them alike, and each product is checked against the first
+/// algorithm's.
+///
+/// ```
+/// use bigmul::{Algorithm, Sweep};
+///
+/// let results = Sweep::new()
+///     .sizes([64, 128])
+///     .algorithms([Algorithm::DIRECT, Algorithm::KARATSUBA])
+///     .rounds(3)
+///     .run();
+/// assert!(results.time("kara", 128).unwrap() > 0.0);
+/// ```
+pub struct Sweep {
+    sizes: Vec<usize>,
+    algorithms: Vec<Algorithm>,
+    rounds: usize,
+    seed: u64,
+}
+
+/// Median seconds per multiplication for each algorithm at each size.
+#[derive(Clone, Debug)]
+pub struct Results {
+    /// Operand sizes in limbs, in sweep order.
+    pub sizes: Vec<usize>,
+    /// `(algorithm name, median seconds per size)`, in sweep order.
+    pub series: Vec<(&'static str, Vec<f64>)>,
+}
+
+impl Sweep {
+    /// All of [`Algorithm::ALL`] at 32 to 1024 limbs, five rounds each.
+    pub fn new() -> Self {
+        Sweep {
+            sizes: vec![32, 64, 128, 256, 512, 1024],
+            algorithms: Algorithm::ALL.to_vec(),
+            rounds: 5,
+            seed: 0,
+        }
+    }
+
+    /// Operand sizes in limbs; both operands of a pair have the same size.
+    pub fn sizes(mut self, sizes: impl IntoIterator<Item = usize>) -> Self {
+        self.sizes = sizes.into_iter().collect();
+        self
+    }
+
+    pub fn algorithms(mut self, algorithms: impl IntoIterator<Item = Algorithm>) -> Self {
+        self.algorithms = algorithms.into_iter().collect();
+        self
+    }
+
+    /// Timed multiplications per algorithm and size, each on fresh operands.
+    pub fn rounds(mut self, rounds: usize) -> Self {
+        self.rounds = rounds.max(1);
+        self
+    }
+
+    /// Seed for the operands, so runs can be repeated exactly.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Runs the sweep. Panics if two algorithms disagree on a product.
+    pub fn run(&self) -> Results {
+        let mut rng = ChaCha20Rng::seed_from_u64(self.seed);
+        let mut series: Vec<(&'static str, Vec<f64>)> = self
+            .algorithms
+            .iter()
+            .map(|a| (a.name, Vec::new()))
+            .collect();
+        for &n in &self.sizes {
+            let mut samples = vec![Vec::with_capacity(self.rounds); self.algorithms.len()];
+            for _ in 0..self.rounds {
+                let a = random_limbs(&mut rng, n);
+                let b = random_limbs(&mut rng, n);
+                let mut reference: Option<(&str, Vec<u32>)> = None;
+                for (alg, times) in self.algorithms.iter().zip(&mut samples) {
+                    let start = Instant::now();
+                    let product = (alg.mul)(&a, &b);
+                    times.push(start.elapsed().as_secs_f64());
+                    match &reference {
+                        Some((first, expected)) => {
+                            assert_eq!(&product, expected, "{} disagrees with {}", alg.name, first)
+                        }
+                        None => reference = Some((alg.name, product)),
+                    }
+                }
+            }
+            for ((_, medians), times) in series.iter_mut().zip(&mut samples) {
+                times.sort_by(f64::total_cmp);
+                medians.push(times[times.len() / 2]);
+            }
+        }
+        Results {
+            sizes: self.sizes.clone(),
+            series,
+        }
+    }
+}
+
+impl Default for Sweep {
+    fn default() -> Self {
+        Sweep::new()
+    }
+}
+
+impl Results {
+    /// Median seconds for `algorithm` at `size` limbs.
+    pub fn time(&self, algorithm: &str, size: usize) -> Option<f64> {
+        let i = self.sizes.iter().position(|&n| n == size)?;
+        let (_, times) = self.series.iter().find(|(name, _)| *name == algorithm)?;
+        Some(times[i])
+    }
+
+    /// How many times faster `fast` was than `slow` at `size` limbs.
+    pub fn speedup(&self, fast: &str, slow: &str, size: usize) -> Option<f64> {
+        Some(self.time(slow, size)? / self.time(fast, size)?)
+    }
+
+    /// The algorithm with the lowest median at `size` limbs.
+    pub fn fastest(&self, size: usize) -> Option<&'static str> {
+        let i = self.sizes.iter().position(|&n| n == size)?;
+        self.series
+            .iter()
+            .min_by(|a, b| a.1[i].total_cmp(&b.1[i]))
+            .map(|(name, _)| *name)
+    }
+}
+
+/// `n` random limbs with a non-zero top limb.
+fn random_limbs(rng: &mut ChaCha20Rng, n: usize) -> Vec<u32> {
+    let mut v: Vec<u32> = (0..n)
+        .map(|_| rng.gen_range(0..limbs::BASE as u32))
+        .collect();
+    if let Some(top) = v.last_mut() {
+        *top = rng.gen_range(1..limbs::BASE as u32);
+    }
+    v
+}
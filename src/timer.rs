@@ -0,0 +1,197 @@
+//! Clocks for timing the sweep: the OS monotonic clock (`Instant`, backed by
+//! `clock_gettime`, `QueryPerformanceCounter` or `mach_absolute_time`), or
+//! on x86-64 the time-stamp counter, for kernels that finish in well under a
+//! microsecond. `ops` replaces time altogether with the limb operations
+//! counted by `bigmul::instrument`, which are identical on every machine.
+//!
+//! Whichever clock is used, its resolution is measured at startup, and the
+//! sweep warns when a measurement comes within `RESOLUTION_MARGIN` of it.
+
+use crate::cli::Config;
+use bigmul::instrument;
+use std::time::{Duration, Instant};
+
+/// Durations shorter than this many clock steps are reported as unreliable.
+pub const RESOLUTION_MARGIN: f64 = 100.0;
+
+/// Calibration period for the TSC frequency.
+const CALIBRATION: Duration = Duration::from_millis(50);
+
+#[derive(Clone, Copy, Debug, Default)]
+pub enum Timer {
+    #[default]
+    Monotonic,
+    /// `rdtsc`, converted with a frequency measured against `Instant`.
+    Tsc { seconds_per_tick: f64 },
+    /// Limb operations instead of seconds: deterministic, for charts that
+    /// must not change between machines or runs.
+    Ops,
+}
+
+/// A point in time on one `Timer`'s clock.
+#[derive(Clone, Copy)]
+pub enum Stamp {
+    Instant(Instant),
+    Ticks(u64),
+    Ops(u64),
+}
+
+#[cfg(target_arch = "x86_64")]
+fn rdtsc() -> u64 {
+    // SAFETY: rdtsc is available on every x86-64 CPU.
+    #[allow(unused_unsafe)]
+    unsafe {
+        std::arch::x86_64::_rdtsc()
+    }
+}
+
+impl Timer {
+    /// Parses a `--timer` value.
+    pub fn parse(s: &str) -> Result<Timer, String> {
+        match s {
+            "monotonic" => Ok(Timer::Monotonic),
+            "tsc" => Timer::tsc(),
+            "ops" => Ok(Timer::Ops),
+            _ => Err(format!(
+                "unknown timer '{}' (expected monotonic, tsc or ops)",
+                s
+            )),
+        }
+    }
+
+    /// Calibrates the time-stamp counter. Fails unless the CPU reports an
+    /// invariant TSC, whose rate does not follow frequency scaling.
+    #[cfg(target_arch = "x86_64")]
+    pub fn tsc() -> Result<Timer, String> {
+        use std::arch::x86_64::__cpuid;
+        // SAFETY: cpuid is available on every x86-64 CPU.
+        #[allow(unused_unsafe)]
+        let (max_extended, power) = unsafe { (__cpuid(0x8000_0000).eax, __cpuid(0x8000_0007)) };
+        if max_extended < 0x8000_0007 || power.edx & (1 << 8) == 0 {
+            return Err("this CPU has no invariant TSC; use --timer monotonic".to_string());
+        }
+        let start = Instant::now();
+        let ticks = rdtsc();
+        while start.elapsed() < CALIBRATION {}
+        let ticks = rdtsc() - ticks;
+        Ok(Timer::Tsc {
+            seconds_per_tick: start.elapsed().as_secs_f64() / ticks as f64,
+        })
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    pub fn tsc() -> Result<Timer, String> {
+        Err("--timer tsc is only available on x86-64".to_string())
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Timer::Monotonic => "monotonic",
+            Timer::Tsc { .. } => "tsc",
+            Timer::Ops => "ops",
+        }
+    }
+
+    /// Whether this "clock" counts operations rather than time.
+    pub fn deterministic(&self) -> bool {
+        matches!(self, Timer::Ops)
+    }
+
+    pub fn now(&self) -> Stamp {
+        match self {
+            Timer::Monotonic => Stamp::Instant(Instant::now()),
+            #[cfg(target_arch = "x86_64")]
+            Timer::Tsc { .. } => Stamp::Ticks(rdtsc()),
+            #[cfg(not(target_arch = "x86_64"))]
+            Timer::Tsc { .. } => unreachable!("tsc timer is x86-64 only"),
+            Timer::Ops => Stamp::Ops(instrument::limb_ops()),
+        }
+    }
+
+    pub fn seconds_since(&self, start: Stamp) -> f64 {
+        match (self, start) {
+            (Timer::Monotonic, Stamp::Instant(t)) => t.elapsed().as_secs_f64(),
+            (Timer::Tsc { seconds_per_tick }, Stamp::Ticks(t)) => {
+                let Stamp::Ticks(now) = self.now() else {
+                    unreachable!()
+                };
+                now.wrapping_sub(t) as f64 * seconds_per_tick
+            }
+            (Timer::Ops, Stamp::Ops(start)) => (instrument::limb_ops() - start) as f64,
+            _ => panic!("stamp taken on a different timer"),
+        }
+    }
+
+    /// The smallest nonzero step the clock was seen to take between two
+    /// consecutive reads, in seconds.
+    pub fn resolution(&self) -> f64 {
+        if self.deterministic() {
+            return 1.0;
+        }
+        let mut best = f64::MAX;
+        for _ in 0..1000 {
+            let start = self.now();
+            let mut step = self.seconds_since(start);
+            while step == 0.0 {
+                step = self.seconds_since(start);
+            }
+            best = best.min(step);
+        }
+        best
+    }
+}
+
+/// Prints the sweep's clock and its resolution, warning when it is coarse,
+/// as far as `--quiet` and `--output` allow, and returns the resolution.
+pub fn announce(config: &Config) -> f64 {
+    let timer = &config.timer;
+    if timer.deterministic() {
+        if config.reports() {
+            println!("Timer: ops (limb operations counted, not timed; identical on every machine)");
+        }
+        return 1.0;
+    }
+    let resolution = timer.resolution();
+    if config.reports() {
+        println!(
+            "Timer: {} (resolution {:.1} ns)",
+            timer.name(),
+            resolution * 1e9
+        );
+    }
+    if resolution > 1e-6 && !config.quiet {
+        eprintln!(
+            "warning: the {} clock only resolves {:.1} us; short multiplications will be noisy",
+            timer.name(),
+            resolution * 1e6
+        );
+    }
+    resolution
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clocks_agree_on_a_sleep() {
+        let mut timers = vec![Timer::Monotonic];
+        timers.extend(Timer::tsc());
+        for timer in timers {
+            assert!(timer.resolution() < 1e-3, "{}", timer.name());
+            let start = timer.now();
+            std::thread::sleep(Duration::from_millis(20));
+            let t = timer.seconds_since(start);
+            assert!((0.015..0.5).contains(&t), "{} measured {}", timer.name(), t);
+        }
+    }
+
+    #[test]
+    fn ops_timer_counts_kernel_work() {
+        let timer = Timer::Ops;
+        let start = timer.now();
+        bigmul::limbs::mul_direct_slices(&[1, 2, 3], &[4, 5]);
+        assert_eq!(timer.seconds_since(start), 6.0);
+        assert_eq!(timer.resolution(), 1.0);
+    }
+}
@@ -0,0 +1,180 @@
+//! Model-checkable versions of the addition, subtraction and schoolbook
+//! multiplication kernels.
+//!
+//! These mirror `add_slices`, `sub_slices` and `mul_direct_slices` with the
+//! allocation and normalization stripped out: operands have equal length,
+//! results go into caller-provided buffers, and each loop states its
+//! invariant as an assertion at the top of the body. That keeps the state
+//! small enough for a bounded model checker to explore exhaustively.
+//!
+//! The Kani harnesses at the bottom prove, for up to `MAX_LIMBS` limbs and
+//! every input with limbs below `BASE`, that no arithmetic overflows, every
+//! output limb is below `BASE`, and the result equals the exact value
+//! computed in `u128`. Run them with
+//!
+//! ```text
+//! cargo kani --features verified
+//! ```
+//!
+//! Ordinary builds compile the module only with `--features verified`, where
+//! the unit tests check it against the production kernels.
+
+use crate::limbs::BASE;
+
+/// Writes `a + b` into `out[..n + 1]` for `n`-limb `a` and `b`.
+pub fn add(a: &[u32], b: &[u32], out: &mut [u32]) {
+    let n = a.len();
+    assert!(b.len() == n && out.len() == n + 1);
+    let mut carry = 0u64;
+    for i in 0..n {
+        // Invariant: carry <= 1, and out[..i] + carry * BASE^i equals
+        // a[..i] + b[..i].
+        assert!(carry <= 1);
+        let sum = a[i] as u64 + b[i] as u64 + carry;
+        out[i] = (sum % BASE) as u32;
+        carry = sum / BASE;
+    }
+    out[n] = carry as u32;
+}
+
+/// Writes `a - b` into `out[..n]` for `n`-limb `a` and `b`, returning the
+/// borrow out of the top limb (1 exactly when `a < b`).
+pub fn sub(a: &[u32], b: &[u32], out: &mut [u32]) -> u32 {
+    let n = a.len();
+    assert!(b.len() == n && out.len() == n);
+    let mut borrow = 0u64;
+    for i in 0..n {
+        // Invariant: borrow <= 1, and out[..i] - borrow * BASE^i equals
+        // a[..i] - b[..i].
+        assert!(borrow <= 1);
+        let sub = b[i] as u64 + borrow;
+        let ai = a[i] as u64;
+        borrow = (ai < sub) as u64;
+        out[i] = (ai + borrow * BASE - sub) as u32;
+    }
+    borrow as u32
+}
+
+/// Writes `a * b` into `out[..2n]` for `n`-limb `a` and `b`; `out` must be
+/// zeroed.
+pub fn mul_direct(a: &[u32], b: &[u32], out: &mut [u32]) {
+    let n = a.len();
+    assert!(b.len() == n && out.len() == 2 * n);
+    for i in 0..n {
+        let mut carry = 0u64;
+        for j in 0..n {
+            // Invariant: carry < BASE and out[i + j] < BASE, so
+            // t <= (BASE - 1) + (BASE - 1)^2 + (BASE - 1) < BASE^2 < 2^64.
+            assert!(carry < BASE && (out[i + j] as u64) < BASE);
+            let t = out[i + j] as u64 + a[i] as u64 * b[j] as u64 + carry;
+            out[i + j] = (t % BASE) as u32;
+            carry = t / BASE;
+        }
+        // Row i has touched out[..i + n] only, so this limb is still zero.
+        assert!(out[i + n] == 0);
+        out[i + n] = carry as u32;
+    }
+}
+
+#[cfg(kani)]
+mod proofs {
+    use super::*;
+
+    /// Bound on operand length; `2 * MAX_LIMBS` limbs must fit in `u128`.
+    const MAX_LIMBS: usize = 2;
+
+    fn any_limbs<const N: usize>() -> [u32; N] {
+        let limbs: [u32; N] = kani::any();
+        for &d in &limbs {
+            kani::assume((d as u64) < BASE);
+        }
+        limbs
+    }
+
+    fn value(limbs: &[u32]) -> u128 {
+        limbs
+            .iter()
+            .rev()
+            .fold(0, |acc, &d| acc * BASE as u128 + d as u128)
+    }
+
+    fn in_range(limbs: &[u32]) -> bool {
+        limbs.iter().all(|&d| (d as u64) < BASE)
+    }
+
+    #[kani::proof]
+    #[kani::unwind(3)]
+    fn add_is_exact() {
+        let a = any_limbs::<MAX_LIMBS>();
+        let b = any_limbs::<MAX_LIMBS>();
+        let mut out = [0; MAX_LIMBS + 1];
+        add(&a, &b, &mut out);
+        assert!(in_range(&out));
+        assert!(out[MAX_LIMBS] <= 1);
+        assert!(value(&out) == value(&a) + value(&b));
+    }
+
+    #[kani::proof]
+    #[kani::unwind(3)]
+    fn sub_is_exact() {
+        let a = any_limbs::<MAX_LIMBS>();
+        let b = any_limbs::<MAX_LIMBS>();
+        let mut out = [0; MAX_LIMBS];
+        let borrow = sub(&a, &b, &mut out);
+        let modulus = (BASE as u128).pow(MAX_LIMBS as u32);
+        assert!(in_range(&out));
+        assert!((borrow == 1) == (value(&a) < value(&b)));
+        assert!(value(&out) + value(&b) == value(&a) + borrow as u128 * modulus);
+    }
+
+    #[kani::proof]
+    #[kani::unwind(3)]
+    fn mul_direct_is_exact() {
+        let a = any_limbs::<MAX_LIMBS>();
+        let b = any_limbs::<MAX_LIMBS>();
+        let mut out = [0; 2 * MAX_LIMBS];
+        mul_direct(&a, &b, &mut out);
+        assert!(in_range(&out));
+        assert!(value(&out) == value(&a) * value(&b));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limbs;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn padded(mut v: Vec<u32>, len: usize) -> Vec<u32> {
+        v.resize(len, 0);
+        v
+    }
+
+    #[test]
+    fn matches_production_kernels() {
+        let mut rng = StdRng::seed_from_u64(144);
+        for n in [1, 2, 7, 30] {
+            let mut a: Vec<u32> = (0..n).map(|_| rng.gen_range(0..BASE as u32)).collect();
+            let b: Vec<u32> = (0..n).map(|_| rng.gen_range(0..BASE as u32)).collect();
+            a[n - 1] = BASE as u32 - 1;
+
+            let mut out = vec![0; n + 1];
+            add(&a, &b, &mut out);
+            assert_eq!(out, padded(limbs::add_slices(&a, &b), n + 1));
+
+            let mut out = vec![0; n];
+            if limbs::cmp_slices(&a, &b) != std::cmp::Ordering::Less {
+                assert_eq!(sub(&a, &b, &mut out), 0);
+                assert_eq!(out, padded(limbs::sub_slices(&a, &b), n));
+            }
+
+            let mut out = vec![0; 2 * n];
+            mul_direct(&a, &b, &mut out);
+            assert_eq!(out, padded(limbs::mul_direct_slices(&a, &b), 2 * n));
+        }
+        let mut out = [0; 1];
+        assert_eq!(sub(&[3], &[5], &mut out), 1);
+        assert_eq!(out, [999_999_998]);
+    }
+}
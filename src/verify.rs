@@ -0,0 +1,260 @@
+//! `bigmul verify`: randomized differential testing of every registered
+//! algorithm against the schoolbook product.
+//!
+//! Operands are drawn from a seeded ChaCha20 stream across all input
+//! classes, at independent sizes so unbalanced shapes are covered too. With
+//! `--paranoid` the kernels' invariant checks run even in a release build,
+//! so a broken intermediate is caught where it happens rather than only as
+//! a wrong final product.
+//!
+//! Independent implementations can join as oracles: Python's built-in
+//! integers, or any program speaking the `--external` protocol (a small
+//! num-bigint or rug wrapper, say). Every oracle is checked against the same
+//! reference as the built-ins and named when it disagrees, so a bug in the
+//! reference itself shows up as all oracles disagreeing at once. Without
+//! oracles, the reference is still checked by casting out nines and by its
+//! residue modulo a random 64-bit prime, which any correct product shares
+//! with the product of the operands' residues.
+
+use crate::cli::Output;
+use crate::external::External;
+use crate::inputs::{Axis, InputClass};
+use crate::interrupt;
+use crate::json;
+use crate::multiplier::{self, Multiplier};
+use bigmul::{BigUint, limbs};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::fmt::Write as _;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Multiplies each pair of lines on stdin and answers with one line.
+const PYTHON_ORACLE: &str = "\
+import sys
+sys.set_int_max_str_digits(0)
+lines = iter(sys.stdin)
+for a in lines:
+    b = next(lines)
+    print(int(a) * int(b), flush=True)
+";
+
+/// An independent implementation to cross-check against.
+pub enum Oracle {
+    /// `python3` with its built-in integers.
+    Python,
+    /// A program speaking the `--external` protocol.
+    Program(String, PathBuf),
+}
+
+impl Oracle {
+    fn spawn(&self) -> Result<External, String> {
+        match self {
+            Oracle::Python => {
+                let mut command = Command::new("python3");
+                command.arg("-c").arg(PYTHON_ORACLE);
+                External::spawn_command("python", command)
+            }
+            Oracle::Program(name, path) => External::spawn(name, path),
+        }
+    }
+}
+
+/// A random 64-bit prime from its own stream of `seed`, leaving the
+/// operand stream as it was.
+fn residue_prime(seed: u64) -> u64 {
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    rng.set_stream(1);
+    loop {
+        let p = rng.r#gen::<u64>() | 1 << 63 | 1;
+        if BigUint::from(p).is_probable_prime(32) {
+            return p;
+        }
+    }
+}
+
+/// Whether `product` has the digit sum modulo 9 and the residue modulo `p`
+/// that `a * b` must have.
+fn fingerprints_agree(a: &BigUint, b: &BigUint, product: &[u32], p: u64) -> bool {
+    let Some(product) = BigUint::from_limbs(product.to_vec()) else {
+        return false;
+    };
+    let residue = a.mod_small(p) as u128 * b.mod_small(p) as u128 % p as u128;
+    let nines = a.digit_sum() % 9 * (b.digit_sum() % 9) % 9;
+    product.mod_small(p) as u128 == residue && product.digit_sum() % 9 == nines
+}
+
+pub enum Verdict {
+    Passed,
+    Failed,
+    Interrupted,
+}
+
+/// One product that disagreed with the reference.
+struct Mismatch {
+    pair: usize,
+    role: &'static str,
+    name: String,
+    class: &'static str,
+    limbs: (usize, usize),
+    problem: &'static str,
+}
+
+/// The `--output json` document. The seed and prime are strings, since
+/// JSON readers commonly hold numbers as doubles and would round them.
+fn document(
+    seed: u64,
+    prime: u64,
+    pairs: usize,
+    complete: bool,
+    names: &[&str],
+    mismatches: &[Mismatch],
+) -> String {
+    let quoted = |names: &[&str]| {
+        names
+            .iter()
+            .map(|n| format!("\"{}\"", json::escape(n)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut out = String::from("{\n");
+    writeln!(out, "  \"complete\": {},", complete).unwrap();
+    writeln!(out, "  \"passed\": {},", complete && mismatches.is_empty()).unwrap();
+    writeln!(out, "  \"seed\": \"{}\",", seed).unwrap();
+    writeln!(out, "  \"prime\": \"{}\",", prime).unwrap();
+    writeln!(out, "  \"pairs\": {},", pairs).unwrap();
+    writeln!(out, "  \"checked\": [{}],", quoted(names)).unwrap();
+    out.push_str("  \"mismatches\": [\n");
+    for (k, m) in mismatches.iter().enumerate() {
+        let sep = if k + 1 < mismatches.len() { "," } else { "" };
+        writeln!(
+            out,
+            "    {{\"pair\": {}, \"role\": \"{}\", \"name\": \"{}\", \"class\": \"{}\", \
+             \"limbs\": [{}, {}], \"problem\": \"{}\"}}{}",
+            m.pair,
+            m.role,
+            json::escape(&m.name),
+            m.class,
+            m.limbs.0,
+            m.limbs.1,
+            m.problem,
+            sep
+        )
+        .unwrap();
+    }
+    out.push_str("  ]\n}\n");
+    out
+}
+
+/// Checks `iterations` random pairs of up to `max_digits` digits each,
+/// reporting every disagreement, and prints the seed so a failure can be
+/// replayed. `quiet` silences everything but errors; with `Output::Json`
+/// stdout gets only the final document.
+pub fn run(
+    iterations: usize,
+    max_digits: usize,
+    seed: Option<u64>,
+    paranoid: bool,
+    oracles: &[Oracle],
+    quiet: bool,
+    output: Output,
+) -> Result<Verdict, String> {
+    limbs::set_paranoid(paranoid);
+    let seed = seed.unwrap_or_else(|| rand::thread_rng().r#gen());
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    let prime = residue_prime(seed);
+    let registry = multiplier::registry();
+    let oracles = oracles
+        .iter()
+        .map(Oracle::spawn)
+        .collect::<Result<Vec<External>, String>>()?;
+    let reports = !quiet && output == Output::Text;
+    if reports {
+        println!(
+            "verifying {} algorithms and {} oracles on {} pairs of up to {} digits (seed {}, residues mod {}{})",
+            registry.len(),
+            oracles.len(),
+            iterations,
+            max_digits,
+            seed,
+            prime,
+            if paranoid { ", paranoid" } else { "" }
+        );
+    }
+
+    let mut mismatches = Vec::new();
+    let mut pairs = 0;
+    for i in 0..iterations {
+        if interrupt::interrupted() {
+            break;
+        }
+        let class = InputClass::ALL[rng.gen_range(0..InputClass::ALL.len())];
+        let a = class.generate_with(rng.gen_range(0..=max_digits), Axis::Digits, &mut rng);
+        let b = class.generate_with(rng.gen_range(0..=max_digits), Axis::Digits, &mut rng);
+        let limbs = (a.as_limbs().len(), b.as_limbs().len());
+        let expected = limbs::mul_direct_slices(a.as_limbs(), b.as_limbs());
+        let mut failed = |role, name: &str, problem| {
+            if !quiet {
+                eprintln!(
+                    "FAIL {} {} on pair {} ({}, {} x {} limbs): {}",
+                    role,
+                    name,
+                    i,
+                    class.name(),
+                    limbs.0,
+                    limbs.1,
+                    problem
+                );
+            }
+            mismatches.push(Mismatch {
+                pair: i,
+                role,
+                name: name.to_string(),
+                class: class.name(),
+                limbs,
+                problem,
+            });
+        };
+        if !fingerprints_agree(&a, &b, &expected, prime) {
+            failed("reference", "direct", "fails the residue check");
+        }
+        let algorithms = registry.iter().map(|m| (m.as_ref(), "algorithm"));
+        let oracles = oracles.iter().map(|o| (o as &dyn Multiplier, "oracle"));
+        for (m, role) in algorithms.chain(oracles) {
+            let got = panic::catch_unwind(AssertUnwindSafe(|| m.mul(a.as_limbs(), b.as_limbs())));
+            match got {
+                Ok(product) if product == expected => {}
+                Ok(_) => failed(role, m.name(), "wrong product"),
+                Err(_) => failed(role, m.name(), "panicked"),
+            }
+        }
+        pairs += 1;
+    }
+
+    let complete = pairs == iterations;
+    if output == Output::Json {
+        let names: Vec<&str> = registry
+            .iter()
+            .map(|m| m.name())
+            .chain(oracles.iter().map(|o| o.name()))
+            .collect();
+        print!(
+            "{}",
+            document(seed, prime, pairs, complete, &names, &mismatches)
+        );
+    }
+    if !complete {
+        Ok(Verdict::Interrupted)
+    } else if mismatches.is_empty() {
+        if reports {
+            println!("all products agree");
+        }
+        Ok(Verdict::Passed)
+    } else {
+        if !quiet {
+            eprintln!("{} mismatches (seed {})", mismatches.len(), seed);
+        }
+        Ok(Verdict::Failed)
+    }
+}
@@ -0,0 +1,54 @@
+//! Runs `bigmul --quick-check`, so `cargo test` fails whenever the
+//! algorithms stop agreeing on the sweep's own operands.
+
+use std::process::Command;
+
+#[test]
+fn every_algorithm_agrees_in_a_quick_check() {
+    let dir = std::env::temp_dir().join(format!("bigmul-quick-check-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_bigmul"))
+        .arg("--quick-check")
+        .current_dir(&dir)
+        .output()
+        .expect("failed to run bigmul");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "{}{}",
+        stdout,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("Quick check passed"), "{}", stdout);
+    assert!(!dir.join("assets").exists(), "quick check wrote results");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn quiet_json_quick_check_prints_one_document() {
+    let dir = std::env::temp_dir().join(format!("bigmul-quick-check-json-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_bigmul"))
+        .args(["--quick-check", "--quiet", "--output", "json"])
+        .current_dir(&dir)
+        .output()
+        .expect("failed to run bigmul");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", stdout);
+    assert!(stdout.starts_with("{\"passed\": true,"), "{}", stdout);
+    assert_eq!(stdout.lines().count(), 1, "{}", stdout);
+    assert!(output.stderr.is_empty());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn quick_check_refuses_standalone_benchmarks() {
+    let output = Command::new(env!("CARGO_BIN_EXE_bigmul"))
+        .args(["--quick-check", "--square"])
+        .output()
+        .expect("failed to run bigmul");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with("error: --quick-check"), "{}", stderr);
+}
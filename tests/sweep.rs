@@ -0,0 +1,33 @@
+//! The programmatic sweep API: runs small measured comparisons and asserts
+//! on relative performance, as a downstream crate would.
+
+use bigmul::{Algorithm, Sweep};
+
+/// Times real work, so it only means something on an idle machine and an
+/// optimized build: `cargo test --release -- --ignored`.
+#[test]
+#[ignore = "wall-clock comparison; run with --release -- --ignored"]
+fn karatsuba_beats_direct_at_large_sizes() {
+    let results = Sweep::new()
+        .sizes([1024])
+        .algorithms([Algorithm::DIRECT, Algorithm::KARATSUBA])
+        .rounds(3)
+        .seed(7)
+        .run();
+    assert_eq!(results.sizes, [1024]);
+    assert_eq!(results.fastest(1024), Some("kara"));
+    assert!(results.speedup("kara", "direct", 1024).unwrap() > 1.5);
+}
+
+#[test]
+fn results_cover_every_algorithm_and_size() {
+    let results = Sweep::new().sizes([1, 40]).rounds(1).run();
+    let names: Vec<&str> = results.series.iter().map(|(name, _)| *name).collect();
+    assert_eq!(names, ["direct", "dc", "kara", "kara_sub"]);
+    for name in names {
+        assert!(results.time(name, 40).is_some());
+        assert_eq!(Algorithm::by_name(name).map(|a| a.name), Some(name));
+    }
+    assert_eq!(results.time("kara", 2), None);
+    assert_eq!(results.time("fft", 40), None);
+}